@@ -0,0 +1,34 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Records the commit and time of the build for `--version --json`: TEMPLATE_RS_COMMIT (empty
+/// outside a git checkout) and TEMPLATE_RS_BUILD_SECS (SOURCE_DATE_EPOCH when set, for
+/// reproducible builds)
+fn main() {
+    let commit = Command::new("git").args(["rev-parse", "HEAD"]).output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=TEMPLATE_RS_COMMIT={commit}");
+
+    let build_secs = env::var("SOURCE_DATE_EPOCH").ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+    println!("cargo:rustc-env=TEMPLATE_RS_BUILD_SECS={build_secs}");
+
+    // Rerun when the checked out commit moves; a missing path would rerun on every build
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let head = Path::new(".git/HEAD");
+    if head.exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        if let Some(reference) = fs::read_to_string(head).ok().and_then(|head| head.strip_prefix("ref: ").map(|r| r.trim().to_string()))
+            && Path::new(".git").join(&reference).exists()
+        {
+            println!("cargo:rerun-if-changed=.git/{reference}");
+        }
+    }
+}
@@ -0,0 +1,15 @@
+import argparse
+
+from app import __version__
+
+
+def main() -> None:
+    parser = argparse.ArgumentParser(prog="app")
+    parser.add_argument("--version", action="version", version=__version__)
+    parser.add_argument("name", nargs="?", default="world")
+    args = parser.parse_args()
+    print(f"Hello, {args.name}!")
+
+
+if __name__ == "__main__":
+    main()
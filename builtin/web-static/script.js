@@ -0,0 +1 @@
+document.getElementById("message").textContent = `Loaded at ${new Date().toLocaleTimeString()}`;
@@ -0,0 +1,126 @@
+use crate::timing::{Phase, span};
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Compression used for a tar archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// .tar.gz / .tgz
+    Gzip,
+    /// .tar.zst / .tzst
+    Zstd,
+}
+
+impl ArchiveFormat {
+    /// Picks the format from an archive's file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::Gzip)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+/// Writes the given files (relative to `base_dir`) into a compressed tar archive
+pub fn create_archive(
+    format: ArchiveFormat,
+    base_dir: &Path,
+    files: &[String],
+    archive_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let entries: Vec<(PathBuf, String)> = files.iter().map(|file| (base_dir.join(file), file.clone())).collect();
+    create_archive_from(format, &entries, archive_path)
+}
+
+/// Creates an archive from files that don't share a base directory, each stored under its name
+pub fn create_archive_from(
+    format: ArchiveFormat,
+    entries: &[(PathBuf, String)],
+    archive_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let _timing = span(Phase::Io);
+    let file = File::create(archive_path)?;
+    match format {
+        ArchiveFormat::Gzip => {
+            let encoder = GzEncoder::new(file, Compression::best());
+            write_tar(encoder, entries)?.finish()?;
+        }
+        ArchiveFormat::Zstd => {
+            let encoder = zstd::Encoder::new(file, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+            write_tar(encoder, entries)?.finish()?;
+        }
+    }
+    Ok(())
+}
+
+/// Extracts a compressed tar archive into a directory
+pub fn extract_archive(
+    format: ArchiveFormat,
+    archive_path: &Path,
+    dest_dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let _timing = span(Phase::Io);
+    let file = File::open(archive_path)?;
+    match format {
+        ArchiveFormat::Gzip => unpack_tar(GzDecoder::new(file), dest_dir),
+        ArchiveFormat::Zstd => unpack_tar(zstd::Decoder::new(file)?, dest_dir),
+    }
+}
+
+/// Lists the regular files in a compressed tar archive as relative paths (forward slashes),
+/// without extracting anything
+pub fn list_archive(format: ArchiveFormat, archive_path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let _timing = span(Phase::Io);
+    let file = File::open(archive_path)?;
+    match format {
+        ArchiveFormat::Gzip => list_tar(GzDecoder::new(file)),
+        ArchiveFormat::Zstd => list_tar(zstd::Decoder::new(file)?),
+    }
+}
+
+/// Writes everything below `dir` into an uncompressed tar archive, entries relative to `dir`.
+/// Symlinks are stored as links.
+pub fn write_directory_tar<W: Write>(dir: &Path, writer: W) -> Result<W, Box<dyn std::error::Error>> {
+    let _timing = span(Phase::Io);
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+    builder.append_dir_all(".", dir)?;
+    Ok(builder.into_inner()?)
+}
+
+fn write_tar<W: Write>(writer: W, entries: &[(PathBuf, String)]) -> Result<W, Box<dyn std::error::Error>> {
+    let mut builder = tar::Builder::new(writer);
+    for (path, name) in entries {
+        builder.append_path_with_name(path, name)?;
+    }
+    Ok(builder.into_inner()?)
+}
+
+fn list_tar<R: Read>(reader: R) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut files = Vec::new();
+    for entry in tar::Archive::new(reader).entries()? {
+        let entry = entry?;
+        if entry.header().entry_type().is_file() {
+            let path = entry.path()?.to_string_lossy().replace('\\', "/");
+            files.push(path.trim_start_matches("./").to_string());
+        }
+    }
+    Ok(files)
+}
+
+fn unpack_tar<R: Read>(reader: R, dest_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_permissions(true);
+    archive.unpack(dest_dir)?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use crate::file::ensure_cache_storage_dir;
+use crate::template::{Template, TEMPLATE_CONFIG_FILE};
+use crate::timing::{Phase, span};
+
+use include_dir::{Dir, include_dir};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Namespace the embedded starter templates are listed under
+pub const BUILTIN_PREFIX: &str = "builtin/";
+
+/// Starter templates compiled into the binary, one directory each
+static BUILTIN_TEMPLATES: Dir = include_dir!("$CARGO_MANIFEST_DIR/builtin");
+
+/// Lists the built-in templates as `builtin/<name>`, backed by a copy in the cache
+/// so they can be applied like any template in storage
+pub fn builtin_templates() -> Result<Vec<Template>, Box<dyn std::error::Error>> {
+    let _timing = span(Phase::Discovery);
+    let root = extract_builtin_templates()?;
+    let mut templates = Vec::new();
+
+    for dir in BUILTIN_TEMPLATES.dirs() {
+        let path = root.join(dir.path());
+        let Some(dir_name) = dir.path().to_str() else {
+            continue;
+        };
+        let config = Template::parse_config(path.join(TEMPLATE_CONFIG_FILE))?;
+        templates.push(Template { name: format!("{BUILTIN_PREFIX}{dir_name}"), path, config });
+    }
+    Ok(templates)
+}
+
+/// Copies the built-in templates into `storage` as ordinary templates named after their directory,
+/// so they can be edited; templates already there are left alone. Returns the names installed.
+pub fn install_builtin_templates(storage: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut installed = Vec::new();
+    for dir in BUILTIN_TEMPLATES.dirs() {
+        let Some(name) = dir.path().to_str() else {
+            continue;
+        };
+        let destination = storage.join(name);
+        if destination.exists() {
+            continue;
+        }
+        // Entries extract relative to the embedded root, below a directory that must exist
+        fs::create_dir_all(&destination)?;
+        dir.extract(storage)?;
+        installed.push(name.to_string());
+    }
+    Ok(installed)
+}
+
+/// Names of the built-in templates, without the `builtin/` prefix
+pub fn builtin_names() -> Vec<&'static str> {
+    BUILTIN_TEMPLATES.dirs().filter_map(|dir| dir.path().to_str()).collect()
+}
+
+/// Writes the embedded templates to cache/builtin/<tool version>/ once per version and returns
+/// that directory. Extraction goes through a temporary directory so a crash never leaves a partial copy.
+fn extract_builtin_templates() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let builtin_dir = ensure_cache_storage_dir()?.join("builtin");
+    let root = builtin_dir.join(env!("CARGO_PKG_VERSION"));
+    if root.is_dir() {
+        return Ok(root);
+    }
+
+    let staging = builtin_dir.join(format!(".{}-{}", env!("CARGO_PKG_VERSION"), std::process::id()));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+    BUILTIN_TEMPLATES.extract(&staging)?;
+
+    // Another process may have finished first, its copy is just as good
+    if let Err(e) = fs::rename(&staging, &root) {
+        fs::remove_dir_all(&staging)?;
+        if !root.is_dir() {
+            return Err(e.into());
+        }
+    }
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_templates_are_valid() {
+        let names: Vec<_> = BUILTIN_TEMPLATES.dirs().filter_map(|d| d.path().to_str()).collect();
+        assert!(names.contains(&"rust-bin"));
+
+        for dir in BUILTIN_TEMPLATES.dirs() {
+            let config = dir.get_file(dir.path().join(".template.toml"))
+                .and_then(|f| f.contents_utf8())
+                .unwrap_or_else(|| panic!("{} has no .template.toml", dir.path().display()));
+            let config: crate::template::TemplateConfig = toml::from_str(config).unwrap();
+            assert!(config.language.is_some() && config.description.is_some());
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+/// Category definition with display name and aliases
+pub struct Category {
+    pub display_name: &'static str,
+    pub aliases: &'static [&'static str],
+}
+
+impl Category {
+    const fn new(display_name: &'static str, aliases: &'static [&'static str]) -> Self {
+        Self { display_name, aliases }
+    }
+}
+
+/// Map of all known template categories with their aliases
+pub fn get_category_map() -> HashMap<&'static str, &'static Category> {
+    // Define all categories
+    static CATEGORIES: &[Category] = &[
+        Category::new("CI/CD", &["ci", "cd", "ci/cd", "pipeline", "github-actions"]),
+        Category::new("CLI", &["cli", "command-line", "terminal", "tui"]),
+        Category::new("Configuration", &["config", "configuration", "dotfiles", "settings"]),
+        Category::new("Data Science", &["data-science", "data", "ml", "notebook", "analysis"]),
+        Category::new("Desktop App", &["desktop", "desktop-app", "gui"]),
+        Category::new("Documentation", &["docs", "documentation", "book", "site"]),
+        Category::new("Embedded", &["embedded", "firmware", "microcontroller", "mcu"]),
+        Category::new("Game", &["game", "gamedev", "game-dev"]),
+        Category::new("Infrastructure", &["infra", "infrastructure", "iac", "terraform", "deployment"]),
+        Category::new("Library", &["library", "lib", "package", "crate", "module"]),
+        Category::new("Mobile App", &["mobile", "mobile-app", "android", "ios"]),
+        Category::new("Plugin", &["plugin", "extension", "addon", "add-on"]),
+        Category::new("Script", &["script", "scripts", "automation"]),
+        Category::new("Web App", &["web-app", "webapp", "frontend", "spa", "website"]),
+        Category::new("Web Service", &["web-service", "service", "api", "backend", "server", "microservice"]),
+        Category::new("Workspace", &["workspace", "monorepo"]),
+    ];
+
+    // Build the map
+    let mut map = HashMap::new();
+    for category in CATEGORIES {
+        for &alias in category.aliases {
+            map.insert(alias, category);
+        }
+    }
+    map
+}
+
+/// Get the display name for a category identifier (case-insensitive, spaces and dashes interchangeable)
+/// Returns the canonical display name if recognized, otherwise returns the original identifier
+pub fn get_category_display_name(identifier: &str) -> String {
+    let map = get_category_map();
+    let normalized = normalize(identifier);
+    map.get(normalized.as_str())
+        .map_or_else(|| identifier.to_string(), |category| category.display_name.to_string())
+}
+
+/// Check if a category identifier is known (case-insensitive)
+pub fn is_known_category(identifier: &str) -> bool {
+    let map = get_category_map();
+    map.contains_key(normalize(identifier).as_str())
+}
+
+/// Lowercases and joins words with dashes, so "Web Service" matches the "web-service" alias
+fn normalize(identifier: &str) -> String {
+    identifier.trim().to_lowercase().split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases() {
+        assert_eq!(get_category_display_name("cli"), "CLI");
+        assert_eq!(get_category_display_name("api"), "Web Service");
+        assert_eq!(get_category_display_name("Web Service"), "Web Service");
+        assert_eq!(get_category_display_name("INFRA"), "Infrastructure");
+    }
+
+    #[test]
+    fn test_unknown_category() {
+        assert_eq!(get_category_display_name("kitchen-sink"), "kitchen-sink");
+        assert!(!is_known_category("kitchen-sink"));
+        assert!(is_known_category("docs"));
+    }
+}
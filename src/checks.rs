@@ -0,0 +1,80 @@
+use crate::file::find_in_path;
+use crate::languages::get_display_name;
+
+use std::path::Path;
+use std::process::Command;
+
+/// A quick command telling whether a generated project is healthy, e.g. `cargo check`
+#[derive(Debug, PartialEq, Eq)]
+pub struct SanityCheck {
+    program: &'static str,
+    args: &'static [&'static str],
+    /// File the project needs in its root for the command to make sense
+    marker: Option<&'static str>,
+}
+
+/// Built-in sanity checks by language display name
+const SANITY_CHECKS: &[(&str, SanityCheck)] = &[
+    ("Rust", SanityCheck { program: "cargo", args: &["check", "--quiet"], marker: Some("Cargo.toml") }),
+    ("JavaScript", SanityCheck { program: "npm", args: &["install", "--dry-run", "--ignore-scripts"], marker: Some("package.json") }),
+    ("TypeScript", SanityCheck { program: "npm", args: &["install", "--dry-run", "--ignore-scripts"], marker: Some("package.json") }),
+    ("Python", SanityCheck {
+        program: if cfg!(windows) { "python" } else { "python3" },
+        args: &["-m", "compileall", "-q", "."],
+        marker: None,
+    }),
+    ("Go", SanityCheck { program: "go", args: &["vet", "./..."], marker: Some("go.mod") }),
+];
+
+/// The sanity check for projects in `language` (any identifier or alias), if there is one
+pub fn sanity_check(language: &str) -> Option<&'static SanityCheck> {
+    let language = get_display_name(language);
+    SANITY_CHECKS.iter().find(|(name, _)| *name == language).map(|(_, check)| check)
+}
+
+impl SanityCheck {
+    /// The command as it would be typed
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.program).chain(self.args.iter().copied()).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Why the check can't run in `target_path`, None when it can
+    pub fn unavailable(&self, target_path: &Path) -> Option<String> {
+        if let Some(marker) = self.marker
+            && !target_path.join(marker).is_file()
+        {
+            return Some(format!("the project has no {marker}"));
+        }
+        find_in_path(self.program).is_none().then(|| format!("'{}' was not found on PATH", self.program))
+    }
+
+    /// Runs the check in `target_path`, returning whether it passed. Its output goes to stderr.
+    pub fn run(&self, target_path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+        let status = Command::new(self.program)
+            .args(self.args)
+            .current_dir(target_path)
+            .stdout(std::io::stderr())
+            .status()
+            .map_err(|e| format!("Cannot run `{}`: {e}", self.command_line()))?;
+        Ok(status.success())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanity_check_by_language() {
+        assert_eq!(sanity_check("rs").map(SanityCheck::command_line).as_deref(), Some("cargo check --quiet"));
+        assert_eq!(sanity_check("ts"), sanity_check("JavaScript"));
+        assert!(sanity_check("Markdown").is_none());
+    }
+
+    #[test]
+    fn test_unavailable_without_marker() {
+        let check = sanity_check("Rust").unwrap();
+        let reason = check.unavailable(&std::env::temp_dir().join("surely-not-a-project")).unwrap();
+        assert_eq!(reason, "the project has no Cargo.toml");
+    }
+}
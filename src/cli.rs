@@ -0,0 +1,159 @@
+use clap::{ColorChoice, CommandFactory, FromArgMatches, Parser};
+use clap::builder::styling::Styles;
+use clap_complete::env::CompleteEnv;
+
+// Import from modules
+use crate::{commands, migrate, onboarding, output, tags, template, theme, timing, BIN_NAME};
+use crate::file::ensure_all_storage_dirs;
+use crate::commands::{Commands, handle_command};
+use crate::compat::BuildInfo;
+use crate::config::UserConfig;
+use crate::theme::{Icon, ThemeConfig};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
+const LICENSE: &str = include_str!("../LICENSE");
+
+fn custom_styles() -> Styles {
+    // Config problems are reported by the command itself; help output falls back to the default theme
+    let theme = UserConfig::load().map(|c| c.theme).unwrap_or_default();
+    theme::styles(&theme).unwrap_or_else(|e| {
+        eprintln!("Warning: {e}, using the default theme");
+        theme::styles(&ThemeConfig::default()).expect("default theme is valid")
+    })
+}
+
+#[derive(Parser)]
+#[command(name = BIN_NAME)]
+#[command(version = VERSION)]
+#[command(disable_version_flag = true)]
+#[command(author = AUTHORS)]
+#[command(about = "A Rust CLI tool for managing programming templates")]
+#[command(arg_required_else_help = true)]
+#[command(styles = custom_styles())]
+pub(crate) struct Cli {
+    /// Display detailed information about this tool
+    #[arg(long)]
+    about: bool,
+
+    /// Display the license information
+    #[arg(long)]
+    license: bool,
+
+    /// Print version
+    #[arg(short = 'V', long)]
+    version: bool,
+
+    /// With --version, print the version, build, features and storage paths as JSON
+    #[arg(long, requires = "version")]
+    json: bool,
+
+    /// Print plain text markers instead of emoji
+    #[arg(long, global = true)]
+    ascii: bool,
+
+    /// Stable, unstyled, one-record-per-line output for scripts (messages go to stderr)
+    #[arg(long, global = true)]
+    plain: bool,
+
+    /// Fail when a template directory can't be read instead of skipping it with a warning
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Report how long discovery, config parsing, rendering and IO took (on stderr)
+    #[arg(long, global = true)]
+    profile_timing: bool,
+    
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+/// Runs the command line: parses the arguments, then carries out the command and returns how it
+/// went. Startup problems and invalid arguments exit the process right away.
+pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+    // A shell asking for completions gets them before anything else runs or prints
+    CompleteEnv::with_factory(Cli::command).var(commands::completions::COMPLETE_VAR).complete();
+
+    // Known before parsing so the storage checks below are timed as well
+    timing::set_enabled(std::env::args_os().any(|arg| arg == "--profile-timing"));
+
+    // Move storage out of the legacy location before anything resolves where storage is
+    if let Err(e) = migrate::migrate_legacy_storage() {
+        eprintln!("Error moving storage: {e}");
+        std::process::exit(1);
+    }
+
+    // --plain also has to turn off colors in clap's own help and error output
+    let mut command = Cli::command();
+    if std::env::args_os().any(|arg| arg == "--plain") {
+        command = command.color(ColorChoice::Never);
+    }
+    let cli = Cli::from_arg_matches(&command.get_matches()).unwrap_or_else(|e| e.exit());
+
+    // A first run at a terminal is set up interactively instead of silently
+    let onboarding = if onboarding::is_first_run(cli.command.as_ref(), cli.plain) {
+        match onboarding::run_wizard() {
+            Ok(onboarding) => Some(onboarding),
+            Err(e) => {
+                eprintln!("Error during setup: {e}");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
+    // Ensure storage directories exist at startup
+    if let Err(e) = ensure_all_storage_dirs() {
+        eprintln!("Error creating storage directories: {e}");
+        std::process::exit(1);
+    }
+    if let Err(e) = migrate::migrate_storage() {
+        eprintln!("Error migrating storage: {e}");
+        std::process::exit(1);
+    }
+    if let Some(onboarding) = onboarding
+        && let Err(e) = onboarding.finish()
+    {
+        eprintln!("Error during setup: {e}");
+        std::process::exit(1);
+    }
+
+    // Config problems are reported by the commands that need the config
+    let config = UserConfig::load().unwrap_or_default();
+    theme::set_ascii(cli.ascii || config.theme.ascii);
+    tags::set_user_tags(&config.tags);
+    output::set_plain(cli.plain);
+    template::set_strict_discovery(cli.strict);
+
+    // Handle about flag first
+    if cli.about {
+        println!("template-rs v{VERSION}");
+        println!("Created by: {AUTHORS}\n");
+        println!("{} Website: https://matthewgreen.gg/", Icon::Website);
+        println!("{} GitHub: https://github.com/greenmatthew/template-rs", Icon::Package);
+        println!("{} Gitea: https://git.matthewgreen.gg/mgreen/template-rs\n", Icon::Tool);
+        println!("Use `{BIN_NAME} --license` to view the license");
+        return Ok(());
+    }
+
+    // Handle license flag first
+    if cli.license {
+        println!("{LICENSE}");
+        return Ok(());
+    }
+
+    if cli.version {
+        if cli.json {
+            println!("{}", serde_json::to_string_pretty(&BuildInfo::current())?);
+        } else {
+            println!("{BIN_NAME} {VERSION}");
+        }
+        return Ok(());
+    }
+
+    // Handle subcommands
+    let result = cli.command.map_or(Ok(()), handle_command);
+    timing::report();
+    result
+}
@@ -0,0 +1,111 @@
+use crate::commands::eject::storage_destination;
+use crate::copy::{CopyEngine, CopyPlan, NativeCopy, Ownership, Timestamps};
+use crate::error::TemplateError;
+use crate::git;
+use crate::names::template_name;
+use crate::template::{Template, TEMPLATE_CONFIG_FILE};
+use crate::theme::Icon;
+
+use std::fs;
+use std::path::Path;
+
+/// Installs a template from a git repository into the template storage: the repository root,
+/// or `subdir` of it, must hold a .template.toml. The repository history is not kept.
+/// `normalize` applies the naming policy to the name it is stored under.
+pub fn handle_add(
+    url: &str,
+    name: Option<&str>,
+    subdir: Option<&str>,
+    reference: Option<&str>,
+    normalize: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let subdir = subdir.map(|s| s.replace('\\', "/").trim_matches('/').to_string()).filter(|s| !s.is_empty());
+    if let Some(subdir) = &subdir
+        && subdir.split('/').any(|part| part.is_empty() || part == "." || part == "..")
+    {
+        return Err(format!("Invalid subdirectory '{subdir}'").into());
+    }
+
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => default_name(url, subdir.as_deref())
+            .ok_or_else(|| format!("Cannot tell a template name from '{url}', pass one with --name"))?,
+    };
+    let name = template_name(&name, normalize)?;
+    let dest_path = storage_destination(&name)?;
+
+    let scratch = tempfile::Builder::new().prefix("template-rs-add-").tempdir()?;
+    let checkout = scratch.path().join("checkout");
+    eprintln!("Cloning {url}{}", reference.map(|r| format!(" at {r}")).unwrap_or_default());
+    git::clone(url, reference, &checkout)?;
+    // Only for the message, so a repository that can't be described is no reason to fail
+    let version = git::describe(&checkout).ok().flatten().map(|version| format!(" at {version}")).unwrap_or_default();
+    install(&checkout, subdir.as_deref(), url, &dest_path)?;
+
+    eprintln!("{} Added template '{name}' from {url}{version}", Icon::Success);
+    eprintln!("{} Stored in {}", Icon::Folder, dest_path.display());
+    Ok(())
+}
+
+/// Copies the template out of a fresh checkout into the storage, after checking it is one
+fn install(checkout: &Path, subdir: Option<&str>, url: &str, dest_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let source = subdir.map_or_else(|| checkout.to_path_buf(), |subdir| checkout.join(subdir));
+    let location = subdir.map(|s| format!("{s}/ in {url}")).unwrap_or_else(|| url.to_string());
+    if !source.is_dir() {
+        return Err(format!("{location} does not exist").into());
+    }
+    if !Template::is_valid_template(&source) {
+        return Err(format!(
+            "{location} has no {TEMPLATE_CONFIG_FILE}, so it isn't a template. Use --subdir for a template inside the repository"
+        ).into());
+    }
+    Template::parse_config(source.join(TEMPLATE_CONFIG_FILE))
+        .map_err(|e| TemplateError::Config(format!("{TEMPLATE_CONFIG_FILE} of {location} is invalid: {e}")))?;
+
+    fs::create_dir_all(dest_path)?;
+    let plan = CopyPlan {
+        source: &source,
+        target: dest_path,
+        excludes: vec!["/.git/".to_string()],
+        protected: Vec::new(),
+        overwrite: false,
+        delete: false,
+        dry_run: false,
+        ownership: Ownership::Invoker,
+        mode_mask: 0,
+        timestamps: Timestamps::Now,
+    };
+    if let Err(e) = NativeCopy.copy(&plan, &mut |_| Ok(())) {
+        let _ = fs::remove_dir_all(dest_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// The name a template is stored under when none is given: the subdirectory's last component,
+/// or else the repository name (`https://host/org/rust-cli.git` gives `rust-cli`)
+pub(crate) fn default_name(url: &str, subdir: Option<&str>) -> Option<String> {
+    let name = match subdir {
+        Some(subdir) => subdir.rsplit('/').next()?,
+        None => {
+            let path = url.trim_end_matches('/');
+            let last = path.rsplit(['/', ':']).next()?;
+            last.strip_suffix(".git").unwrap_or(last)
+        }
+    };
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_name() {
+        assert_eq!(default_name("https://github.com/org/rust-cli.git", None).as_deref(), Some("rust-cli"));
+        assert_eq!(default_name("git@github.com:org/rust-cli", None).as_deref(), Some("rust-cli"));
+        assert_eq!(default_name("git@host:repo.git", None).as_deref(), Some("repo"));
+        assert_eq!(default_name("https://github.com/org/templates/", Some("rust/cli")).as_deref(), Some("cli"));
+        assert_eq!(default_name("", None), None);
+    }
+}
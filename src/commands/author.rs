@@ -1,23 +1,74 @@
+use crate::commands::eject::storage_destination;
+use crate::commands::validate::warn_if_oversized;
+use crate::config::UserConfig;
+use crate::copy::{Change, CopyEngine, CopyPlan, NativeCopy, Ownership, Timestamps};
+use crate::eol::{Eol, EolOverride};
+use crate::hooks::Hooks;
+use crate::output::{is_plain, print_record};
+use crate::names;
+use crate::languages::{detect_primary_language, get_display_name, is_known_language, suggest_languages};
 use crate::path::resolve_path;
-use crate::template::{TemplateConfig, Template};
+use crate::patterns::parse_ignore_file;
+use crate::prerequisites::Prerequisites;
+use crate::template::{FileConfig, TemplateConfig, Template, TEMPLATE_CONFIG_FILE};
+use crate::theme::Icon;
+use crate::variables::VariableSpec;
+
+use clap::ValueEnum;
+
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Starting shape of the .template.toml written by `author`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AuthorPreset {
+    /// Only the name, language and description
+    Minimal,
+    /// Every section filled in with an example to edit or delete
+    Full,
+    /// A template that adds CI configuration to existing projects
+    Ci,
+}
 
 pub fn handle_author(
     path: &str,
+    from: Option<&str>,
     name: Option<String>,
+    language: Option<&str>,
+    preset: Option<AuthorPreset>,
+    normalize: bool,
+    dry_run: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Authoring new template...");
+    eprintln!("Authoring new template...");
+
+    // Validate before touching the filesystem so a typo doesn't leave a half-made template
+    let mut language = language.map(resolve_language).transpose()?;
+
+    if let Some(from) = from {
+        return author_from(from, path, name, language, preset, normalize, dry_run);
+    }
     
     // Resolve the target path
     let target_path = resolve_path(path, None)?;
-    println!("Target path: {}", target_path.display());
+    eprintln!("Target path: {}", target_path.display());
+
+    // Determine template name for messaging and config
+    let template_name = name.unwrap_or_else(|| {
+        target_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map_or_else(|| "unknown".to_string(), String::from)
+    });
+    let template_name = names::template_name(&template_name, normalize)?;
     
     // Create the directory if it doesn't exist
-    if !target_path.exists() {
-        println!("Creating directory: {}", target_path.display());
-        fs::create_dir_all(&target_path)?;
-    } else if !target_path.is_dir() {
+    let missing_dirs = missing_dirs(&target_path);
+    if target_path.exists() && !target_path.is_dir() {
         return Err(format!("Path exists but is not a directory: {}", target_path.display()).into());
+    } else if !dry_run && !missing_dirs.is_empty() {
+        eprintln!("Creating directory: {}", target_path.display());
+        fs::create_dir_all(&target_path)?;
     }
     
     // Check if .template.toml already exists
@@ -26,32 +77,249 @@ pub fn handle_author(
         return Err(format!("Template already exists at {}", target_path.display()).into());
     }
     
-    // Determine template name for messaging and config
-    let template_name = name.unwrap_or_else(|| {
-        target_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .map_or_else(|| "unknown".to_string(), String::from)
-    });
+    if !dry_run {
+        eprintln!("Creating .template.toml for template '{template_name}'");
+    }
+
+    // Authoring over existing content: guess the language from its files unless one was given
+    let user_config = UserConfig::load()?;
+    if language.is_none() && target_path.is_dir() {
+        let probe = Template { name: template_name.clone(), path: target_path.clone(), config: TemplateConfig::default() };
+        language = detect_primary_language(probe.apply_files(&user_config.excludes(true))?).map(String::from);
+        if let Some(language) = &language {
+            eprintln!("Detected language: {language}");
+        }
+    }
+
+    let sample_config = sample_config(preset, &template_name, language);
+
+    if dry_run {
+        print_dry_run(&missing_dirs, &config_path);
+        eprintln!("{} Dry run - template '{template_name}' would be created, nothing was written", Icon::DryRun);
+        return Ok(());
+    }
+
+    Template::save_config(&sample_config, config_path, false)?;
+
+    // Catch things like a committed node_modules early
+    let template = Template { name: template_name.clone(), path: target_path.clone(), config: sample_config };
+    let report = template.size_report()?;
+    warn_if_oversized(&template_name, &report, user_config.size_warning_bytes());
+    
+    eprintln!("{} Template '{template_name}' created successfully!", Icon::Success);
+    eprintln!("{} Edit .template.toml to customize your template metadata", Icon::Note);
+    eprintln!("{} Add your template files to {}", Icon::Folder, target_path.display());
     
-    println!("Creating .template.toml for template '{template_name}'");
+    Ok(())
+}
+
+/// Snapshots the project in `from` as a new template stored under `name`: everything is copied
+/// except version control, build output and what the project's .gitignore leaves out, then a
+/// .template.toml is written next to it. `config_name` overrides the name written to the config.
+fn author_from(
+    from: &str,
+    name: &str,
+    config_name: Option<String>,
+    mut language: Option<String>,
+    preset: Option<AuthorPreset>,
+    normalize: bool,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = resolve_path(from, None)?;
+    if !source.is_dir() {
+        return Err(format!("Not a directory: {}", source.display()).into());
+    }
+    if source.join(TEMPLATE_CONFIG_FILE).exists() {
+        return Err(format!("{} is already a template", source.display()).into());
+    }
+    eprintln!("Source project: {}", source.display());
+
+    let template_name = names::template_name(name, normalize)?;
+    let dest_path = storage_destination(&template_name)?;
+    eprintln!("Target path: {}", dest_path.display());
 
-    let sample_config = TemplateConfig {
-        name: Some(template_name.clone()),
-        language: None,
+    let user_config = UserConfig::load()?;
+    let mut excludes = user_config.excludes(true);
+    excludes.extend(gitignore_patterns(&source)?);
+
+    if language.is_none() {
+        let probe = Template { name: template_name.clone(), path: source.clone(), config: TemplateConfig::default() };
+        language = detect_primary_language(probe.apply_files(&excludes)?).map(String::from);
+        if let Some(language) = &language {
+            eprintln!("Detected language: {language}");
+        }
+    }
+    let sample_config = sample_config(preset, config_name.as_deref().unwrap_or(&template_name), language);
+
+    if !dry_run {
+        fs::create_dir_all(&dest_path)?;
+    }
+    let plan = CopyPlan {
+        source: &source,
+        target: &dest_path,
+        excludes,
+        protected: Vec::new(),
+        overwrite: false,
+        delete: false,
+        dry_run,
+        ownership: Ownership::Invoker,
+        mode_mask: 0,
+        timestamps: Timestamps::Now,
+    };
+    let changes = match NativeCopy.copy(&plan, &mut |_| Ok(())) {
+        Ok(changes) => changes,
+        Err(e) => {
+            if !dry_run {
+                let _ = fs::remove_dir_all(&dest_path);
+            }
+            return Err(format!("Failed to copy {}: {e}", source.display()).into());
+        }
+    };
+
+    if dry_run {
+        print_snapshot_dry_run(&changes);
+        eprintln!("{} Dry run - template '{template_name}' would be created, nothing was written", Icon::DryRun);
+        return Ok(());
+    }
+
+    Template::save_config(&sample_config, dest_path.join(TEMPLATE_CONFIG_FILE), false)?;
+
+    let template = Template { name: template_name.clone(), path: dest_path.clone(), config: sample_config };
+    let report = template.size_report()?;
+    warn_if_oversized(&template_name, &report, user_config.size_warning_bytes());
+
+    let copied = changes.iter().filter(|change| matches!(change, Change::Copy(path) if !path.ends_with('/'))).count();
+    eprintln!("{} Template '{template_name}' created from {} ({copied} files)", Icon::Success, source.display());
+    eprintln!("{} Edit .template.toml to customize your template metadata", Icon::Note);
+    eprintln!("{} Stored in {}", Icon::Folder, dest_path.display());
+    Ok(())
+}
+
+/// Patterns of what git ignores in a project: its root .gitignore and .git/info/exclude.
+/// Nested .gitignore files aren't read.
+fn gitignore_patterns(project: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut patterns = Vec::new();
+    for file in [project.join(".gitignore"), project.join(".git").join("info").join("exclude")] {
+        if file.is_file() {
+            patterns.extend(parse_ignore_file(&fs::read_to_string(&file)?));
+        }
+    }
+    Ok(patterns)
+}
+
+/// Lists the files a snapshot would copy, followed by the config it would write
+fn print_snapshot_dry_run(changes: &[Change]) {
+    let entries: Vec<&str> = changes.iter()
+        .filter_map(|change| match change {
+            Change::Copy(path) => Some(path.as_str()),
+            Change::Delete(_) => None,
+        })
+        .chain([TEMPLATE_CONFIG_FILE])
+        .collect();
+
+    if is_plain() {
+        for entry in &entries {
+            print_record(&["create", entry]);
+        }
+        return;
+    }
+
+    println!("Would create:");
+    for entry in &entries {
+        println!("  {entry}");
+    }
+}
+
+/// The directories `create_dir_all` would create for a path, outermost first
+fn missing_dirs(path: &Path) -> Vec<PathBuf> {
+    let mut missing: Vec<PathBuf> = path.ancestors()
+        .take_while(|p| !p.as_os_str().is_empty() && !p.exists())
+        .map(Path::to_path_buf)
+        .collect();
+    missing.reverse();
+    missing
+}
+
+/// Lists what authoring would create, directories ending in '/'
+fn print_dry_run(dirs: &[PathBuf], config_path: &Path) {
+    let entries: Vec<String> = dirs.iter()
+        .map(|dir| format!("{}/", dir.display()))
+        .chain([config_path.display().to_string()])
+        .collect();
+
+    if is_plain() {
+        for entry in &entries {
+            print_record(&["create", entry]);
+        }
+        return;
+    }
+
+    println!("Would create:");
+    for entry in &entries {
+        println!("  {entry}");
+    }
+}
+
+/// The .template.toml a new template starts from; without a preset, the usual metadata sample
+fn sample_config(preset: Option<AuthorPreset>, template_name: &str, language: Option<String>) -> TemplateConfig {
+    let sample = TemplateConfig {
+        name: Some(template_name.to_string()),
+        language,
         description: Some(format!("A template for {template_name}")),
         author: Some("Your Name".to_string()),
         version: Some("1.0.0".to_string()),
         tags: Some(vec!["project".to_string(), "template".to_string()]),
         min_tool_version: Some("0.1.0".to_string()),
-        metadata: None,
+        ..TemplateConfig::default()
     };
 
-    Template::save_config(&sample_config, config_path, false)?;
-    
-    println!("✅ Template '{template_name}' created successfully!");
-    println!("📝 Edit .template.toml to customize your template metadata");
-    println!("📁 Add your template files to {}", target_path.display());
-    
-    Ok(())
+    match preset {
+        None => sample,
+        Some(AuthorPreset::Minimal) => TemplateConfig {
+            name: sample.name,
+            language: sample.language,
+            description: sample.description,
+            ..TemplateConfig::default()
+        },
+        Some(AuthorPreset::Full) => TemplateConfig {
+            category: Some("cli".to_string()),
+            max_tool_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            requires_features: Some(vec!["create-once".to_string(), "eol".to_string(), "hooks".to_string(), "variables".to_string()]),
+            prerequisites: Some(Prerequisites { binaries: vec!["git".to_string()], min_free_disk_mib: Some(10) }),
+            eol: Some(Eol::Keep),
+            eol_overrides: Some(vec![EolOverride { pattern: "*.sh".to_string(), eol: Eol::Lf }]),
+            files: Some(BTreeMap::from([(
+                "README.md".to_string(),
+                FileConfig { create_once: true, ..FileConfig::default() },
+            )])),
+            variables: Some(BTreeMap::from([(
+                "project_name".to_string(),
+                VariableSpec { description: Some("Name of the generated project".to_string()), default: Some("my-project".to_string()), ..VariableSpec::default() },
+            )])),
+            verbatim: Some(vec![".github/**".to_string()]),
+            hooks: Some(Hooks { pre_init: Vec::new(), post_init: vec!["git init".to_string()] }),
+            ..sample
+        },
+        Some(AuthorPreset::Ci) => TemplateConfig {
+            category: Some("ci".to_string()),
+            description: Some(format!("CI configuration added to projects by {template_name}")),
+            tags: Some(vec!["ci".to_string()]),
+            eol: Some(Eol::Lf),
+            ..sample
+        },
+    }
+}
+
+/// Normalizes a language identifier to its display name, rejecting unknown ones with suggestions
+fn resolve_language(identifier: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if is_known_language(identifier) {
+        return Ok(get_display_name(identifier));
+    }
+
+    let suggestions = suggest_languages(identifier);
+    if suggestions.is_empty() {
+        Err(format!("Unknown language '{identifier}'").into())
+    } else {
+        Err(format!("Unknown language '{identifier}'. Did you mean: {}?", suggestions.join(", ")).into())
+    }
 }
@@ -0,0 +1,126 @@
+use crate::archive::{ArchiveFormat, create_archive_from, extract_archive};
+use crate::config::{USER_CONFIG_FILE, user_config_path};
+use crate::error::TemplateError;
+use crate::file::{ensure_cache_storage_dir, ensure_persistent_storage_dir, format_size};
+use crate::path::{BACKUP_STORAGE, CACHE_STORAGE, resolve_path};
+use crate::prompt::confirm;
+use crate::index::INDEX_FILE;
+use crate::template::{Template, collect_files};
+use crate::theme::Icon;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn handle_backup(output: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let output_path = resolve_path(output, None)?;
+    let format = archive_format(&output_path)?;
+    let storage_dir = ensure_persistent_storage_dir()?;
+
+    eprintln!("Backing up {} to {}", storage_dir.display(), output_path.display());
+
+    let entries = library_entries(&storage_dir, &library_files(&storage_dir)?)?;
+    create_archive_from(format, &entries, &output_path)?;
+
+    let size = fs::metadata(&output_path)?.len();
+    eprintln!("{} Backed up {} file(s) ({})", Icon::Success, entries.len(), format_size(size));
+    Ok(())
+}
+
+pub fn handle_restore(file: &str, yes: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let archive_path = resolve_path(file, None)?;
+    if !archive_path.is_file() {
+        return Err(format!("Backup file not found: {}", archive_path.display()).into());
+    }
+    let format = archive_format(&archive_path)?;
+    let storage_dir = ensure_persistent_storage_dir()?;
+
+    eprintln!("Restoring {} into {}", archive_path.display(), storage_dir.display());
+
+    let existing = library_files(&storage_dir)?;
+    if !existing.is_empty() {
+        eprintln!("{} Files in the backup will overwrite {} existing file(s) with the same path", Icon::Warning, existing.len());
+        if !yes && !confirm("Continue with the restore?")? {
+            return Err(TemplateError::Aborted("Aborted, nothing was restored. Use --yes to skip this confirmation.".to_string()).into());
+        }
+    }
+
+    extract_archive(format, &archive_path, &storage_dir)?;
+
+    // Backups keep config.toml at their root, next to the rest of storage
+    let restored_config = storage_dir.join(USER_CONFIG_FILE);
+    let config_path = user_config_path()?;
+    if restored_config.exists() && restored_config != config_path {
+        fs::rename(&restored_config, &config_path)?;
+    }
+
+    // Backups leave the cache out, so the discovery index is rebuilt for the restored templates
+    let index_path = ensure_cache_storage_dir()?.join(INDEX_FILE);
+    if index_path.exists() {
+        fs::remove_file(&index_path)?;
+    }
+    Template::discover_all()?;
+
+    eprintln!("{} Restore complete!", Icon::Success);
+    Ok(())
+}
+
+fn archive_format(path: &Path) -> Result<ArchiveFormat, Box<dyn std::error::Error>> {
+    ArchiveFormat::from_path(path).ok_or_else(|| {
+        format!("Unsupported backup format: {} (use .tar.zst or .tar.gz)", path.display()).into()
+    })
+}
+
+/// Lists everything in storage worth keeping: templates, history, objects, but not caches
+/// or the automatic backups taken before migrations. The discovery index in the cache is left
+/// out too; it holds absolute paths, so a restore rebuilds it instead.
+pub fn library_files(storage_dir: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let skipped = [CACHE_STORAGE, BACKUP_STORAGE].map(|dir| format!("{dir}/"));
+
+    let mut files = Vec::new();
+    collect_files(storage_dir, storage_dir, &mut files)?;
+    files.retain(|f| !skipped.iter().any(|prefix| f.starts_with(prefix.as_str())));
+    files.sort();
+    Ok(files)
+}
+
+/// Archive entries for a backup: the storage files plus config.toml when it lives in a separate
+/// config directory, stored at the archive root like it used to be
+pub fn library_entries(storage_dir: &Path, files: &[String]) -> Result<Vec<(PathBuf, String)>, Box<dyn std::error::Error>> {
+    let mut entries: Vec<(PathBuf, String)> = files.iter().map(|file| (storage_dir.join(file), file.clone())).collect();
+    let config_path = user_config_path()?;
+    if !config_path.starts_with(storage_dir) && config_path.exists() {
+        entries.push((config_path, USER_CONFIG_FILE.to_string()));
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::{TEMPLATE_STORAGE, TestStorage};
+    use crate::template::TEMPLATE_CONFIG_FILE;
+
+    #[test]
+    fn test_backup_and_restore() {
+        let storage = TestStorage::new();
+        let scratch = tempfile::tempdir().unwrap();
+        let backup = scratch.path().join("library.tar.gz");
+        let template_dir = storage.path().join(TEMPLATE_STORAGE).join("service");
+        fs::create_dir_all(&template_dir).unwrap();
+        fs::write(template_dir.join(TEMPLATE_CONFIG_FILE), "description = \"Service\"\n").unwrap();
+        fs::write(template_dir.join("main.rs"), "fn main() {}\n").unwrap();
+        fs::write(storage.path().join(USER_CONFIG_FILE), "protected = [\"secrets/\"]\n").unwrap();
+        Template::discover_all().unwrap();
+
+        handle_backup(&backup.display().to_string()).unwrap();
+        fs::remove_dir_all(&template_dir).unwrap();
+        fs::remove_file(storage.path().join(USER_CONFIG_FILE)).unwrap();
+        fs::remove_dir_all(storage.path().join(CACHE_STORAGE)).unwrap();
+
+        handle_restore(&backup.display().to_string(), true).unwrap();
+        assert_eq!(fs::read_to_string(template_dir.join("main.rs")).unwrap(), "fn main() {}\n");
+        assert_eq!(fs::read_to_string(storage.path().join(USER_CONFIG_FILE)).unwrap(), "protected = [\"secrets/\"]\n");
+        let index = fs::read_to_string(storage.path().join(CACHE_STORAGE).join(INDEX_FILE)).unwrap();
+        assert!(index.contains("Service"), "{index}");
+    }
+}
@@ -0,0 +1,106 @@
+use crate::commands::init::{check_clean_worktree, handle_init, ApplyOptions, Terminal};
+use crate::error::TemplateError;
+use crate::output::{is_plain, print_record};
+use crate::path::resolve_path;
+use crate::theme::Icon;
+
+use serde::Deserialize;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// A batch manifest: templates to apply in one run, in order
+#[derive(Debug, Deserialize)]
+struct BatchPlan {
+    #[serde(default, rename = "apply")]
+    entries: Vec<BatchEntry>,
+}
+
+/// One `[[apply]]` table of a batch manifest
+#[derive(Debug, Deserialize)]
+struct BatchEntry {
+    /// Name of the template to apply
+    template: String,
+    /// Target directory, relative to the manifest's directory unless absolute; created if missing
+    target: String,
+    /// Variables recorded for the target, like `init` records them in .template.lock
+    #[serde(default)]
+    variables: BTreeMap<String, String>,
+    /// Overwrite existing files
+    #[serde(default)]
+    force: bool,
+}
+
+/// Applies every entry of a batch manifest, then reports how each one went. Entries are
+/// independent: a failing entry is reported and the rest still run.
+pub fn handle_batch(plan_file: &str, dry_run: bool, yes: bool, allow_dirty: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let plan_path = resolve_path(plan_file, None)?;
+    let content = fs::read_to_string(&plan_path)
+        .map_err(|e| format!("Cannot read {}: {e}", plan_path.display()))?;
+    let plan: BatchPlan = toml::from_str(&content)
+        .map_err(|e| TemplateError::Config(format!("Failed to parse {}: {e}", plan_path.display())))?;
+    if plan.entries.is_empty() {
+        return Err(format!("{} has no [[apply]] entries", plan_path.display()).into());
+    }
+
+    let base_dir = plan_path.parent().unwrap_or_else(|| Path::new("."));
+    let targets = plan.entries.iter()
+        .map(|entry| resolve_path(&entry.target, Some(base_dir)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Entries dirty the worktree for the ones after them, so check it once before anything is written
+    if !allow_dirty {
+        let worktrees: BTreeSet<&Path> = targets.iter()
+            .filter_map(|target| target.ancestors().find(|p| p.exists()))
+            .collect();
+        for worktree in worktrees {
+            check_clean_worktree(worktree, dry_run, &mut Terminal::default())?;
+        }
+    }
+
+    let mut results = Vec::new();
+    for (index, (entry, target)) in plan.entries.iter().zip(&targets).enumerate() {
+        eprintln!("\n[{}/{}] {} -> {}", index + 1, plan.entries.len(), entry.template, target.display());
+        let options = ApplyOptions {
+            dry_run,
+            force: entry.force,
+            create_dir: true,
+            allow_existing: true,
+            allow_dirty: true,
+            yes,
+            default_excludes: true,
+            variables: entry.variables.clone(),
+            ..ApplyOptions::default()
+        };
+        let result = handle_init(Some(&entry.template), Some(target.display().to_string()), &options);
+        results.push((entry, target, result));
+    }
+
+    if !is_plain() {
+        eprintln!("\nBatch report:");
+    }
+    let mut failed = 0;
+    for (entry, target, result) in &results {
+        let target = target.display().to_string();
+        match result {
+            Ok(()) if is_plain() => print_record(&["ok", &entry.template, &target, "-"]),
+            Ok(()) => println!("{} {} -> {target}", Icon::Success, entry.template),
+            Err(e) if is_plain() => print_record(&["failed", &entry.template, &target, &e.to_string()]),
+            Err(e) => println!("{} {} -> {target}: {e}", Icon::Failure, entry.template),
+        }
+        if result.is_err() {
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{failed} of {} batch entries failed", results.len()).into());
+    }
+    if dry_run {
+        eprintln!("{} Dry run - {} entries would be applied", Icon::DryRun, results.len());
+    } else {
+        eprintln!("{} Applied {} entries", Icon::Success, results.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use crate::commands::init::{apply, ApplyOptions, Unattended};
+use crate::output::{is_plain, print_record};
+use crate::receipt::ReceiptTarget;
+use crate::timing;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Timings for one apply of the template
+struct Sample {
+    /// Time per phase, in the order `timing::totals` reports them
+    phases: Vec<(&'static str, Duration)>,
+    total: Duration,
+}
+
+pub fn handle_bench(template: &str, iterations: u32, variables: BTreeMap<String, String>) -> Result<(), Box<dyn std::error::Error>> {
+    if iterations == 0 {
+        return Err("Iterations must be at least 1".into());
+    }
+
+    eprintln!("Benchmarking template: {template} ({iterations} iterations)");
+
+    // Every iteration goes through the same pipeline as `new`, minus output, prompts, hooks and history
+    let options = ApplyOptions {
+        create_dir: true,
+        allow_dirty: true,
+        no_hooks: true,
+        no_history: true,
+        default_excludes: true,
+        receipt: Some(ReceiptTarget::None),
+        variables,
+        ..ApplyOptions::default()
+    };
+    let scratch = tempfile::Builder::new().prefix("template-rs-bench-").tempdir()?;
+
+    let was_enabled = timing::is_enabled();
+    timing::set_enabled(true);
+    let samples = run_samples(template, iterations, &options, scratch.path());
+    timing::set_enabled(was_enabled);
+    let (samples, file_count, byte_count) = samples?;
+
+    eprintln!("Template size: {file_count} file(s), {byte_count} bytes\n");
+    if !is_plain() {
+        println!("  {:<10} {:>10} {:>10} {:>10}", "phase", "min", "mean", "max");
+    }
+    let phases: Vec<&str> = samples[0].phases.iter().map(|(name, _)| *name).collect();
+    for (index, name) in phases.into_iter().enumerate() {
+        print_phase(name, samples.iter().map(|s| s.phases[index].1));
+    }
+    print_phase("other", samples.iter().map(|s| s.total.saturating_sub(s.phases.iter().map(|(_, time)| *time).sum())));
+    print_phase("total", samples.iter().map(|s| s.total));
+
+    Ok(())
+}
+
+/// Applies the template `iterations` times into fresh directories under `scratch`, returning
+/// the samples and how many files and bytes the last apply wrote
+fn run_samples(
+    template: &str,
+    iterations: u32,
+    options: &ApplyOptions,
+    scratch: &Path,
+) -> Result<(Vec<Sample>, usize, u64), Box<dyn std::error::Error>> {
+    let mut samples = Vec::new();
+    let mut file_count = 0;
+    let mut byte_count = 0;
+
+    for iteration in 0..iterations {
+        let target_dir = scratch.join(iteration.to_string());
+        timing::reset();
+        let start = Instant::now();
+        let report = apply(Some(template), &target_dir, options, &mut Unattended)?;
+        let total = start.elapsed();
+
+        samples.push(Sample { phases: timing::totals(), total });
+        file_count = report.written.len();
+        byte_count = report.written.iter()
+            .filter_map(|file| fs::metadata(target_dir.join(file)).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+    }
+
+    Ok((samples, file_count, byte_count))
+}
+
+fn print_phase(name: &str, durations: impl Iterator<Item = Duration>) {
+    let durations: Vec<Duration> = durations.collect();
+    let min = durations.iter().min().copied().unwrap_or_default();
+    let max = durations.iter().max().copied().unwrap_or_default();
+    let total: Duration = durations.iter().sum();
+    let mean = total / u32::try_from(durations.len()).unwrap_or(u32::MAX).max(1);
+
+    if is_plain() {
+        // Milliseconds without the unit so the columns parse as numbers
+        let ms = |d: Duration| format!("{:.3}", d.as_secs_f64() * 1000.0);
+        print_record(&[name, &ms(min), &ms(mean), &ms(max)]);
+    } else {
+        println!("  {name:<10} {:>10} {:>10} {:>10}", format_ms(min), format_ms(mean), format_ms(max));
+    }
+}
+
+fn format_ms(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
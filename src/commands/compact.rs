@@ -0,0 +1,143 @@
+use crate::archive::{ArchiveFormat, create_archive};
+use crate::file::{format_size, remove_empty_dirs};
+use crate::history::{UsageHistory, now_secs};
+use crate::output::{is_plain, print_record};
+use crate::template::{Template, TEMPLATE_ARCHIVE_FILE, TEMPLATE_CONFIG_FILE};
+use crate::theme::Icon;
+
+use std::fs;
+use std::time::UNIX_EPOCH;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub fn handle_compact(days: u64, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    eprintln!("Compacting templates unused for {days} day(s)...");
+
+    let history = UsageHistory::load()?;
+    let cutoff = now_secs().saturating_sub(days.saturating_mul(SECONDS_PER_DAY));
+    let mut compacted = 0;
+    let mut saved = 0;
+
+    for template in Template::discover_stored()? {
+        if template.is_packed() || template.is_compacted() {
+            continue;
+        }
+
+        // Templates that were never applied count from when their config was last touched
+        let last_active = match history.last_used(&template.name) {
+            Some(last_used) => last_used,
+            None => config_modified_secs(&template)?,
+        };
+        if last_active > cutoff {
+            continue;
+        }
+
+        let before = template.size_report()?.total;
+        if dry_run {
+            if is_plain() {
+                print_record(&["would-compact", &template.name, &before.to_string()]);
+            } else {
+                println!("  would compact {} ({})", template.name, format_size(before));
+            }
+            compacted += 1;
+            continue;
+        }
+
+        compact_template(&template)?;
+        let after = template.size_report()?.total;
+        if is_plain() {
+            print_record(&["compacted", &template.name, &before.to_string(), &after.to_string()]);
+        } else {
+            println!("  compacted {} ({} -> {})", template.name, format_size(before), format_size(after));
+        }
+
+        compacted += 1;
+        saved += before.saturating_sub(after);
+    }
+
+    if dry_run {
+        eprintln!("{} Dry run - {compacted} template(s) would be compacted", Icon::DryRun);
+    } else {
+        eprintln!("{} Compacted {compacted} template(s), saved {}", Icon::Success, format_size(saved));
+        eprintln!("Compacted templates are extracted to the cache when used; `{} unpack` restores one for editing", crate::BIN_NAME);
+    }
+
+    Ok(())
+}
+
+/// Compresses a template's files into an archive in its directory and removes the originals
+fn compact_template(template: &Template) -> Result<(), Box<dyn std::error::Error>> {
+    let files = template.files()?;
+    let archive_path = template.path.join(TEMPLATE_ARCHIVE_FILE);
+
+    create_archive(ArchiveFormat::Gzip, &template.path, &files, &archive_path)?;
+
+    for file in &files {
+        fs::remove_file(template.path.join(file))?;
+    }
+    remove_empty_dirs(&template.path)?;
+    Ok(())
+}
+
+fn config_modified_secs(template: &Template) -> Result<u64, Box<dyn std::error::Error>> {
+    let modified = fs::metadata(template.path.join(TEMPLATE_CONFIG_FILE))?.modified()?;
+    Ok(modified.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::{TEMPLATE_STORAGE, TestStorage};
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    /// Writes a template whose config was last touched `days_ago`
+    fn write_template(root: &Path, name: &str, days_ago: u64) {
+        let dir = root.join(name);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        let config = fs::File::create(dir.join(TEMPLATE_CONFIG_FILE)).unwrap();
+        config.set_modified(SystemTime::now() - Duration::from_secs(days_ago * SECONDS_PER_DAY)).unwrap();
+    }
+
+    #[test]
+    fn test_compacts_cold_templates_only() {
+        let storage = TestStorage::new();
+        let root = storage.path().join(TEMPLATE_STORAGE);
+        write_template(&root, "cold", 90);
+        write_template(&root, "fresh", 1);
+        write_template(&root, "used", 90);
+        UsageHistory::record_use("used", None).unwrap();
+
+        handle_compact(30, true).unwrap();
+        assert!(!root.join("cold").join(TEMPLATE_ARCHIVE_FILE).exists());
+
+        handle_compact(30, false).unwrap();
+        assert!(root.join("cold").join(TEMPLATE_ARCHIVE_FILE).exists());
+        assert!(!root.join("cold/src").exists());
+        for name in ["fresh", "used"] {
+            assert!(!root.join(name).join(TEMPLATE_ARCHIVE_FILE).exists(), "{name} was compacted");
+            assert!(root.join(name).join("src/main.rs").exists());
+        }
+    }
+
+    #[test]
+    fn test_compacted_templates_are_used_from_the_cache() {
+        let storage = TestStorage::new();
+        let root = storage.path().join(TEMPLATE_STORAGE);
+        write_template(&root, "cold", 90);
+        handle_compact(30, false).unwrap();
+
+        let template = Template::find("cold").unwrap().unwrap();
+        let content_dir = template.content_dir().unwrap();
+        assert!(!content_dir.starts_with(&template.path));
+        assert_eq!(fs::read_to_string(content_dir.join("src/main.rs")).unwrap(), "fn main() {}\n");
+        assert_eq!(template.files().unwrap(), ["src/main.rs"]);
+        // Using it leaves it compacted; only unpacking brings the files back
+        assert!(template.is_compacted() && !root.join("cold/src").exists());
+
+        template.inflate().unwrap();
+        assert!(!template.is_compacted());
+        assert_eq!(fs::read_to_string(root.join("cold/src/main.rs")).unwrap(), "fn main() {}\n");
+    }
+}
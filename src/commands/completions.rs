@@ -0,0 +1,83 @@
+use crate::config::UserConfig;
+use crate::path::xdg_home;
+use crate::preset::PRESET_PREFIX;
+use crate::template::Template;
+
+use clap_complete::aot::Shell;
+use clap_complete::engine::CompletionCandidate;
+use clap_complete::env::Shells;
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Environment variable the shell sets when asking the binary for completions
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Prints the script registering completions for `shell`. The script calls back into the binary
+/// for every completion, so subcommands, flags and template names are always current.
+pub fn handle_completions(shell: Shell) -> Result<(), Box<dyn std::error::Error>> {
+    write_registration(shell, &mut io::stdout())
+}
+
+/// Writes the completion registration script for `shell`
+pub fn write_registration(shell: Shell, out: &mut dyn Write) -> Result<(), Box<dyn std::error::Error>> {
+    let shell_name = shell.to_string();
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell_name)
+        .ok_or_else(|| format!("Completions are not available for {shell_name}"))?;
+    let bin = crate::BIN_NAME;
+    completer.write_registration(COMPLETE_VAR, bin, bin, bin, out)?;
+    Ok(())
+}
+
+/// Where `shell` loads completions from on its own, for shells that have such a directory:
+/// bash-completion's user directory and fish's completions directory
+pub fn registration_path(shell: Shell) -> Option<PathBuf> {
+    let bin = crate::BIN_NAME;
+    match shell {
+        Shell::Bash => Some(xdg_home("XDG_DATA_HOME", ".local/share").join("bash-completion/completions").join(bin)),
+        Shell::Fish => Some(xdg_home("XDG_CONFIG_HOME", ".config").join("fish/completions").join(format!("{bin}.fish"))),
+        _ => None,
+    }
+}
+
+/// Writes the registration script where `shell` picks it up by itself and returns that path;
+/// None for shells without a completions directory, which have to source it from their startup file
+pub fn install_registration(shell: Shell) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let Some(path) = registration_path(shell) else {
+        return Ok(None);
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut script = Vec::new();
+    write_registration(shell, &mut script)?;
+    fs::write(&path, script)?;
+    Ok(Some(path))
+}
+
+/// Names of all available templates, for completing template arguments. Completion must never
+/// fail loudly, so discovery errors just leave nothing to offer.
+pub fn template_candidates() -> Vec<CompletionCandidate> {
+    Template::discover_all()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|template| {
+            let help = template.description().map(|description| description.to_string().into());
+            CompletionCandidate::new(template.name).help(help)
+        })
+        .collect()
+}
+
+/// Template names plus the presets of config.toml, for `init` and `new`
+pub fn apply_candidates() -> Vec<CompletionCandidate> {
+    let presets = UserConfig::load().map(|config| config.presets).unwrap_or_default();
+    let mut candidates = template_candidates();
+    candidates.extend(presets.into_iter().map(|(name, preset)| {
+        let help = preset.description.unwrap_or(preset.template);
+        CompletionCandidate::new(format!("{PRESET_PREFIX}{name}")).help(Some(help.into()))
+    }));
+    candidates
+}
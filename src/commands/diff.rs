@@ -0,0 +1,141 @@
+use crate::commands::init::{ApplyOptions, Terminal, supplied_variables};
+use crate::config::UserConfig;
+use crate::eol::EolPolicy;
+use crate::error::TemplateError;
+use crate::lock::{TEMPLATE_LOCK_FILE, TemplateLock};
+use crate::objects::is_executable;
+use crate::output::{is_plain, print_record};
+use crate::overrides::override_content;
+use crate::patch::{FileChange, format_git_patch};
+use crate::path::resolve_path;
+use crate::render::TemplateRenderer;
+use crate::template::Template;
+use crate::theme::Icon;
+use crate::variables::resolve_values;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+
+/// How a file of the project differs from the template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    /// The template has it, the project doesn't
+    Added,
+    /// Both have it with different content
+    Changed,
+    /// The project got it from the template, which no longer has it
+    Removed,
+}
+
+impl Status {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Changed => "changed",
+            Self::Removed => "removed",
+        }
+    }
+}
+
+/// Compares a project with the template it was created from (the one in .template.lock, or
+/// `template`) and prints the differences as a git-style patch from the project to the template:
+/// what `init --force` would change. With `name_status` only the paths are listed.
+pub fn handle_diff(
+    path: Option<String>,
+    template: Option<&str>,
+    name_status: bool,
+    variables: BTreeMap<String, String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let target_path = match path {
+        Some(p) => resolve_path(&p, None)?,
+        None => env::current_dir()?,
+    };
+    let lock = TemplateLock::load(&target_path)?;
+    let name = match (template, &lock) {
+        (Some(name), _) => name.to_string(),
+        (None, Some(lock)) => lock.template.clone(),
+        (None, None) => return Err(format!(
+            "No {TEMPLATE_LOCK_FILE} found in {}. Name the template to compare with: --template NAME",
+            target_path.display()
+        ).into()),
+    };
+    let template_info = Template::find(&name)?.ok_or_else(|| TemplateError::NotFound(name.clone()))?;
+    // What was recorded only describes the project for the template it was recorded for
+    let lock = lock.filter(|lock| lock.template == template_info.name);
+
+    let user_config = UserConfig::load()?;
+    let default_excludes = lock.as_ref().is_none_or(|lock| lock.options.default_excludes);
+    let template_files = template_info.apply_files(&user_config.excludes(default_excludes))?;
+    let options = ApplyOptions { default_excludes, variables, ..ApplyOptions::default() };
+    let supplied = supplied_variables(&template_info, &target_path, &options, lock.as_ref(), &user_config, &mut Terminal::default());
+    let declared = template_info.config.variables.clone().unwrap_or_default();
+    let values = resolve_values(&declared, &supplied, &mut |_| Ok(None), &mut |_, _| Ok(())).unwrap_or_else(|e| {
+        eprintln!("{} Template '{}' has {e}; its placeholders are compared as they are", Icon::Warning, template_info.name);
+        supplied
+    });
+    let renderer = TemplateRenderer::new(&template_info, values)?;
+    let eol = EolPolicy::new(&template_info, &user_config);
+    let content_dir = template_info.content_dir()?;
+
+    let mut changes = Vec::new();
+    for file in &template_files {
+        let path = renderer.render_path(file)?;
+        // Compare with what applying would write: rendered, with the project's line endings,
+        // or the project's override in its place
+        let upstream = match override_content(&target_path, &path)? {
+            Some(content) => content,
+            None => {
+                let content = fs::read(content_dir.join(file))?;
+                let content = renderer.apply(file, &content)?.unwrap_or(content);
+                eol.apply(file, &content).unwrap_or(content)
+            }
+        };
+        let local_path = target_path.join(&path);
+        let local = if local_path.is_file() { Some(fs::read(&local_path)?) } else { None };
+        let status = match &local {
+            None => Status::Added,
+            Some(local) if *local != upstream => Status::Changed,
+            Some(_) => continue,
+        };
+        let executable = is_executable(&content_dir.join(file))?;
+        changes.push((status, FileChange { path, old: local, new: Some(upstream), executable }));
+    }
+    for file in lock.iter().flat_map(|lock| lock.files.keys()).filter(|file| !template_files.contains(file)) {
+        let path = renderer.render_path(file)?;
+        let local_path = target_path.join(&path);
+        if local_path.is_file() {
+            let executable = is_executable(&local_path)?;
+            changes.push((Status::Removed, FileChange { path, old: Some(fs::read(&local_path)?), new: None, executable }));
+        }
+    }
+
+    if name_status || is_plain() {
+        for (status, change) in &changes {
+            if is_plain() {
+                print_record(&[status.label(), &change.path]);
+            } else {
+                println!("{:<8} {}", status.label(), change.path);
+            }
+        }
+    } else {
+        let files: Vec<FileChange> = changes.iter().map(|(_, change)| change.clone()).collect();
+        print!("{}", format_git_patch(&files));
+    }
+
+    if !is_plain() {
+        let count = |status: Status| changes.iter().filter(|(s, _)| *s == status).count();
+        if changes.is_empty() {
+            eprintln!("{} {} matches template '{}'", Icon::Success, target_path.display(), template_info.name);
+        } else {
+            eprintln!(
+                "{} added, {} changed, {} removed compared with template '{}'",
+                count(Status::Added),
+                count(Status::Changed),
+                count(Status::Removed),
+                template_info.name
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use crate::config::UserConfig;
+use crate::copy::CopyEngineKind;
+use crate::file::{ensure_template_storage_dir, find_in_path};
+use crate::output::{is_plain, print_record};
+use crate::path::storage_dirs;
+use crate::template::{Template, template_roots};
+use crate::theme::Icon;
+
+/// Outcome of a single doctor check
+struct Check {
+    name: &'static str,
+    ok: bool,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, ok: bool, detail: impl Into<String>) -> Self {
+        Self { name, ok, detail: detail.into() }
+    }
+}
+
+/// Checks the installation for problems that make commands fail or behave oddly
+pub fn handle_doctor() -> Result<(), Box<dyn std::error::Error>> {
+    let dirs = storage_dirs();
+    let mut checks = vec![
+        Check::new("storage", true, dirs.data.display().to_string()),
+        Check::new("config-dir", true, dirs.config.display().to_string()),
+    ];
+
+    let config = UserConfig::load();
+    let copy_engine = config.as_ref().map(|c| c.copy_engine).unwrap_or_default();
+    checks.push(match config {
+        Ok(_) => Check::new("config", true, "config.toml is valid"),
+        Err(e) => Check::new("config", false, e.to_string()),
+    });
+
+    // rsync is only needed when config.toml asks for it instead of the built-in copy
+    checks.push(match (find_in_path("rsync"), copy_engine) {
+        (Some(path), _) => Check::new("rsync", true, path.display().to_string()),
+        (None, CopyEngineKind::Native) => Check::new("rsync", true, "not installed; not needed by the built-in copy engine"),
+        (None, CopyEngineKind::Rsync) => {
+            Check::new("rsync", false, "rsync was not found on PATH; copy_engine = \"rsync\" needs it")
+        }
+    });
+
+    // Configured roots are only searched when they exist, so a missing one just hides its templates
+    if let Ok(roots) = template_roots() {
+        for root in roots.iter().skip(1) {
+            checks.push(if root.path.is_dir() {
+                Check::new("root", true, format!("{} ({})", root.label, root.path.display()))
+            } else {
+                Check::new("root", false, format!("{} ({}) doesn't exist, its templates are not found", root.label, root.path.display()))
+            });
+        }
+    }
+
+    let cycles = Template::symlink_cycles(&ensure_template_storage_dir()?)?;
+    if cycles.is_empty() {
+        checks.push(Check::new("symlinks", true, "no symlink cycles in template storage"));
+    }
+    for cycle in cycles {
+        checks.push(Check::new(
+            "symlinks",
+            false,
+            format!("{} loops back to {} and is skipped", cycle.path.display(), cycle.target.display()),
+        ));
+    }
+
+    for check in &checks {
+        if is_plain() {
+            print_record(&[check.name, if check.ok { "ok" } else { "problem" }, &check.detail]);
+        } else {
+            let icon = if check.ok { Icon::Success } else { Icon::Failure };
+            println!("{icon} {:<10} {}", check.name, check.detail);
+        }
+    }
+
+    let problems = checks.iter().filter(|c| !c.ok).count();
+    if problems > 0 {
+        return Err(format!("{problems} problem(s) found").into());
+    }
+    eprintln!("\nNo problems found");
+    Ok(())
+}
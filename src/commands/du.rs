@@ -0,0 +1,101 @@
+use crate::file::{dir_size, ensure_persistent_storage_dir, format_size};
+use crate::output::{is_plain, print_record};
+use crate::template::Template;
+use crate::theme::Icon;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+pub fn handle_du() -> Result<(), Box<dyn std::error::Error>> {
+    let storage_dir = ensure_persistent_storage_dir()?;
+
+    // Top-level storage areas (templates/, objects/, cache/, versions/) and loose files (config, history)
+    let mut areas = Vec::new();
+    for entry in fs::read_dir(&storage_dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if file_type.is_dir() {
+            areas.push((format!("{name}/"), dir_size(entry.path())?));
+        } else if file_type.is_file() {
+            areas.push((name, entry.metadata()?.len()));
+        }
+    }
+    sort_largest_first(&mut areas);
+
+    let total: u64 = areas.iter().map(|(_, size)| size).sum();
+    if is_plain() {
+        for (name, size) in &areas {
+            print_record(&["storage", name, &size.to_string()]);
+        }
+    } else {
+        println!("{} {} ({})\n", Icon::Disk, storage_dir.display(), format_size(total));
+        for (name, size) in &areas {
+            println!("  {:>10}  {name}", format_size(*size));
+        }
+    }
+
+    let templates = Template::discover_stored()?;
+    if templates.is_empty() {
+        return Ok(());
+    }
+
+    let mut sizes = Vec::new();
+    let mut namespaces: BTreeMap<String, (u64, usize)> = BTreeMap::new();
+    for template in &templates {
+        let size = template.size_report()?.total;
+
+        let storage = if template.is_packed() {
+            "packed"
+        } else if template.is_compacted() {
+            "compacted"
+        } else {
+            "files"
+        };
+        sizes.push((template.name.clone(), size, storage));
+
+        if let Some((namespace, _)) = template.name.rsplit_once('/') {
+            let entry = namespaces.entry(format!("{namespace}/")).or_default();
+            entry.0 += size;
+            entry.1 += 1;
+        }
+    }
+
+    if !namespaces.is_empty() {
+        let mut namespaces: Vec<(String, (u64, usize))> = namespaces.into_iter().collect();
+        namespaces.sort_by(|a, b| b.1.0.cmp(&a.1.0).then_with(|| a.0.cmp(&b.0)));
+
+        if !is_plain() {
+            println!("\n{} Namespaces:\n", Icon::Folder);
+        }
+        for (namespace, (size, count)) in &namespaces {
+            if is_plain() {
+                print_record(&["namespace", namespace, &size.to_string(), &count.to_string()]);
+            } else {
+                println!("  {:>10}  {namespace} ({count} template(s))", format_size(*size));
+            }
+        }
+    }
+
+    sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    if !is_plain() {
+        println!("\n{} Templates:\n", Icon::Package);
+    }
+    for (name, size, storage) in &sizes {
+        if is_plain() {
+            print_record(&["template", name, &size.to_string(), storage]);
+        } else if *storage == "files" {
+            println!("  {:>10}  {name}", format_size(*size));
+        } else {
+            println!("  {:>10}  {name} ({storage})", format_size(*size));
+        }
+    }
+
+    eprintln!("\nUse `{} prune` to drop old template versions and unreferenced objects", crate::BIN_NAME);
+    Ok(())
+}
+
+/// Sorts (name, size) pairs by size descending, then by name
+fn sort_largest_first(entries: &mut [(String, u64)]) {
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+}
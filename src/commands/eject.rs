@@ -0,0 +1,110 @@
+use crate::builtin::BUILTIN_PREFIX;
+use crate::copy::{process_umask, Change, CopyEngine, CopyPlan, NativeCopy, Ownership, Timestamps};
+use crate::error::TemplateError;
+use crate::file::{create_dir_if_missing, ensure_template_storage_dir};
+use crate::lock::TEMPLATE_LOCK_FILE;
+use crate::names::{check_name, template_name};
+use crate::objects::store_blob;
+use crate::oci::{oci_reference, pull_template};
+use crate::origin::{TemplateOrigin, TEMPLATE_ORIGIN_FILE};
+use crate::template::{Template, TEMPLATE_ARCHIVE_FILE, TEMPLATE_MANIFEST_FILE};
+use crate::theme::Icon;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Copies a template (typically a built-in one, or one pulled from a registry) into the template
+/// storage for customization, recording where it came from in .template-origin.toml. `normalize`
+/// applies the naming policy to the destination name.
+pub fn handle_eject(template: &str, destination: Option<&str>, normalize: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let pulled = oci_reference(template).map(|_| pull_template(template)).transpose()?;
+    let template_info = match &pulled {
+        Some(pulled) => pulled.template.clone(),
+        None => Template::find(template)?.ok_or_else(|| TemplateError::NotFound(template.to_string()))?,
+    };
+
+    let destination = match (destination, oci_reference(template)) {
+        (Some(destination), _) => template_name(destination, normalize)?,
+        (None, Some(reference)) => template_name(&registry_name(reference), normalize)?,
+        (None, None) => template_info.name.trim_start_matches(BUILTIN_PREFIX).to_string(),
+    };
+    let dest_path = storage_destination(&destination)?;
+
+    eprintln!("Ejecting '{}' into {}", template_info.name, dest_path.display());
+    create_dir_if_missing(&dest_path)?;
+
+    let mut origin = TemplateOrigin::new(&template_info.name, template_info.version());
+    for file in copy_template(&template_info, &dest_path)? {
+        let target = dest_path.join(&file);
+        if target.symlink_metadata()?.file_type().is_symlink() {
+            continue;
+        }
+        // Keep the upstream content in the object store so it can be diffed against later
+        let hash = store_blob(&fs::read(&target)?)?;
+        origin.files.insert(file, hash);
+    }
+    origin.save(&dest_path)?;
+
+    eprintln!("{} Ejected {} file(s) as '{destination}'", Icon::Success, origin.files.len());
+    eprintln!("{} Customize it in {}", Icon::Folder, dest_path.display());
+    Ok(())
+}
+
+/// Default name of a template ejected from a registry: the last part of the repository,
+/// without the tag or digest (`oci://ghcr.io/org/rust-cli:1.2` is `rust-cli`)
+fn registry_name(reference: &str) -> String {
+    let repository = reference.rsplit('/').next().unwrap_or(reference);
+    repository.split(['@', ':']).next().unwrap_or(repository).to_string()
+}
+
+/// Where a new template named `destination` goes in the template storage. Fails for names that
+/// aren't plain relative paths, the built-in prefix, and names already taken.
+pub fn storage_destination(destination: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    check_name(destination)?;
+
+    let dest_path = ensure_template_storage_dir()?.join(destination);
+    if dest_path.exists() {
+        return Err(format!("{} already exists; choose another name or remove it first", dest_path.display()).into());
+    }
+    Ok(dest_path)
+}
+
+/// Copies the template's content files plus the metadata that makes it a template (config, README,
+/// ignore file, tests, snapshots, plugins) into `dest_path`, keeping their permissions. Returns the
+/// copied files as relative paths.
+fn copy_template(template: &Template, dest_path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let content_dir = template.content_dir()?;
+    let mut sources = vec![content_dir.as_path()];
+    // Packed templates keep their content in the object store and their metadata next to the manifest
+    if content_dir != template.path {
+        sources.push(&template.path);
+    }
+
+    let mut files = Vec::new();
+    for source in sources {
+        let plan = CopyPlan {
+            source,
+            target: dest_path,
+            excludes: [TEMPLATE_MANIFEST_FILE, TEMPLATE_ARCHIVE_FILE, TEMPLATE_LOCK_FILE, TEMPLATE_ORIGIN_FILE]
+                .map(String::from)
+                .to_vec(),
+            protected: Vec::new(),
+            overwrite: false,
+            delete: false,
+            dry_run: false,
+            ownership: Ownership::Invoker,
+            mode_mask: process_umask(),
+            timestamps: Timestamps::Now,
+        };
+        for change in NativeCopy.copy(&plan, &mut |_| Ok(()))? {
+            if let Change::Copy(path) = change
+                && !path.ends_with('/')
+            {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
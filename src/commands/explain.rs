@@ -0,0 +1,194 @@
+use crate::commands::init::{ApplyOptions, Terminal, metadata_excludes, supplied_variables};
+use crate::config::{DEFAULT_EXCLUDES, DEFAULT_PROTECTED, UserConfig};
+use crate::copy::CopyEngineKind;
+use crate::error::TemplateError;
+use crate::lock::TemplateLock;
+use crate::oci::{oci_reference, pull_template};
+use crate::output::{is_plain, print_record};
+use crate::overrides::TEMPLATE_OVERRIDES_DIR;
+use crate::path::resolve_path;
+use crate::patterns::{matches_dir, matches_pattern};
+use crate::render::TemplateRenderer;
+use crate::template::Template;
+use crate::variables::resolve_values;
+
+use std::path::Path;
+
+/// What applying the template would do to one path of the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Create,
+    Overwrite,
+    Skip,
+    Delete,
+    Keep,
+}
+
+impl Action {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Overwrite => "overwrite",
+            Self::Skip => "skip",
+            Self::Delete => "delete",
+            Self::Keep => "keep",
+        }
+    }
+}
+
+/// The decision for one path and why it was made
+struct Decision {
+    path: String,
+    action: Action,
+    reason: String,
+}
+
+/// What the decisions depend on: the template's files under their names in the target, the
+/// exclusion and protection rules with where each came from, and the options
+struct Planner<'a> {
+    template: &'a Template,
+    target: &'a Path,
+    options: &'a ApplyOptions,
+    /// (path in the target, path in the template)
+    files: Vec<(String, String)>,
+    /// (pattern, where it comes from)
+    excludes: Vec<(String, &'static str)>,
+    protected: Vec<(String, &'static str)>,
+}
+
+/// Reports, for paths of the target, exactly what applying the template would do to them and
+/// why: the exclude rule that skips them, the existing file that is kept, the option that
+/// overwrites or deletes. Without paths, every file of the template is explained.
+pub fn handle_explain(
+    template: &str,
+    target: &str,
+    paths: &[String],
+    options: &ApplyOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let target_path = resolve_path(target, None)?;
+    let pulled = oci_reference(template).map(|_| pull_template(template)).transpose()?;
+    let template_info = match &pulled {
+        Some(pulled) => pulled.template.clone(),
+        None => Template::find(template)?.ok_or_else(|| TemplateError::NotFound(template.to_string()))?,
+    };
+    let user_config = UserConfig::load()?;
+    let planner = Planner::new(&template_info, &target_path, options, &user_config)?;
+
+    let engine = match user_config.copy_engine {
+        CopyEngineKind::Native => "built-in copy",
+        CopyEngineKind::Rsync => "rsync",
+    };
+    let mode = match (options.force, options.delete) {
+        (false, false) => "existing files are kept, nothing is deleted",
+        (true, false) => "--force overwrites existing files, nothing is deleted",
+        (false, true) => "existing files are kept, --delete removes files the template doesn't have",
+        (true, true) => "--force and --delete make the target match the template",
+    };
+    eprintln!("Template: {}", template_info.name);
+    eprintln!("Target: {}", target_path.display());
+    eprintln!("Strategy: {engine}; {mode}\n");
+
+    let paths: Vec<String> = if paths.is_empty() {
+        planner.files.iter().map(|(path, _)| path.clone()).collect()
+    } else {
+        paths.iter().map(|path| path.trim_start_matches("./").trim_end_matches('/').to_string()).collect()
+    };
+    for path in &paths {
+        for decision in planner.explain(path) {
+            if is_plain() {
+                print_record(&[&decision.path, decision.action.label(), &decision.reason]);
+            } else {
+                println!("{} {}", decision.action.label(), decision.path);
+                println!("  {}", decision.reason);
+            }
+        }
+    }
+    Ok(())
+}
+
+impl<'a> Planner<'a> {
+    fn new(template: &'a Template, target: &'a Path, options: &'a ApplyOptions, user_config: &UserConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut excludes: Vec<(String, &'static str)> = metadata_excludes().into_iter().map(|p| (p, "template metadata")).collect();
+        if options.default_excludes && user_config.default_excludes {
+            excludes.extend(DEFAULT_EXCLUDES.iter().map(|p| ((*p).to_string(), "built-in exclude")));
+        }
+        excludes.extend(user_config.exclude.iter().map(|p| (p.clone(), "exclude in config.toml")));
+        excludes.extend(template.ignore_patterns()?.into_iter().map(|p| (p, "the template's .templateignore")));
+
+        let mut protected: Vec<(String, &'static str)> = DEFAULT_PROTECTED.iter().map(|p| ((*p).to_string(), "built-in protection")).collect();
+        protected.extend(user_config.protected.iter().map(|p| (p.clone(), "protected in config.toml")));
+
+        // Names with variables in them are explained under the name they get in the target
+        let previous_lock = TemplateLock::load(target)?;
+        let supplied = supplied_variables(template, target, options, previous_lock.as_ref(), user_config, &mut Terminal::default());
+        let declared = template.config.variables.clone().unwrap_or_default();
+        let values = resolve_values(&declared, &supplied, &mut |_| Ok(None), &mut |_, _| Ok(())).unwrap_or(supplied);
+        let renderer = TemplateRenderer::new(template, values)?;
+        let files = template.list_files()?.into_iter()
+            .map(|file| (renderer.render_path(&file).unwrap_or_else(|_| file.clone()), file))
+            .collect();
+
+        Ok(Self { template, target, options, files, excludes, protected })
+    }
+
+    /// The decisions for a path; a directory of the template gets one for each file below it
+    fn explain(&self, path: &str) -> Vec<Decision> {
+        if let Some((_, file)) = self.files.iter().find(|(rendered, _)| rendered == path) {
+            return vec![self.explain_template_file(path, file)];
+        }
+        let below: Vec<Decision> = self.files.iter()
+            .filter(|(rendered, _)| rendered.starts_with(&format!("{path}/")))
+            .map(|(rendered, file)| self.explain_template_file(rendered, file))
+            .collect();
+        if below.is_empty() { vec![self.explain_extra(path)] } else { below }
+    }
+
+    /// A path the template has a file for
+    fn explain_template_file(&self, path: &str, file: &str) -> Decision {
+        let decide = |action, reason: String| Decision { path: path.to_string(), action, reason };
+        if let Some((pattern, source)) = self.excludes.iter().find(|(pattern, _)| matches_pattern(file, pattern)) {
+            return decide(Action::Skip, format!("excluded by '{pattern}' ({source})"));
+        }
+
+        let exists = self.target.join(path).exists();
+        let decision = if !exists {
+            decide(Action::Create, "the template has it and the target doesn't".to_string())
+        } else if self.template.is_create_once(file) {
+            decide(Action::Skip, "the target has it and it is create_once, so it is never overwritten, even with --force".to_string())
+        } else if self.options.force {
+            decide(Action::Overwrite, "the target has it and --force overwrites existing files".to_string())
+        } else {
+            decide(Action::Skip, "the target already has it, and existing files are only overwritten with --force".to_string())
+        };
+        self.with_override(decision)
+    }
+
+    /// A path the template doesn't have
+    fn explain_extra(&self, path: &str) -> Decision {
+        let decide = |action, reason: String| Decision { path: path.to_string(), action, reason };
+        if !self.target.join(path).exists() {
+            return decide(Action::Skip, "neither the template nor the target has it".to_string());
+        }
+        if !self.options.delete {
+            return decide(Action::Keep, "the template doesn't have it, and only --delete removes such files".to_string());
+        }
+        let is_dir = self.target.join(path).is_dir();
+        let matches = |pattern: &str| if is_dir { matches_dir(path, pattern) } else { matches_pattern(path, pattern) };
+        if let Some((pattern, source)) = self.excludes.iter().find(|(pattern, _)| matches(pattern)) {
+            return decide(Action::Keep, format!("excluded by '{pattern}' ({source}), and excluded paths are never deleted"));
+        }
+        if let Some((pattern, source)) = self.protected.iter().find(|(pattern, _)| matches(pattern)) {
+            return decide(Action::Keep, format!("protected by '{pattern}' ({source})"));
+        }
+        decide(Action::Delete, "the template doesn't have it and --delete removes such files".to_string())
+    }
+
+    /// Notes that the project's override replaces whatever the template writes
+    fn with_override(&self, mut decision: Decision) -> Decision {
+        let has_override = self.target.join(TEMPLATE_OVERRIDES_DIR).join(&decision.path).is_file();
+        if has_override {
+            decision.reason.push_str(&format!("; afterwards {TEMPLATE_OVERRIDES_DIR}/{} replaces it", decision.path));
+        }
+        decision
+    }
+}
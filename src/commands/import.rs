@@ -0,0 +1,81 @@
+use crate::commands::add::default_name;
+use crate::commands::eject::storage_destination;
+use crate::git;
+use crate::import::{Conversion, ImportFormat, convert_cargo_generate, convert_cookiecutter};
+use crate::names::template_name;
+use crate::output::{is_plain, print_record};
+use crate::template::TEMPLATE_CONFIG_FILE;
+use crate::theme::Icon;
+
+use std::fs;
+use std::path::Path;
+
+/// Converts a template of another tool, from a local directory or a git repository, into the
+/// native format and stores it. `normalize` applies the naming policy to the name it is stored
+/// under; with `dry_run` the converted .template.toml and files are shown instead.
+pub fn handle_import(
+    format: ImportFormat,
+    source: &str,
+    name: Option<&str>,
+    reference: Option<&str>,
+    normalize: bool,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => default_name(source, None)
+            .ok_or_else(|| format!("Cannot tell a template name from '{source}', pass one with --name"))?,
+    };
+    let name = template_name(&name, normalize)?;
+    let dest_path = storage_destination(&name)?;
+
+    let local = Path::new(source);
+    let mut conversion = if local.is_dir() {
+        if reference.is_some() {
+            return Err("--ref only applies to git repositories, not local directories".into());
+        }
+        convert(format, local)?
+    } else {
+        let scratch = tempfile::Builder::new().prefix("template-rs-import-").tempdir()?;
+        let checkout = scratch.path().join("checkout");
+        eprintln!("Cloning {source}{}", reference.map(|r| format!(" at {r}")).unwrap_or_default());
+        git::clone(source, reference, &checkout)?;
+        convert(format, &checkout)?
+    };
+    conversion.config.name = Some(name.clone());
+    conversion.config.description.get_or_insert_with(|| format!("Imported from {source}"));
+
+    for warning in &conversion.warnings {
+        eprintln!("{} {warning}", Icon::Warning);
+    }
+    if dry_run {
+        if is_plain() {
+            for (path, _) in &conversion.files {
+                print_record(&[path]);
+            }
+        } else {
+            println!("{TEMPLATE_CONFIG_FILE}:\n{}", toml::to_string_pretty(&conversion.config)?);
+            println!("Files:");
+            for (path, _) in &conversion.files {
+                println!("  {path}");
+            }
+        }
+        eprintln!("{} Dry run - would import '{name}' into {}", Icon::DryRun, dest_path.display());
+        return Ok(());
+    }
+
+    if let Err(e) = conversion.write_to(&dest_path) {
+        let _ = fs::remove_dir_all(&dest_path);
+        return Err(e);
+    }
+    eprintln!("{} Imported template '{name}' from {source} ({} file(s))", Icon::Success, conversion.files.len());
+    eprintln!("{} Stored in {}", Icon::Folder, dest_path.display());
+    Ok(())
+}
+
+fn convert(format: ImportFormat, source: &Path) -> Result<Conversion, Box<dyn std::error::Error>> {
+    match format {
+        ImportFormat::Cookiecutter => convert_cookiecutter(source),
+        ImportFormat::CargoGenerate => convert_cargo_generate(source),
+    }
+}
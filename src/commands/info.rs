@@ -0,0 +1,124 @@
+use crate::categories::get_category_display_name;
+use crate::error::TemplateError;
+use crate::file::format_size;
+use crate::history::{UsageHistory, format_date};
+use crate::languages::get_display_name;
+use crate::markdown::render_markdown;
+use crate::output::{is_plain, print_record};
+use crate::tags::normalize_tags;
+use crate::template::{Template, TEMPLATE_README_FILE};
+use crate::theme::Icon;
+
+use std::fs;
+use std::io::{self, IsTerminal};
+
+/// Prints everything known about one template: where it lives, its metadata, variables, hooks,
+/// size and usage, its full .template.toml and finally its TEMPLATE_README.md, rendered
+pub fn handle_info(template: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let template_info = Template::find(template)?
+        .ok_or_else(|| TemplateError::NotFound(template.to_string()))?;
+    let config = &template_info.config;
+
+    let storage = if template_info.is_builtin() {
+        "built-in"
+    } else if template_info.is_packed() {
+        "packed"
+    } else if template_info.is_compacted() {
+        "compacted"
+    } else {
+        "files"
+    };
+    let file_count = template_info.list_files()?.len();
+    let size = template_info.size_report()?.total;
+    let history = UsageHistory::load()?;
+    let usage = history.templates.get(&template_info.name);
+
+    let mut fields: Vec<(&str, String)> = vec![("template", template_info.name.clone()), ("path", template_info.path.display().to_string())];
+    fields.extend([
+        ("name", config.name.clone()),
+        ("description", config.description.clone()),
+        ("language", template_info.language().map(get_display_name)),
+        ("category", template_info.category().map(get_category_display_name)),
+        ("author", config.author.clone()),
+        ("version", config.version.clone()),
+        ("tags", template_info.tags().map(|tags| normalize_tags(tags).join(", "))),
+    ].into_iter().filter_map(|(field, value)| Some((field, value?))));
+    fields.extend([
+        ("storage", storage.to_string()),
+        ("files", file_count.to_string()),
+        ("size", if is_plain() { size.to_string() } else { format_size(size) }),
+        ("uses", usage.map_or(0, |entry| entry.count).to_string()),
+    ]);
+    if let Some(entry) = usage {
+        fields.push(("last-used", if is_plain() { entry.last_used.to_string() } else { format_date(entry.last_used) }));
+    }
+    let readme_path = template_info.path.join(TEMPLATE_README_FILE);
+    let readme = if readme_path.is_file() { Some(fs::read_to_string(&readme_path)?) } else { None };
+    if readme.is_some() {
+        fields.push(("readme", readme_path.display().to_string()));
+    }
+
+    let variables = config.variables.clone().unwrap_or_default();
+    let hooks: Vec<(&str, &str)> = config.hooks.iter()
+        .flat_map(|hooks| hooks.commands())
+        .map(|(stage, command)| (stage.key(), command))
+        .collect();
+
+    if is_plain() {
+        for (field, value) in &fields {
+            print_record(&["field", field, value]);
+        }
+        for (name, spec) in &variables {
+            print_record(&["variable", name, spec.kind.label(), spec.default.as_deref().unwrap_or("-"), spec.description.as_deref().unwrap_or("-")]);
+        }
+        for (stage, command) in &hooks {
+            print_record(&["hook", stage, command]);
+        }
+        return Ok(());
+    }
+
+    println!("{} {}\n", Icon::Package, template_info.name);
+    for (field, value) in &fields[1..] {
+        let mut label = field.replace('-', " ");
+        label[..1].make_ascii_uppercase();
+        println!("  {label}: {value}");
+    }
+
+    if !variables.is_empty() {
+        println!("\nVariables:");
+        for (name, spec) in &variables {
+            let mut details = vec![spec.kind.label().to_string()];
+            if let Some(default) = &spec.default {
+                details.push(format!("default \"{default}\""));
+            }
+            if let Some(choices) = &spec.choices {
+                details.push(format!("one of {}", choices.join("/")));
+            }
+            if let Some(from) = &spec.from {
+                details.push(format!("read from {from}"));
+            }
+            let description = spec.description.as_deref().map(|d| format!(" - {d}")).unwrap_or_default();
+            println!("  {name} ({}){description}", details.join(", "));
+        }
+    }
+    if !hooks.is_empty() {
+        println!("\nHooks:");
+        for (stage, command) in &hooks {
+            println!("  {stage}: {command}");
+        }
+    }
+
+    println!("\n.template.toml:");
+    for line in toml::to_string_pretty(config)?.lines() {
+        println!("  {line}");
+    }
+
+    if let Some(readme) = readme {
+        let styled = io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        println!("\n{TEMPLATE_README_FILE}:\n");
+        for line in render_markdown(&readme, styled).lines() {
+            println!("  {line}");
+        }
+    }
+    Ok(())
+}
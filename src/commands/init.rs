@@ -1,37 +1,468 @@
+use crate::archive::write_directory_tar;
+use crate::checks::sanity_check;
+use crate::config::UserConfig;
+use crate::copy::{Change, ChangeHandler, CopyPlan, Ownership, Timestamps, process_umask};
+use crate::eol::EolPolicy;
+use crate::error::TemplateError;
+use crate::events::{Event, emit};
 use crate::path::resolve_path;
 use crate::file::{ensure_template_storage_dir};
-use crate::template::{Template, TEMPLATE_CONFIG_FILE};
+use crate::git;
+use crate::history::{UsageHistory, source_date_epoch};
+use crate::hooks::{HookStage, run_hook};
+use crate::languages::get_display_name;
+use crate::lock::{TemplateLock, TEMPLATE_LOCK_FILE};
+use crate::objects::store_blob;
+use crate::oci::{oci_reference, pull_template};
+use crate::origin::TEMPLATE_ORIGIN_FILE;
+use crate::overrides::{TEMPLATE_OVERRIDES_DIR, apply_overrides};
+use crate::output::{is_plain, print_record};
+use crate::patch::{FileChange, format_git_patch};
+use crate::placeholders::Placeholder;
+use crate::plugins::{PluginFunctions, run_post_processors};
+use crate::prompt::{ask_variable, confirm, select_files};
+use crate::receipt::{Receipt, ReceiptTarget, TEMPLATE_HISTORY_FILE};
+use crate::remote::RemoteTarget;
+use crate::render::{RenderedFiles, TemplateRenderer, remove_emptied_dirs};
+use crate::template::{
+    Template, TEMPLATE_ARCHIVE_FILE, TEMPLATE_CONFIG_FILE, TEMPLATE_MANIFEST_FILE, TEMPLATE_PLUGIN_DIR,
+    TEMPLATE_IGNORE_FILE, TEMPLATE_README_FILE, TEMPLATE_SNAPSHOT_DIR, TEMPLATE_TEST_FILE,
+};
+use crate::theme::Icon;
+use crate::variables::{Question, project_value, resolve_values};
+use crate::versions::VersionRecord;
+use crate::workspace::WorkspaceConfig;
 
+use clap::ValueEnum;
+
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
-use std::process::Command;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Prefix of the environment variables giving template variable values (TEMPLATE_RS_VAR_NAME)
+const VARIABLE_ENV_PREFIX: &str = "TEMPLATE_RS_VAR_";
+
+/// Options controlling how a template is applied by `init` and `new`
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Default)]
+pub struct ApplyOptions {
+    /// Preview changes without copying files
+    pub dry_run: bool,
+    /// Overwrite existing files
+    pub force: bool,
+    /// Remove files not present in template
+    pub delete: bool,
+    /// Create the target directory if it doesn't exist (for 'new' command)
+    pub create_dir: bool,
+    /// Let 'new' merge into a directory that already has content
+    pub allow_existing: bool,
+    /// Write into a git worktree that has uncommitted changes
+    pub allow_dirty: bool,
+    /// Allow applying a different template over a previously initialized directory
+    pub reinit: bool,
+    /// Skip confirmation prompts and variable questions, and run hooks unasked
+    pub yes: bool,
+    /// Skip the summary confirmation only, because the whole apply was already confirmed (workspace members)
+    pub confirmed: bool,
+    /// Apply the built-in exclusions (.git/, .DS_Store, build dirs, ...)
+    pub default_excludes: bool,
+    /// Emit newline-delimited JSON progress events on stdout instead of the file listing
+    pub json_events: bool,
+    /// Interactively pick which of the template's files to apply
+    pub select: bool,
+    /// Owner and/or group for created files (`USER`, `USER:GROUP` or `:GROUP`)
+    pub chown: Option<String>,
+    /// Keep the template files' owner and group; otherwise files belong to whoever runs the command
+    pub preserve_owner: bool,
+    /// Permission bits cleared on created files; None uses config.toml's mode_mask, then the umask
+    pub mode_mask: Option<u32>,
+    /// Print the changes as a git patch on stdout instead of making them
+    pub as_patch: bool,
+    /// Leave stdout alone instead of listing the changes there, because it carries an archive
+    pub quiet: bool,
+    /// Never ask: variables come from --var, the environment, config.toml or their defaults, and
+    /// anything needing an answer fails unless --yes gives it
+    pub from_defaults: bool,
+    /// Skip the template's pre_init and post_init hooks
+    pub no_hooks: bool,
+    /// Run the language's sanity check on the result, failing when it fails
+    pub check: bool,
+    /// Leave the usage history and the version records alone, as for benchmark runs
+    pub no_history: bool,
+    /// Where to record a receipt of the changes; None uses config.toml's receipts
+    pub receipt: Option<ReceiptTarget>,
+    /// Modification times of created files; None uses config.toml's timestamps
+    pub timestamps: Option<Timestamps>,
+    /// Variables to record in .template.lock, over any carried forward from a previous apply
+    pub variables: BTreeMap<String, String>,
+}
+
+/// What applying a template without a terminal did (or would do, for a dry run)
+#[derive(Debug, Clone, Default)]
+pub struct ApplyReport {
+    /// Name of the applied template
+    pub template: String,
+    /// Files written to the target, relative to it
+    pub written: Vec<String>,
+    /// Files the target already had and kept, with why: `kept-existing` or `create-once`
+    pub kept: Vec<(String, &'static str)>,
+    /// Paths removed because the template doesn't have them (`delete`)
+    pub deleted: Vec<String>,
+    /// Placeholders left unrendered, as `path:line: placeholder`
+    pub leftovers: Vec<String>,
+    /// Where a receipt of the changes was recorded, if one was
+    pub receipt: Option<PathBuf>,
+}
+
+/// Where applying a template reports its progress and gets its answers from
+pub(crate) trait ApplySink {
+    /// Shows a line of progress
+    fn say(&mut self, line: &str);
+
+    /// Whether someone is there to answer questions
+    fn can_ask(&self) -> bool;
+
+    /// Asks for a variable's value, None keeping its default
+    fn ask_variable(&mut self, question: &Question) -> Result<Option<String>, String>;
+
+    /// Asks a yes/no question
+    fn confirm(&mut self, question: &str) -> Result<bool, Box<dyn std::error::Error>>;
+
+    /// Lets the template's files be picked, None when picking was cancelled
+    fn select(&mut self, files: &[String]) -> Result<Option<Vec<String>>, Box<dyn std::error::Error>>;
+
+    /// Passes on a progress event (only sent with `json_events`)
+    fn event(&mut self, event: &Event) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Shows what the copy changed (or would change, for a dry run)
+    fn changes(&mut self, changes: &[Change], dry_run: bool);
+}
+
+/// The command line: progress on stderr, the changes and events on stdout, questions on the terminal
+#[derive(Debug, Default)]
+pub(crate) struct Terminal {
+    json_events: bool,
+    quiet: bool,
+}
+
+impl Terminal {
+    pub(crate) fn new(options: &ApplyOptions) -> Self {
+        Self { json_events: options.json_events, quiet: options.quiet }
+    }
+}
+
+impl ApplySink for Terminal {
+    fn say(&mut self, line: &str) {
+        eprintln!("{line}");
+    }
+
+    fn can_ask(&self) -> bool {
+        io::stdin().is_terminal()
+    }
+
+    fn ask_variable(&mut self, question: &Question) -> Result<Option<String>, String> {
+        ask_variable(question).map_err(|e| e.to_string())
+    }
+
+    fn confirm(&mut self, question: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        Ok(confirm(question)?)
+    }
+
+    fn select(&mut self, files: &[String]) -> Result<Option<Vec<String>>, Box<dyn std::error::Error>> {
+        Ok(select_files(files)?)
+    }
+
+    fn event(&mut self, event: &Event) -> Result<(), Box<dyn std::error::Error>> {
+        emit(event)
+    }
+
+    fn changes(&mut self, changes: &[Change], dry_run: bool) {
+        if self.json_events || self.quiet {
+            // Progress was reported as events, or stdout is taken
+        } else if is_plain() {
+            if dry_run {
+                print_changes(changes, "copy", "delete");
+            } else {
+                print_changes(changes, "copied", "deleted");
+            }
+        } else if changes.is_empty() {
+            eprintln!("Nothing to do, the target is up to date");
+        } else if dry_run {
+            print_change_list(changes, "Would copy:", "Would delete:");
+        } else {
+            print_change_list(changes, "Copied:", "Deleted:");
+        }
+    }
+}
+
+/// Nobody watching: nothing is shown and nothing is asked, so whatever needs an answer is
+/// declined (hooks are skipped, deletions refused) unless `yes` gives it
+#[derive(Debug, Default)]
+pub(crate) struct Unattended;
+
+impl ApplySink for Unattended {
+    fn say(&mut self, _line: &str) {}
+
+    fn can_ask(&self) -> bool {
+        false
+    }
+
+    fn ask_variable(&mut self, _question: &Question) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    fn confirm(&mut self, _question: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        Ok(false)
+    }
+
+    fn select(&mut self, _files: &[String]) -> Result<Option<Vec<String>>, Box<dyn std::error::Error>> {
+        Err("Picking files needs the command line".into())
+    }
+
+    fn event(&mut self, _event: &Event) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    fn changes(&mut self, _changes: &[Change], _dry_run: bool) {}
+}
+
+/// Path patterns that steer the copy: what is never copied and what is never deleted
+struct Filters {
+    excludes: Vec<String>,
+    protected: Vec<String>,
+    /// Where files with placeholders in their path end up, by template file; they belong to
+    /// the template, so --delete keeps them
+    rendered: BTreeMap<String, String>,
+}
+
+impl Filters {
+    /// The filters of applying a template with the given renderer
+    fn new(
+        template_info: &Template,
+        user_config: &UserConfig,
+        default_excludes: bool,
+        renderer: &TemplateRenderer,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut filters = Self {
+            excludes: user_config.excludes(default_excludes),
+            protected: user_config.protected_paths(),
+            rendered: BTreeMap::new(),
+        };
+        for file in template_info.apply_files(&filters.excludes)? {
+            let rendered = renderer.render_path(&file)?;
+            if rendered != file {
+                filters.rendered.insert(file, rendered);
+            }
+        }
+        Ok(filters)
+    }
+
+    /// Where a template file lands in the target
+    fn rendered_path<'a>(&'a self, file: &'a str) -> &'a str {
+        self.rendered.get(file).map_or(file, String::as_str)
+    }
+}
 
-#[allow(clippy::fn_params_excessive_bools)]
 pub fn handle_init(
-    template: &str,
+    template: Option<&str>,
     path: Option<String>,
+    options: &ApplyOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = match path.as_deref().and_then(RemoteTarget::parse) {
+        Some(remote) => apply_remote(template, &remote, options),
+        None => apply_template(template, path, options),
+    };
+    if options.json_events
+        && let Err(e) = &result
+    {
+        emit(&Event::Error { message: &e.to_string() })?;
+    }
+    result
+}
+
+/// Applies a template to a directory on another machine: it is rendered into a local staging
+/// directory, hooks and the sanity check included, whose contents are then uploaded over SSH
+fn apply_remote(template: Option<&str>, remote: &RemoteTarget, options: &ApplyOptions) -> Result<(), Box<dyn std::error::Error>> {
+    if options.delete || options.as_patch || options.chown.is_some() || options.preserve_owner {
+        return Err("--delete, --as-patch, --chown and --preserve-owner can't be used with a remote target".into());
+    }
+    eprintln!("Remote target: {remote}");
+
+    let scratch = tempfile::Builder::new().prefix("template-rs-remote-").tempdir()?;
+    let staging = scratch.path().join("project");
+    let local = ApplyOptions { create_dir: true, allow_dirty: true, ..options.clone() };
+    apply_template(template, Some(staging.display().to_string()), &local)?;
+    if options.dry_run {
+        eprintln!("{} Dry run - the result would be uploaded to {remote}", Icon::DryRun);
+        return Ok(());
+    }
+    eprintln!("Uploading to {remote}");
+    remote.upload(&staging, options.force)?;
+    eprintln!("{} Uploaded to {remote}", Icon::Success);
+    Ok(())
+}
+
+/// Format of the archive `new --output` writes the generated project as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProjectArchive {
+    /// Uncompressed tar, with paths relative to the project root
+    Tar,
+}
+
+/// Generates a project without keeping it on disk: it is rendered into a staging directory, hooks
+/// and the sanity check included, and written as an archive to `archive_path` ('-' for stdout)
+pub fn apply_to_archive(
+    template: &str,
+    archive_path: &str,
+    format: ProjectArchive,
+    options: &ApplyOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let to_stdout = archive_path == "-";
+    if to_stdout && options.json_events {
+        return Err("--json-events and an archive can't both go to stdout".into());
+    }
+    let archive_path = (!to_stdout).then(|| resolve_path(archive_path, None)).transpose()?;
+
+    let scratch = tempfile::Builder::new().prefix("template-rs-archive-").tempdir()?;
+    let staging = scratch.path().join("project");
+    let local = ApplyOptions { create_dir: true, allow_dirty: true, quiet: to_stdout && !options.dry_run, ..options.clone() };
+    apply_template(Some(template), Some(staging.display().to_string()), &local)?;
+    if !options.dry_run {
+        match (format, &archive_path) {
+            (ProjectArchive::Tar, Some(path)) => {
+                write_directory_tar(&staging, std::fs::File::create(path)?)?;
+            }
+            (ProjectArchive::Tar, None) => {
+                write_directory_tar(&staging, io::stdout().lock())?.flush()?;
+            }
+        }
+    }
+
+    if options.dry_run {
+        eprintln!("{} Dry run - the project would be written as a tar archive", Icon::DryRun);
+    } else if let Some(path) = archive_path {
+        eprintln!("{} Wrote the project to {}", Icon::Success, path.display());
+    }
+    Ok(())
+}
+
+/// Verifies the template's prerequisites before anything is written, reporting every missing one.
+/// Dry runs only warn.
+fn check_prerequisites(
+    template: &Template,
+    target_path: &Path,
     dry_run: bool,
-    force: bool,
-    delete: bool,
-    create_dir: bool,
+    sink: &mut dyn ApplySink,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(prerequisites) = &template.config.prerequisites else {
+        return Ok(());
+    };
+    let missing = prerequisites.missing(target_path);
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    sink.say(&format!("{} Template '{}' has unmet prerequisites:", Icon::Warning, template.name));
+    for problem in &missing {
+        sink.say(&format!("  - {problem}"));
+    }
+    if dry_run {
+        return Ok(());
+    }
+    Err(TemplateError::Prerequisites(format!("{} prerequisite(s) missing for template '{}'", missing.len(), template.name)).into())
+}
+
+/// Refuses to write into a git worktree with uncommitted changes, so the template's changes
+/// can be reviewed as a diff of their own. Dry runs only warn.
+pub(crate) fn check_clean_worktree(target_path: &Path, dry_run: bool, sink: &mut dyn ApplySink) -> Result<(), Box<dyn std::error::Error>> {
+    const MAX_LISTED: usize = 10;
+
+    let Some(changes) = git::uncommitted_changes(target_path).filter(|c| !c.is_empty()) else {
+        return Ok(());
+    };
+
+    sink.say(&format!("{} The git worktree has {} uncommitted change(s):", Icon::Warning, changes.len()));
+    for change in changes.iter().take(MAX_LISTED) {
+        sink.say(&format!("  {change}"));
+    }
+    if changes.len() > MAX_LISTED {
+        sink.say(&format!("  ... and {} more", changes.len() - MAX_LISTED));
+    }
+
+    if dry_run {
+        sink.say("Commit or stash them first, or pass --allow-dirty");
+        return Ok(());
+    }
+    Err("Uncommitted changes in the target's git worktree; commit or stash them first, or pass --allow-dirty".into())
+}
+
+/// Validates a `--chown` value before anything is copied: `USER`, `USER:GROUP` or `:GROUP`
+fn check_owner_spec(owner: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let is_name = |name: &str| !name.is_empty() && !name.contains([':', ' ', '\t']);
+    let valid = match owner.split_once(':') {
+        None => is_name(owner),
+        Some(("", group)) => is_name(group),
+        Some((user, group)) => is_name(user) && is_name(group),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid --chown '{owner}', expected USER, USER:GROUP or :GROUP").into())
+    }
+}
+
+/// Applies a template from the command line to `path`, the current directory when none is given
+fn apply_template(
+    template: Option<&str>,
+    path: Option<String>,
+    options: &ApplyOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Initializing...");
-    println!("Using template: {template}");
-    
-    // Resolve the target path - use current directory if none provided
     let target_path = match path {
         Some(p) => resolve_path(&p, None)?,
         None => env::current_dir()?,
     };
-    
+    apply(template, &target_path, options, &mut Terminal::new(options))?;
+    Ok(())
+}
+
+/// Applies a template to `target_path`, or the one recorded in its lock file when `template` is
+/// None. This is the whole of `init` and `new`, also behind workspace members, `bench` and
+/// `TemplateStore::apply`; they only differ in the sink that shows progress and answers questions.
+pub(crate) fn apply(
+    template: Option<&str>,
+    target_path: &Path,
+    options: &ApplyOptions,
+    sink: &mut dyn ApplySink,
+) -> Result<ApplyReport, Box<dyn std::error::Error>> {
+    let ApplyOptions { dry_run, force, delete, create_dir, allow_existing, allow_dirty, reinit, yes, default_excludes, json_events, select, as_patch, .. } = *options;
+
+    sink.say("Initializing...");
+
+    if let Some(owner) = &options.chown {
+        check_owner_spec(owner)?;
+    }
+
     // Create directory if requested (for 'new' command)
     if create_dir {
         if !target_path.exists() {
-            println!("Creating directory: {}", target_path.display());
+            sink.say(&format!("Creating directory: {}", target_path.display()));
             if !dry_run {
-                std::fs::create_dir_all(&target_path)?;
+                std::fs::create_dir_all(target_path)?;
             }
         } else if !target_path.is_dir() {
             return Err(format!("Path exists but is not a directory: {}", target_path.display()).into());
+        } else if !(force || allow_existing) && std::fs::read_dir(target_path)?.next().is_some() {
+            // 'new' is for fresh projects; merging into existing content is what 'init' is for
+            return Err(format!(
+                "Target directory is not empty: {}\nUse `{bin} init {name} {path}` to apply the template to an existing project, \
+                 or pass --allow-existing (keep existing files) or --force (overwrite them)",
+                target_path.display(),
+                bin = crate::BIN_NAME,
+                name = template.unwrap_or("<template>"),
+                path = target_path.display(),
+            ).into());
         }
     } else {
         // Check if the target path exists (for 'init' command)
@@ -39,84 +470,1165 @@ pub fn handle_init(
             return Err(format!("Target path does not exist: {}", target_path.display()).into());
         }
     }
-    
-    println!("Target path: {}", target_path.display());
-    
+
+    sink.say(&format!("Target path: {}", target_path.display()));
+
+    // A patch leaves the worktree alone, so it may be dirty
+    if !allow_dirty && !as_patch {
+        check_clean_worktree(target_path, dry_run, sink)?;
+    }
+
+    // Detect a prior initialization and default to its recorded choices
+    let previous_lock = TemplateLock::load(target_path)?;
+    let template = resolve_template_name(template, previous_lock.as_ref(), target_path, sink)?;
+    sink.say(&format!("Using template: {template}"));
+
     // Ensure template storage exists
     let template_dir = ensure_template_storage_dir()?;
 
-    // Find the template
-    let template_info = Template::find(template)?
-        .ok_or_else(|| format!("Template '{template}' not found. Use 'template-rs list' to see available templates."))?;
-    
-    println!("Found template: {}", template_info.path.display());
+    // Find the template; one from a registry is pulled for this run only
+    let pulled = oci_reference(template).map(|_| pull_template(template)).transpose()?;
+    let template_info = match &pulled {
+        Some(pulled) => pulled.template.clone(),
+        None => Template::find(template)?
+            .ok_or_else(|| TemplateError::NotFound(template.to_string()))?,
+    };
+    template_info.check_compatibility()?;
+    template_info.check_contents()?;
+    check_prerequisites(&template_info, target_path, dry_run || as_patch, sink)?;
+    if let Some(workspace) = &template_info.config.workspace {
+        check_workspace(&template_info.name, workspace, sink)?;
+    }
+
+    if let Some(lock) = &previous_lock {
+        check_previous_lock(lock, &template_info, reinit, sink)?;
+    }
+
+    sink.say(&format!("Found template: {}", template_info.path.display()));
     if let Some(description) = template_info.description() {
-        println!("Description: {description}");
+        sink.say(&format!("Description: {description}"));
     }
-    
+
     // Rest of the function remains the same, but use template_info.path instead of source_template
     let source_template = &template_info.path;
-    
+
     if !source_template.exists() {
         return Err(format!("Template '{template}' not found in {}", template_dir.display()).into());
     }
-    
-    // Build rsync command
-    let mut cmd = Command::new("rsync");
-    // -r recursive, -l copy symlinks, -p preserve permissions, -v verbose
-    // Omit -t to NOT preserve timestamps (files get current time)
-    cmd.arg("-rlpv");
 
-    // Exclude the template configuration file
-    cmd.arg(format!("--exclude={TEMPLATE_CONFIG_FILE}"));
-    
-    if dry_run {
-        cmd.arg("--dry-run");
-        cmd.arg("--itemize-changes");
+    let user_config = UserConfig::load()?;
+    let supplied = supplied_variables(&template_info, target_path, options, previous_lock.as_ref(), &user_config, sink);
+    let declared = template_info.config.variables.clone().unwrap_or_default();
+    // Variables nobody supplied are asked for, unless nobody is there to answer
+    let interactive = !yes && !json_events && !options.from_defaults && sink.can_ask();
+    let mut plugins = if declared.is_empty() { PluginFunctions::default() } else { PluginFunctions::load(&template_info)? };
+    let resolved = resolve_values(
+        &declared,
+        &supplied,
+        &mut |question: &Question| if interactive { sink.ask_variable(question) } else { Ok(None) },
+        &mut |name, value| plugins.validate(name, value),
+    );
+    let values = match resolved {
+        Ok(values) => values,
+        Err(e) if dry_run => {
+            sink.say(&format!("{} Template '{}' has {e}", Icon::Warning, template_info.name));
+            supplied
+        }
+        Err(e) if options.from_defaults => return Err(format!(
+            "Template '{}' has {e}, and --from-defaults never asks. Pass values with --var NAME=VALUE, \
+             {VARIABLE_ENV_PREFIX}NAME or [variables] in config.toml",
+            template_info.name
+        ).into()),
+        Err(e) => return Err(format!("Template '{}' has {e}. Pass values with --var NAME=VALUE", template_info.name).into()),
+    };
+    let renderer = TemplateRenderer::new(&template_info, values.clone())?;
+    let mut filters = Filters::new(&template_info, &user_config, default_excludes, &renderer)?;
+
+    // Files left unpicked are excluded like any other pattern, so every later step skips them
+    if select {
+        if json_events {
+            return Err("--select is interactive and can't be combined with --json-events".into());
+        }
+        let files = template_info.apply_files(&filters.excludes)?;
+        let picked = sink.select(&files)?.ok_or_else(|| TemplateError::Aborted("Aborted, nothing was changed.".to_string()))?;
+        filters.excludes.extend(files.iter().filter(|f| !picked.contains(f)).map(|f| format!("/{f}")));
+    }
+
+    if as_patch {
+        if template_info.has_hooks() {
+            sink.say(&format!("{} The template's hooks are not run for a patch", Icon::Note));
+        }
+        let application = Application {
+            template: &template_info,
+            target: target_path,
+            options,
+            user_config: &user_config,
+            filters: &filters,
+            renderer: &renderer,
+        };
+        print_patch(&application, values, sink)?;
+        return Ok(ApplyReport { template: template_info.name.clone(), ..ApplyReport::default() });
     }
-    
-    if !force {
-        cmd.arg("--ignore-existing");
+
+    // Hooks are confirmed up front, so an answer never interrupts a half-done copy
+    let hooks = plan_hooks(&template_info, target_path, &renderer, options, sink)?;
+
+    if json_events {
+        let target = target_path.display().to_string();
+        sink.event(&Event::Planning { template: &template_info.name, target: &target, dry_run })?;
     }
-    
+
+    // Preview deletions before anything is removed
+    let mut deletions = Vec::new();
     if delete {
-        cmd.arg("--delete");
-    }
-    
-    // Add trailing slash to source for proper rsync behavior
-    let source_str = format!("{}/", source_template.display());
-    cmd.arg(&source_str);
-    cmd.arg(&target_path);
-    
+        let kept;
+        (deletions, kept) = plan_deletions(&template_info, target_path, options, &filters)?;
+        if !kept.is_empty() {
+            sink.say(&format!("{} Protected from deletion:", Icon::Protected));
+            for path in &kept {
+                sink.say(&format!("  {path}"));
+            }
+        }
+        if !dry_run && !deletions.is_empty() {
+            sink.say(&format!("The following {} path(s) will be deleted:", deletions.len()));
+            for path in &deletions {
+                sink.say(&format!("  {path}"));
+            }
+            if !yes && options.from_defaults {
+                return Err("--from-defaults never asks for confirmation, pass --yes to delete these paths".into());
+            }
+        }
+    }
+
+    // Files the target already has are left alone, except with --force (and never for create_once files)
+    let conflicts = existing_conflicts(&template_info, target_path, options, &filters)?;
+
+    // A last look at everything that was resolved, confirmed before anything is written
+    if interactive && !dry_run && !options.confirmed {
+        let files = template_info.apply_files(&filters.excludes)?;
+        let existing = files.iter()
+            .filter(|file| renderer.render_path(file).is_ok_and(|path| target_path.join(path).exists()))
+            .count();
+        let summary = Summary {
+            template: &template_info,
+            target: target_path,
+            options,
+            values: &values,
+            files: files.len(),
+            existing,
+            create_once: if force { conflicts.len() } else { 0 },
+            deletions: deletions.len(),
+            hooks: hooks.len(),
+        };
+        summary.show(sink);
+        if !sink.confirm("Apply the template?")? {
+            return Err(TemplateError::Aborted("Aborted, nothing was changed. Use --yes to skip this confirmation.".to_string()).into());
+        }
+    } else if !dry_run && !deletions.is_empty() && !yes && !sink.confirm("Delete these paths?")? {
+        return Err(TemplateError::Aborted("Aborted, nothing was changed. Use --yes to skip this confirmation.".to_string()).into());
+    }
+
     // Show user what's happening
     if dry_run {
-        println!("🔍 Dry run - showing what would be copied:");
+        sink.say(&format!("{} Dry run - showing what would be copied:", Icon::DryRun));
     } else if force && delete {
-        println!("⚠️  Force + delete mode - destination will match template exactly");
+        sink.say(&format!("{} Force + delete mode - destination will match template exactly", Icon::Warning));
     } else if force {
-        println!("⚠️  Force mode - overwriting existing files");
+        sink.say(&format!("{} Force mode - overwriting existing files", Icon::Warning));
     } else if delete {
-        println!("⚠️  Delete mode - removing files not in template");
+        sink.say(&format!("{} Delete mode - removing files not in template", Icon::Warning));
     } else {
-        println!("📁 Copying template files (skipping existing files)");
-    }
-    
-    // Execute rsync
-    let output = cmd.output()?;
-    
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("rsync failed: {stderr}").into());
-    }
-    
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    if !stdout.trim().is_empty() {
-        println!("{stdout}");
-    }
-    
+        sink.say(&format!("{} Copying template files (skipping existing files)", Icon::Folder));
+    }
+
+    if json_events {
+        for (path, resolution) in &conflicts {
+            sink.event(&Event::Conflict { path, resolution })?;
+        }
+    }
+
+    // Files already in the target, to tell replaced files from added ones in the receipt
+    let receipt_target = options.receipt.unwrap_or(user_config.receipts);
+    let existing = if receipt_target != ReceiptTarget::None && !dry_run {
+        existing_files(&template_info, target_path, &filters)?
+    } else {
+        BTreeSet::new()
+    };
+
+    run_hooks(&hooks, HookStage::PreInit, target_path, json_events, sink)?;
+
+    // Copy the template, reporting changes as they are made
+    let (mut copied, mut deleted) = (0, 0);
+    let mut on_change = |change: &Change| -> Result<(), Box<dyn std::error::Error>> {
+        match change {
+            Change::Copy(path) if json_events => {
+                copied += 1;
+                sink.event(&Event::FileCopied { path })
+            }
+            Change::Delete(path) if json_events => {
+                deleted += 1;
+                sink.event(&Event::FileDeleted { path })
+            }
+            _ => Ok(()),
+        }
+    };
+    let application = Application {
+        template: &template_info,
+        target: target_path,
+        options,
+        user_config: &user_config,
+        filters: &filters,
+        renderer: &renderer,
+    };
+    let Written { changes, rendered, overrides } = write_files(&application, &conflicts, &mut on_change)?;
+    sink.changes(&changes, dry_run);
+    if dry_run {
+        report_overrides(&overrides, true, sink);
+    }
+
+    let mut report = ApplyReport {
+        template: template_info.name.clone(),
+        deleted: changes.iter()
+            .filter_map(|change| match change {
+                Change::Delete(path) if !path.ends_with('/') => Some(path.clone()),
+                _ => None,
+            })
+            .collect(),
+        leftovers: rendered.leftovers.iter().map(|(path, placeholder)| format!("{path}:{}: {}", placeholder.line, placeholder.text)).collect(),
+        kept: conflicts.clone(),
+        ..ApplyReport::default()
+    };
+    report.written = if dry_run {
+        changes.iter()
+            .filter_map(|change| match change {
+                Change::Copy(path) if !path.ends_with('/') => Some(path.clone()),
+                _ => None,
+            })
+            .collect()
+    } else {
+        rendered.paths.clone()
+    };
+
     if !dry_run {
-        println!("✅ Template initialization complete!");
+        if renderer.is_active() {
+            report_leftovers(&rendered.leftovers, renderer.literal_braces(), json_events, sink)?;
+        }
+        report_overrides(&overrides, false, sink);
+
+        let lock = build_lock(&template_info, options, &filters, values)?;
+        lock.save(target_path)?;
+        if !options.no_history {
+            VersionRecord::from_lock(&lock).save()?;
+            UsageHistory::record_use(&template_info.name, template_info.version())?;
+        }
+        report.receipt = record_receipt(&template_info, target_path, receipt_target, &rendered.paths, &existing, &changes)?;
+        if let Some(path) = &report.receipt {
+            sink.say(&format!("{} Recorded receipt in {}", Icon::Note, path.display()));
+        }
+        run_hooks(&hooks, HookStage::PostInit, target_path, json_events, sink)
+            .map_err(|e| format!("The template was applied, but a post_init hook failed: {e}"))?;
+        sink.say(&format!("{} Template initialization complete!", Icon::Success));
+    }
+
+    if let Some(workspace) = &template_info.config.workspace {
+        apply_workspace(workspace, target_path, options, sink)?;
+    }
+
+    if !dry_run {
+        run_sanity_check(&template_info, target_path, options, sink)?;
+    }
+
+    if json_events {
+        sink.event(&Event::Done { copied, deleted, conflicts: conflicts.len(), dry_run })?;
+    }
+
+    Ok(report)
+}
+
+/// One application of a template: where it goes and everything resolved for it
+struct Application<'a> {
+    template: &'a Template,
+    target: &'a Path,
+    options: &'a ApplyOptions,
+    user_config: &'a UserConfig,
+    filters: &'a Filters,
+    renderer: &'a TemplateRenderer,
+}
+
+/// What writing a template's files changed in the target
+struct Written {
+    /// What the copy did (or would do, for a dry run)
+    changes: Vec<Change>,
+    /// The files rendered in place, empty for a dry run
+    rendered: RenderedFiles,
+    /// Files the project's overrides replaced (or would replace)
+    overrides: Vec<String>,
+}
+
+/// Copies the template into the target and finishes the copied files: rendering, plugins, line
+/// endings, modification times and the project's overrides. Files in `conflicts` are left as
+/// they are. Nothing is printed or asked; a dry run only previews the copy.
+fn write_files(
+    application: &Application,
+    conflicts: &[(String, &'static str)],
+    on_change: &mut ChangeHandler,
+) -> Result<Written, Box<dyn std::error::Error>> {
+    let Application { template: template_info, target: target_path, options, user_config, filters, renderer } = *application;
+    let engine = user_config.copy_engine.engine();
+    let content_dir = template_info.content_dir()?;
+    let mut plan = copy_plan(template_info, &content_dir, target_path, options, options.dry_run, filters, true)?;
+    plan.mode_mask = match options.mode_mask {
+        Some(mask) => mask,
+        None => user_config.mode_mask()?.unwrap_or_else(process_umask),
+    };
+    plan.timestamps = options.timestamps.unwrap_or(user_config.timestamps);
+    let changes = engine.copy(&plan, on_change).map_err(|e| TemplateError::Copy(e.to_string()))?;
+    if options.dry_run {
+        let overrides = apply_overrides(target_path, &[], true)?;
+        return Ok(Written { changes, rendered: RenderedFiles::default(), overrides });
+    }
+
+    let copied_files: Vec<String> = template_info.apply_files(&filters.excludes)?
+        .into_iter()
+        .filter(|file| !conflicts.iter().any(|(path, _)| path == filters.rendered_path(file)))
+        .collect();
+    let overwrite = |file: &str| options.force && !template_info.is_create_once(file);
+    let rendered = renderer.apply_to_files(target_path, &copied_files, overwrite)?;
+    // Files kept at their rendered path leave the directories copied for them empty
+    for file in filters.rendered.keys() {
+        remove_emptied_dirs(target_path, &target_path.join(file));
+    }
+    run_post_processors(template_info, target_path, &rendered.paths)?;
+    EolPolicy::new(template_info, user_config).apply_to_files(target_path, &rendered.paths)?;
+    if plan.timestamps == Timestamps::Preserve {
+        preserve_modified_times(renderer, &content_dir, target_path, &copied_files, &rendered.paths)?;
+    } else if let Some(epoch) = source_date_epoch() {
+        set_modified_times(target_path, &rendered.paths, epoch)?;
+    }
+    let overrides = apply_overrides(target_path, &[], false)?;
+    Ok(Written { changes, rendered, overrides })
+}
+
+/// Tells which files the project's overrides replaced (or would replace)
+fn report_overrides(paths: &[String], dry_run: bool, sink: &mut dyn ApplySink) {
+    if paths.is_empty() {
+        return;
+    }
+    let (icon, verb) = if dry_run { (Icon::DryRun, "Would apply") } else { (Icon::Note, "Applied") };
+    sink.say(&format!("{icon} {verb} {} override(s) from {TEMPLATE_OVERRIDES_DIR}/:", paths.len()));
+    for path in paths {
+        sink.say(&format!("  {path}"));
+    }
+}
+
+/// Everything resolved for applying a template, shown for a final confirmation
+struct Summary<'a> {
+    template: &'a Template,
+    target: &'a Path,
+    options: &'a ApplyOptions,
+    values: &'a BTreeMap<String, String>,
+    /// Files the template applies, after exclusions and --select
+    files: usize,
+    /// Files the target already has
+    existing: usize,
+    /// Existing create_once files that --force leaves alone
+    create_once: usize,
+    deletions: usize,
+    hooks: usize,
+}
+
+impl Summary<'_> {
+    /// Shows the summary as an aligned table
+    fn show(&self, sink: &mut dyn ApplySink) {
+        let options = self.options;
+        let version = self.template.version().map(|v| format!(" {v}")).unwrap_or_default();
+        let mut rows = vec![
+            ("Template", format!("{}{version}", self.template.name)),
+            ("Target", self.target.display().to_string()),
+        ];
+        if self.values.is_empty() {
+            rows.push(("Variables", "none".to_string()));
+        }
+        for (index, (name, value)) in self.values.iter().enumerate() {
+            rows.push((if index == 0 { "Variables" } else { "" }, format!("{name} = {value}")));
+        }
+        let features = self.template.features();
+        if !features.is_empty() {
+            rows.push(("Features", features.into_iter().collect::<Vec<_>>().join(", ")));
+        }
+
+        let existing = match (self.existing, options.force) {
+            (0, _) => "no existing files are touched".to_string(),
+            (count, true) if self.create_once > 0 => format!(
+                "{} existing file(s) overwritten (--force), {} create_once file(s) kept",
+                count.saturating_sub(self.create_once),
+                self.create_once
+            ),
+            (count, true) => format!("{count} existing file(s) overwritten (--force)"),
+            (count, false) => format!("{count} existing file(s) kept"),
+        };
+        let selected = if options.select { " (--select)" } else { "" };
+        rows.push(("Files", format!("{} to apply{selected}, {existing}", self.files)));
+        if options.delete {
+            rows.push(("Delete", format!("{} path(s) not in the template (--delete)", self.deletions)));
+        }
+        if self.hooks > 0 {
+            rows.push(("Hooks", format!("{} command(s) to run", self.hooks)));
+        }
+        if options.check || self.template.config.check == Some(true) {
+            let check = self.template.language().and_then(sanity_check);
+            rows.push(("Check", check.map_or_else(|| "none known for the language".to_string(), |check| check.command_line())));
+        }
+
+        sink.say(&format!("\n{} Summary:", Icon::Package));
+        for (label, value) in rows {
+            sink.say(&format!("  {label:<10} {value}"));
+        }
+        sink.say("");
+    }
+}
+
+/// The template's hook commands with their placeholders rendered, when they should run: with
+/// --yes, or once confirmed. Dry runs only list them, and without a terminal to confirm on
+/// they're skipped.
+fn plan_hooks(
+    template_info: &Template,
+    target_path: &Path,
+    renderer: &TemplateRenderer,
+    options: &ApplyOptions,
+    sink: &mut dyn ApplySink,
+) -> Result<Vec<(HookStage, String)>, Box<dyn std::error::Error>> {
+    let commands = hook_commands(template_info, renderer)?;
+    if commands.is_empty() {
+        return Ok(commands);
+    }
+    if options.no_hooks {
+        sink.say(&format!("{} Skipping {} hook(s) of template '{}' (--no-hooks)", Icon::Note, commands.len(), template_info.name));
+        return Ok(Vec::new());
+    }
+    if options.dry_run {
+        sink.say(&format!("{} Would run {} hook(s) in {}:", Icon::DryRun, commands.len(), target_path.display()));
+        for (stage, command) in &commands {
+            sink.say(&format!("  {}: {command}", stage.key()));
+        }
+        return Ok(Vec::new());
+    }
+    if !options.yes {
+        if options.from_defaults {
+            return Err(format!(
+                "Template '{}' has {} hook(s) and --from-defaults never asks for confirmation, pass --yes to run them or --no-hooks",
+                template_info.name,
+                commands.len()
+            ).into());
+        }
+        if options.json_events || !sink.can_ask() {
+            sink.say(&format!("{} Skipping {} hook(s) of template '{}', pass --yes to run them", Icon::Warning, commands.len(), template_info.name));
+            return Ok(Vec::new());
+        }
+        sink.say(&format!("{} Template '{}' runs these commands in {}:", Icon::Tool, template_info.name, target_path.display()));
+        for (stage, command) in &commands {
+            sink.say(&format!("  {}: {command}", stage.key()));
+        }
+        if !sink.confirm("Run these hooks?")? {
+            sink.say(&format!("{} Skipping hooks, the template is applied without them", Icon::Note));
+            return Ok(Vec::new());
+        }
+    }
+    Ok(commands)
+}
+
+/// The template's hook commands with their placeholders rendered
+fn hook_commands(template_info: &Template, renderer: &TemplateRenderer) -> Result<Vec<(HookStage, String)>, Box<dyn std::error::Error>> {
+    let Some(hooks) = template_info.config.hooks.as_ref() else {
+        return Ok(Vec::new());
+    };
+    let commands = hooks.commands()
+        .map(|(stage, command)| {
+            let rendered = renderer.render_value(command).map_err(|e| format!("Hook '{command}': {e}"))?;
+            Ok((stage, rendered))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(commands)
+}
+
+/// Runs the language's sanity check in the generated project when `--check` or the template's
+/// `check = true` asks for it. A failing check only fails the run with --check.
+fn run_sanity_check(
+    template_info: &Template,
+    target_path: &Path,
+    options: &ApplyOptions,
+    sink: &mut dyn ApplySink,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !options.check && template_info.config.check != Some(true) {
+        return Ok(());
+    }
+    let Some(check) = template_info.language().and_then(sanity_check) else {
+        let language = template_info.language().map_or_else(|| "this template's".to_string(), get_display_name);
+        sink.say(&format!("{} No sanity check is known for {language} projects", Icon::Note));
+        return Ok(());
+    };
+    let command = check.command_line();
+    if let Some(reason) = check.unavailable(target_path) {
+        sink.say(&format!("{} Skipping the sanity check `{command}`: {reason}", Icon::Warning));
+        return Ok(());
+    }
+
+    sink.say(&format!("{} Checking the generated project: {command}", Icon::Tool));
+    let passed = check.run(target_path)?;
+    if options.json_events {
+        sink.event(&Event::CheckFinished { command: &command, passed })?;
+    }
+    if passed {
+        sink.say(&format!("{} Sanity check passed", Icon::Success));
+    } else if options.check {
+        return Err(TemplateError::CheckFailed(format!("The project was generated, but the sanity check `{command}` failed")).into());
+    } else {
+        sink.say(&format!("{} The sanity check `{command}` failed, the generated project may need fixes", Icon::Warning));
+    }
+    Ok(())
+}
+
+/// Runs the planned hooks of one stage in the target, stopping at the first failure
+fn run_hooks(
+    hooks: &[(HookStage, String)],
+    stage: HookStage,
+    target_path: &Path,
+    json_events: bool,
+    sink: &mut dyn ApplySink,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for (_, command) in hooks.iter().filter(|(hook_stage, _)| *hook_stage == stage) {
+        if json_events {
+            sink.event(&Event::HookStarted { stage: stage.key(), command })?;
+        } else {
+            sink.say(&format!("{} Running {} hook: {command}", Icon::Tool, stage.key()));
+        }
+        run_hook(command, target_path)?;
     }
-    
     Ok(())
 }
+
+/// Warns about placeholders rendering left in the applied files, usually a misspelled variable
+/// name. `literal_braces` tells how the template's syntax escapes braces meant literally.
+fn report_leftovers(
+    leftovers: &[(String, Placeholder)],
+    literal_braces: &str,
+    json_events: bool,
+    sink: &mut dyn ApplySink,
+) -> Result<(), Box<dyn std::error::Error>> {
+    const MAX_LISTED: usize = 20;
+
+    if json_events {
+        for (path, placeholder) in leftovers {
+            sink.event(&Event::UnrenderedPlaceholder { path, line: placeholder.line, placeholder: &placeholder.text })?;
+        }
+        return Ok(());
+    }
+    if leftovers.is_empty() {
+        return Ok(());
+    }
+
+    sink.say(&format!("{} {} placeholder(s) were left unrendered:", Icon::Warning, leftovers.len()));
+    for (path, placeholder) in leftovers.iter().take(MAX_LISTED) {
+        sink.say(&format!("  {path}:{}: {}", placeholder.line, placeholder.text));
+    }
+    if leftovers.len() > MAX_LISTED {
+        sink.say(&format!("  ... and {} more", leftovers.len() - MAX_LISTED));
+    }
+    sink.say(&format!("Check the variable names, or write literal braces as {literal_braces}"));
+    Ok(())
+}
+
+/// Refuses a workspace template whose members can't be applied: bad paths, missing templates,
+/// or members that are workspaces themselves
+fn check_workspace(name: &str, workspace: &WorkspaceConfig, sink: &mut dyn ApplySink) -> Result<(), Box<dyn std::error::Error>> {
+    let mut problems = workspace.problems();
+    for member in &workspace.members {
+        match Template::find(&member.template)? {
+            None => problems.push(format!("member template '{}' not found", member.template)),
+            Some(found) if found.config.workspace.is_some() => {
+                problems.push(format!("member template '{}' is a workspace itself", member.template));
+            }
+            Some(_) => {}
+        }
+    }
+    if problems.is_empty() {
+        return Ok(());
+    }
+
+    sink.say(&format!("{} Workspace template '{name}' can't be applied:", Icon::Failure));
+    for problem in &problems {
+        sink.say(&format!("  - {problem}"));
+    }
+    Err(format!("{} problem(s) in the workspace of template '{name}'", problems.len()).into())
+}
+
+/// Creates each member of a workspace template in its subdirectory of `target_path`, then writes
+/// the root workspace file. An existing root file is only replaced with --force.
+fn apply_workspace(
+    workspace: &WorkspaceConfig,
+    target_path: &Path,
+    options: &ApplyOptions,
+    sink: &mut dyn ApplySink,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for (index, member) in workspace.members.iter().enumerate() {
+        let member_path = target_path.join(member.path.trim_end_matches('/'));
+        sink.say(&format!("\n[{}/{}] {} -> {}", index + 1, workspace.members.len(), member.template, member_path.display()));
+        let member_options = ApplyOptions {
+            create_dir: true,
+            allow_existing: true,
+            // The workspace's own files were just written, members don't see them as uncommitted work
+            allow_dirty: true,
+            delete: false,
+            select: false,
+            confirmed: true,
+            variables: workspace.member_variables(member, &options.variables),
+            ..options.clone()
+        };
+        apply(Some(&member.template), &member_path, &member_options, sink)?;
+    }
+
+    let Some(root) = workspace.root else {
+        return Ok(());
+    };
+    let root_path = target_path.join(root.file_name());
+    let members: Vec<&str> = workspace.members.iter().map(|m| m.path.trim_end_matches('/')).collect();
+    if root_path.exists() && !options.force {
+        sink.say(&format!("{} Keeping existing {}, add the members to it yourself: {}", Icon::Note, root.file_name(), members.join(", ")));
+    } else if options.dry_run {
+        sink.say(&format!("{} Would write {} listing {} member(s)", Icon::DryRun, root.file_name(), members.len()));
+    } else {
+        std::fs::write(&root_path, root.render(&members))?;
+        sink.say(&format!("{} Wrote {} listing {} member(s)", Icon::Success, root.file_name(), members.len()));
+    }
+    Ok(())
+}
+
+/// Prints everything applying the template would change in the target as a git patch on
+/// stdout, rendered and post-processed like a real application, including the lock file.
+/// The target is left untouched.
+fn print_patch(
+    application: &Application,
+    values: BTreeMap<String, String>,
+    sink: &mut dyn ApplySink,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Application { template: template_info, target: target_path, options, user_config, filters, renderer } = *application;
+    let content_dir = template_info.content_dir()?;
+    let plan = copy_plan(template_info, &content_dir, target_path, options, true, filters, true)?;
+    let changes = user_config.copy_engine.engine().copy(&plan, &mut |_| Ok(()))?;
+    let copied: Vec<String> = changes.iter()
+        .filter_map(|change| match change {
+            Change::Copy(path) if !path.ends_with('/') => Some(path.clone()),
+            _ => None,
+        })
+        .collect();
+
+    // Produce the files in a scratch directory, the way they would land in the target
+    let scratch_dir = tempfile::Builder::new().prefix("template-rs-patch-").tempdir()?;
+    let scratch = scratch_dir.path();
+    for file in &copied {
+        let destination = scratch.join(file);
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(content_dir.join(file), destination)?;
+    }
+    let rendered = renderer.apply_to_files(scratch, &copied, |_| true)?;
+    if renderer.is_active() {
+        report_leftovers(&rendered.leftovers, renderer.literal_braces(), false, sink)?;
+    }
+    run_post_processors(template_info, scratch, &rendered.paths)?;
+    EolPolicy::new(template_info, user_config).apply_to_files(scratch, &rendered.paths)?;
+
+    let mut files = Vec::new();
+    for (file, path) in copied.iter().zip(&rendered.paths) {
+        let destination = target_path.join(path);
+        // Rendered paths can land on files the target has; those follow --force and create_once
+        if destination.exists() && (!options.force || template_info.is_create_once(file)) {
+            continue;
+        }
+        let new = std::fs::read(scratch.join(path))?;
+        let old = destination.exists().then(|| std::fs::read(&destination)).transpose()?;
+        if old.as_ref() != Some(&new) {
+            files.push(FileChange { path: path.clone(), old, new: Some(new), executable: is_executable(&scratch.join(path)) });
+        }
+    }
+    for change in &changes {
+        if let Change::Delete(path) = change
+            && !path.ends_with('/')
+        {
+            let old = std::fs::read(target_path.join(path))?;
+            files.push(FileChange { path: path.clone(), old: Some(old), new: None, executable: is_executable(&target_path.join(path)) });
+        }
+    }
+
+    let lock_path = target_path.join(TEMPLATE_LOCK_FILE);
+    let old_lock = lock_path.exists().then(|| std::fs::read(&lock_path)).transpose()?;
+    let new_lock = build_lock(template_info, options, filters, values)?.to_toml()?.into_bytes();
+    files.push(FileChange { path: TEMPLATE_LOCK_FILE.to_string(), old: old_lock, new: Some(new_lock), executable: false });
+
+    print!("{}", format_git_patch(&files));
+    sink.say(&format!("{} Patch touches {} file(s); review it, then apply it with `git apply`", Icon::Success, files.len()));
+    Ok(())
+}
+
+/// Check if a file has an executable bit set; never on platforms without them
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+/// Records what applying the template did, as configured: added and replaced files, and
+/// deletions. Returns where the receipt went, None when none is recorded.
+fn record_receipt(
+    template_info: &Template,
+    target_path: &Path,
+    receipt_target: ReceiptTarget,
+    written: &[String],
+    existing: &BTreeSet<String>,
+    changes: &[Change],
+) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    if receipt_target == ReceiptTarget::None {
+        return Ok(None);
+    }
+    let mut receipt = Receipt::new("init", &template_info.name, template_info.version());
+    for path in written {
+        receipt.add_file(if existing.contains(path) { "replaced" } else { "added" }, path);
+    }
+    for change in changes {
+        if let Change::Delete(path) = change
+            && !path.ends_with('/')
+        {
+            receipt.add_file("deleted", path);
+        }
+    }
+    receipt.record(target_path, receipt_target)
+}
+
+/// The files of the template the target already has, by their rendered paths, to tell replaced
+/// files from added ones in a receipt
+fn existing_files(template_info: &Template, target_path: &Path, filters: &Filters) -> Result<BTreeSet<String>, Box<dyn std::error::Error>> {
+    Ok(template_info.apply_files(&filters.excludes)?
+        .iter()
+        .map(|file| filters.rendered_path(file))
+        .filter(|path| target_path.join(path).exists())
+        .map(String::from)
+        .collect())
+}
+
+/// Lists template files the target already has that won't be overwritten, with the reason.
+/// Paths are the rendered ones, where the files land in the target.
+fn existing_conflicts(
+    template_info: &Template,
+    target_path: &Path,
+    options: &ApplyOptions,
+    filters: &Filters,
+) -> Result<Vec<(String, &'static str)>, Box<dyn std::error::Error>> {
+    let mut conflicts = Vec::new();
+    for file in template_info.apply_files(&filters.excludes)? {
+        let path = filters.rendered_path(&file).to_string();
+        if !target_path.join(&path).exists() {
+            continue;
+        }
+        if !options.force {
+            conflicts.push((path, "kept-existing"));
+        } else if template_info.is_create_once(&file) {
+            conflicts.push((path, "create-once"));
+        }
+    }
+    Ok(conflicts)
+}
+
+/// Lists the paths `--delete` would remove, and those it keeps because they're protected
+fn plan_deletions(
+    template_info: &Template,
+    target_path: &Path,
+    options: &ApplyOptions,
+    filters: &Filters,
+) -> Result<(Vec<String>, Vec<String>), Box<dyn std::error::Error>> {
+    let engine = UserConfig::load()?.copy_engine.engine();
+    let content_dir = template_info.content_dir()?;
+    let unprotected = copy_plan(template_info, &content_dir, target_path, options, true, filters, false)?;
+    let protected = copy_plan(template_info, &content_dir, target_path, options, true, filters, true)?;
+    let unprotected_preview = engine.copy(&unprotected, &mut |_| Ok(()))?;
+    let protected_preview = engine.copy(&protected, &mut |_| Ok(()))?;
+
+    let (_, candidates) = split_changes(&unprotected_preview);
+    let (_, deletions) = split_changes(&protected_preview);
+
+    let kept: Vec<&str> = candidates.iter()
+        .copied()
+        .filter(|path| !deletions.contains(path))
+        .collect();
+
+    // Only report the top of each protected tree, not every file inside .git/
+    let kept_roots = kept.iter()
+        .copied()
+        .filter(|path| {
+            !kept.iter().any(|other| {
+                other != path && other.ends_with('/') && path.starts_with(other)
+            })
+        })
+        .map(String::from)
+        .collect();
+
+    Ok((deletions.into_iter().map(String::from).collect(), kept_roots))
+}
+
+/// Patterns of the files that describe a template rather than belong to the projects made from
+/// it (configuration, documentation, test expectations, ...), plus the target's own records.
+/// None of them are ever copied or deleted.
+pub(crate) fn metadata_excludes() -> Vec<String> {
+    vec![
+        TEMPLATE_CONFIG_FILE.to_string(),
+        format!("/{TEMPLATE_README_FILE}"),
+        format!("/{TEMPLATE_IGNORE_FILE}"),
+        TEMPLATE_TEST_FILE.to_string(),
+        format!("/{TEMPLATE_MANIFEST_FILE}"),
+        format!("/{TEMPLATE_ARCHIVE_FILE}"),
+        format!("/{TEMPLATE_SNAPSHOT_DIR}/"),
+        format!("/{TEMPLATE_PLUGIN_DIR}/"),
+        format!("/{TEMPLATE_ORIGIN_FILE}"),
+        // Never copy over (or delete) the target's record of prior initialization
+        TEMPLATE_LOCK_FILE.to_string(),
+        format!("/{TEMPLATE_HISTORY_FILE}"),
+    ]
+}
+
+/// Describes copying the template into the target.
+/// With `preview` set nothing is changed.
+/// With `protect` set, paths matching the protected patterns are never deleted.
+fn copy_plan<'a>(
+    template_info: &Template,
+    source: &'a Path,
+    target_path: &'a Path,
+    options: &ApplyOptions,
+    preview: bool,
+    filters: &Filters,
+    protect: bool,
+) -> Result<CopyPlan<'a>, Box<dyn std::error::Error>> {
+    let mut excludes = metadata_excludes();
+
+    // Default and user-configured exclusions (.git/, build dirs, ...)
+    excludes.extend(filters.excludes.iter().cloned());
+    // What the template's author never wants applied
+    excludes.extend(template_info.ignore_patterns()?);
+
+    // create_once files are only copied when missing, even with --force, and files whose
+    // rendered path the target already has aren't copied unless they replace it
+    for file in template_info.apply_files(&filters.excludes)? {
+        let path = filters.rendered_path(&file);
+        if target_path.join(path).exists()
+            && (template_info.is_create_once(&file) || (path != file && !options.force))
+        {
+            excludes.push(format!("/{file}"));
+        }
+    }
+
+    let mut protected = if protect { filters.protected.clone() } else { Vec::new() };
+    protected.extend(filters.rendered.values().map(|path| format!("/{path}")));
+
+    let ownership = match &options.chown {
+        Some(owner) => Ownership::Set(owner.clone()),
+        None if options.preserve_owner => Ownership::Preserve,
+        None => Ownership::Invoker,
+    };
+
+    Ok(CopyPlan {
+        source,
+        target: target_path,
+        excludes,
+        protected,
+        overwrite: options.force,
+        delete: options.delete,
+        dry_run: preview,
+        ownership,
+        // Only matters for files actually created, see apply_template
+        mode_mask: 0,
+        timestamps: Timestamps::Now,
+    })
+}
+
+/// Splits changes into copied and deleted paths
+fn split_changes(changes: &[Change]) -> (Vec<&str>, Vec<&str>) {
+    let mut copies = Vec::new();
+    let mut deletions = Vec::new();
+
+    for change in changes {
+        match change {
+            Change::Copy(path) => copies.push(path.as_str()),
+            Change::Delete(path) => deletions.push(path.as_str()),
+        }
+    }
+
+    (copies, deletions)
+}
+
+/// Prints the changes of a copy with copied and deleted entries kept apart, under the headings
+/// given ("Would copy:" for a dry run)
+fn print_change_list(changes: &[Change], copy_heading: &str, delete_heading: &str) {
+    let (copies, deletions) = split_changes(changes);
+
+    if !copies.is_empty() {
+        println!("{copy_heading}");
+        for path in &copies {
+            println!("  {path}");
+        }
+    }
+
+    if !deletions.is_empty() {
+        println!("{delete_heading}");
+        for path in &deletions {
+            println!("  {path}");
+        }
+    }
+}
+
+/// Prints changes as plain records, one `<action>\t<path>` line per change
+fn print_changes(changes: &[Change], copy_action: &str, delete_action: &str) {
+    let (copies, deletions) = split_changes(changes);
+    for path in copies {
+        print_record(&[copy_action, path]);
+    }
+    for path in deletions {
+        print_record(&[delete_action, path]);
+    }
+}
+
+/// Picks the template to apply, falling back to the one recorded in the target's lock file
+fn resolve_template_name<'a>(
+    template: Option<&'a str>,
+    previous_lock: Option<&'a TemplateLock>,
+    target_path: &Path,
+    sink: &mut dyn ApplySink,
+) -> Result<&'a str, Box<dyn std::error::Error>> {
+    match (template, previous_lock) {
+        (Some(template), _) => Ok(template),
+        (None, Some(lock)) => {
+            sink.say(&format!("Reusing template recorded in {TEMPLATE_LOCK_FILE}"));
+            Ok(&lock.template)
+        }
+        (None, None) => Err(format!(
+            "No template specified and no {TEMPLATE_LOCK_FILE} found in {}",
+            target_path.display()
+        ).into()),
+    }
+}
+
+/// Warns about a prior initialization and refuses to layer a different template without --reinit
+fn check_previous_lock(
+    lock: &TemplateLock,
+    template_info: &Template,
+    reinit: bool,
+    sink: &mut dyn ApplySink,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let recorded_version = lock.version.as_deref().unwrap_or("unknown");
+    sink.say(&format!(
+        "{} Directory was already initialized from template '{}' (version {recorded_version})",
+        Icon::Warning,
+        lock.template
+    ));
+
+    if lock.template == template_info.name {
+        sink.say("Re-applying the same template");
+    } else if reinit {
+        sink.say(&format!("Re-initializing with template '{}'", template_info.name));
+    } else {
+        return Err(format!(
+            "Directory was initialized from template '{}', not '{}'. Use --reinit to apply a different template.",
+            lock.template, template_info.name
+        ).into());
+    }
+
+    Ok(())
+}
+
+/// Gives the written files a fixed modification time (SOURCE_DATE_EPOCH), so archives and
+/// checksums of the generated project are reproducible
+fn set_modified_times(target_path: &Path, files: &[String], epoch: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let time = UNIX_EPOCH + Duration::from_secs(epoch);
+    for file in files {
+        let path = target_path.join(file);
+        if std::fs::symlink_metadata(&path)?.is_file() {
+            std::fs::File::options().write(true).open(&path)?.set_modified(time)?;
+        }
+    }
+    Ok(())
+}
+
+/// Gives the written files the modification times of the template files they came from, which
+/// rendering, post-processing and line ending fixes would otherwise have reset
+fn preserve_modified_times(
+    renderer: &TemplateRenderer,
+    content_dir: &Path,
+    target_path: &Path,
+    files: &[String],
+    written: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    for file in files {
+        let rendered = renderer.render_path(file)?;
+        let path = target_path.join(&rendered);
+        if written.contains(&rendered) && std::fs::symlink_metadata(&path)?.is_file() {
+            let modified = std::fs::metadata(content_dir.join(file))?.modified()?;
+            std::fs::File::options().write(true).open(&path)?.set_modified(modified)?;
+        }
+    }
+    Ok(())
+}
+
+/// Variables supplied for this application, later sources winning: config.toml's [variables],
+/// values read from the target's own files (`from` sources), the values recorded when the same
+/// template was applied before, TEMPLATE_RS_VAR_<NAME> environment variables, then those passed
+/// in. Config and environment values only count for
+/// variables the template declares.
+pub(crate) fn supplied_variables(
+    template_info: &Template,
+    target_path: &Path,
+    options: &ApplyOptions,
+    previous_lock: Option<&TemplateLock>,
+    user_config: &UserConfig,
+    sink: &mut dyn ApplySink,
+) -> BTreeMap<String, String> {
+    let declared = template_info.config.variables.as_ref();
+    let is_declared = |name: &str| declared.is_some_and(|declared| declared.contains_key(name));
+
+    let mut supplied: BTreeMap<String, String> = user_config.variables.iter()
+        .filter(|(name, _)| is_declared(name))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    // What the project itself says beats the user's general defaults
+    for (name, spec) in declared.into_iter().flatten() {
+        let Some(from) = &spec.from else { continue };
+        match project_value(target_path, from) {
+            Ok(Some(value)) => {
+                sink.say(&format!("Variable {name} = {value} (from {from})"));
+                supplied.insert(name.clone(), value);
+            }
+            Ok(None) => {}
+            Err(e) => sink.say(&format!("{} Variable {name}: {e}", Icon::Warning)),
+        }
+    }
+    if let Some(previous) = previous_lock.filter(|previous| previous.template == template_info.name) {
+        supplied.extend(previous.variables.clone());
+    }
+    for name in declared.into_iter().flat_map(BTreeMap::keys) {
+        if let Ok(value) = env::var(format!("{VARIABLE_ENV_PREFIX}{}", name.to_uppercase())) {
+            supplied.insert(name.clone(), value);
+        }
+    }
+    supplied.extend(options.variables.clone());
+    supplied
+}
+
+/// The lock an application records, with the template's content stored in the object store
+fn build_lock(
+    template_info: &Template,
+    options: &ApplyOptions,
+    filters: &Filters,
+    variables: BTreeMap<String, String>,
+) -> Result<TemplateLock, Box<dyn std::error::Error>> {
+    let mut lock = TemplateLock::new(&template_info.name, template_info.version());
+    lock.options.force = options.force;
+    lock.options.delete = options.delete;
+    lock.options.default_excludes = options.default_excludes;
+    lock.variables = variables;
+
+    let content_dir = template_info.content_dir()?;
+    for file in template_info.apply_files(&filters.excludes)? {
+        let content = std::fs::read(content_dir.join(&file))?;
+        lock.files.insert(file, store_blob(&content)?);
+    }
+    Ok(lock)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::TestStorage;
+    use std::fs;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_absolute_symlinks() {
+        let storage = TestStorage::new();
+        let template_dir = storage.path().join("templates/linked");
+        fs::create_dir_all(&template_dir).unwrap();
+        fs::write(template_dir.join(TEMPLATE_CONFIG_FILE), "").unwrap();
+        std::os::unix::fs::symlink("/etc/passwd", template_dir.join("passwd")).unwrap();
+        let target = storage.path().join("project");
+
+        let options = ApplyOptions { create_dir: true, ..ApplyOptions::default() };
+        let error = apply(Some("linked"), &target, &options, &mut Unattended).unwrap_err();
+        assert!(error.to_string().contains("passwd links outside the template"), "{error}");
+        assert!(fs::symlink_metadata(target.join("passwd")).is_err());
+    }
+
+    #[test]
+    fn test_existing_files_with_rendered_paths() {
+        let scratch = tempfile::tempdir().unwrap();
+        let template_dir = scratch.path().join("templates/app");
+        let target = scratch.path().join("project");
+        fs::create_dir_all(template_dir.join("{{ name }}")).unwrap();
+        fs::create_dir_all(target.join("demo")).unwrap();
+        fs::write(template_dir.join(TEMPLATE_CONFIG_FILE), "[files.\"{{ name }}/settings.toml\"]\ncreate_once = true\n").unwrap();
+        for file in ["{{ name }}/settings.toml", "{{ name }}/notes.md", "README.md"] {
+            fs::write(template_dir.join(file), "template\n").unwrap();
+        }
+        for file in ["demo/settings.toml", "demo/notes.md"] {
+            fs::write(target.join(file), "local\n").unwrap();
+        }
+
+        let template = Template::discover_in(&scratch.path().join("templates")).unwrap().remove(0);
+        let renderer = TemplateRenderer::new(&template, BTreeMap::from([("name".to_string(), "demo".to_string())])).unwrap();
+        let mut rendered = BTreeMap::new();
+        for file in template.files().unwrap() {
+            let path = renderer.render_path(&file).unwrap();
+            if path != file {
+                rendered.insert(file, path);
+            }
+        }
+        let filters = Filters { excludes: Vec::new(), protected: Vec::new(), rendered };
+
+        // Without --force both rendered files are kept, and neither copy is made
+        let options = ApplyOptions::default();
+        let conflicts = existing_conflicts(&template, &target, &options, &filters).unwrap();
+        assert_eq!(conflicts, [("demo/notes.md".to_string(), "kept-existing"), ("demo/settings.toml".to_string(), "kept-existing")]);
+        let plan = copy_plan(&template, &template_dir, &target, &options, true, &filters, true).unwrap();
+        assert!(plan.excludes.contains(&"/{{ name }}/settings.toml".to_string()));
+        assert!(plan.excludes.contains(&"/{{ name }}/notes.md".to_string()));
+
+        // --force replaces the rendered file, but never the create_once one
+        let options = ApplyOptions { force: true, ..ApplyOptions::default() };
+        let conflicts = existing_conflicts(&template, &target, &options, &filters).unwrap();
+        assert_eq!(conflicts, [("demo/settings.toml".to_string(), "create-once")]);
+        let plan = copy_plan(&template, &template_dir, &target, &options, true, &filters, true).unwrap();
+        assert!(plan.excludes.contains(&"/{{ name }}/settings.toml".to_string()));
+        assert!(!plan.excludes.contains(&"/{{ name }}/notes.md".to_string()));
+    }
+
+    #[test]
+    fn test_rejects_rendered_paths_climbing_out() {
+        let storage = TestStorage::new();
+        let template_dir = storage.path().join("templates/escape");
+        fs::create_dir_all(&template_dir).unwrap();
+        fs::write(template_dir.join(TEMPLATE_CONFIG_FILE), "[variables]\nname = {}\n").unwrap();
+        fs::write(template_dir.join("{{ name }}.txt"), "escaped\n").unwrap();
+        let target = storage.path().join("project");
+
+        let options = ApplyOptions {
+            create_dir: true,
+            variables: BTreeMap::from([("name".to_string(), "../escaped".to_string())]),
+            ..ApplyOptions::default()
+        };
+        let error = apply(Some("escape"), &target, &options, &mut Unattended).unwrap_err();
+        assert!(error.to_string().contains("not a valid relative path"), "{error}");
+        assert!(!storage.path().join("escaped.txt").exists());
+    }
+}
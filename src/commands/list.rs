@@ -1,114 +1,486 @@
-use crate::template::Template;
+use crate::commands::validate::warn_if_oversized;
+use crate::config::UserConfig;
+use crate::file::{ensure_template_storage_dir, format_size};
+use crate::output::{is_plain, print_record};
+use crate::registry::find_updates;
+use crate::template::{STORAGE_ROOT, Template, TemplateRoot, template_roots};
+use crate::categories::{get_category_display_name, is_known_category};
 use crate::languages::{get_display_name, is_known_language};
-use std::collections::BTreeMap;
+use crate::tags::{get_tag_display_name, normalize_tags};
+use crate::theme::Icon;
 
-pub fn handle_list(verbose: bool, language: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
-    print_header();
+use clap::ValueEnum;
+use serde::Serialize;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Options controlling which templates `list` shows and how
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// Show detailed information for each template
+    pub verbose: bool,
+    /// Only show templates with this language
+    pub language: Option<String>,
+    /// Only show templates in this category
+    pub category: Option<String>,
+    /// Only show templates having all of these tags
+    pub tags: Vec<String>,
+    /// List the tags in use instead of the templates
+    pub all_tags: bool,
+    /// Field the listing is grouped by
+    pub group_by: GroupBy,
+    /// Only show how many templates there are per language, category and tag
+    pub count: bool,
+    /// Only show templates that a registry has a newer version of
+    pub updates: bool,
+    /// Only show templates that declare variables
+    pub has_variables: bool,
+    /// Only show templates using all of these features (see `Template::features`)
+    pub features: Vec<String>,
+    /// Only show templates with (`Some(true)`) or without (`Some(false)`) hooks
+    pub hooks: Option<bool>,
+    /// Print the templates as JSON or YAML for scripts instead of the listing
+    pub output: Option<OutputFormat>,
+    /// Only show templates that can't be used, with the reason
+    pub broken: bool,
+}
+
+/// Serialization format of `list --output`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+}
+
+/// A template as `list --output` serializes it
+#[derive(Debug, Serialize)]
+struct ListedTemplate<'a> {
+    name: &'a str,
+    path: String,
+    language: Option<String>,
+    category: Option<String>,
+    description: Option<&'a str>,
+    version: Option<&'a str>,
+    tags: Vec<String>,
+    /// Label of the template root it was found in
+    root: &'a str,
+}
+
+impl<'a> ListedTemplate<'a> {
+    fn new(template: &'a Template, roots: &'a [TemplateRoot]) -> Self {
+        Self {
+            name: &template.name,
+            path: template.path.display().to_string(),
+            language: template.language().map(get_display_name),
+            category: template.category().map(get_category_display_name),
+            description: template.description(),
+            version: template.version(),
+            tags: template.tags().map(normalize_tags).unwrap_or_default(),
+            root: template.root_label(roots),
+        }
+    }
+}
+
+/// Field templates are grouped under in `list`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    #[default]
+    Language,
+    Category,
+    Tag,
+}
+
+impl GroupBy {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Language => "language",
+            Self::Category => "category",
+            Self::Tag => "tag",
+        }
+    }
+
+    /// The template's values for this field; only tags can have more than one
+    fn values(self, template: &Template) -> Vec<&str> {
+        match self {
+            Self::Language => template.language().into_iter().collect(),
+            Self::Category => template.category().into_iter().collect(),
+            Self::Tag => template.tags().unwrap_or_default().iter().map(String::as_str).collect(),
+        }
+    }
+
+    fn display_name(self, value: &str) -> String {
+        match self {
+            Self::Language => get_display_name(value),
+            Self::Category => get_category_display_name(value),
+            Self::Tag => get_tag_display_name(value),
+        }
+    }
+
+    fn is_known(self, value: &str) -> bool {
+        match self {
+            Self::Language => is_known_language(value),
+            Self::Category => is_known_category(value),
+            // Tags are free-form, aliases only merge spelling variants
+            Self::Tag => true,
+        }
+    }
+}
+
+pub fn handle_list(options: &ListOptions) -> Result<(), Box<dyn std::error::Error>> {
+    if !is_plain() && options.output.is_none() {
+        print_header();
+    }
     
+    if options.broken {
+        return display_broken();
+    }
+
     let mut templates = Template::discover_all()?;
-    filter_by_language(&mut templates, language);
+    if let Some(language) = &options.language {
+        filter_by(&mut templates, GroupBy::Language, language);
+    }
+    if let Some(category) = &options.category {
+        filter_by(&mut templates, GroupBy::Category, category);
+    }
+    for tag in &options.tags {
+        filter_by(&mut templates, GroupBy::Tag, tag);
+    }
+    filter_by_capabilities(&mut templates, options);
+
+    if options.all_tags {
+        display_tags(&templates);
+        return Ok(());
+    }
+
+    if let Some(format) = options.output {
+        return print_serialized(&templates, format);
+    }
     
     if templates.is_empty() {
-        print_no_templates_message(language);
+        print_no_templates_message(options);
         return Ok(());
     }
     
-    let has_unrecognized = check_for_unrecognized(&templates);
+    if options.count {
+        display_counts(&templates);
+        return Ok(());
+    }
+
+    if options.updates {
+        return display_updates(&templates);
+    }
+
+    let roots = template_roots()?;
+    if is_plain() {
+        display_plain(&templates, &roots, options.verbose);
+        return Ok(());
+    }
+
+    let has_unrecognized = check_for_unrecognized(&templates, options.group_by);
     
-    if verbose {
-        display_verbose(&templates);
+    if options.verbose {
+        let threshold = UserConfig::load()?.size_warning_bytes();
+        display_verbose(&templates, &roots, options.group_by, threshold);
     } else {
-        display_simple(&templates);
+        display_simple(&templates, &roots, options.group_by);
     }
+    print_footer(&templates, options.group_by);
     
     if has_unrecognized {
-        println!("* Unrecognized language (not in standard list)\n");
+        eprintln!("* Unrecognized {} (not in standard list)\n", options.group_by.label());
     }
     
     Ok(())
 }
 
 fn print_header() {
-    println!("Templates are located in {}", crate::path::TEMPLATE_STORAGE);
-    println!("They require a .template.toml file in their root dir");
-    println!("Use `{} author --help` to learn how to create a template\n", env!("CARGO_BIN_NAME"));
-}
-
-fn filter_by_language(templates: &mut Vec<Template>, language: Option<&str>) {
-    if let Some(lang) = language {
-        let lang_lower = lang.to_lowercase();
-        
-        // Handle special cases
-        if lang_lower == "unknown" {
-            templates.retain(|t| t.language().is_none());
-        } else if lang_lower == "unrecognized" {
-            templates.retain(|t| {
-                t.language()
-                    .is_some_and(|l| !is_known_language(l))
-            });
-        } else {
-            // Normal language filtering - check if input matches any alias for the template's language
-            templates.retain(|t| {
-                t.language().is_some_and(|template_lang| {
-                    // Get display name of template's language
-                    let template_display = get_display_name(template_lang);
-                    // Get display name of user's input
-                    let input_display = get_display_name(lang);
-                    // Match if display names are the same (both resolve to same canonical name)
-                    template_display.eq_ignore_ascii_case(&input_display)
-                })
-            });
-        }
+    let template_dir = crate::path::storage_dirs().data.join(crate::path::TEMPLATE_STORAGE);
+    eprintln!("Templates are located in {}", template_dir.display());
+    eprintln!("They require a .template.toml file in their root dir");
+    eprintln!("Use `{} author --help` to learn how to create a template\n", crate::BIN_NAME);
+}
+
+fn filter_by(templates: &mut Vec<Template>, field: GroupBy, filter: &str) {
+    let filter_lower = filter.to_lowercase();
+    
+    // Handle special cases
+    if filter_lower == "unknown" {
+        templates.retain(|t| field.values(t).is_empty());
+    } else if filter_lower == "unrecognized" {
+        templates.retain(|t| {
+            field.values(t).into_iter()
+                .any(|v| !field.is_known(v))
+        });
+    } else {
+        // Normal filtering - check if input matches any alias for one of the template's values
+        templates.retain(|t| {
+            field.values(t).into_iter().any(|template_value| {
+                // Get display name of template's value
+                let template_display = field.display_name(template_value);
+                // Get display name of user's input
+                let input_display = field.display_name(filter);
+                // Match if display names are the same (both resolve to same canonical name)
+                template_display.eq_ignore_ascii_case(&input_display)
+            })
+        });
     }
 }
 
-fn print_no_templates_message(language: Option<&str>) {
-    if let Some(lang) = language {
-        println!("No templates found for language filter '{lang}'.");
-        println!("Use `{} list` to see all available templates.", env!("CARGO_BIN_NAME"));
+/// Keeps the templates whose parsed config has the capabilities asked for
+fn filter_by_capabilities(templates: &mut Vec<Template>, options: &ListOptions) {
+    if options.has_variables {
+        templates.retain(Template::has_variables);
+    }
+    if let Some(hooks) = options.hooks {
+        templates.retain(|t| t.has_hooks() == hooks);
+    }
+    if !options.features.is_empty() {
+        templates.retain(|t| {
+            let features = t.features();
+            options.features.iter().all(|wanted| features.contains(&wanted.to_lowercase()))
+        });
+    }
+}
+
+/// Prints the templates as a JSON or YAML list on stdout, an empty list when none match
+fn print_serialized(templates: &[Template], format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let roots = template_roots()?;
+    let listed: Vec<ListedTemplate> = templates.iter().map(|template| ListedTemplate::new(template, &roots)).collect();
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&listed)?),
+        OutputFormat::Yaml => print!("{}", serde_yaml_ng::to_string(&listed)?),
+    }
+    Ok(())
+}
+
+fn print_no_templates_message(options: &ListOptions) {
+    let mut filters: Vec<String> = [("language", &options.language), ("category", &options.category)]
+        .into_iter()
+        .filter_map(|(field, value)| value.as_ref().map(|v| format!("{field} filter '{v}'")))
+        .collect();
+    filters.extend(options.tags.iter().map(|t| format!("tag '{t}'")));
+    if options.has_variables {
+        filters.push("--has-variables".to_string());
+    }
+    match options.hooks {
+        Some(true) => filters.push("--hooked".to_string()),
+        Some(false) => filters.push("--no-hooks".to_string()),
+        None => {}
+    }
+    filters.extend(options.features.iter().map(|f| format!("feature '{f}'")));
+
+    if filters.is_empty() {
+        eprintln!("No templates found.");
+        eprintln!("Templates should be directories in the template storage with a .template.toml file.");
     } else {
-        println!("No templates found.");
-        println!("Templates should be directories in ~/.template-rs/templates/ with a .template.toml file.");
+        eprintln!("No templates found for {}.", filters.join(" and "));
+        eprintln!("Use `{} list` to see all available templates.", crate::BIN_NAME);
     }
 }
 
-fn check_for_unrecognized(templates: &[Template]) -> bool {
+fn check_for_unrecognized(templates: &[Template], group_by: GroupBy) -> bool {
     templates.iter()
-        .filter_map(|t| t.language())
-        .any(|lang| !is_known_language(lang))
+        .flat_map(|t| group_by.values(t))
+        .any(|value| !group_by.is_known(value))
 }
 
-fn group_by_language(templates: &[Template]) -> BTreeMap<String, Vec<&Template>> {
-    let mut by_language: BTreeMap<String, Vec<&Template>> = BTreeMap::new();
+fn group_templates(templates: &[Template], group_by: GroupBy) -> BTreeMap<String, Vec<&Template>> {
+    let mut groups: BTreeMap<String, Vec<&Template>> = BTreeMap::new();
     
     for template in templates {
-        let lang_key = match template.language() {
-            Some(lang) => {
-                let display = get_display_name(lang);
-                if is_known_language(lang) {
+        // A template with several tags is listed under each of them, but only once per tag
+        let mut keys: BTreeSet<String> = group_by.values(template).into_iter()
+            .map(|value| {
+                let display = group_by.display_name(value);
+                if group_by.is_known(value) {
                     display
                 } else {
                     format!("{display}*")
                 }
-            }
-            None => "\u{FFFF}Unknown".to_string(), // Unicode max char to sort last
-        };
-        by_language.entry(lang_key).or_default().push(template);
+            })
+            .collect();
+        if keys.is_empty() {
+            keys.insert("\u{FFFF}Unknown".to_string()); // Unicode max char to sort last
+        }
+        for key in keys {
+            groups.entry(key).or_default().push(template);
+        }
     }
     
-    by_language
+    groups
+}
+
+/// Number of templates per group, largest first; a template with several tags counts once per tag
+fn count_templates(templates: &[Template], group_by: GroupBy) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = group_templates(templates, group_by)
+        .into_iter()
+        .map(|(group, members)| (group.trim_start_matches('\u{FFFF}').to_string(), members.len()))
+        .collect();
+    // Stable sort keeps the alphabetical order (Unknown last) among equal counts
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts
+}
+
+fn print_footer(templates: &[Template], group_by: GroupBy) {
+    let counts: Vec<String> = count_templates(templates, group_by)
+        .into_iter()
+        .map(|(group, count)| format!("{group}: {count}"))
+        .collect();
+    println!("{} template(s) ({})\n", templates.len(), counts.join(", "));
 }
 
-fn display_verbose(templates: &[Template]) {
-    let by_language = group_by_language(templates);
+/// Totals per language, category and tag for `list --count`.
+/// Plain records: field, value, count; then "total" and the overall count.
+fn display_counts(templates: &[Template]) {
+    for group_by in GroupBy::value_variants() {
+        let counts = count_templates(templates, *group_by);
+        if !is_plain() {
+            println!("By {}:", group_by.label());
+        }
+        for (group, count) in counts {
+            if is_plain() {
+                print_record(&[group_by.label(), &group, &count.to_string()]);
+            } else {
+                println!("  {count:>4}  {group}");
+            }
+        }
+        if !is_plain() {
+            println!();
+        }
+    }
+
+    if is_plain() {
+        print_record(&["total", &templates.len().to_string()]);
+    } else {
+        println!("Total: {} template(s)", templates.len());
+    }
+}
+
+/// The tags in use among the templates, alphabetically, with how many templates have each.
+/// Plain records: tag, count.
+fn display_tags(templates: &[Template]) {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for template in templates {
+        for tag in template.tags().map(normalize_tags).unwrap_or_default() {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+
+    if counts.is_empty() && !is_plain() {
+        eprintln!("No tags in use.");
+        return;
+    }
+    for (tag, count) in &counts {
+        if is_plain() {
+            print_record(&[tag, &count.to_string()]);
+        } else {
+            println!("  {count:>4}  {tag}");
+        }
+    }
+}
+
+/// Lists stored templates that can't be used: those whose .template.toml doesn't parse, and
+/// those that parse but are incomplete (see `Template::problems`), each with the reason
+fn display_broken() -> Result<(), Box<dyn std::error::Error>> {
+    let broken = broken_templates(&ensure_template_storage_dir()?)?;
+    for (name, path, problems) in &broken {
+        if is_plain() {
+            for problem in problems {
+                print_record(&[name, path, problem]);
+            }
+        } else {
+            println!("{} {name} ({path})", Icon::Failure);
+            for problem in problems {
+                for line in problem.lines() {
+                    println!("    {line}");
+                }
+            }
+        }
+    }
+    if broken.is_empty() {
+        eprintln!("{} No broken templates", Icon::Success);
+    } else if !is_plain() {
+        eprintln!("\n{} broken template(s), fix or delete them", broken.len());
+    }
+    Ok(())
+}
+
+/// A template that can't be used: its name, path and the reasons
+type Broken = (String, String, Vec<String>);
+
+/// The templates below `root` that can't be used, sorted by name
+fn broken_templates(root: &Path) -> Result<Vec<Broken>, Box<dyn std::error::Error>> {
+    let (templates, unparsable) = Template::discover_with_broken(root)?;
+    let mut broken: Vec<Broken> = unparsable
+        .into_iter()
+        .map(|template| (template.name, template.path.display().to_string(), vec![template.error]))
+        .collect();
+    for template in templates {
+        let problems = template.problems();
+        if !problems.is_empty() {
+            broken.push((template.name, template.path.display().to_string(), problems));
+        }
+    }
+    broken.sort();
+
+    Ok(broken)
+}
+
+fn display_updates(templates: &[Template]) -> Result<(), Box<dyn std::error::Error>> {
+    let user_config = UserConfig::load()?;
+    if user_config.registries.is_empty() {
+        return Err("No registries configured; add `registries = [\"<dir>\"]` to config.toml".into());
+    }
+
+    let updates = find_updates(templates, &user_config)?;
+    if is_plain() {
+        for update in &updates {
+            print_record(&[
+                &update.template,
+                update.installed.as_deref().unwrap_or("-"),
+                &update.available,
+                &update.registry,
+            ]);
+        }
+        return Ok(());
+    }
+
+    if updates.is_empty() {
+        eprintln!("{} All templates are up to date with {} registry(ies)", Icon::Success, user_config.registries.len());
+        return Ok(());
+    }
+
+    let name_width = updates.iter().map(|u| u.template.len()).max().unwrap_or(0).max("TEMPLATE".len());
+    let installed_width = updates.iter()
+        .map(|u| u.installed.as_deref().map_or(1, str::len))
+        .max()
+        .unwrap_or(0)
+        .max("INSTALLED".len());
+    println!("{:<name_width$}  {:<installed_width$}  AVAILABLE  REGISTRY", "TEMPLATE", "INSTALLED");
+    for update in &updates {
+        println!(
+            "{:<name_width$}  {:<installed_width$}  {:<9}  {}",
+            update.template,
+            update.installed.as_deref().unwrap_or("-"),
+            update.available,
+            update.registry
+        );
+    }
+    eprintln!("\n{} update(s) available; nothing was changed", updates.len());
+    Ok(())
+}
+
+fn display_verbose(templates: &[Template], roots: &[TemplateRoot], group_by: GroupBy, size_threshold: u64) {
+    let groups = group_templates(templates, group_by);
     
     println!("Available templates:\n");
     
-    for (lang, templates_in_lang) in by_language {
+    for (group, templates_in_group) in groups {
         // Strip the sorting prefix for display
-        let display_lang = lang.trim_start_matches('\u{FFFF}');
-        println!("  {display_lang}:");
-        for template in templates_in_lang {
+        let display_group = group.trim_start_matches('\u{FFFF}');
+        println!("  {display_group}:");
+        for template in templates_in_group {
             if let Some(name) = &template.config.name {
                 println!("    Name: {name}");
             }
@@ -116,6 +488,13 @@ fn display_verbose(templates: &[Template]) {
             if let Some(description) = template.description() {
                 println!("    Description: {description}");
             }
+            // The grouping field is already the heading, show the others
+            if group_by != GroupBy::Category && let Some(category) = template.category() {
+                println!("    Category: {}", get_category_display_name(category));
+            }
+            if group_by != GroupBy::Language && let Some(language) = template.language() {
+                println!("    Language: {}", get_display_name(language));
+            }
             if let Some(author) = template.author() {
                 println!("    Author: {author}");
             }
@@ -123,23 +502,28 @@ fn display_verbose(templates: &[Template]) {
                 println!("    Version: {version}");
             }
             if let Some(tags) = template.tags() {
-                println!("    Tags: {}", tags.join(", "));
+                println!("    Tags: {}", normalize_tags(tags).join(", "));
+            }
+            println!("    Root: {}", template.root_label(roots));
+            if let Ok(report) = template.size_report() {
+                println!("    Size: {}", format_size(report.total));
+                warn_if_oversized(&template.name, &report, size_threshold);
             }
             println!();
         }
     }
 }
 
-fn display_simple(templates: &[Template]) {
-    let by_language = group_by_language(templates);
+fn display_simple(templates: &[Template], roots: &[TemplateRoot], group_by: GroupBy) {
+    let groups = group_templates(templates, group_by);
     
     println!("Available templates:\n");
     
-    for (lang, templates_in_lang) in by_language {
+    for (group, templates_in_group) in groups {
         // Strip the sorting prefix for display
-        let display_lang = lang.trim_start_matches('\u{FFFF}');
-        println!("  {display_lang}:");
-        for template in templates_in_lang {
+        let display_group = group.trim_start_matches('\u{FFFF}');
+        println!("  {display_group}:");
+        for template in templates_in_group {
             if let Some(name) = &template.config.name {
                 println!("    Name: {name}");
             }
@@ -147,7 +531,164 @@ fn display_simple(templates: &[Template]) {
             if let Some(description) = template.description() {
                 println!("    Description: {description}");
             }
+            // Only templates from the configured roots are marked, storage is the usual place
+            let root = template.root_label(roots);
+            if root != STORAGE_ROOT && !template.is_builtin() {
+                println!("    Root: {root}");
+            }
             println!();
         }
     }
 }
+
+/// One tab-separated record per template for `--plain`:
+/// path, language, category, description; with --verbose: path, name, language, category,
+/// version, author, tags (comma-separated), size in bytes, description, root. Missing values are "-".
+fn display_plain(templates: &[Template], roots: &[TemplateRoot], verbose: bool) {
+    for template in templates {
+        let language = template.language().map_or_else(|| "-".to_string(), get_display_name);
+        let category = template.category().map_or_else(|| "-".to_string(), get_category_display_name);
+        let description = template.description().unwrap_or("-");
+
+        if verbose {
+            let tags = template.tags().map_or_else(|| "-".to_string(), |tags| normalize_tags(tags).join(","));
+            let size = template.size_report().map_or_else(|_| "-".to_string(), |r| r.total.to_string());
+            print_record(&[
+                &template.name,
+                template.config.name.as_deref().unwrap_or("-"),
+                &language,
+                &category,
+                template.version().unwrap_or("-"),
+                template.author().unwrap_or("-"),
+                &tags,
+                &size,
+                description,
+                template.root_label(roots),
+            ]);
+        } else {
+            print_record(&[&template.name, &language, &category, description]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::{TEMPLATE_STORAGE, TestStorage};
+    use crate::template::TEMPLATE_CONFIG_FILE;
+
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn template(name: &str, config: &str) -> Template {
+        Template { name: name.to_string(), path: PathBuf::from("/templates").join(name), config: toml::from_str(config).unwrap() }
+    }
+
+    fn templates() -> Vec<Template> {
+        vec![
+            template("cli", "language = \"rust\"\ntags = [\"cli\", \"k8s\"]\n\n[variables]\nname = { default = \"demo\" }\n"),
+            template("web", "language = \"typescript\"\ntags = [\"web\"]\n\n[hooks]\npost_init = [\"npm install\"]\n"),
+            template("operator", "language = \"rs\"\ntags = [\"kubernetes\"]\n\n[hooks]\npost_init = [\"cargo check\"]\n"),
+            template("odd", "language = \"brainfuck\"\n"),
+            template("bare", ""),
+        ]
+    }
+
+    fn names(templates: &[Template]) -> Vec<&str> {
+        templates.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    fn filtered(field: GroupBy, filter: &str) -> Vec<String> {
+        let mut templates = templates();
+        filter_by(&mut templates, field, filter);
+        templates.into_iter().map(|t| t.name).collect()
+    }
+
+    #[test]
+    fn test_filter_by_language() {
+        assert_eq!(filtered(GroupBy::Language, "Rust"), ["cli", "operator"]);
+        assert_eq!(filtered(GroupBy::Language, "rs"), ["cli", "operator"]);
+        assert_eq!(filtered(GroupBy::Language, "unknown"), ["bare"]);
+        assert_eq!(filtered(GroupBy::Language, "unrecognized"), ["odd"]);
+        assert!(filtered(GroupBy::Language, "go").is_empty());
+    }
+
+    #[test]
+    fn test_filter_by_tag() {
+        assert_eq!(filtered(GroupBy::Tag, "kubernetes"), ["cli", "operator"]);
+        assert_eq!(filtered(GroupBy::Tag, "K8S"), ["cli", "operator"]);
+        assert_eq!(filtered(GroupBy::Tag, "unknown"), ["odd", "bare"]);
+    }
+
+    #[test]
+    fn test_filter_by_capabilities() {
+        let filter = |options: ListOptions| {
+            let mut templates = templates();
+            filter_by_capabilities(&mut templates, &options);
+            names(&templates).into_iter().map(str::to_string).collect::<Vec<_>>()
+        };
+
+        assert_eq!(filter(ListOptions { has_variables: true, ..ListOptions::default() }), ["cli"]);
+        assert_eq!(filter(ListOptions { hooks: Some(true), ..ListOptions::default() }), ["web", "operator"]);
+        assert_eq!(filter(ListOptions { hooks: Some(false), ..ListOptions::default() }), ["cli", "odd", "bare"]);
+        assert_eq!(filter(ListOptions { features: vec!["Hooks".to_string(), "web".to_string()], ..ListOptions::default() }), ["web"]);
+        assert_eq!(filter(ListOptions::default()).len(), 5);
+    }
+
+    #[test]
+    fn test_filters_combine() {
+        let mut templates = templates();
+        filter_by(&mut templates, GroupBy::Language, "rust");
+        filter_by(&mut templates, GroupBy::Tag, "k8s");
+        assert_eq!(names(&templates), ["cli", "operator"]);
+        filter_by_capabilities(&mut templates, &ListOptions { hooks: Some(true), ..ListOptions::default() });
+        assert_eq!(names(&templates), ["operator"]);
+        filter_by(&mut templates, GroupBy::Tag, "cli");
+        assert!(templates.is_empty());
+    }
+
+    #[test]
+    fn test_count_templates() {
+        let templates = templates();
+        assert_eq!(
+            count_templates(&templates, GroupBy::Language),
+            [("Rust".to_string(), 2), ("TypeScript".to_string(), 1), ("brainfuck*".to_string(), 1), ("Unknown".to_string(), 1)],
+        );
+        // A template counts once under each of its tags, aliases merged
+        assert_eq!(
+            count_templates(&templates, GroupBy::Tag),
+            [("Kubernetes".to_string(), 2), ("Unknown".to_string(), 2), ("CLI".to_string(), 1), ("Web".to_string(), 1)],
+        );
+    }
+
+    #[test]
+    fn test_listed_template_fields() {
+        let roots = vec![TemplateRoot { label: "team".to_string(), path: PathBuf::from("/templates") }];
+        let template = template("cli", "language = \"rs\"\ncategory = \"cli\"\nversion = \"1.2.0\"\ntags = [\"k8s\", \"cli\"]\n");
+        let listed = serde_json::to_value(ListedTemplate::new(&template, &roots)).unwrap();
+        assert_eq!(listed["name"], "cli");
+        assert_eq!(listed["language"], "Rust");
+        assert_eq!(listed["category"], "CLI");
+        assert_eq!(listed["version"], "1.2.0");
+        assert_eq!(listed["description"], serde_json::Value::Null);
+        assert_eq!(listed["tags"], serde_json::json!(normalize_tags(template.tags().unwrap())));
+        assert_eq!(listed["root"], "team");
+    }
+
+    #[test]
+    fn test_broken_templates() {
+        let storage = TestStorage::new();
+        let root = storage.path().join(TEMPLATE_STORAGE);
+        for (name, config) in [("fine", "language = \"rust\"\n"), ("garbled", "language = [\n")] {
+            fs::create_dir_all(root.join(name)).unwrap();
+            fs::write(root.join(name).join(TEMPLATE_CONFIG_FILE), config).unwrap();
+        }
+
+        let broken = broken_templates(&root).unwrap();
+        assert_eq!(broken.len(), 1);
+        let (name, path, problems) = &broken[0];
+        assert_eq!(name, "garbled");
+        assert_eq!(path, &root.join("garbled").display().to_string());
+        assert_eq!(problems.len(), 1);
+    }
+}
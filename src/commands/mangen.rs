@@ -0,0 +1,25 @@
+use crate::cli::Cli;
+use crate::file::create_dir_if_missing;
+use crate::path::resolve_path;
+use crate::theme::Icon;
+
+use clap::{Command, CommandFactory};
+
+/// Writes roff man pages for the CLI and every visible subcommand (`template.1`,
+/// `template-init.1`, ...) into `out_dir`, for packagers to install into man1
+pub fn handle_mangen(out_dir: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = resolve_path(out_dir, None)?;
+    create_dir_if_missing(&out_dir)?;
+
+    // Propagated so every page's header carries the version, not only the top-level one
+    let command = Cli::command().propagate_version(true);
+    let pages = page_count(&command);
+    clap_mangen::generate_to(command, &out_dir)?;
+    eprintln!("{} Wrote {pages} man page(s) to {}", Icon::Success, out_dir.display());
+    Ok(())
+}
+
+/// One page for the command and one for each of its visible subcommands, recursively
+fn page_count(command: &Command) -> usize {
+    1 + command.get_subcommands().filter(|sub| !sub.is_hide_set()).map(page_count).sum::<usize>()
+}
@@ -1,20 +1,81 @@
+pub mod add;
 pub mod author;
+pub mod import;
 pub mod list;
 pub mod init;
+pub mod upgrade;
+pub mod diff;
+pub mod test;
+pub mod bench;
+pub mod validate;
+pub mod pack;
+pub mod compact;
+pub mod backup;
+pub mod prune;
+pub mod du;
+pub mod info;
+pub mod eject;
+pub mod plugin;
+pub mod suggest;
+pub mod search;
+pub mod pick;
+pub mod explain;
+pub mod doctor;
+pub mod batch;
+pub mod set;
+pub mod parametrize;
+pub mod remove;
+pub mod rename;
+pub mod stats;
+pub mod completions;
+pub mod mangen;
 
+use author::AuthorPreset;
 use clap::Subcommand;
+use clap_complete::aot::Shell;
+use clap_complete::engine::ArgValueCandidates;
+use completions::{apply_candidates, template_candidates};
+use init::{ApplyOptions, ProjectArchive};
+use list::{GroupBy, ListOptions, OutputFormat};
+use test::SnapshotMode;
+
+use crate::copy::{Timestamps, parse_mode_mask};
+use crate::import::ImportFormat;
+use crate::preset::expand_preset;
+use crate::receipt::ReceiptTarget;
+use crate::variables::is_valid_name;
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Create a new template for reuse
     Author {
-        /// Path where the template will be created
-        #[arg(help = "Path where the template will be created")]
+        /// Path where the template will be created, or its name in the template storage with --from
+        #[arg(help = "Path where the template will be created, or its name in the template storage with --from")]
         path: String,
+
+        /// Existing project to copy into the template storage, leaving out .git, build output and gitignored files
+        #[arg(long, value_name = "DIR", help = "Existing project to copy into the template storage, leaving out .git, build output and gitignored files")]
+        from: Option<String>,
         
         /// Name for the template (defaults to directory name)
         #[arg(short, long, help = "Name for the template (defaults to directory name)")]
         name: Option<String>,
+
+        /// Main programming language of the template (e.g. rs, python, ts)
+        #[arg(short, long, help = "Main programming language of the template (e.g. rs, python, ts)")]
+        language: Option<String>,
+
+        /// Starting shape of .template.toml (minimal, full or ci)
+        #[arg(short, long, value_enum, help = "Starting shape of .template.toml (minimal, full or ci)")]
+        preset: Option<AuthorPreset>,
+
+        /// Keep the template name as given instead of lowercasing and slugifying it
+        #[arg(long, help = "Keep the template name as given instead of lowercasing and slugifying it")]
+        no_normalize: bool,
+
+        /// Show which files and directories would be created without writing anything
+        #[arg(long, help = "Show which files and directories would be created without writing anything")]
+        dry_run: bool,
     },
 
     /// List all available templates
@@ -26,16 +87,71 @@ pub enum Commands {
         /// Filter templates by programming language
         #[arg(short, long, help = "Filter templates by programming language")]
         language: Option<String>,
+
+        /// Filter templates by category (cli, web-service, library, infra, docs, ...)
+        #[arg(short, long, help = "Filter templates by category (cli, web-service, library, infra, docs, ...)")]
+        category: Option<String>,
+
+        /// Only show templates having this tag, or any of its aliases (repeatable, all must match)
+        #[arg(short, long = "tag", value_name = "TAG", help = "Only show templates having this tag, or any of its aliases (repeatable, all must match)")]
+        tags: Vec<String>,
+
+        /// List the tags in use and how many templates have each, instead of the templates
+        #[arg(long, conflicts_with_all = ["count", "updates", "output"], help = "List the tags in use and how many templates have each, instead of the templates")]
+        all_tags: bool,
+
+        /// Group templates by language, category or tag
+        #[arg(short, long, value_enum, default_value_t = GroupBy::Language, help = "Group templates by language, category or tag")]
+        group_by: GroupBy,
+
+        /// Only show template counts per language, category and tag
+        #[arg(long, help = "Only show template counts per language, category and tag")]
+        count: bool,
+
+        /// Show templates with newer versions in the configured registries (changes nothing)
+        #[arg(long, conflicts_with = "count", help = "Show templates with newer versions in the configured registries (changes nothing)")]
+        updates: bool,
+
+        /// Only show templates that declare variables
+        #[arg(long, help = "Only show templates that declare variables")]
+        has_variables: bool,
+
+        /// Only show templates using a feature: variables, hooks, eol, workspaces, a prerequisite binary, a tag, ... (repeatable)
+        #[arg(long = "feature", value_name = "FEATURE", help = "Only show templates using a feature: variables, hooks, eol, workspaces, a prerequisite binary, a tag, ... (repeatable)")]
+        features: Vec<String>,
+
+        /// Only show templates that run hooks
+        #[arg(long, conflicts_with = "no_hooks", help = "Only show templates that run hooks")]
+        hooked: bool,
+
+        /// Only show templates that run no hooks
+        #[arg(long, help = "Only show templates that run no hooks")]
+        no_hooks: bool,
+
+        /// Print the matching templates as JSON or YAML for scripts and editors
+        #[arg(long, value_enum, value_name = "FORMAT", conflicts_with_all = ["count", "updates"], help = "Print the matching templates as JSON or YAML for scripts and editors")]
+        output: Option<OutputFormat>,
+
+        /// Only show templates that can't be used (unparsable config, missing objects, ...) and why
+        #[arg(long, conflicts_with_all = ["count", "updates", "output", "all_tags"], help = "Only show templates that can't be used (unparsable config, missing objects, ...) and why")]
+        broken: bool,
+    },
+
+    /// Show everything about one template: metadata, variables, hooks, files and size
+    Info {
+        /// Name of the template to show
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to show")]
+        template: String,
     },
 
     /// Initialize existing directory using an existing template
     Init {
-        /// Name of the template to use
-        #[arg(help = "Name of the template to use")]
-        template: String,
+        /// Name of the template to use, @PRESET from config.toml, or oci://REGISTRY/REPOSITORY:TAG (defaults to the one recorded in .template.lock)
+        #[arg(add = ArgValueCandidates::new(apply_candidates), help = "Name of the template to use, @PRESET from config.toml, or oci://REGISTRY/REPOSITORY:TAG (defaults to the one recorded in .template.lock)")]
+        template: Option<String>,
         
-        /// Target directory (defaults to current directory)
-        #[arg(help = "Target directory (defaults to current directory)")]
+        /// Target directory, or [user@]host:path over SSH (defaults to current directory)
+        #[arg(help = "Target directory, or [user@]host:path over SSH (defaults to current directory)")]
         path: Option<String>,
 
         /// Preview changes without copying files
@@ -49,18 +165,488 @@ pub enum Commands {
         /// Remove files not present in template
         #[arg(long, help = "Remove files not present in template")]
         delete: bool,
+
+        /// Allow applying a different template over a previously initialized directory
+        #[arg(long, help = "Allow applying a different template over a previously initialized directory")]
+        reinit: bool,
+
+        /// Skip the summary and other confirmations, take variable defaults and run hooks without asking
+        #[arg(short, long, help = "Skip the summary and other confirmations, take variable defaults and run hooks without asking")]
+        yes: bool,
+
+        /// Copy .git/, .DS_Store, build directories, etc. that are excluded by default
+        #[arg(long, help = "Copy .git/, .DS_Store, build directories, etc. that are excluded by default")]
+        no_default_excludes: bool,
+
+        /// Print newline-delimited JSON progress events on stdout
+        #[arg(long, help = "Print newline-delimited JSON progress events on stdout")]
+        json_events: bool,
+
+        /// Write into a git worktree that has uncommitted changes
+        #[arg(long, help = "Write into a git worktree that has uncommitted changes")]
+        allow_dirty: bool,
+
+        /// Pick the template files to apply from an interactive checklist
+        #[arg(long, help = "Pick the template files to apply from an interactive checklist")]
+        select: bool,
+
+        /// Print the changes as a git patch on stdout instead of writing them, for review and `git apply`
+        #[arg(long, conflicts_with_all = ["dry_run", "json_events"], help = "Print the changes as a git patch on stdout instead of writing them, for review and `git apply`")]
+        as_patch: bool,
+
+        /// Give created files this owner and/or group (USER, USER:GROUP or :GROUP); changing the owner needs root
+        #[arg(long, value_name = "USER:GROUP", conflicts_with = "preserve_owner", help = "Give created files this owner and/or group (USER, USER:GROUP or :GROUP); changing the owner needs root")]
+        chown: Option<String>,
+
+        /// Keep the template files' owner and group instead of the invoking user's (needs root)
+        #[arg(long, help = "Keep the template files' owner and group instead of the invoking user's (needs root)")]
+        preserve_owner: bool,
+
+        /// Clear these permission bits on created files and directories (octal, like a umask); defaults to mode_mask in config.toml, then the current umask
+        #[arg(long, value_name = "OCTAL", value_parser = parse_mode_mask, help = "Clear these permission bits on created files and directories (octal, like a umask); defaults to mode_mask in config.toml, then the current umask")]
+        mode_mask: Option<u32>,
+
+        /// Don't run the template's pre_init and post_init hooks
+        #[arg(long, help = "Don't run the template's pre_init and post_init hooks")]
+        no_hooks: bool,
+
+        /// Run the language's sanity check (cargo check, ...) on the generated project and fail if it fails
+        #[arg(long, help = "Run the language's sanity check (cargo check, ...) on the generated project and fail if it fails")]
+        check: bool,
+
+        /// Record what was done in the project: none, changelog (CHANGELOG.md) or history (.template-history); defaults to receipts in config.toml
+        #[arg(long, value_enum, value_name = "TARGET", help = "Record what was done in the project: none, changelog (CHANGELOG.md) or history (.template-history); defaults to receipts in config.toml")]
+        receipt: Option<ReceiptTarget>,
+
+        /// Modification times of created files: now, or preserve the template's; defaults to timestamps in config.toml
+        #[arg(long, value_enum, value_name = "WHEN", help = "Modification times of created files: now, or preserve the template's; defaults to timestamps in config.toml")]
+        timestamps: Option<Timestamps>,
+
+        /// Value for a template variable (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable, help = "Value for a template variable (repeatable)")]
+        variables: Vec<(String, String)>,
+    },
+
+    /// Explain what applying a template would do to each path of a target, and why
+    Explain {
+        /// Name of the template to explain, or oci://REGISTRY/REPOSITORY:TAG
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to explain, or oci://REGISTRY/REPOSITORY:TAG")]
+        template: String,
+
+        /// Target directory the template would be applied to
+        #[arg(help = "Target directory the template would be applied to")]
+        target: String,
+
+        /// Paths in the target to explain (default: every file of the template)
+        #[arg(help = "Paths in the target to explain (default: every file of the template)")]
+        paths: Vec<String>,
+
+        /// Explain applying with --force
+        #[arg(short, long, help = "Explain applying with --force")]
+        force: bool,
+
+        /// Explain applying with --delete
+        #[arg(long, help = "Explain applying with --delete")]
+        delete: bool,
+
+        /// Explain applying with --no-default-excludes
+        #[arg(long, help = "Explain applying with --no-default-excludes")]
+        no_default_excludes: bool,
+
+        /// Value for a template variable (repeatable), for files named after variables
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable, help = "Value for a template variable (repeatable), for files named after variables")]
+        variables: Vec<(String, String)>,
+    },
+
+    /// Show how a project differs from its template, as a patch from the project to the template
+    Diff {
+        /// Project directory (defaults to current directory)
+        #[arg(help = "Project directory (defaults to current directory)")]
+        path: Option<String>,
+
+        /// Template to compare with (defaults to the one recorded in .template.lock)
+        #[arg(short, long, add = ArgValueCandidates::new(template_candidates), help = "Template to compare with (defaults to the one recorded in .template.lock)")]
+        template: Option<String>,
+
+        /// Only list the added, changed and removed files
+        #[arg(long, help = "Only list the added, changed and removed files")]
+        name_status: bool,
+
+        /// Value for a template variable (repeatable), over the ones recorded in .template.lock
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable, help = "Value for a template variable (repeatable), over the ones recorded in .template.lock")]
+        variables: Vec<(String, String)>,
+    },
+
+    /// Bring a previously initialized directory up to date with what changed in its template
+    #[command(alias = "update")]
+    Upgrade {
+        /// Project directory containing .template.lock (defaults to current directory)
+        #[arg(help = "Project directory containing .template.lock (defaults to current directory)")]
+        path: Option<String>,
+
+        /// Classify every file without changing anything
+        #[arg(short = 'n', long, help = "Classify every file without changing anything")]
+        dry_run: bool,
+
+        /// Write conflicting template versions to .rej files instead of inserting conflict markers
+        #[arg(long, help = "Write conflicting template versions to .rej files instead of inserting conflict markers")]
+        rej: bool,
+
+        /// Show each conflict and ask whether to accept the template's version, keep yours or mark it
+        #[arg(short, long, help = "Show each conflict and ask whether to accept the template's version, keep yours or mark it")]
+        interactive: bool,
+
+        /// Record what was done in the project: none, changelog (CHANGELOG.md) or history (.template-history); defaults to receipts in config.toml
+        #[arg(long, value_enum, value_name = "TARGET", help = "Record what was done in the project: none, changelog (CHANGELOG.md) or history (.template-history); defaults to receipts in config.toml")]
+        receipt: Option<ReceiptTarget>,
+    },
+
+    /// Apply a template into a scratch directory and check it against .template-test.toml
+    Test {
+        /// Name of the template to test
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to test")]
+        template: String,
+
+        /// Keep the scratch output directory for inspection
+        #[arg(long, help = "Keep the scratch output directory for inspection")]
+        keep: bool,
+
+        /// Compare the output against the snapshot stored in .template-snapshot/
+        #[arg(long, help = "Compare the output against the snapshot stored in .template-snapshot/")]
+        snapshot: bool,
+
+        /// Replace the stored snapshot with the current output
+        #[arg(long, help = "Replace the stored snapshot with the current output")]
+        update_snapshots: bool,
+    },
+
+    /// Check templates for problems such as oversized contents
+    Validate {
+        /// Name of the template to validate (defaults to all templates)
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to validate (defaults to all templates)")]
+        template: Option<String>,
+    },
+
+    /// Move a template's files into the deduplicated object store
+    Pack {
+        /// Name of the template to pack
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to pack")]
+        template: String,
+    },
+
+    /// Restore a packed or compacted template's files for editing
+    Unpack {
+        /// Name of the template to unpack
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to unpack")]
+        template: String,
+    },
+
+    /// Compress templates that haven't been used recently
+    Compact {
+        /// Compact templates unused for at least this many days
+        #[arg(long, default_value_t = 90, help = "Compact templates unused for at least this many days")]
+        days: u64,
+
+        /// Show which templates would be compacted
+        #[arg(short = 'n', long, help = "Show which templates would be compacted")]
+        dry_run: bool,
+    },
+
+    /// Archive all templates, configuration and history into one file
+    Backup {
+        /// Backup file to write (.tar.zst or .tar.gz)
+        #[arg(short, long, help = "Backup file to write (.tar.zst or .tar.gz)")]
+        output: String,
+    },
+
+    /// Restore templates, configuration and history from a backup file
+    Restore {
+        /// Backup file to restore (.tar.zst or .tar.gz)
+        #[arg(help = "Backup file to restore (.tar.zst or .tar.gz)")]
+        file: String,
+
+        /// Skip confirmation prompts
+        #[arg(short, long, help = "Skip confirmation prompts")]
+        yes: bool,
+    },
+
+    /// Remove superseded template versions and objects nothing refers to anymore
+    Prune {
+        /// Number of most recently applied versions to keep per template
+        #[arg(long, default_value_t = 3, help = "Number of most recently applied versions to keep per template")]
+        keep: usize,
+
+        /// Project directory whose .template.lock must stay upgradable (repeatable)
+        #[arg(short, long = "project", value_name = "PATH", help = "Project directory whose .template.lock must stay upgradable (repeatable)")]
+        projects: Vec<String>,
+
+        /// Show what would be removed
+        #[arg(short = 'n', long, help = "Show what would be removed")]
+        dry_run: bool,
+    },
+
+    /// Change one field of a template's .template.toml (e.g. `set my/cli version 2.0.0`)
+    Set {
+        /// Name of the template to edit
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to edit")]
+        template: String,
+
+        /// Field to change, dotted for nested tables (e.g. description, prerequisites.binaries)
+        #[arg(help = "Field to change, dotted for nested tables (e.g. description, prerequisites.binaries)")]
+        key: String,
+
+        /// New value: TOML (true, 42, [\"a\", \"b\"]) or plain text
+        #[arg(required_unless_present = "unset", help = "New value: TOML (true, 42, [\"a\", \"b\"]) or plain text")]
+        value: Option<String>,
+
+        /// Remove the field instead of setting it
+        #[arg(long, conflicts_with = "value", help = "Remove the field instead of setting it")]
+        unset: bool,
+    },
+
+    /// Turn a literal string in a template's files and file names into a {{ variable }} placeholder
+    Parametrize {
+        /// Name of the template to edit
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to edit")]
+        template: String,
+
+        /// Literal text to replace (e.g. the project name the template was made from)
+        #[arg(long, value_name = "TEXT", help = "Literal text to replace (e.g. the project name the template was made from)")]
+        find: String,
+
+        /// Variable the placeholder refers to; declared in .template.toml with the text as default
+        #[arg(long = "var", value_name = "NAME", help = "Variable the placeholder refers to; declared in .template.toml with the text as default")]
+        variable: String,
+
+        /// Show what would be rewritten without changing anything
+        #[arg(short = 'n', long, help = "Show what would be rewritten without changing anything")]
+        dry_run: bool,
+    },
+
+    /// Delete a template from your template storage
+    Remove {
+        /// Name of the template to remove
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to remove")]
+        template: String,
+
+        /// Remove without asking for confirmation
+        #[arg(short, long, help = "Remove without asking for confirmation")]
+        force: bool,
+
+        /// Show what would be deleted without deleting anything
+        #[arg(short = 'n', long, help = "Show what would be deleted without deleting anything")]
+        dry_run: bool,
+    },
+
+    /// Move a template to another name in your template storage
+    #[command(alias = "move")]
+    Rename {
+        /// Name of the template to rename
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to rename")]
+        template: String,
+
+        /// New name, a path in template storage (e.g. rust/binaries/cli)
+        #[arg(help = "New name, a path in template storage (e.g. rust/binaries/cli)")]
+        new_name: String,
+
+        /// Also set `name` in .template.toml to the last part of the new name
+        #[arg(long, help = "Also set `name` in .template.toml to the last part of the new name")]
+        update_name: bool,
+
+        /// Keep the new name as given instead of lowercasing and slugifying it
+        #[arg(long, help = "Keep the new name as given instead of lowercasing and slugifying it")]
+        no_normalize: bool,
+
+        /// Show what would be moved without moving anything
+        #[arg(short = 'n', long, help = "Show what would be moved without moving anything")]
+        dry_run: bool,
+    },
+
+    /// Print a shell completion script (bash, zsh, fish, powershell or elvish)
+    Completions {
+        /// Shell to complete for, e.g. `source <(template completions bash)`
+        #[arg(value_enum, help = "Shell to complete for, e.g. `source <(template completions bash)`")]
+        shell: Shell,
+    },
+
+    /// Write man pages for the CLI and each subcommand, for packaging
+    #[command(hide = true)]
+    Mangen {
+        /// Directory to write the pages to
+        #[arg(long, value_name = "DIR", default_value = "man", help = "Directory to write the pages to")]
+        out_dir: String,
+    },
+
+    /// Show disk usage of the template library, largest first
+    Du,
+
+    /// Show how often each template is used, with totals per language and category
+    Stats {
+        /// Print the statistics as JSON for dashboards
+        #[arg(long, help = "Print the statistics as JSON for dashboards")]
+        json: bool,
+    },
+
+    /// List template applications, newest first
+    History {
+        /// Print the applications as JSON for dashboards
+        #[arg(long, help = "Print the applications as JSON for dashboards")]
+        json: bool,
+
+        /// Only show this many of the latest applications
+        #[arg(long, value_name = "COUNT", help = "Only show this many of the latest applications")]
+        limit: Option<usize>,
+    },
+
+    /// Check storage, config, required tools and symlink cycles for problems
+    Doctor,
+
+    /// Install a template from a git repository into your template storage
+    Add {
+        /// Repository to clone (https, ssh or a local path)
+        #[arg(help = "Repository to clone (https, ssh or a local path)")]
+        url: String,
+
+        /// Name of the template in storage (defaults to the repository or subdirectory name)
+        #[arg(long, help = "Name of the template in storage (defaults to the repository or subdirectory name)")]
+        name: Option<String>,
+
+        /// Directory of the repository holding the template, when it isn't the root
+        #[arg(long, value_name = "PATH", help = "Directory of the repository holding the template, when it isn't the root")]
+        subdir: Option<String>,
+
+        /// Branch, tag or commit to install (defaults to the default branch)
+        #[arg(long = "ref", value_name = "REF", help = "Branch, tag or commit to install (defaults to the default branch)")]
+        reference: Option<String>,
+
+        /// Keep the template name as given instead of lowercasing and slugifying it
+        #[arg(long, help = "Keep the template name as given instead of lowercasing and slugifying it")]
+        no_normalize: bool,
+    },
+
+    /// Convert a template of another tool (cookiecutter, cargo-generate) into a native template in your storage
+    Import {
+        /// Format of the template to convert
+        #[arg(long, value_enum, help = "Format of the template to convert")]
+        format: ImportFormat,
+
+        /// Directory or git repository (https, ssh) holding the template
+        #[arg(help = "Directory or git repository (https, ssh) holding the template")]
+        source: String,
+
+        /// Name of the template in storage (defaults to the directory or repository name)
+        #[arg(long, help = "Name of the template in storage (defaults to the directory or repository name)")]
+        name: Option<String>,
+
+        /// Branch, tag or commit to import from a repository (defaults to the default branch)
+        #[arg(long = "ref", value_name = "REF", help = "Branch, tag or commit to import from a repository (defaults to the default branch)")]
+        reference: Option<String>,
+
+        /// Keep the template name as given instead of lowercasing and slugifying it
+        #[arg(long, help = "Keep the template name as given instead of lowercasing and slugifying it")]
+        no_normalize: bool,
+
+        /// Show the converted .template.toml and files without storing anything
+        #[arg(short = 'n', long, help = "Show the converted .template.toml and files without storing anything")]
+        dry_run: bool,
+    },
+
+    /// Copy a template (e.g. a built-in one) into your template storage to customize it
+    Eject {
+        /// Name of the template to copy, or oci://REGISTRY/REPOSITORY:TAG
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to copy, or oci://REGISTRY/REPOSITORY:TAG")]
+        template: String,
+
+        /// Name of the copy in template storage (defaults to the name without 'builtin/', or the repository name for oci://)
+        #[arg(help = "Name of the copy in template storage (defaults to the name without 'builtin/', or the repository name for oci://)")]
+        destination: Option<String>,
+
+        /// Keep the destination name as given instead of lowercasing and slugifying it
+        #[arg(long, help = "Keep the destination name as given instead of lowercasing and slugifying it")]
+        no_normalize: bool,
+    },
+
+    /// Find templates by name, tag, language or description, best match first
+    Search {
+        /// Words to look for; each must match, small typos are forgiven
+        #[arg(required = true, help = "Words to look for; each must match, small typos are forgiven")]
+        query: Vec<String>,
+
+        /// Show at most this many templates
+        #[arg(long, value_name = "N", help = "Show at most this many templates")]
+        limit: Option<usize>,
+    },
+
+    /// Choose a template from a filterable list with a preview, then apply it
+    Pick {
+        /// Directory to apply the picked template to; created when it doesn't exist (default: current directory)
+        #[arg(help = "Directory to apply the picked template to; created when it doesn't exist (default: current directory)")]
+        path: Option<String>,
+
+        /// Preview changes without copying files
+        #[arg(short = 'n', long, help = "Preview changes without copying files")]
+        dry_run: bool,
+
+        /// Overwrite existing files
+        #[arg(short, long, help = "Overwrite existing files")]
+        force: bool,
+    },
+
+    /// Suggest templates matching the language of an existing project
+    Suggest {
+        /// Project directory to inspect
+        #[arg(default_value = ".", help = "Project directory to inspect")]
+        path: String,
+    },
+
+    /// Apply the templates listed in a batch manifest (plan.toml) in one run
+    Batch {
+        /// Manifest with [[apply]] entries of template, target and variables
+        #[arg(help = "Manifest with [[apply]] entries of template, target and variables")]
+        plan: String,
+
+        /// Preview every entry without copying files
+        #[arg(short = 'n', long, help = "Preview every entry without copying files")]
+        dry_run: bool,
+
+        /// Skip confirmation prompts
+        #[arg(short, long, help = "Skip confirmation prompts")]
+        yes: bool,
+
+        /// Write into a git worktree that has uncommitted changes
+        #[arg(long, help = "Write into a git worktree that has uncommitted changes")]
+        allow_dirty: bool,
+    },
+
+    /// Apply a template repeatedly into scratch directories and report timings
+    Bench {
+        /// Name of the template to benchmark
+        #[arg(add = ArgValueCandidates::new(template_candidates), help = "Name of the template to benchmark")]
+        template: String,
+
+        /// Number of times to apply the template
+        #[arg(short = 'n', long, default_value_t = 10, help = "Number of times to apply the template")]
+        iterations: u32,
+
+        /// Value for a template variable (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable, help = "Value for a template variable (repeatable)")]
+        variables: Vec<(String, String)>,
     },
 
     /// Create a new directory using an existing template
     New {
-        /// Name of the template to use
-        #[arg(help = "Name of the template to use")]
+        /// Name of the template to use, @PRESET from config.toml, or oci://REGISTRY/REPOSITORY:TAG
+        #[arg(add = ArgValueCandidates::new(apply_candidates), help = "Name of the template to use, @PRESET from config.toml, or oci://REGISTRY/REPOSITORY:TAG")]
         template: String,
         
-        /// Path where the new directory will be created
-        #[arg(help = "Path where the new directory will be created")]
+        /// Path where the new directory will be created, or [user@]host:path over SSH; with --output, the archive to write ('-' for stdout)
+        #[arg(help = "Path where the new directory will be created, or [user@]host:path over SSH; with --output, the archive to write ('-' for stdout)")]
         path: String,
 
+        /// Write the project as an archive to PATH instead of creating a directory
+        #[arg(long, value_enum, value_name = "FORMAT", conflicts_with_all = ["delete", "chown", "preserve_owner", "allow_existing"], help = "Write the project as an archive to PATH instead of creating a directory")]
+        output: Option<ProjectArchive>,
+
         /// Preview changes without copying files
         #[arg(short = 'n', long, help = "Preview changes without copying files")]
         dry_run: bool,
@@ -68,26 +654,274 @@ pub enum Commands {
         /// Overwrite existing files
         #[arg(short, long, help = "Overwrite existing files")]
         force: bool,
+
+        /// Create the project in a non-empty directory, keeping files that already exist
+        #[arg(long, help = "Create the project in a non-empty directory, keeping files that already exist")]
+        allow_existing: bool,
     
         /// Remove files not present in template
         #[arg(long, help = "Remove files not present in template")]
         delete: bool,
+
+        /// Skip the summary and other confirmations, take variable defaults and run hooks without asking
+        #[arg(short, long, help = "Skip the summary and other confirmations, take variable defaults and run hooks without asking")]
+        yes: bool,
+
+        /// Copy .git/, .DS_Store, build directories, etc. that are excluded by default
+        #[arg(long, help = "Copy .git/, .DS_Store, build directories, etc. that are excluded by default")]
+        no_default_excludes: bool,
+
+        /// Print newline-delimited JSON progress events on stdout
+        #[arg(long, help = "Print newline-delimited JSON progress events on stdout")]
+        json_events: bool,
+
+        /// Write into a git worktree that has uncommitted changes
+        #[arg(long, help = "Write into a git worktree that has uncommitted changes")]
+        allow_dirty: bool,
+
+        /// Run unattended: take variables from --var, TEMPLATE_RS_VAR_<NAME>, config.toml or their defaults, and fail instead of asking anything
+        #[arg(long, help = "Run unattended: take variables from --var, TEMPLATE_RS_VAR_<NAME>, config.toml or their defaults, and fail instead of asking anything")]
+        from_defaults: bool,
+
+        /// Give created files this owner and/or group (USER, USER:GROUP or :GROUP); changing the owner needs root
+        #[arg(long, value_name = "USER:GROUP", conflicts_with = "preserve_owner", help = "Give created files this owner and/or group (USER, USER:GROUP or :GROUP); changing the owner needs root")]
+        chown: Option<String>,
+
+        /// Keep the template files' owner and group instead of the invoking user's (needs root)
+        #[arg(long, help = "Keep the template files' owner and group instead of the invoking user's (needs root)")]
+        preserve_owner: bool,
+
+        /// Clear these permission bits on created files and directories (octal, like a umask); defaults to mode_mask in config.toml, then the current umask
+        #[arg(long, value_name = "OCTAL", value_parser = parse_mode_mask, help = "Clear these permission bits on created files and directories (octal, like a umask); defaults to mode_mask in config.toml, then the current umask")]
+        mode_mask: Option<u32>,
+
+        /// Don't run the template's pre_init and post_init hooks
+        #[arg(long, help = "Don't run the template's pre_init and post_init hooks")]
+        no_hooks: bool,
+
+        /// Run the language's sanity check (cargo check, ...) on the generated project and fail if it fails
+        #[arg(long, help = "Run the language's sanity check (cargo check, ...) on the generated project and fail if it fails")]
+        check: bool,
+
+        /// Record what was done in the project: none, changelog (CHANGELOG.md) or history (.template-history); defaults to receipts in config.toml
+        #[arg(long, value_enum, value_name = "TARGET", help = "Record what was done in the project: none, changelog (CHANGELOG.md) or history (.template-history); defaults to receipts in config.toml")]
+        receipt: Option<ReceiptTarget>,
+
+        /// Modification times of created files: now, or preserve the template's; defaults to timestamps in config.toml
+        #[arg(long, value_enum, value_name = "WHEN", help = "Modification times of created files: now, or preserve the template's; defaults to timestamps in config.toml")]
+        timestamps: Option<Timestamps>,
+
+        /// Value for a template variable (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable, help = "Value for a template variable (repeatable)")]
+        variables: Vec<(String, String)>,
     },
+
+    /// Run a `template-rs-<name>` plugin found on PATH
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+impl Commands {
+    /// Check if the command works with the template storage, so a first run should set it up
+    /// before it runs. Output generators, diagnostics and plugins never trigger the setup.
+    pub fn needs_storage(&self) -> bool {
+        !matches!(self, Self::Completions { .. } | Self::Mangen { .. } | Self::Doctor | Self::External(_))
+    }
+}
+
+/// Parses a `--var NAME=VALUE` argument
+fn parse_variable(arg: &str) -> Result<(String, String), String> {
+    let (name, value) = arg.split_once('=').ok_or("expected NAME=VALUE")?;
+    if !is_valid_name(name) {
+        return Err(format!("'{name}' is not a valid variable name (letters, digits and underscores)"));
+    }
+    Ok((name.to_string(), value.to_string()))
 }
 
 pub fn handle_command(command: Commands) -> Result<(), Box<dyn std::error::Error>> {
     match command {
-        Commands::Author { path, name } => {
-            author::handle_author(&path, name)
+        Commands::Author { path, from, name, language, preset, no_normalize, dry_run } => {
+            author::handle_author(&path, from.as_deref(), name, language.as_deref(), preset, !no_normalize, dry_run)
+        }
+        Commands::List { verbose, language, category, tags, all_tags, group_by, count, updates, has_variables, features, hooked, no_hooks, output, broken } => {
+            let hooks = if hooked { Some(true) } else if no_hooks { Some(false) } else { None };
+            let options = ListOptions { verbose, language, category, tags, all_tags, group_by, count, updates, has_variables, features, hooks, output, broken };
+            list::handle_list(&options)
+        }
+        Commands::Info { template } => {
+            info::handle_info(&template)
+        }
+        Commands::Init { template, path, dry_run, force, delete, reinit, yes, no_default_excludes, json_events, allow_dirty, select, as_patch, chown, preserve_owner, mode_mask, no_hooks, check, receipt, timestamps, variables } => {
+            let options = ApplyOptions {
+                dry_run,
+                force,
+                delete,
+                create_dir: false,
+                allow_existing: false,
+                allow_dirty,
+                reinit,
+                yes,
+                confirmed: false,
+                default_excludes: !no_default_excludes,
+                json_events,
+                select,
+                as_patch,
+                quiet: false,
+                from_defaults: false,
+                chown,
+                preserve_owner,
+                mode_mask,
+                no_hooks,
+                check,
+                no_history: false,
+                receipt,
+                timestamps,
+                variables: variables.into_iter().collect(),
+            };
+            match template {
+                Some(template) => {
+                    let (template, options) = expand_preset(&template, &options)?;
+                    init::handle_init(Some(&template), path, &options)
+                }
+                None => init::handle_init(None, path, &options),
+            }
+        }
+        Commands::New { template, path, output, dry_run, force, allow_existing, delete, yes, no_default_excludes, json_events, allow_dirty, from_defaults, chown, preserve_owner, mode_mask, no_hooks, check, receipt, timestamps, variables } => {
+            let options = ApplyOptions {
+                dry_run,
+                force,
+                delete,
+                create_dir: true,
+                allow_existing,
+                allow_dirty,
+                reinit: false,
+                yes,
+                confirmed: false,
+                default_excludes: !no_default_excludes,
+                json_events,
+                select: false,
+                as_patch: false,
+                quiet: false,
+                from_defaults,
+                chown,
+                preserve_owner,
+                mode_mask,
+                no_hooks,
+                check,
+                no_history: false,
+                receipt,
+                timestamps,
+                variables: variables.into_iter().collect(),
+            };
+            let (template, options) = expand_preset(&template, &options)?;
+            match output {
+                Some(format) => init::apply_to_archive(&template, &path, format, &options),
+                None => init::handle_init(Some(&template), Some(path), &options),
+            }
+        }
+        Commands::Explain { template, target, paths, force, delete, no_default_excludes, variables } => {
+            let options = ApplyOptions {
+                force,
+                delete,
+                default_excludes: !no_default_excludes,
+                variables: variables.into_iter().collect(),
+                ..ApplyOptions::default()
+            };
+            explain::handle_explain(&template, &target, &paths, &options)
+        }
+        Commands::Diff { path, template, name_status, variables } => {
+            diff::handle_diff(path, template.as_deref(), name_status, variables.into_iter().collect())
+        }
+        Commands::Upgrade { path, dry_run, rej, interactive, receipt } => {
+            upgrade::handle_upgrade(path, dry_run, rej, interactive, receipt)
+        }
+        Commands::Test { template, keep, snapshot, update_snapshots } => {
+            let mode = if update_snapshots {
+                SnapshotMode::Update
+            } else if snapshot {
+                SnapshotMode::Compare
+            } else {
+                SnapshotMode::Off
+            };
+            test::handle_test(&template, keep, mode)
+        }
+        Commands::Validate { template } => {
+            validate::handle_validate(template.as_deref())
+        }
+        Commands::Pack { template } => {
+            pack::handle_pack(&template)
+        }
+        Commands::Unpack { template } => {
+            pack::handle_unpack(&template)
+        }
+        Commands::Compact { days, dry_run } => {
+            compact::handle_compact(days, dry_run)
+        }
+        Commands::Backup { output } => {
+            backup::handle_backup(&output)
+        }
+        Commands::Restore { file, yes } => {
+            backup::handle_restore(&file, yes)
+        }
+        Commands::Prune { keep, projects, dry_run } => {
+            prune::handle_prune(keep, &projects, dry_run)
+        }
+        Commands::Set { template, key, value, unset: _ } => {
+            set::handle_set(&template, &key, value.as_deref())
+        }
+        Commands::Parametrize { template, find, variable, dry_run } => {
+            parametrize::handle_parametrize(&template, &find, &variable, dry_run)
+        }
+        Commands::Remove { template, force, dry_run } => {
+            remove::handle_remove(&template, force, dry_run)
+        }
+        Commands::Rename { template, new_name, update_name, no_normalize, dry_run } => {
+            rename::handle_rename(&template, &new_name, update_name, !no_normalize, dry_run)
+        }
+        Commands::Completions { shell } => {
+            completions::handle_completions(shell)
+        }
+        Commands::Mangen { out_dir } => {
+            mangen::handle_mangen(&out_dir)
+        }
+        Commands::Du => {
+            du::handle_du()
+        }
+        Commands::Stats { json } => {
+            stats::handle_stats(json)
+        }
+        Commands::History { json, limit } => {
+            stats::handle_history(json, limit)
+        }
+        Commands::Doctor => {
+            doctor::handle_doctor()
+        }
+        Commands::Add { url, name, subdir, reference, no_normalize } => {
+            add::handle_add(&url, name.as_deref(), subdir.as_deref(), reference.as_deref(), !no_normalize)
+        }
+        Commands::Import { format, source, name, reference, no_normalize, dry_run } => {
+            import::handle_import(format, &source, name.as_deref(), reference.as_deref(), !no_normalize, dry_run)
+        }
+        Commands::Eject { template, destination, no_normalize } => {
+            eject::handle_eject(&template, destination.as_deref(), !no_normalize)
+        }
+        Commands::Search { query, limit } => {
+            search::handle_search(&query, limit)
+        }
+        Commands::Pick { path, dry_run, force } => {
+            pick::handle_pick(path, dry_run, force)
+        }
+        Commands::Suggest { path } => {
+            suggest::handle_suggest(&path)
         }
-        Commands::List { verbose, language} => {
-            list::handle_list(verbose, language.as_deref())
+        Commands::External(args) => {
+            plugin::handle_external(&args)
         }
-        Commands::Init { template, path, dry_run, force, delete } => {
-            init::handle_init(&template, path, dry_run, force, delete, false)
+        Commands::Batch { plan, dry_run, yes, allow_dirty } => {
+            batch::handle_batch(&plan, dry_run, yes, allow_dirty)
         }
-        Commands::New { template, path, dry_run, force, delete } => {
-            init::handle_init(&template, Some(path), dry_run, force, delete, true)
+        Commands::Bench { template, iterations, variables } => {
+            bench::handle_bench(&template, iterations, variables.into_iter().collect())
         }
     }
 }
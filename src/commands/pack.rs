@@ -0,0 +1,95 @@
+use crate::error::TemplateError;
+use crate::file::{format_size, remove_empty_dirs};
+use crate::lock::hash_bytes;
+use crate::objects::{Manifest, ManifestEntry, has_blob, is_executable, store_blob};
+use crate::template::{Template, TEMPLATE_MANIFEST_FILE};
+use crate::theme::Icon;
+
+use std::fs;
+
+pub fn handle_pack(template: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let template_info = find_template(template)?;
+    if template_info.is_packed() {
+        return Err(format!("Template '{}' is already packed", template_info.name).into());
+    }
+    if template_info.is_compacted() {
+        return Err(format!("Template '{}' is compacted, unpack it first", template_info.name).into());
+    }
+
+    eprintln!("Packing template '{}' into the object store...", template_info.name);
+
+    let mut manifest = Manifest::default();
+    let mut total_bytes = 0;
+    let mut shared = 0;
+
+    for file in template_info.files()? {
+        let path = template_info.path.join(&file);
+        let content = fs::read(&path)?;
+        total_bytes += content.len() as u64;
+
+        let hash = store_blob_counting_shared(&content, &mut shared)?;
+        let executable = is_executable(&path)?;
+        manifest.files.insert(file, ManifestEntry { hash, executable });
+    }
+
+    // Write the manifest before removing anything so an interruption never loses files
+    manifest.save(template_info.path.join(TEMPLATE_MANIFEST_FILE))?;
+
+    for file in manifest.files.keys() {
+        fs::remove_file(template_info.path.join(file))?;
+    }
+    remove_empty_dirs(&template_info.path)?;
+
+    eprintln!(
+        "{} Packed {} file(s) ({}), {shared} already stored and shared with other templates",
+        Icon::Success,
+        manifest.files.len(),
+        format_size(total_bytes)
+    );
+    eprintln!("Use `{} unpack {}` to restore the files for editing", crate::BIN_NAME, template_info.name);
+    Ok(())
+}
+
+pub fn handle_unpack(template: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let template_info = find_template(template)?;
+
+    if template_info.is_compacted() {
+        eprintln!("Extracting compacted template '{}' for editing...", template_info.name);
+        template_info.inflate()?;
+        eprintln!("{} Extracted into {}", Icon::Success, template_info.path.display());
+        return Ok(());
+    }
+
+    if !template_info.is_packed() {
+        return Err(format!("Template '{}' is neither packed nor compacted", template_info.name).into());
+    }
+
+    eprintln!("Materializing template '{}' for editing...", template_info.name);
+
+    let manifest_path = template_info.path.join(TEMPLATE_MANIFEST_FILE);
+    let manifest = Manifest::load(&manifest_path)?;
+    manifest.materialize(&template_info.path)?;
+    fs::remove_file(manifest_path)?;
+
+    eprintln!("{} Restored {} file(s) into {}", Icon::Success, manifest.files.len(), template_info.path.display());
+    Ok(())
+}
+
+fn find_template(template: &str) -> Result<Template, Box<dyn std::error::Error>> {
+    let template_info = Template::find(template)?
+        .ok_or_else(|| TemplateError::NotFound(template.to_string()))?;
+    if template_info.is_builtin() {
+        return Err(format!("'{}' is built into the binary and can't be packed or unpacked", template_info.name).into());
+    }
+    Ok(template_info)
+}
+
+/// Stores a blob, counting it as shared when identical content was already in the store
+fn store_blob_counting_shared(content: &[u8], shared: &mut usize) -> Result<String, Box<dyn std::error::Error>> {
+    let hash = hash_bytes(content);
+    if has_blob(&hash)? {
+        *shared += 1;
+        return Ok(hash);
+    }
+    store_blob(content)
+}
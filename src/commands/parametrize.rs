@@ -0,0 +1,251 @@
+use crate::commands::set::edit_config;
+use crate::encoding::decode;
+use crate::error::TemplateError;
+use crate::output::{is_plain, print_record};
+use crate::patterns::matches_any;
+use crate::render::{RenderEngine, remove_emptied_dirs};
+use crate::template::{Template, TEMPLATE_CONFIG_FILE};
+use crate::theme::Icon;
+use crate::variables::is_valid_name;
+
+use toml_edit::{DocumentMut, Item, Value};
+
+use std::collections::BTreeSet;
+use std::fs;
+
+/// What parametrizing changes in a template
+#[derive(Debug, Default)]
+struct Rewrites {
+    /// Files whose content has the literal, with the number of occurrences
+    contents: Vec<(String, usize)>,
+    /// Files whose path has the literal, old and new path
+    paths: Vec<(String, String)>,
+}
+
+/// Replaces every occurrence of a literal string in a template's files and file names with a
+/// `{{ variable }}` placeholder, and declares the variable with the literal as its default so
+/// the template keeps producing the same files when no value is given.
+pub fn handle_parametrize(template: &str, find: &str, variable: &str, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if find.is_empty() {
+        return Err("--find needs a non-empty string".into());
+    }
+    if find.contains('/') {
+        return Err("--find can't contain '/', file names are rewritten one component at a time".into());
+    }
+    if !is_valid_name(variable) {
+        return Err(format!("'{variable}' is not a valid variable name (letters, digits and underscores)").into());
+    }
+
+    let template_info = Template::find(template)?
+        .ok_or_else(|| TemplateError::NotFound(template.to_string()))?;
+    if template_info.is_builtin() {
+        return Err(format!(
+            "'{}' is built into the binary and can't be edited, eject it first: `{} eject {}`",
+            template_info.name,
+            crate::BIN_NAME,
+            template_info.name
+        ).into());
+    }
+    if template_info.is_packed() {
+        return Err(format!(
+            "Template '{}' is packed into the object store, unpack it first: `{} unpack {}`",
+            template_info.name,
+            crate::BIN_NAME,
+            template_info.name
+        ).into());
+    }
+
+    if template_info.config.engine == Some(RenderEngine::None) {
+        return Err(format!("Template '{}' sets engine = \"none\", its placeholders would never be rendered", template_info.name).into());
+    }
+
+    if template_info.is_compacted() && !dry_run {
+        eprintln!("Extracting compacted template '{}' for editing...", template_info.name);
+        template_info.inflate()?;
+    }
+
+    let placeholder = format!("{{{{ {variable} }}}}");
+    let rewrites = rewrite_template(&template_info, find, &placeholder, dry_run)?;
+    if rewrites.contents.is_empty() && rewrites.paths.is_empty() {
+        return Err(format!("'{find}' doesn't occur in the files or file names of template '{}'", template_info.name).into());
+    }
+    print_rewrites(&rewrites, dry_run);
+
+    let declared = template_info.config.variables.as_ref().is_some_and(|variables| variables.contains_key(variable));
+    if !dry_run {
+        let config_path = template_info.path.join(TEMPLATE_CONFIG_FILE);
+        let mut content = rename_file_settings(&fs::read_to_string(&config_path)?, &rewrites.paths)?;
+        if !declared {
+            let spec = format!("{{ default = {} }}", Value::from(find));
+            content = edit_config(&content, &format!("variables.{variable}"), Some(&spec))
+                .map_err(|e| format!("Cannot declare variable '{variable}': {e}"))?
+                .0;
+        }
+        fs::write(&config_path, content)?;
+    }
+
+    let summary = format!(
+        "'{find}' with {placeholder} in {} file(s) and {} path(s) of template '{}'",
+        rewrites.contents.len(),
+        rewrites.paths.len(),
+        template_info.name
+    );
+    if dry_run {
+        eprintln!("{} Would replace {summary}", Icon::DryRun);
+    } else {
+        eprintln!("{} Replaced {summary}", Icon::Success);
+    }
+    if declared {
+        eprintln!("Variable '{variable}' is already declared in {TEMPLATE_CONFIG_FILE}");
+    } else if !dry_run {
+        eprintln!("Declared variable '{variable}' with default '{find}'");
+    }
+    Ok(())
+}
+
+/// Rewrites the literal in the template's file contents and paths. Verbatim files and files
+/// that aren't text keep their content but are still renamed.
+fn rewrite_template(template_info: &Template, find: &str, placeholder: &str, dry_run: bool) -> Result<Rewrites, Box<dyn std::error::Error>> {
+    let content_dir = template_info.content_dir()?;
+    let verbatim = template_info.config.verbatim.clone().unwrap_or_default();
+    let mut rewrites = Rewrites::default();
+    let files = template_info.files()?;
+
+    // Refuse collisions before anything is touched, so a failure never leaves half a rewrite
+    let mut renamed_to = BTreeSet::new();
+    for file in &files {
+        let renamed = file.replace(find, placeholder);
+        if renamed != *file && (content_dir.join(&renamed).exists() || !renamed_to.insert(renamed.clone())) {
+            return Err(format!("Cannot rename {file}: {renamed} already exists").into());
+        }
+    }
+
+    for file in files {
+        let path = content_dir.join(&file);
+        let declared = template_info.config.files.as_ref()
+            .and_then(|files| files.get(&file))
+            .and_then(|config| config.encoding.as_deref());
+        if !matches_any(&file, &verbatim)
+            && let Some((text, encoding)) = decode(&fs::read(&path)?, declared)
+            && text.contains(find)
+        {
+            rewrites.contents.push((file.clone(), text.matches(find).count()));
+            if !dry_run {
+                let rewritten = encoding.encode(&text.replace(find, placeholder))
+                    .ok_or_else(|| format!("{file}: the placeholder can't be written in the file's encoding"))?;
+                fs::write(&path, rewritten)?;
+            }
+        }
+
+        let renamed = file.replace(find, placeholder);
+        if renamed != file {
+            if !dry_run {
+                let destination = content_dir.join(&renamed);
+                if let Some(parent) = destination.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(&path, &destination)?;
+                remove_emptied_dirs(&content_dir, &path);
+            }
+            rewrites.paths.push((file, renamed));
+        }
+    }
+    Ok(rewrites)
+}
+
+/// Moves per-file settings (`[files."path"]`) of renamed files to their new path
+fn rename_file_settings(content: &str, renames: &[(String, String)]) -> Result<String, Box<dyn std::error::Error>> {
+    let mut document: DocumentMut = content.parse()
+        .map_err(|e| format!("{TEMPLATE_CONFIG_FILE} is not valid TOML: {e}"))?;
+    if let Some(files) = document.get_mut("files").and_then(Item::as_table_like_mut) {
+        for (old, new) in renames {
+            if let Some(settings) = files.remove(old) {
+                files.insert(new, settings);
+            }
+        }
+    }
+    Ok(document.to_string())
+}
+
+fn print_rewrites(rewrites: &Rewrites, dry_run: bool) {
+    if is_plain() {
+        for (file, count) in &rewrites.contents {
+            print_record(&["rewrite", file, &count.to_string()]);
+        }
+        for (old, new) in &rewrites.paths {
+            print_record(&["rename", old, new]);
+        }
+        return;
+    }
+
+    if !rewrites.contents.is_empty() {
+        println!("{}", if dry_run { "Would rewrite:" } else { "Rewrote:" });
+        for (file, count) in &rewrites.contents {
+            println!("  {file} ({count})");
+        }
+    }
+    if !rewrites.paths.is_empty() {
+        println!("{}", if dry_run { "Would rename:" } else { "Renamed:" });
+        for (old, new) in &rewrites.paths {
+            println!("  {old} -> {new}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::{TEMPLATE_STORAGE, TestStorage};
+    use std::path::{Path, PathBuf};
+
+    fn write_template(storage: &TestStorage, files: &[(&str, &str)]) -> PathBuf {
+        let dir = storage.path().join(TEMPLATE_STORAGE).join("service");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(TEMPLATE_CONFIG_FILE), "[files.\"acme.toml\"]\ncreate_once = true\n").unwrap();
+        for (file, content) in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn read(dir: &Path, file: &str) -> String {
+        fs::read_to_string(dir.join(file)).unwrap()
+    }
+
+    #[test]
+    fn test_parametrize_contents_and_names() {
+        let storage = TestStorage::new();
+        let dir = write_template(&storage, &[("acme.toml", "name = \"acme\"\n"), ("src/acme/lib.rs", "// acme and acme\n"), ("README.md", "plain\n")]);
+
+        handle_parametrize("service", "acme", "project", true).unwrap();
+        assert_eq!(read(&dir, "acme.toml"), "name = \"acme\"\n");
+
+        handle_parametrize("service", "acme", "project", false).unwrap();
+        assert_eq!(read(&dir, "{{ project }}.toml"), "name = \"{{ project }}\"\n");
+        assert_eq!(read(&dir, "src/{{ project }}/lib.rs"), "// {{ project }} and {{ project }}\n");
+        assert_eq!(read(&dir, "README.md"), "plain\n");
+        assert!(!dir.join("acme.toml").exists() && !dir.join("src/acme").exists());
+
+        let template = Template::find("service").unwrap().unwrap();
+        let declared = &template.config.variables.as_ref().unwrap()["project"];
+        assert_eq!(declared.default.as_deref(), Some("acme"));
+        // The renamed file keeps its settings
+        assert!(template.is_create_once("{{ project }}.toml"));
+    }
+
+    #[test]
+    fn test_parametrize_refuses_name_collisions() {
+        let storage = TestStorage::new();
+        let dir = write_template(&storage, &[("acme.txt", "acme\n"), ("{{ project }}.txt", "taken\n"), ("notes.md", "acme\n")]);
+
+        let error = handle_parametrize("service", "acme", "project", false).unwrap_err();
+        assert!(error.to_string().contains("already exists"), "{error}");
+        // Nothing was rewritten before the collision was found
+        assert_eq!(read(&dir, "acme.txt"), "acme\n");
+        assert_eq!(read(&dir, "notes.md"), "acme\n");
+        assert_eq!(read(&dir, "{{ project }}.txt"), "taken\n");
+        assert!(!read(&dir, TEMPLATE_CONFIG_FILE).contains("project"));
+    }
+}
@@ -0,0 +1,33 @@
+use crate::commands::init::{self, ApplyOptions};
+use crate::error::TemplateError;
+use crate::template::Template;
+use crate::tui::pick_template;
+
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+/// Lets the user choose a template from a filterable full-screen list, then applies it to `path`:
+/// a directory that doesn't exist yet is created like `new` does, an existing one is initialized
+/// like `init` does (the current directory when no path is given).
+pub fn handle_pick(path: Option<String>, dry_run: bool, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(format!("The picker needs a terminal; use `{0} list` and `{0} init <TEMPLATE>` instead", crate::BIN_NAME).into());
+    }
+
+    let templates = Template::discover_all()?;
+    if templates.is_empty() {
+        return Err(format!("No templates available. Add one with `{} add <PATH>`", crate::BIN_NAME).into());
+    }
+    let Some(template) = pick_template(&templates)? else {
+        return Err(TemplateError::Aborted("No template picked".to_string()).into());
+    };
+
+    let options = ApplyOptions {
+        dry_run,
+        force,
+        default_excludes: true,
+        create_dir: path.as_deref().is_some_and(|path| !Path::new(path).exists()),
+        ..ApplyOptions::default()
+    };
+    init::handle_init(Some(&template.name), path, &options)
+}
@@ -0,0 +1,53 @@
+use crate::config::USER_CONFIG_FILE;
+use crate::file::{ensure_persistent_storage_dir, ensure_template_storage_dir, find_in_path};
+use crate::output::is_plain;
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Prefix of plugin executables: `template-rs foo` runs `template-rs-foo`
+const PLUGIN_PREFIX: &str = "template-rs-";
+
+/// Runs an external subcommand plugin found on PATH, passing the remaining arguments through.
+///
+/// Plugins receive their context through the environment:
+/// * `TEMPLATE_RS_STORAGE` - the persistent storage directory (~/.template-rs)
+/// * `TEMPLATE_RS_TEMPLATES` - the template storage directory
+/// * `TEMPLATE_RS_CONFIG` - the user configuration file (may not exist)
+/// * `TEMPLATE_RS_VERSION` - the version of this tool
+/// * `TEMPLATE_RS_BIN` - the executable that dispatched to the plugin
+/// * `TEMPLATE_RS_PLAIN` - "1" when `--plain` output was requested
+pub fn handle_external(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (name, plugin_args) = args.split_first().ok_or("No subcommand given")?;
+
+    let plugin = find_plugin(name).ok_or_else(|| {
+        format!(
+            "Unknown command '{name}', and no {PLUGIN_PREFIX}{name} plugin was found on PATH. Use `{} --help` to see the built-in commands.",
+            crate::BIN_NAME
+        )
+    })?;
+
+    let storage_dir = ensure_persistent_storage_dir()?;
+    let status = Command::new(&plugin)
+        .args(plugin_args)
+        .env("TEMPLATE_RS_STORAGE", &storage_dir)
+        .env("TEMPLATE_RS_TEMPLATES", ensure_template_storage_dir()?)
+        .env("TEMPLATE_RS_CONFIG", storage_dir.join(USER_CONFIG_FILE))
+        .env("TEMPLATE_RS_VERSION", env!("CARGO_PKG_VERSION"))
+        .env("TEMPLATE_RS_BIN", env::current_exe()?)
+        .env("TEMPLATE_RS_PLAIN", if is_plain() { "1" } else { "0" })
+        .status()
+        .map_err(|e| format!("Failed to run plugin {}: {e}", plugin.display()))?;
+
+    // Hand the plugin's exit code back to the caller unchanged
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Looks for an executable `template-rs-<name>` in the directories on PATH
+fn find_plugin(name: &str) -> Option<PathBuf> {
+    find_in_path(&format!("{PLUGIN_PREFIX}{name}"))
+}
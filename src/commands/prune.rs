@@ -0,0 +1,149 @@
+use crate::config::UserConfig;
+use crate::file::{ensure_object_storage_dir, ensure_version_storage_dir, format_size, remove_empty_dirs};
+use crate::lock::TemplateLock;
+use crate::objects::{Manifest, list_blobs, remove_blob};
+use crate::origin::TemplateOrigin;
+use crate::output::{is_plain, print_record};
+use crate::path::resolve_path;
+use crate::template::{template_roots, Template, TEMPLATE_MANIFEST_FILE};
+use crate::theme::Icon;
+use crate::versions::VersionRecord;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+
+pub fn handle_prune(keep: usize, projects: &[String], dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let config = UserConfig::load()?;
+
+    let mut locks = Vec::new();
+    for project in projects {
+        let project_path = resolve_path(project, None)?;
+        let lock = TemplateLock::load(&project_path)?
+            .ok_or_else(|| format!("No .template.lock found in {}", project_path.display()))?;
+        locks.push((project_path, lock));
+    }
+
+    eprintln!("Pruning template versions, keeping the {keep} most recent of each template...");
+
+    let mut by_template: BTreeMap<String, Vec<(PathBuf, VersionRecord)>> = BTreeMap::new();
+    for (path, record) in VersionRecord::load_all()? {
+        by_template.entry(record.template.clone()).or_default().push((path, record));
+    }
+
+    let mut live = BTreeSet::new();
+    let mut removed_versions = 0;
+
+    for (template, mut records) in by_template {
+        records.sort_by_key(|(_, record)| std::cmp::Reverse(record.recorded_at));
+
+        for (index, (path, record)) in records.into_iter().enumerate() {
+            let reason = if index < keep {
+                None
+            } else if config.pins.iter().any(|pin| record.is_pinned_by(pin)) {
+                Some("pinned".to_string())
+            } else {
+                locks.iter()
+                    .find(|(_, lock)| lock.template == record.template && lock.files == record.files)
+                    .map(|(project_path, _)| format!("in use by {}", project_path.display()))
+            };
+
+            if index < keep || reason.is_some() {
+                if let Some(reason) = reason {
+                    if is_plain() {
+                        print_record(&["keep", &template, &record.id(), &reason]);
+                    } else {
+                        println!("  keeping {template} {} ({reason})", record.label());
+                    }
+                }
+                live.extend(record.files.into_values());
+                continue;
+            }
+
+            if !dry_run {
+                fs::remove_file(&path)?;
+            }
+            let action = if dry_run { "would remove" } else { "removed" };
+            if is_plain() {
+                print_record(&[&action.replace(' ', "-"), &template, &record.id()]);
+            } else {
+                println!("  {action} {template} {}", record.label());
+            }
+            removed_versions += 1;
+        }
+    }
+
+    // Objects still needed: project locks the user pointed at, packed templates and the
+    // upstream content of ejected templates, in every root (shadowed templates included)
+    for (_, lock) in &locks {
+        live.extend(lock.files.values().cloned());
+    }
+    let mut templates = Vec::new();
+    for root in template_roots()? {
+        if root.path.is_dir() {
+            templates.extend(Template::discover_in(&root.path)?);
+        }
+    }
+    for template in templates {
+        if template.is_packed() {
+            let manifest = Manifest::load(template.path.join(TEMPLATE_MANIFEST_FILE))?;
+            live.extend(manifest.files.into_values().map(|entry| entry.hash));
+        }
+        if let Some(origin) = TemplateOrigin::load(&template.path)? {
+            live.extend(origin.files.into_values());
+        }
+    }
+
+    let mut removed_objects = 0;
+    let mut freed = 0;
+    for hash in list_blobs()? {
+        if live.contains(&hash) {
+            continue;
+        }
+        if !dry_run {
+            freed += remove_blob(&hash)?;
+        }
+        removed_objects += 1;
+    }
+
+    if dry_run {
+        eprintln!(
+            "{} Dry run - {removed_versions} version(s) and {removed_objects} unreferenced object(s) would be removed",
+            Icon::DryRun
+        );
+    } else {
+        remove_empty_dirs(ensure_version_storage_dir()?)?;
+        remove_empty_dirs(ensure_object_storage_dir()?)?;
+        eprintln!(
+            "{} Removed {removed_versions} version(s) and {removed_objects} unreferenced object(s), freed {}",
+            Icon::Success,
+            format_size(freed)
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::pack::{handle_pack, handle_unpack};
+    use crate::path::TestStorage;
+
+    #[test]
+    fn test_prune_keeps_objects_of_packed_root_templates() {
+        let storage = TestStorage::new();
+        let root = storage.path().join("team-templates");
+        fs::create_dir_all(root.join("service/src")).unwrap();
+        fs::write(root.join("service/.template.toml"), "description = \"Team service\"\n").unwrap();
+        fs::write(root.join("service/src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(storage.path().join("config.toml"), "roots = [\"team-templates\"]\n").unwrap();
+
+        handle_pack("service").unwrap();
+        assert!(!root.join("service/src/main.rs").exists());
+
+        handle_prune(1, &[], false).unwrap();
+        handle_unpack("service").unwrap();
+        assert_eq!(fs::read_to_string(root.join("service/src/main.rs")).unwrap(), "fn main() {}\n");
+    }
+}
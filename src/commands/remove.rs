@@ -0,0 +1,141 @@
+use crate::error::TemplateError;
+use crate::file::{ensure_template_storage_dir, format_size};
+use crate::output::{is_plain, print_record};
+use crate::prompt::confirm;
+use crate::template::Template;
+use crate::theme::Icon;
+
+use std::fs;
+use std::path::Path;
+
+/// Deletes a template from the template storage, after confirmation unless `force` is set.
+/// Templates nested in its directory go with it, and namespace directories left empty are
+/// removed too.
+pub fn handle_remove(template: &str, force: bool, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let template_info = Template::find(template)?
+        .ok_or_else(|| TemplateError::NotFound(template.to_string()))?;
+    if template_info.is_builtin() {
+        return Err(format!("'{}' is built into the binary and can't be removed", template_info.name).into());
+    }
+    let storage_dir = ensure_template_storage_dir()?;
+    if !template_info.path.starts_with(&storage_dir) || template_info.path == storage_dir {
+        return Err(format!("Template '{}' is not in the template storage ({})", template_info.name, storage_dir.display()).into());
+    }
+
+    let nested: Vec<String> = Template::discover_stored()?
+        .into_iter()
+        .filter(|other| other.name != template_info.name && other.path.starts_with(&template_info.path))
+        .map(|other| other.name)
+        .collect();
+    let size = template_info.size_report()?.total;
+    let packed = template_info.is_packed();
+
+    if dry_run {
+        print_removal(&template_info, &nested);
+        eprintln!("{} Dry run - template '{}' ({}) would be removed, nothing was deleted", Icon::DryRun, template_info.name, format_size(size));
+        return Ok(());
+    }
+
+    if !force {
+        eprintln!("Template '{}' ({}) will be deleted from {}", template_info.name, format_size(size), template_info.path.display());
+        if !nested.is_empty() {
+            eprintln!("{} It contains {} other template(s) that are deleted with it: {}", Icon::Warning, nested.len(), nested.join(", "));
+        }
+        if !confirm("Remove this template?")? {
+            return Err(TemplateError::Aborted("Aborted, nothing was deleted. Use --force to skip this confirmation.".to_string()).into());
+        }
+    }
+
+    fs::remove_dir_all(&template_info.path)?;
+    remove_empty_parents(&storage_dir, &template_info.path)?;
+
+    eprintln!("{} Removed template '{}'", Icon::Success, template_info.name);
+    if packed {
+        eprintln!("{} Its contents stay in the object store until `{} prune` finds them unused", Icon::Note, crate::BIN_NAME);
+    }
+    Ok(())
+}
+
+/// Lists what removing the template would delete, directories ending in '/'
+fn print_removal(template: &Template, nested: &[String]) {
+    let mut entries = vec![format!("{}/", template.path.display())];
+    entries.extend(nested.iter().map(|name| format!("{name} (nested template)")));
+
+    if is_plain() {
+        print_record(&["delete", &entries[0]]);
+        for name in nested {
+            print_record(&["delete-nested", name]);
+        }
+        return;
+    }
+
+    println!("Would delete:");
+    for entry in &entries {
+        println!("  {entry}");
+    }
+}
+
+/// Removes the now empty namespace directories between `removed` and the storage root
+pub fn remove_empty_parents(storage_dir: &Path, removed: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    for parent in removed.ancestors().skip(1).take_while(|parent| *parent != storage_dir) {
+        if fs::read_dir(parent)?.next().is_some() {
+            break;
+        }
+        fs::remove_dir(parent)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::{TEMPLATE_STORAGE, TestStorage};
+    use crate::template::TEMPLATE_CONFIG_FILE;
+
+    fn write_template(root: &Path, name: &str) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(TEMPLATE_CONFIG_FILE), "").unwrap();
+        fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    }
+
+    #[test]
+    fn test_remove_with_nested_templates() {
+        let storage = TestStorage::new();
+        let root = storage.path().join(TEMPLATE_STORAGE);
+        write_template(&root, "team/app");
+        write_template(&root, "team/app/worker");
+        write_template(&root, "other");
+
+        handle_remove("team/app", true, true).unwrap();
+        assert!(root.join("team/app/worker/main.rs").exists());
+
+        handle_remove("team/app", true, false).unwrap();
+        assert!(!root.join("team").exists());
+        assert!(root.join("other/main.rs").exists());
+
+        let error = handle_remove("team/app", true, false).unwrap_err();
+        assert!(matches!(error.downcast_ref::<TemplateError>(), Some(TemplateError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_remove_shadowed_template() {
+        let storage = TestStorage::new();
+        let root = storage.path().join(TEMPLATE_STORAGE);
+        let team_root = storage.path().join("team-templates");
+        fs::write(storage.path().join("config.toml"), "roots = [\"team-templates\"]\n").unwrap();
+        write_template(&root, "service");
+        write_template(&team_root, "service");
+
+        // The stored template shadows the team's; removing it uncovers the team's again
+        handle_remove("service", true, false).unwrap();
+        assert!(!root.join("service").exists());
+        let uncovered = Template::find("service").unwrap().unwrap();
+        assert!(uncovered.path.starts_with(&team_root));
+
+        // Templates of other roots aren't the storage's to delete
+        let error = handle_remove("service", true, false).unwrap_err();
+        assert!(error.to_string().contains("not in the template storage"), "{error}");
+        assert!(team_root.join("service/main.rs").exists());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::commands::eject::storage_destination;
+use crate::commands::remove::remove_empty_parents;
+use crate::commands::set::edit_config;
+use crate::error::TemplateError;
+use crate::file::ensure_template_storage_dir;
+use crate::history::UsageHistory;
+use crate::names::template_name;
+use crate::output::{is_plain, print_record};
+use crate::template::{Template, TEMPLATE_CONFIG_FILE};
+use crate::theme::Icon;
+
+use std::fs;
+
+/// Moves a template to another name in the template storage (`rust/cli` to `rust/binaries/cli`),
+/// carrying its usage history along. With `update_name` the `name` in .template.toml becomes the
+/// last component of the new name. `normalize` applies the naming policy to the new name.
+pub fn handle_rename(template: &str, new_name: &str, update_name: bool, normalize: bool, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let template_info = Template::find(template)?
+        .ok_or_else(|| TemplateError::NotFound(template.to_string()))?;
+    if template_info.is_builtin() {
+        return Err(format!(
+            "'{}' is built into the binary and can't be renamed, eject it under the new name instead: `{} eject {} {new_name}`",
+            template_info.name,
+            crate::BIN_NAME,
+            template_info.name
+        ).into());
+    }
+
+    let new_name = template_name(new_name, normalize)?;
+    if new_name == template_info.name {
+        return Err(format!("Template '{}' already has that name", template_info.name).into());
+    }
+    let storage_dir = ensure_template_storage_dir()?;
+    let dest_path = storage_destination(&new_name)?;
+    if dest_path.starts_with(&template_info.path) {
+        return Err(format!("Cannot move template '{}' into its own directory", template_info.name).into());
+    }
+    let config_name = new_name.rsplit('/').next().unwrap_or(&new_name);
+    let renames_config = update_name && template_info.config.name.as_deref() != Some(config_name);
+
+    if dry_run {
+        if is_plain() {
+            print_record(&["move", &template_info.name, &new_name]);
+            if renames_config {
+                print_record(&["set-name", &new_name, config_name]);
+            }
+        } else {
+            println!("Would move:");
+            println!("  {} -> {}", template_info.path.display(), dest_path.display());
+            if renames_config {
+                println!("Would set name = \"{config_name}\" in {TEMPLATE_CONFIG_FILE}");
+            }
+        }
+        eprintln!("{} Dry run - template '{}' would be renamed to '{new_name}', nothing was moved", Icon::DryRun, template_info.name);
+        return Ok(());
+    }
+
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(&template_info.path, &dest_path)?;
+    remove_empty_parents(&storage_dir, &template_info.path)?;
+
+    if renames_config {
+        let config_path = dest_path.join(TEMPLATE_CONFIG_FILE);
+        let (content, _) = edit_config(&fs::read_to_string(&config_path)?, "name", Some(config_name))
+            .map_err(|e| format!("Template moved, but its name could not be updated: {e}"))?;
+        fs::write(&config_path, content)?;
+    }
+
+    let mut history = UsageHistory::load()?;
+    if history.rename(&template_info.name, &new_name) {
+        history.save()?;
+    }
+
+    eprintln!("{} Renamed template '{}' to '{new_name}'", Icon::Success, template_info.name);
+    if !update_name && let Some(name) = &template_info.config.name {
+        eprintln!("{} {TEMPLATE_CONFIG_FILE} still names it '{name}', pass --update-name to change that", Icon::Note);
+    }
+    eprintln!("Projects applied from it still record '{}' in .template.lock; pass the new name to `init` there", template_info.name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::{TEMPLATE_STORAGE, TestStorage};
+    use std::path::Path;
+
+    fn write_template(root: &Path, name: &str, config: &str) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(TEMPLATE_CONFIG_FILE), config).unwrap();
+        fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    }
+
+    #[test]
+    fn test_rename_moves_template_and_history() {
+        let storage = TestStorage::new();
+        let root = storage.path().join(TEMPLATE_STORAGE);
+        write_template(&root, "rust/cli", "name = \"cli\"\n");
+        UsageHistory::record_use("rust/cli", None).unwrap();
+
+        handle_rename("rust/cli", "tools/runner", false, false, true).unwrap();
+        assert!(root.join("rust/cli/main.rs").exists());
+
+        handle_rename("rust/cli", "tools/runner", true, false, false).unwrap();
+        assert!(root.join("tools/runner/main.rs").exists());
+        // The namespace left empty goes with it
+        assert!(!root.join("rust").exists());
+        let renamed = Template::find("tools/runner").unwrap().unwrap();
+        assert_eq!(renamed.config.name.as_deref(), Some("runner"));
+        let history = UsageHistory::load().unwrap();
+        assert!(history.last_used("tools/runner").is_some());
+        assert!(history.last_used("rust/cli").is_none());
+    }
+
+    #[test]
+    fn test_rename_onto_existing_name() {
+        let storage = TestStorage::new();
+        let root = storage.path().join(TEMPLATE_STORAGE);
+        write_template(&root, "cli", "");
+        write_template(&root, "web", "description = \"Web\"\n");
+
+        let error = handle_rename("cli", "web", false, false, false).unwrap_err();
+        assert!(error.to_string().contains("already exists"), "{error}");
+        assert!(root.join("cli/main.rs").exists());
+        assert_eq!(fs::read_to_string(root.join("web").join(TEMPLATE_CONFIG_FILE)).unwrap(), "description = \"Web\"\n");
+
+        let error = handle_rename("cli", "cli", false, false, false).unwrap_err();
+        assert!(error.to_string().contains("already has that name"), "{error}");
+    }
+}
@@ -0,0 +1,34 @@
+use crate::languages::get_display_name;
+use crate::output::{is_plain, print_record};
+use crate::search::search;
+use crate::template::Template;
+
+/// Lists the templates matching a query, best match first. Every word of the query has to match
+/// the name, a tag, the language, the category or the description, allowing typos.
+pub fn handle_search(query: &[String], limit: Option<usize>) -> Result<(), Box<dyn std::error::Error>> {
+    let query = query.join(" ");
+    let templates = Template::discover_all()?;
+    let mut results = search(&templates, &query);
+    if let Some(limit) = limit {
+        results.truncate(limit);
+    }
+
+    if results.is_empty() {
+        eprintln!("No templates match '{query}'.");
+        eprintln!("Use `{} list` to see all available templates.", crate::BIN_NAME);
+        return Ok(());
+    }
+
+    for (template, score) in &results {
+        let language = template.language().map(get_display_name).unwrap_or_default();
+        let description = template.description().unwrap_or("");
+        if is_plain() {
+            print_record(&[&template.name, &score.to_string(), &language, description]);
+        } else if description.is_empty() {
+            println!("  {}", template.name);
+        } else {
+            println!("  {} - {description}", template.name);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use crate::compat::parse_version;
+use crate::error::TemplateError;
+use crate::template::{Template, TemplateConfig, TEMPLATE_CONFIG_FILE};
+use crate::theme::Icon;
+
+use toml_edit::{DocumentMut, Item, Key, Value};
+
+use std::fs;
+
+/// Fields whose value must be a version number
+const VERSION_FIELDS: [&str; 3] = ["version", "min_tool_version", "max_tool_version"];
+
+/// Sets (or with `value` of `None`, removes) one field of a template's .template.toml, keeping
+/// the rest of the file as written. The edited file must still parse as a template config.
+pub fn handle_set(template: &str, key: &str, value: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let template_info = Template::find(template)?
+        .ok_or_else(|| TemplateError::NotFound(template.to_string()))?;
+    if template_info.is_builtin() {
+        return Err(format!(
+            "'{}' is built into the binary and can't be edited, eject it first: `{} eject {}`",
+            template_info.name,
+            crate::BIN_NAME,
+            template_info.name
+        ).into());
+    }
+
+    let config_path = template_info.path.join(TEMPLATE_CONFIG_FILE);
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Cannot read {}: {e}", config_path.display()))?;
+    let (edited, custom) = edit_config(&content, key, value)
+        .map_err(|e| format!("Cannot set '{key}' of template '{}': {e}", template_info.name))?;
+    fs::write(&config_path, edited)?;
+
+    if custom {
+        eprintln!("{} '{key}' is not a field this version knows, it is kept as custom metadata", Icon::Warning);
+    }
+    match value {
+        Some(value) => eprintln!("{} Set {key} = {value} for template '{}'", Icon::Success, template_info.name),
+        None => eprintln!("{} Removed {key} from template '{}'", Icon::Success, template_info.name),
+    }
+    Ok(())
+}
+
+/// Applies one edit to the contents of a .template.toml, returning the new contents and whether
+/// the key is custom metadata rather than a known field.
+///
+/// `key` is a dotted TOML key (`prerequisites.binaries`, `files."README.md".create_once`). A value
+/// is taken as TOML (`true`, `42`, `["a", "b"]`) when that gives a valid config, and as a plain
+/// string otherwise, so `set t version 2.0.0` needs no quoting.
+pub fn edit_config(content: &str, key: &str, value: Option<&str>) -> Result<(String, bool), String> {
+    let path = Key::parse(key).map_err(|e| format!("invalid key: {e}"))?;
+    let (last, parents) = path.split_last().ok_or("empty key")?;
+    let mut document: DocumentMut = content.parse().map_err(|e| format!("{TEMPLATE_CONFIG_FILE} is not valid TOML: {e}"))?;
+
+    let Some(value) = value else {
+        let table = parent_table(&mut document, parents)?;
+        if table.as_table_like_mut().and_then(|t| t.remove(last.get())).is_none() {
+            return Err("it is not set".to_string());
+        }
+        let config = parse_edited(&document)?;
+        return Ok((document.to_string(), is_custom(&config, &path)));
+    };
+
+    if VERSION_FIELDS.contains(&key) && parse_version(value).is_none() {
+        return Err(format!("'{value}' is not a version number (like 1.2.3)"));
+    }
+
+    let mut candidates = Vec::new();
+    if let Ok(parsed) = value.parse::<Value>()
+        && !parsed.is_str()
+    {
+        candidates.push(parsed);
+    }
+    candidates.push(Value::from(value));
+
+    let mut error = String::new();
+    for candidate in candidates {
+        let table = parent_table(&mut document, parents)?;
+        table[last.get()] = Item::Value(candidate);
+        match parse_edited(&document) {
+            Ok(config) => return Ok((document.to_string(), is_custom(&config, &path))),
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+/// The table a dotted key's last part lives in, creating missing tables along the way
+fn parent_table<'a>(document: &'a mut DocumentMut, parents: &[Key]) -> Result<&'a mut Item, String> {
+    let mut item = document.as_item_mut();
+    for (depth, key) in parents.iter().enumerate() {
+        if !item.is_table_like() {
+            let name: Vec<&str> = parents[..depth].iter().map(Key::get).collect();
+            return Err(format!("'{}' is not a table", name.join(".")));
+        }
+        item = &mut item[key.get()];
+        if item.is_none() {
+            *item = Item::Table(toml_edit::Table::new());
+        }
+    }
+    if item.is_table_like() {
+        Ok(item)
+    } else {
+        let name: Vec<&str> = parents.iter().map(Key::get).collect();
+        Err(format!("'{}' is not a table", name.join(".")))
+    }
+}
+
+fn parse_edited(document: &DocumentMut) -> Result<TemplateConfig, String> {
+    toml::from_str(&document.to_string()).map_err(|e| e.message().to_string())
+}
+
+/// Check if the key's top-level field ended up in the catch-all metadata
+fn is_custom(config: &TemplateConfig, path: &[Key]) -> bool {
+    config.metadata.as_ref().is_some_and(|metadata| metadata.contains_key(path[0].get()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "# My template\nname = \"demo\"  # shown in list\nversion = \"1.0.0\"\n";
+
+    #[test]
+    fn test_edit_config() {
+        let (edited, custom) = edit_config(CONFIG, "version", Some("2.0.0")).unwrap();
+        assert_eq!(edited, "# My template\nname = \"demo\"  # shown in list\nversion = \"2.0.0\"\n");
+        assert!(!custom);
+
+        // TOML values are used when they fit, plain strings otherwise
+        let (edited, _) = edit_config(CONFIG, "tags", Some(r#"["cli", "rust"]"#)).unwrap();
+        assert!(edited.contains(r#"tags = ["cli", "rust"]"#));
+        let (edited, _) = edit_config(CONFIG, "description", Some("42")).unwrap();
+        assert!(edited.contains("description = \"42\""));
+
+        let (edited, _) = edit_config(CONFIG, r#"files."README.md".create_once"#, Some("true")).unwrap();
+        assert!(toml::from_str::<TemplateConfig>(&edited).unwrap().files.unwrap()["README.md"].create_once);
+
+        let (edited, custom) = edit_config(CONFIG, "maintainer", Some("me")).unwrap();
+        assert!(edited.contains("maintainer = \"me\""));
+        assert!(custom);
+
+        let (edited, _) = edit_config(CONFIG, "version", None).unwrap();
+        assert!(!edited.contains("version"));
+    }
+
+    #[test]
+    fn test_edit_config_rejects_invalid() {
+        assert!(edit_config(CONFIG, "version", Some("two")).is_err());
+        assert!(edit_config(CONFIG, "eol", Some("sideways")).is_err());
+        assert!(edit_config(CONFIG, "name.first", Some("x")).is_err());
+        assert!(edit_config(CONFIG, "author", None).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::categories::get_category_display_name;
+use crate::history::{UsageHistory, UseRecord, format_date, now_secs};
+use crate::languages::get_display_name;
+use crate::output::{is_plain, print_record};
+use crate::template::Template;
+use crate::theme::Icon;
+
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+
+/// Adoption figures of the template library, as `stats --json` exports them
+#[derive(Debug, Serialize)]
+struct Stats {
+    /// Unix timestamp (seconds) the figures were taken at
+    generated_at: u64,
+    total_uses: u64,
+    templates: Vec<TemplateStats>,
+    languages: BTreeMap<String, GroupStats>,
+    categories: BTreeMap<String, GroupStats>,
+}
+
+/// Usage of one template; templates that were removed after being used are kept with
+/// `available` unset
+#[derive(Debug, Serialize)]
+struct TemplateStats {
+    name: String,
+    language: Option<String>,
+    category: Option<String>,
+    version: Option<String>,
+    uses: u64,
+    /// Unix timestamp (seconds) of the last application
+    last_used: Option<u64>,
+    available: bool,
+}
+
+/// Templates and uses of one language or category
+#[derive(Debug, Default, Serialize)]
+struct GroupStats {
+    templates: usize,
+    uses: u64,
+}
+
+/// Shows how much each template is used, and totals per language and category
+pub fn handle_stats(json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let stats = collect_stats(&Template::discover_all()?, &UsageHistory::load()?);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else if is_plain() {
+        for template in &stats.templates {
+            let last_used = template.last_used.map(|at| at.to_string()).unwrap_or_default();
+            print_record(&["template", &template.name, &template.uses.to_string(), &last_used]);
+        }
+        for (language, group) in &stats.languages {
+            print_record(&["language", language, &group.templates.to_string(), &group.uses.to_string()]);
+        }
+        for (category, group) in &stats.categories {
+            print_record(&["category", category, &group.templates.to_string(), &group.uses.to_string()]);
+        }
+    } else {
+        print_stats(&stats);
+    }
+    Ok(())
+}
+
+/// Lists template applications, newest first
+pub fn handle_history(json: bool, limit: Option<usize>) -> Result<(), Box<dyn std::error::Error>> {
+    let history = UsageHistory::load()?;
+    let uses: Vec<&UseRecord> = history.uses.iter().rev().take(limit.unwrap_or(usize::MAX)).collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&uses)?);
+        return Ok(());
+    }
+    for record in &uses {
+        let version = record.version.as_deref().unwrap_or_default();
+        if is_plain() {
+            print_record(&[&record.at.to_string(), &record.template, version]);
+        } else {
+            println!("  {}  {} {version}", format_date(record.at), record.template);
+        }
+    }
+    if uses.is_empty() && !is_plain() {
+        eprintln!("No template has been applied yet");
+    }
+    Ok(())
+}
+
+fn collect_stats(templates: &[Template], history: &UsageHistory) -> Stats {
+    let mut stats = Stats {
+        generated_at: now_secs(),
+        total_uses: history.templates.values().map(|entry| entry.count).sum(),
+        templates: Vec::new(),
+        languages: BTreeMap::new(),
+        categories: BTreeMap::new(),
+    };
+
+    for template in templates {
+        let usage = history.templates.get(&template.name);
+        let uses = usage.map_or(0, |entry| entry.count);
+        let language = template.language().map(get_display_name);
+        let category = template.category().map(get_category_display_name);
+        for (groups, key) in [(&mut stats.languages, &language), (&mut stats.categories, &category)] {
+            let group = groups.entry(key.as_deref().unwrap_or("unspecified").to_string()).or_default();
+            group.templates += 1;
+            group.uses += uses;
+        }
+        stats.templates.push(TemplateStats {
+            name: template.name.clone(),
+            language,
+            category,
+            version: template.version().map(String::from),
+            uses,
+            last_used: usage.map(|entry| entry.last_used),
+            available: true,
+        });
+    }
+    for (name, entry) in &history.templates {
+        if !templates.iter().any(|template| &template.name == name) {
+            stats.templates.push(TemplateStats {
+                name: name.clone(),
+                language: None,
+                category: None,
+                version: None,
+                uses: entry.count,
+                last_used: Some(entry.last_used),
+                available: false,
+            });
+        }
+    }
+
+    // Most used first, then most recently used
+    stats.templates.sort_by(|a, b| b.uses.cmp(&a.uses).then(b.last_used.cmp(&a.last_used)).then_with(|| a.name.cmp(&b.name)));
+    stats
+}
+
+fn print_stats(stats: &Stats) {
+    println!("{} {} template(s), {} use(s)\n", Icon::Package, stats.templates.iter().filter(|t| t.available).count(), stats.total_uses);
+    for template in &stats.templates {
+        let last_used = template.last_used.map_or_else(|| "never".to_string(), format_date);
+        let removed = if template.available { "" } else { " (removed)" };
+        println!("  {:>6}  {:<10}  {}{removed}", template.uses, last_used, template.name);
+    }
+
+    for (heading, groups) in [("Languages", &stats.languages), ("Categories", &stats.categories)] {
+        println!("\n{heading}:");
+        for (name, group) in groups {
+            println!("  {name}: {} template(s), {} use(s)", group.templates, group.uses);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::UsageEntry;
+    use crate::template::TemplateConfig;
+
+    use std::path::PathBuf;
+
+    fn template(name: &str, language: Option<&str>) -> Template {
+        Template {
+            name: name.to_string(),
+            path: PathBuf::from(name),
+            config: TemplateConfig { language: language.map(String::from), ..TemplateConfig::default() },
+        }
+    }
+
+    #[test]
+    fn test_collect_stats() {
+        let templates = [template("cli", Some("rs")), template("lib", Some("Rust")), template("site", None)];
+        let mut history = UsageHistory::default();
+        history.templates.insert("lib".to_string(), UsageEntry { count: 3, last_used: 20 });
+        history.templates.insert("gone".to_string(), UsageEntry { count: 1, last_used: 10 });
+
+        let stats = collect_stats(&templates, &history);
+        assert_eq!(stats.total_uses, 4);
+        let order: Vec<&str> = stats.templates.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(order, ["lib", "gone", "cli", "site"]);
+        assert!(!stats.templates[1].available);
+        assert_eq!(stats.languages["Rust"].templates, 2);
+        assert_eq!(stats.languages["Rust"].uses, 3);
+        assert_eq!(stats.languages["unspecified"].templates, 1);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::config::UserConfig;
+use crate::languages::{detect_primary_language, get_display_name};
+use crate::output::{is_plain, print_record};
+use crate::path::resolve_path;
+use crate::patterns::matches_any;
+use crate::template::{collect_files, Template};
+
+/// Detects the language of an existing project and lists the templates written in it
+pub fn handle_suggest(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let project_path = resolve_path(path, None)?;
+    if !project_path.is_dir() {
+        return Err(format!("Not a directory: {}", project_path.display()).into());
+    }
+
+    let excludes = UserConfig::load()?.excludes(true);
+    let mut files = Vec::new();
+    collect_files(&project_path, &project_path, &mut files)?;
+    files.retain(|f| !matches_any(f, &excludes));
+
+    let Some(language) = detect_primary_language(&files) else {
+        eprintln!("Could not detect the language of {}", project_path.display());
+        eprintln!("Use `{} list` to see all available templates.", crate::BIN_NAME);
+        return Ok(());
+    };
+    eprintln!("Detected language: {language}");
+
+    let mut templates = Template::discover_all()?;
+    templates.retain(|t| t.language().is_some_and(|l| get_display_name(l) == language));
+
+    if templates.is_empty() {
+        eprintln!("No {language} templates found.");
+        eprintln!("Use `{} list` to see all available templates.", crate::BIN_NAME);
+        return Ok(());
+    }
+
+    if !is_plain() {
+        println!("Suggested templates:\n");
+    }
+    for template in &templates {
+        let description = template.description().unwrap_or("");
+        if is_plain() {
+            print_record(&[&template.name, language, description]);
+        } else if description.is_empty() {
+            println!("  {}", template.name);
+        } else {
+            println!("  {} - {description}", template.name);
+        }
+    }
+    Ok(())
+}
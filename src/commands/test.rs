@@ -0,0 +1,259 @@
+use crate::config::UserConfig;
+use crate::encoding::decode;
+use crate::error::TemplateError;
+use crate::output::{is_plain, print_record};
+use crate::placeholders::{Placeholder, find_placeholders};
+use crate::plugins::PluginFunctions;
+use crate::render::TemplateRenderer;
+use crate::template::{Template, TEMPLATE_SNAPSHOT_DIR, TEMPLATE_TEST_FILE, collect_files};
+use crate::theme::Icon;
+use crate::variables::resolve_values;
+
+use serde::Deserialize;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Expectations from a template's optional .template-test.toml
+#[derive(Debug, Default, Deserialize)]
+struct Expectations {
+    /// Files that must exist in the output
+    #[serde(default)]
+    files: Vec<String>,
+    /// Fail if the output contains files not listed in `files`
+    #[serde(default)]
+    exact: bool,
+    /// Strings that must appear in the given output files
+    #[serde(default)]
+    contains: BTreeMap<String, Vec<String>>,
+    /// Fail if any `{{ ... }}` placeholder is left in the output
+    #[serde(default = "default_true")]
+    forbid_placeholders: bool,
+    /// Values for the template's variables; ones not given take their defaults
+    #[serde(default)]
+    variables: BTreeMap<String, String>,
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+/// How the applied output is compared against the template's stored snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotMode {
+    /// Don't compare against a snapshot
+    Off,
+    /// Fail when the output differs from the stored snapshot
+    Compare,
+    /// Replace the stored snapshot with the current output
+    Update,
+}
+
+pub fn handle_test(template: &str, keep: bool, snapshot: SnapshotMode) -> Result<(), Box<dyn std::error::Error>> {
+    eprintln!("Testing template: {template}");
+
+    let template_info = Template::find(template)?
+        .ok_or_else(|| TemplateError::NotFound(template.to_string()))?;
+    template_info.check_compatibility()?;
+    template_info.check_contents()?;
+
+    let expectations = load_expectations(&template_info.path)?;
+
+    // Apply the template into a scratch directory
+    let scratch = tempfile::Builder::new().prefix("template-rs-test-").tempdir()?;
+    let output_dir = scratch.path().to_path_buf();
+    let excludes = UserConfig::load()?.excludes(true);
+    let declared = template_info.config.variables.clone().unwrap_or_default();
+    let mut plugins = if declared.is_empty() { PluginFunctions::default() } else { PluginFunctions::load(&template_info)? };
+    let values = resolve_values(&declared, &expectations.variables, &mut |_| Ok(None), &mut |name, value| plugins.validate(name, value)).map_err(|e| {
+        format!("Template '{}' has {e}. Set values in [variables] of {TEMPLATE_TEST_FILE}", template_info.name)
+    })?;
+    let copied = template_info.copy_files_to(&output_dir, &excludes)?;
+    let renderer = TemplateRenderer::new(&template_info, values)?;
+    let rendered = renderer.apply_to_files(&output_dir, &copied, |_| true)?;
+    let output_files = rendered.paths;
+    eprintln!("Applied {} file(s) into {}", output_files.len(), output_dir.display());
+
+    let mut failures = check_expectations(&template_info, &expectations, &output_dir, &output_files)?;
+    if expectations.forbid_placeholders {
+        // Rendering knows which braces are raw text; without variables every placeholder is left over
+        let leftovers = if renderer.is_active() {
+            rendered.leftovers
+        } else {
+            scan_placeholders(&template_info, &output_dir, &output_files)
+        };
+        failures.extend(leftovers.iter().map(|(file, placeholder)| {
+            format!("leftover placeholder {} at {file}:{}", placeholder.text, placeholder.line)
+        }));
+    }
+
+    let snapshot_dir = template_info.path.join(TEMPLATE_SNAPSHOT_DIR);
+    match snapshot {
+        SnapshotMode::Off => {}
+        SnapshotMode::Compare => {
+            failures.extend(compare_snapshot(&snapshot_dir, &output_dir, &output_files)?);
+        }
+        SnapshotMode::Update => {
+            update_snapshot(&snapshot_dir, &output_dir, &output_files)?;
+            eprintln!("{} Snapshot updated at {}", Icon::Snapshot, snapshot_dir.display());
+        }
+    }
+
+    if keep {
+        eprintln!("Output kept at {}", scratch.keep().display());
+    }
+
+    if failures.is_empty() {
+        eprintln!("{} All checks passed", Icon::Success);
+        Ok(())
+    } else {
+        for failure in &failures {
+            if is_plain() {
+                print_record(&["fail", failure]);
+            } else {
+                println!("  {} {failure}", Icon::Failure);
+            }
+        }
+        Err(format!("{} check(s) failed for template '{}'", failures.len(), template_info.name).into())
+    }
+}
+
+/// Loads the expectations file, falling back to the default checks when there is none
+fn load_expectations(template_path: &Path) -> Result<Expectations, Box<dyn std::error::Error>> {
+    let path = template_path.join(TEMPLATE_TEST_FILE);
+    if !path.exists() {
+        eprintln!("No {TEMPLATE_TEST_FILE} found, running default checks");
+        return Ok(Expectations { forbid_placeholders: true, ..Expectations::default() });
+    }
+
+    let content = fs::read_to_string(&path)?;
+    let expectations = toml::from_str(&content)
+        .map_err(|e| TemplateError::Config(format!("Failed to parse {}: {e}", path.display())))?;
+    Ok(expectations)
+}
+
+/// Runs every check against the applied output, returning a description of each failure
+fn check_expectations(
+    template: &Template,
+    expectations: &Expectations,
+    output_dir: &Path,
+    output_files: &[String],
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut failures = Vec::new();
+
+    for file in &expectations.files {
+        if !output_files.contains(file) {
+            failures.push(format!("expected file missing: {file}"));
+        }
+    }
+
+    if expectations.exact {
+        for file in output_files {
+            if !expectations.files.contains(file) {
+                failures.push(format!("unexpected file: {file}"));
+            }
+        }
+    }
+
+    for (file, needles) in &expectations.contains {
+        let Some(content) = read_text(template, output_dir, file) else {
+            failures.push(format!("cannot read {file} to check its contents"));
+            continue;
+        };
+        for needle in needles {
+            if !content.contains(needle.as_str()) {
+                failures.push(format!("{file} does not contain {needle:?}"));
+            }
+        }
+    }
+
+    Ok(failures)
+}
+
+/// Finds the `{{ ... }}` placeholders in the output files, with the file they're in
+fn scan_placeholders(template: &Template, output_dir: &Path, output_files: &[String]) -> Vec<(String, Placeholder)> {
+    let mut found = Vec::new();
+    for file in output_files {
+        // Binary files and text in an unknown encoding can't be searched for placeholders
+        let Some(content) = read_text(template, output_dir, file) else {
+            continue;
+        };
+        found.extend(find_placeholders(&content).into_iter().map(|placeholder| (file.clone(), placeholder)));
+    }
+    found
+}
+
+/// Reads an output file as text, decoding UTF-16 and the encoding the template declares for it
+fn read_text(template: &Template, output_dir: &Path, file: &str) -> Option<String> {
+    let content = fs::read(output_dir.join(file)).ok()?;
+    decode(&content, template.declared_encoding(file)).map(|(text, _)| text)
+}
+
+/// Compares the output against the stored snapshot, returning a description of each difference
+fn compare_snapshot(
+    snapshot_dir: &Path,
+    output_dir: &Path,
+    output_files: &[String],
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    if !snapshot_dir.is_dir() {
+        return Ok(vec![format!(
+            "no snapshot stored at {}, run with --update-snapshots to create one",
+            snapshot_dir.display()
+        )]);
+    }
+
+    let mut snapshot_files = Vec::new();
+    collect_files(snapshot_dir, snapshot_dir, &mut snapshot_files)?;
+
+    let mut differences = Vec::new();
+    for file in &snapshot_files {
+        if !output_files.contains(file) {
+            differences.push(format!("snapshot file no longer generated: {file}"));
+        }
+    }
+
+    for file in output_files {
+        if !snapshot_files.contains(file) {
+            differences.push(format!("new file not in snapshot: {file}"));
+            continue;
+        }
+
+        let expected = fs::read(snapshot_dir.join(file))?;
+        let actual = fs::read(output_dir.join(file))?;
+        if expected == actual {
+            continue;
+        }
+
+        match (String::from_utf8(expected), String::from_utf8(actual)) {
+            (Ok(expected), Ok(actual)) => {
+                let patch = diffy::create_patch(&expected, &actual);
+                differences.push(format!("{file} differs from snapshot:\n{patch}"));
+            }
+            _ => differences.push(format!("{file} differs from snapshot (binary)")),
+        }
+    }
+
+    Ok(differences)
+}
+
+/// Replaces the stored snapshot with the current output
+fn update_snapshot(
+    snapshot_dir: &Path,
+    output_dir: &Path,
+    output_files: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if snapshot_dir.exists() {
+        fs::remove_dir_all(snapshot_dir)?;
+    }
+
+    for file in output_files {
+        let destination = snapshot_dir.join(file);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(output_dir.join(file), destination)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,438 @@
+use crate::config::UserConfig;
+use crate::copy::{process_umask, write_with_mode};
+use crate::path::resolve_path;
+use crate::encoding::decode;
+use crate::eol::EolPolicy;
+use crate::error::TemplateError;
+use crate::lock::{hash_bytes, TemplateLock, TEMPLATE_LOCK_FILE};
+use crate::merge::{FileMerge, MergeOutcome, plan_file_merge};
+use crate::objects::{load_blob, store_blob};
+use crate::output::{is_plain, print_record};
+use crate::overrides::{apply_overrides, override_content};
+use crate::patch::{FileChange, format_git_patch};
+use crate::plugins::PluginFunctions;
+use crate::prompt::ask;
+use crate::receipt::{Receipt, ReceiptTarget};
+use crate::render::TemplateRenderer;
+use crate::template::Template;
+use crate::theme::Icon;
+use crate::timing::{Phase, span};
+use crate::variables::resolve_values;
+use crate::versions::VersionRecord;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+
+/// Suffix of the file holding the template version of a file that could not be merged
+const REJECT_SUFFIX: &str = ".rej";
+
+/// Planned change for a single template file
+struct PlannedFile {
+    path: String,
+    /// The template file it comes from, whose permissions written files get
+    source: PathBuf,
+    upstream: Vec<u8>,
+    merge: FileMerge,
+}
+
+/// What to do with a file both the template and the project changed in ways that overlap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resolution {
+    /// Take the template's version, dropping the local changes
+    Accept,
+    /// Keep the local version, leaving out the template's changes
+    Skip,
+    /// Write conflict markers (or a .rej file) to resolve by hand
+    Mark,
+}
+
+/// Updates a project with what changed in its template since the version recorded in
+/// .template.lock: files the project didn't touch are replaced, ones it changed are merged
+/// three-way, and overlapping changes become conflicts. With `interactive` each conflict is
+/// shown and the user accepts the template's version, skips it or marks it for resolving by hand.
+pub fn handle_upgrade(
+    path: Option<String>,
+    dry_run: bool,
+    reject_files: bool,
+    interactive: bool,
+    receipt_target: Option<ReceiptTarget>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if interactive && !dry_run && !io::stdin().is_terminal() {
+        return Err("--interactive asks about each conflict and needs a terminal".into());
+    }
+    eprintln!("Upgrading...");
+
+    // Resolve the target path - use current directory if none provided
+    let target_path = match path {
+        Some(p) => resolve_path(&p, None)?,
+        None => env::current_dir()?,
+    };
+    eprintln!("Target path: {}", target_path.display());
+
+    let lock = TemplateLock::load(&target_path)?.ok_or_else(|| {
+        format!(
+            "No {TEMPLATE_LOCK_FILE} found in {}. Use `{} init` to apply a template first.",
+            target_path.display(),
+            crate::BIN_NAME
+        )
+    })?;
+
+    let template_info = Template::find(&lock.template)?
+        .ok_or_else(|| TemplateError::NotFound(lock.template.clone()))?;
+    template_info.check_compatibility()?;
+    template_info.check_contents()?;
+
+    eprintln!(
+        "Template: {} ({} -> {})",
+        template_info.name,
+        lock.version.as_deref().unwrap_or("unknown"),
+        template_info.version().unwrap_or("unknown")
+    );
+
+    let user_config = UserConfig::load()?;
+    let excludes = user_config.excludes(lock.options.default_excludes);
+    let template_files = template_info.apply_files(&excludes)?;
+    let eol = EolPolicy::new(&template_info, &user_config);
+
+    // Variables the new version adds take their defaults; ones without a default must be recorded first
+    let declared = template_info.config.variables.clone().unwrap_or_default();
+    let mut plugins = if declared.is_empty() { PluginFunctions::default() } else { PluginFunctions::load(&template_info)? };
+    let values = resolve_values(&declared, &lock.variables, &mut |_| Ok(None), &mut |name, value| plugins.validate(name, value)).map_err(|e| {
+        format!("Template '{}' has {e}. Add the values to [variables] in {TEMPLATE_LOCK_FILE}", template_info.name)
+    })?;
+    let renderer = TemplateRenderer::new(&template_info, values.clone())?;
+
+    let planned = plan_upgrade(&template_info, &template_files, &lock, &target_path, &renderer, &eol)?;
+    let removed = lock.files.keys()
+        .filter(|f| !template_files.contains(f))
+        .map(|f| renderer.render_path(f))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    print_report(&planned, &removed);
+
+    if dry_run {
+        eprintln!("{} Dry run - no files were changed", Icon::DryRun);
+        return Ok(());
+    }
+
+    let mut resolutions = BTreeMap::new();
+    if interactive {
+        for file in planned.iter().filter(|file| file.merge.outcome == MergeOutcome::Conflict) {
+            resolutions.insert(file.path.clone(), ask_resolution(&target_path, file)?);
+        }
+    }
+
+    let mut receipt = Receipt::new("upgrade", &template_info.name, template_info.version());
+    receipt.previous_version.clone_from(&lock.version);
+    let mode_mask = user_config.mode_mask()?.unwrap_or_else(process_umask);
+    let mut conflicts = Vec::new();
+    for file in &planned {
+        let destination = target_path.join(&file.path);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        match (file.merge.outcome, &file.merge.content) {
+            (MergeOutcome::Conflict, _) if resolutions.get(&file.path) == Some(&Resolution::Accept) => {
+                write_with_mode(&destination, &file.upstream, &file.source, mode_mask)?;
+                receipt.add_file("replaced", &file.path);
+            }
+            (MergeOutcome::Conflict, _) if resolutions.get(&file.path) == Some(&Resolution::Skip) => {
+                receipt.add_file("kept", &file.path);
+            }
+            (MergeOutcome::Conflict, Some(marked)) if !reject_files => {
+                write_with_mode(&destination, marked, &file.source, mode_mask)?;
+                receipt.add_file("conflicted", &file.path);
+                conflicts.push(file.path.clone());
+            }
+            (MergeOutcome::Conflict, _) => {
+                // No usable merge: leave the local file alone and put the template version beside it
+                let reject_path = format!("{}{REJECT_SUFFIX}", file.path);
+                write_with_mode(&target_path.join(&reject_path), &file.upstream, &file.source, mode_mask)?;
+                receipt.add_file("conflicted", &file.path);
+                conflicts.push(reject_path);
+            }
+            (outcome, Some(content)) => {
+                write_with_mode(&destination, content, &file.source, mode_mask)?;
+                receipt.add_file(if outcome == MergeOutcome::LocallyModified { "merged" } else { "updated" }, &file.path);
+            }
+            (_, None) => {}
+        }
+    }
+
+    // Overrides of files the template doesn't have (or no longer has) are layered over as well
+    let conflicted: Vec<String> = planned.iter()
+        .filter(|file| file.merge.outcome == MergeOutcome::Conflict)
+        .filter(|file| resolutions.get(&file.path).is_none_or(|resolution| *resolution == Resolution::Mark))
+        .map(|file| file.path.clone())
+        .collect();
+    for path in apply_overrides(&target_path, &conflicted, false)? {
+        receipt.add_file("overridden", &path);
+    }
+
+    let mut new_lock = TemplateLock::new(&template_info.name, template_info.version());
+    new_lock.options = lock.options;
+    new_lock.variables = values;
+    let content_dir = template_info.content_dir()?;
+    for file in &template_files {
+        let content = fs::read(content_dir.join(file))?;
+        new_lock.files.insert(file.clone(), store_blob(&content)?);
+    }
+    new_lock.save(&target_path)?;
+    VersionRecord::from_lock(&new_lock).save()?;
+    let receipt_target = receipt_target.unwrap_or(user_config.receipts);
+    if let Some(path) = receipt.record(&target_path, receipt_target)? {
+        eprintln!("{} Recorded receipt in {}", Icon::Note, path.display());
+    }
+
+    if conflicts.is_empty() {
+        eprintln!("{} Upgrade complete!", Icon::Success);
+    } else {
+        eprintln!("{} Upgrade complete with {} conflict(s) to resolve by hand:", Icon::Warning, conflicts.len());
+        for path in &conflicts {
+            eprintln!("  {path}");
+        }
+        eprintln!("Conflict markers: 'ours' is your version, 'theirs' is the template's.");
+        eprintln!("{REJECT_SUFFIX} files hold the template's version of files that could not be merged.");
+    }
+    Ok(())
+}
+
+/// Classifies every template file against the project and the recorded base
+fn plan_upgrade(
+    template_info: &Template,
+    template_files: &[String],
+    lock: &TemplateLock,
+    target_path: &Path,
+    renderer: &TemplateRenderer,
+    eol: &EolPolicy,
+) -> Result<Vec<PlannedFile>, Box<dyn std::error::Error>> {
+    let _timing = span(Phase::Render);
+    let mut planned = Vec::new();
+    let content_dir = template_info.content_dir()?;
+
+    for file in template_files {
+        // Compare everything rendered and with the line endings the project was given
+        let project_form = |content: Vec<u8>| -> Result<Vec<u8>, String> {
+            let content = renderer.apply(file, &content)?.unwrap_or(content);
+            Ok(eol.apply(file, &content).unwrap_or(content))
+        };
+        let path = renderer.render_path(file)?;
+        let source = content_dir.join(file);
+        // The project's override stands in for the template's version
+        let upstream = match override_content(target_path, &path)? {
+            Some(content) => content,
+            None => project_form(fs::read(&source)?)?,
+        };
+        let local_path = target_path.join(&path);
+        let local = if local_path.is_file() { Some(fs::read(&local_path)?) } else { None };
+
+        let recorded_hash = lock.files.get(file).map(String::as_str);
+        let base = match recorded_hash {
+            Some(hash) => load_blob(hash)?.map(project_form).transpose()?,
+            None => None,
+        };
+        let converted_hash = base.as_deref().map(hash_bytes);
+        let base_hash = converted_hash.as_deref().or(recorded_hash);
+
+        let merge = if template_info.is_create_once(file) && local.is_some() {
+            FileMerge { outcome: MergeOutcome::AlreadyCurrent, content: None }
+        } else {
+            let declared = template_info.declared_encoding(file);
+            plan_transcoded_merge(base_hash, base.as_deref(), local.as_deref(), &upstream, declared)
+                .unwrap_or_else(|| plan_file_merge(base_hash, base.as_deref(), local.as_deref(), &upstream))
+        };
+        planned.push(PlannedFile { path, source, upstream, merge });
+    }
+
+    Ok(planned)
+}
+
+/// Shows a conflicting file as a diff from the local version to the template's and asks what to
+/// do with it. End of input marks it, like upgrading without --interactive.
+fn ask_resolution(target_path: &Path, file: &PlannedFile) -> Result<Resolution, Box<dyn std::error::Error>> {
+    let local_path = target_path.join(&file.path);
+    let local = if local_path.is_file() { Some(fs::read(&local_path)?) } else { None };
+    let change = FileChange { path: file.path.clone(), old: local, new: Some(file.upstream.clone()), executable: false };
+    eprintln!("\n{} Conflict in {}; the template's changes overlap yours:", Icon::Warning, file.path);
+    eprint!("{}", format_git_patch(&[change]));
+    loop {
+        let answer = ask("[a]ccept the template's version, [s]kip it and keep yours, or [m]ark the conflict", "m")?;
+        match answer.as_deref().map(str::to_lowercase).as_deref() {
+            Some("a" | "accept") => return Ok(Resolution::Accept),
+            Some("s" | "skip") => return Ok(Resolution::Skip),
+            Some("m" | "mark") | None => return Ok(Resolution::Mark),
+            Some(_) => eprintln!("  Answer a, s or m"),
+        }
+    }
+}
+
+/// Merges text that isn't plain UTF-8 (UTF-16, or a declared encoding like Latin-1) as UTF-8 and
+/// encodes the result back, so it merges like any other text instead of being treated as binary.
+/// None when the plain byte-wise merge applies or the sides can't all be decoded the same way.
+fn plan_transcoded_merge(
+    base_hash: Option<&str>,
+    base: Option<&[u8]>,
+    local: Option<&[u8]>,
+    upstream: &[u8],
+    declared: Option<&str>,
+) -> Option<FileMerge> {
+    let local = local?;
+    let (upstream_text, encoding) = decode(upstream, declared)?;
+    let unchanged_side = local == upstream
+        || base_hash.is_some_and(|hash| hash_bytes(local) == hash || hash_bytes(upstream) == hash);
+    if encoding.is_plain_utf8() || unchanged_side {
+        return None;
+    }
+
+    let (local_text, local_encoding) = decode(local, declared)?;
+    if local_encoding != encoding {
+        return None;
+    }
+    let base_text = match base {
+        Some(base) => Some(decode(base, declared)?.0),
+        None => None,
+    };
+    let base_text_hash = base_text.as_deref().map(|text| hash_bytes(text.as_bytes()));
+
+    let merge = plan_file_merge(
+        base_text_hash.as_deref().or(base_hash),
+        base_text.as_ref().map(String::as_bytes),
+        Some(local_text.as_bytes()),
+        upstream_text.as_bytes(),
+    );
+    let content = match merge.content {
+        Some(content) => Some(encoding.encode(str::from_utf8(&content).ok()?)?),
+        None => None,
+    };
+    Some(FileMerge { outcome: merge.outcome, content })
+}
+
+fn print_report(planned: &[PlannedFile], removed: &[String]) {
+    if is_plain() {
+        for file in planned {
+            print_record(&[file.merge.outcome.label(), &file.path]);
+        }
+        for file in removed {
+            print_record(&["removed-upstream", file]);
+        }
+        return;
+    }
+
+    println!("\nFiles:");
+    for file in planned {
+        println!("  {:<17} {}", file.merge.outcome.label(), file.path);
+    }
+    for file in removed {
+        println!("  {:<17} {file}", "removed-upstream");
+    }
+
+    let count = |outcome: MergeOutcome| planned.iter().filter(|p| p.merge.outcome == outcome).count();
+    eprintln!(
+        "\n{} clean-update, {} already-current, {} locally-modified, {} conflict",
+        count(MergeOutcome::CleanUpdate),
+        count(MergeOutcome::AlreadyCurrent),
+        count(MergeOutcome::LocallyModified),
+        count(MergeOutcome::Conflict)
+    );
+    if !removed.is_empty() {
+        eprintln!("{} file(s) no longer in the template are kept as-is", removed.len());
+    }
+    eprintln!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init::{ApplyOptions, handle_init};
+    use crate::path::TestStorage;
+    use crate::template::TEMPLATE_CONFIG_FILE;
+
+    const LINES: &str = "one\ntwo\nthree\nfour\nfive\nsix\nseven\n";
+
+    /// Applies version 1.0 of a template to a new project, then moves the template to 2.0 with
+    /// `upstream` as its new files; returns the project
+    fn applied_project(storage: &TestStorage, files: &[(&str, &str)], upstream: &[(&str, &str)]) -> PathBuf {
+        let template_dir = storage.path().join("templates/service");
+        fs::create_dir_all(&template_dir).unwrap();
+        fs::write(template_dir.join(TEMPLATE_CONFIG_FILE), "version = \"1.0\"\n").unwrap();
+        for (file, content) in files {
+            fs::write(template_dir.join(file), content).unwrap();
+        }
+        let project = storage.path().join("project");
+        let options = ApplyOptions { create_dir: true, yes: true, receipt: Some(ReceiptTarget::None), ..ApplyOptions::default() };
+        handle_init(Some("service"), Some(project.display().to_string()), &options).unwrap();
+
+        fs::write(template_dir.join(TEMPLATE_CONFIG_FILE), "version = \"2.0\"\n").unwrap();
+        for (file, content) in upstream {
+            fs::write(template_dir.join(file), content).unwrap();
+        }
+        project
+    }
+
+    fn upgrade(project: &Path, reject_files: bool) {
+        handle_upgrade(Some(project.display().to_string()), false, reject_files, false, Some(ReceiptTarget::None)).unwrap();
+    }
+
+    #[test]
+    fn test_upgrade_outcomes() {
+        let storage = TestStorage::new();
+        let both = LINES.replace("seven", "SEVEN");
+        let project = applied_project(
+            &storage,
+            &[("unchanged.txt", LINES), ("local.txt", LINES), ("upstream.txt", LINES), ("both.txt", LINES)],
+            &[("upstream.txt", &LINES.replace("two", "TWO")), ("local.txt", LINES), ("both.txt", &both)],
+        );
+        fs::write(project.join("local.txt"), LINES.replace("six", "6")).unwrap();
+        fs::write(project.join("both.txt"), LINES.replace("one", "1")).unwrap();
+
+        upgrade(&project, false);
+        let read = |file: &str| fs::read_to_string(project.join(file)).unwrap();
+        assert_eq!(read("unchanged.txt"), LINES);
+        assert_eq!(read("local.txt"), LINES.replace("six", "6"));
+        assert_eq!(read("upstream.txt"), LINES.replace("two", "TWO"));
+        assert_eq!(read("both.txt"), both.replace("one", "1"));
+        assert_eq!(TemplateLock::load(&project).unwrap().unwrap().version.as_deref(), Some("2.0"));
+    }
+
+    #[test]
+    fn test_upgrade_conflicts() {
+        let storage = TestStorage::new();
+        let project = applied_project(&storage, &[("notes.txt", LINES)], &[("notes.txt", &LINES.replace("four", "FOUR"))]);
+        let local = LINES.replace("four", "4");
+        fs::write(project.join("notes.txt"), &local).unwrap();
+
+        // A .rej file beside the untouched local version
+        upgrade(&project, true);
+        assert_eq!(fs::read_to_string(project.join("notes.txt")).unwrap(), local);
+        assert_eq!(fs::read_to_string(project.join("notes.txt.rej")).unwrap(), LINES.replace("four", "FOUR"));
+
+        // Conflict markers in the file itself
+        fs::remove_dir_all(&project).unwrap();
+        let project = applied_project(&storage, &[("notes.txt", LINES)], &[("notes.txt", &LINES.replace("four", "FOUR"))]);
+        fs::write(project.join("notes.txt"), &local).unwrap();
+        upgrade(&project, false);
+        let marked = fs::read_to_string(project.join("notes.txt")).unwrap();
+        assert!(marked.contains("<<<<<<< ours\n4\n"), "{marked}");
+        assert!(marked.contains("=======\nFOUR\n>>>>>>> theirs\n"), "{marked}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_upgrade_keeps_executable_bits() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let storage = TestStorage::new();
+        fs::write(storage.path().join("config.toml"), "mode_mask = \"022\"\n").unwrap();
+        let project = applied_project(&storage, &[("run.sh", "#!/bin/sh\n")], &[("run.sh", "#!/bin/sh\necho hi\n")]);
+        let script = storage.path().join("templates/service/run.sh");
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o777)).unwrap();
+
+        upgrade(&project, false);
+        let mode = fs::metadata(project.join("run.sh")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::config::UserConfig;
+use crate::encoding::is_known_encoding;
+use crate::error::TemplateError;
+use crate::file::format_size;
+use crate::output::{is_plain, print_record};
+use crate::template::{SizeReport, Template};
+use crate::theme::Icon;
+
+/// Number of largest entries to point at when a template is oversized
+const LARGEST_ENTRIES_SHOWN: usize = 3;
+
+pub fn handle_validate(template: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let templates = match template {
+        Some(name) => vec![Template::find(name)?
+            .ok_or_else(|| TemplateError::NotFound(name.to_string()))?],
+        None => Template::discover_all()?,
+    };
+
+    let threshold = UserConfig::load()?.size_warning_bytes();
+    let mut warnings = 0;
+
+    for template in &templates {
+        let report = template.size_report()?;
+        let oversized = warn_if_oversized(&template.name, &report, threshold);
+        let compatibility = template.check_compatibility();
+        if let Err(e) = &compatibility {
+            eprintln!("{} {e}", Icon::Warning);
+        }
+        let unknown_encodings = warn_unknown_encodings(template);
+        let broken_workspace = warn_workspace_problems(template);
+        let warned = oversized || compatibility.is_err() || unknown_encodings || broken_workspace;
+        if warned {
+            warnings += 1;
+        }
+
+        if is_plain() {
+            let status = if compatibility.is_err() {
+                "incompatible"
+            } else if broken_workspace {
+                "broken-workspace"
+            } else if unknown_encodings {
+                "unknown-encoding"
+            } else if oversized {
+                "oversized"
+            } else {
+                "ok"
+            };
+            print_record(&[status, &template.name, &report.total.to_string()]);
+        } else if !warned {
+            println!("{} {} ({})", Icon::Success, template.name, format_size(report.total));
+        }
+    }
+
+    eprintln!("\nValidated {} template(s), {warnings} with warnings", templates.len());
+    Ok(())
+}
+
+/// Prints a warning (to stderr) for each file whose declared encoding isn't recognized, returning
+/// whether there were any. Such files are copied verbatim instead of being transcoded.
+fn warn_unknown_encodings(template: &Template) -> bool {
+    let mut warned = false;
+    for (file, config) in template.config.files.iter().flatten() {
+        if let Some(label) = &config.encoding
+            && !is_known_encoding(label)
+        {
+            eprintln!("{} {}: unknown encoding '{label}' for {file}", Icon::Warning, template.name);
+            warned = true;
+        }
+    }
+    warned
+}
+
+/// Prints a warning (to stderr) for each problem in a workspace template's member list,
+/// returning whether there were any
+fn warn_workspace_problems(template: &Template) -> bool {
+    let Some(workspace) = &template.config.workspace else {
+        return false;
+    };
+    let problems = workspace.problems();
+    for problem in &problems {
+        eprintln!("{} {}: {problem}", Icon::Warning, template.name);
+    }
+    !problems.is_empty()
+}
+
+/// Prints a warning (to stderr) if a template exceeds the size threshold, naming its largest entries.
+/// Returns whether a warning was printed.
+pub fn warn_if_oversized(template_name: &str, report: &SizeReport, threshold: u64) -> bool {
+    if report.total <= threshold {
+        return false;
+    }
+
+    eprintln!(
+        "{} Template '{template_name}' is {} (warning threshold {})",
+        Icon::Warning,
+        format_size(report.total),
+        format_size(threshold)
+    );
+    eprintln!("    Largest entries:");
+    for (name, size) in report.entries.iter().take(LARGEST_ENTRIES_SHOWN) {
+        eprintln!("      {name} ({})", format_size(*size));
+    }
+    true
+}
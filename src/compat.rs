@@ -0,0 +1,167 @@
+use crate::history::format_date;
+use crate::path::{CACHE_STORAGE, TEMPLATE_STORAGE, storage_dirs};
+use crate::template::TemplateConfig;
+
+use serde::Serialize;
+
+use std::path::PathBuf;
+
+/// Version of this tool, compared against `min_tool_version`/`max_tool_version`
+pub const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Template capabilities this build understands, for `requires_features` in .template.toml
+pub fn tool_features() -> Vec<&'static str> {
+    let mut features = vec!["categories", "checks", "create-once", "encodings", "eol", "hooks", "managed-regions", "prerequisites", "render-engines", "snapshots", "variable-sources", "variable-types", "variables", "workspaces"];
+    if cfg!(feature = "wasm-plugins") {
+        features.push("plugins");
+    }
+    features
+}
+
+/// Cargo features this binary was built with
+pub fn build_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "git-library") {
+        features.push("git-library");
+    }
+    if cfg!(feature = "wasm-plugins") {
+        features.push("wasm-plugins");
+    }
+    features
+}
+
+/// What `--version --json` reports: the build and the storage in use, for support scripts and
+/// bug reports
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    /// Commit the binary was built from, None outside a git checkout
+    pub commit: Option<&'static str>,
+    /// UTC date of the build (YYYY-MM-DD)
+    pub build_date: String,
+    /// Operating system and architecture, as Rust names them
+    pub target: String,
+    /// Cargo features compiled in
+    pub build_features: Vec<&'static str>,
+    /// Template capabilities, for `requires_features`
+    pub template_features: Vec<&'static str>,
+    pub paths: StoragePaths,
+}
+
+/// Where this run keeps its data and config
+#[derive(Debug, Serialize)]
+pub struct StoragePaths {
+    pub data: PathBuf,
+    pub config: PathBuf,
+    pub templates: PathBuf,
+    pub cache: PathBuf,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        let commit = env!("TEMPLATE_RS_COMMIT");
+        let build_secs = env!("TEMPLATE_RS_BUILD_SECS").parse().unwrap_or(0);
+        let dirs = storage_dirs();
+        Self {
+            name: env!("CARGO_PKG_NAME"),
+            version: TOOL_VERSION,
+            commit: (!commit.is_empty()).then_some(commit),
+            build_date: format_date(build_secs),
+            target: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+            build_features: build_features(),
+            template_features: tool_features(),
+            paths: StoragePaths {
+                data: dirs.data.clone(),
+                config: dirs.config.clone(),
+                templates: dirs.data.join(TEMPLATE_STORAGE),
+                cache: dirs.data.join(CACHE_STORAGE),
+            },
+        }
+    }
+}
+
+/// Checks a template's version bounds and required features against this build.
+/// The error explains what is missing, so old binaries fail up front instead of producing broken output.
+pub fn check_compatibility(name: &str, config: &TemplateConfig) -> Result<(), String> {
+    let current = parse_version(TOOL_VERSION).expect("package version is valid");
+
+    if let Some(min) = &config.min_tool_version {
+        let required = parse_version(min)
+            .ok_or_else(|| format!("Template '{name}' has an invalid min_tool_version '{min}'"))?;
+        if current < required {
+            return Err(format!(
+                "Template '{name}' requires {} {min} or newer, this is {TOOL_VERSION}",
+                env!("CARGO_PKG_NAME")
+            ));
+        }
+    }
+
+    if let Some(max) = &config.max_tool_version {
+        let supported = parse_version(max)
+            .ok_or_else(|| format!("Template '{name}' has an invalid max_tool_version '{max}'"))?;
+        if current > supported {
+            return Err(format!(
+                "Template '{name}' supports {} up to {max}, this is {TOOL_VERSION}",
+                env!("CARGO_PKG_NAME")
+            ));
+        }
+    }
+
+    let available = tool_features();
+    let missing: Vec<&str> = config.requires_features.iter()
+        .flatten()
+        .map(String::as_str)
+        .filter(|feature| !available.contains(feature))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Template '{name}' needs features your version doesn't have: {} (this is {} {TOOL_VERSION})",
+            missing.join(", "),
+            env!("CARGO_PKG_NAME")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses "1.2.3" (missing parts count as 0, pre-release and build suffixes are ignored)
+pub fn parse_version(version: &str) -> Option<[u64; 3]> {
+    let core = version.trim().trim_start_matches('v').split(['-', '+']).next()?;
+    let mut parts = [0; 3];
+    for (index, part) in core.split('.').enumerate() {
+        *parts.get_mut(index)? = part.parse().ok()?;
+    }
+    Some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("1.2.3"), Some([1, 2, 3]));
+        assert_eq!(parse_version("v2.0"), Some([2, 0, 0]));
+        assert_eq!(parse_version("1.4.0-beta.1"), Some([1, 4, 0]));
+        assert_eq!(parse_version("1.2.3.4"), None);
+        assert_eq!(parse_version("latest"), None);
+    }
+
+    #[test]
+    fn test_check_compatibility() {
+        let config = |min: Option<&str>, max: Option<&str>, features: &[&str]| TemplateConfig {
+            min_tool_version: min.map(String::from),
+            max_tool_version: max.map(String::from),
+            requires_features: Some(features.iter().map(|f| (*f).to_string()).collect()),
+            ..TemplateConfig::default()
+        };
+
+        assert!(check_compatibility("t", &config(Some("0.1.0"), Some("999.0.0"), &["create-once"])).is_ok());
+        assert!(check_compatibility("t", &config(Some("999.0.0"), None, &[])).is_err());
+        assert!(check_compatibility("t", &config(None, Some("0.0.1"), &[])).is_err());
+
+        let error = check_compatibility("t", &config(None, None, &["time-travel"])).unwrap_err();
+        assert!(error.contains("time-travel"));
+    }
+}
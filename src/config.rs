@@ -0,0 +1,191 @@
+use crate::copy::{CopyEngineKind, Timestamps, parse_mode_mask};
+use crate::receipt::ReceiptTarget;
+use crate::eol::Eol;
+use crate::error::TemplateError;
+use crate::preset::Preset;
+use crate::file::ensure_config_dir;
+use crate::theme::ThemeConfig;
+use crate::timing::{Phase, span};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+pub const USER_CONFIG_FILE: &str = "config.toml";
+
+/// Version of the config format written by this build
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
+
+/// Paths that `--delete` never removes, in rsync pattern syntax
+pub const DEFAULT_PROTECTED: &[&str] = &[".git/", ".hg/", ".template.lock", ".template-overrides/", ".env"];
+
+/// Paths never copied from a template unless `--no-default-excludes` is given
+pub const DEFAULT_EXCLUDES: &[&str] = &[
+    ".git/",
+    ".hg/",
+    ".svn/",
+    ".DS_Store",
+    "Thumbs.db",
+    "target/",
+    "node_modules/",
+    "__pycache__/",
+    ".venv/",
+];
+
+/// User configuration from config.toml in the config directory (see `path::storage_dirs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserConfig {
+    /// Config format version (0 for files written before versioning)
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Extra paths (rsync patterns) that `--delete` never removes
+    #[serde(default)]
+    pub protected: Vec<String>,
+    /// Extra paths (rsync patterns) never copied from templates
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Whether the built-in exclusions apply (can still be turned off per run)
+    #[serde(default = "default_true")]
+    pub default_excludes: bool,
+    /// Warn when a template is larger than this many MiB
+    #[serde(default = "default_size_warning_mib")]
+    pub size_warning_mib: u64,
+    /// Template versions (`name@version`, or `name` for all versions) that `prune` never removes
+    #[serde(default)]
+    pub pins: Vec<String>,
+    /// Output colors and icons
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    /// Extra tag aliases, display name -> spellings that mean the same tag
+    #[serde(default)]
+    pub tags: BTreeMap<String, Vec<String>>,
+    /// Line endings for templates that don't set their own `eol`
+    #[serde(default)]
+    pub eol: Option<Eol>,
+    /// Directories laid out like the template storage that publish newer template versions
+    #[serde(default)]
+    pub registries: Vec<String>,
+    /// More directories to find templates in after the template storage, in order of
+    /// precedence (a team share, /usr/share/template-rs, ...); a template shadows same-named
+    /// ones in later roots
+    #[serde(default)]
+    pub roots: Vec<String>,
+    /// How `init` and `new` copy files: the built-in engine, or the `rsync` binary
+    #[serde(default)]
+    pub copy_engine: CopyEngineKind,
+    /// Permission bits (octal, like a umask) cleared on files `init` and `new` create; the
+    /// process umask when unset
+    #[serde(default)]
+    pub mode_mask: Option<String>,
+    /// Modification times of files `init` and `new` create: `now`, or `preserve` the template's
+    #[serde(default)]
+    pub timestamps: Timestamps,
+    /// Where `init`, `new` and `upgrade` record what they did in the project: `none`,
+    /// `changelog` (CHANGELOG.md) or `history` (.template-history)
+    #[serde(default)]
+    pub receipts: ReceiptTarget,
+    /// Values for template variables (author, organization, ...) used by every template that
+    /// declares them, unless the project or the command line gives another
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    /// Directory holding templates and the rest of storage instead of the platform data
+    /// directory, relative to the config directory; read by `path::storage_dirs`, and
+    /// overridden by $TEMPLATE_RS_HOME
+    #[serde(default)]
+    pub storage: Option<String>,
+    /// Named template, option and variable combinations used as `new @name path`
+    #[serde(default)]
+    pub presets: BTreeMap<String, Preset>,
+}
+
+impl Default for UserConfig {
+    fn default() -> Self {
+        Self {
+            schema_version: CONFIG_SCHEMA_VERSION,
+            protected: Vec::new(),
+            exclude: Vec::new(),
+            default_excludes: true,
+            size_warning_mib: default_size_warning_mib(),
+            pins: Vec::new(),
+            theme: ThemeConfig::default(),
+            tags: BTreeMap::new(),
+            eol: None,
+            registries: Vec::new(),
+            roots: Vec::new(),
+            copy_engine: CopyEngineKind::default(),
+            mode_mask: None,
+            timestamps: Timestamps::default(),
+            receipts: ReceiptTarget::default(),
+            variables: BTreeMap::new(),
+            storage: None,
+            presets: BTreeMap::new(),
+        }
+    }
+}
+
+/// Path of the user's config.toml, which may not exist yet
+pub fn user_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(ensure_config_dir()?.join(USER_CONFIG_FILE))
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+const fn default_size_warning_mib() -> u64 {
+    50
+}
+
+impl UserConfig {
+    /// Loads the user configuration, falling back to defaults when there is none
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let _timing = span(Phase::Config);
+        let config_path = user_config_path()?;
+        if !config_path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&config_path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| TemplateError::Config(format!("Failed to parse {}: {e}", config_path.display())))?;
+        if config.schema_version > CONFIG_SCHEMA_VERSION {
+            return Err(TemplateError::Config(format!(
+                "{} uses config schema version {}, but this build only understands up to {CONFIG_SCHEMA_VERSION}",
+                config_path.display(),
+                config.schema_version
+            )).into());
+        }
+        Ok(config)
+    }
+
+    /// The full protected set: built-in defaults followed by user patterns
+    pub fn protected_paths(&self) -> Vec<String> {
+        DEFAULT_PROTECTED.iter()
+            .map(|p| (*p).to_string())
+            .chain(self.protected.iter().cloned())
+            .collect()
+    }
+
+    /// The configured permission mask, if any
+    pub fn mode_mask(&self) -> Result<Option<u32>, Box<dyn std::error::Error>> {
+        self.mode_mask.as_deref()
+            .map(|mask| parse_mode_mask(mask).map_err(|e| format!("mode_mask in {USER_CONFIG_FILE}: {e}").into()))
+            .transpose()
+    }
+
+    /// The size in bytes above which templates are reported as oversized
+    pub const fn size_warning_bytes(&self) -> u64 {
+        self.size_warning_mib.saturating_mul(1024 * 1024)
+    }
+
+    /// The exclusion set: built-in defaults (unless disabled here or by the caller) plus user patterns
+    pub fn excludes(&self, use_defaults: bool) -> Vec<String> {
+        let defaults: &[&str] = if use_defaults && self.default_excludes { DEFAULT_EXCLUDES } else { &[] };
+        defaults.iter()
+            .map(|p| (*p).to_string())
+            .chain(self.exclude.iter().cloned())
+            .collect()
+    }
+}
@@ -0,0 +1,611 @@
+use crate::patterns::{matches_dir, matches_pattern};
+use crate::timing::{Phase, span};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Which implementation copies template files into a target (`copy_engine` in config.toml)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyEngineKind {
+    /// Built-in copy that needs no external tools
+    #[default]
+    Native,
+    /// The `rsync` binary on PATH
+    Rsync,
+}
+
+impl CopyEngineKind {
+    pub fn engine(self) -> Box<dyn CopyEngine> {
+        match self {
+            Self::Native => Box::new(NativeCopy),
+            Self::Rsync => Box::new(RsyncCopy),
+        }
+    }
+}
+
+/// A change a copy makes to the target (or would make, in a dry run). Paths are relative,
+/// with forward slashes, and directories end in '/'.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Copy(String),
+    Delete(String),
+}
+
+/// Owner and group given to copied files
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Ownership {
+    /// Whoever runs the command
+    #[default]
+    Invoker,
+    /// The owner and group of the template's files
+    Preserve,
+    /// `USER`, `USER:GROUP` or `:GROUP`, by name or numeric id
+    Set(String),
+}
+
+/// Modification times of the files `init` and `new` create (`timestamps` in config.toml)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Timestamps {
+    /// The time they are written, or SOURCE_DATE_EPOCH when that is set
+    #[default]
+    Now,
+    /// The modification times of the template's files
+    Preserve,
+}
+
+/// What to copy where, and how
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone)]
+pub struct CopyPlan<'a> {
+    /// Directory whose contents are copied
+    pub source: &'a Path,
+    /// Directory the contents are copied into
+    pub target: &'a Path,
+    /// Patterns of paths that are neither copied nor deleted
+    pub excludes: Vec<String>,
+    /// Patterns of target paths that `delete` keeps
+    pub protected: Vec<String>,
+    /// Replace files the target already has; otherwise they are left alone
+    pub overwrite: bool,
+    /// Remove target paths the source doesn't have
+    pub delete: bool,
+    /// Only report what would change
+    pub dry_run: bool,
+    pub ownership: Ownership,
+    /// Permission bits cleared on created files and directories, like a umask
+    pub mode_mask: u32,
+    /// Whether copied files keep the source's modification times
+    pub timestamps: Timestamps,
+}
+
+/// Callback seeing each change as soon as it is made
+pub type ChangeHandler<'a> = dyn FnMut(&Change) -> Result<(), Box<dyn std::error::Error>> + 'a;
+
+/// Copies a directory tree into a target, the way `init` and `new` apply a template
+pub trait CopyEngine {
+    /// Carries out the plan, passing every change to `on_change` as it happens, and returns them all.
+    /// Deletions come first, then copies in path order.
+    fn copy(&self, plan: &CopyPlan, on_change: &mut ChangeHandler) -> Result<Vec<Change>, Box<dyn std::error::Error>>;
+}
+
+/// Pure-Rust copy engine, so applying templates needs no external tools
+pub struct NativeCopy;
+
+impl CopyEngine for NativeCopy {
+    fn copy(&self, plan: &CopyPlan, on_change: &mut ChangeHandler) -> Result<Vec<Change>, Box<dyn std::error::Error>> {
+        let _timing = span(Phase::Io);
+        let owner = owner_ids(&plan.ownership)?;
+        let mut changes = Vec::new();
+        let mut report = |change: Change| -> Result<(), Box<dyn std::error::Error>> {
+            on_change(&change)?;
+            changes.push(change);
+            Ok(())
+        };
+
+        if plan.delete {
+            delete_extraneous(plan, "", &mut report)?;
+        }
+        copy_dir(plan, "", owner, &mut report)?;
+        Ok(changes)
+    }
+}
+
+/// Joins a name onto a relative path
+fn join_relative(dir: &str, name: &str) -> String {
+    if dir.is_empty() { name.to_string() } else { format!("{dir}/{name}") }
+}
+
+fn is_excluded(plan: &CopyPlan, path: &str, is_dir: bool) -> bool {
+    let matches = |pattern: &String| if is_dir { matches_dir(path, pattern) } else { matches_pattern(path, pattern) };
+    plan.excludes.iter().any(matches)
+}
+
+fn is_protected(plan: &CopyPlan, path: &str, is_dir: bool) -> bool {
+    let matches = |pattern: &String| if is_dir { matches_dir(path, pattern) } else { matches_pattern(path, pattern) };
+    plan.protected.iter().any(matches)
+}
+
+/// Directory entries sorted by name, so changes come in a stable order
+fn sorted_entries(dir: &Path) -> Result<Vec<fs::DirEntry>, Box<dyn std::error::Error>> {
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(fs::DirEntry::file_name);
+    Ok(entries)
+}
+
+/// Copies one source directory (relative path, "" for the root) into the target
+fn copy_dir(
+    plan: &CopyPlan,
+    dir: &str,
+    owner: Option<OwnerIds>,
+    report: &mut dyn FnMut(Change) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in sorted_entries(&plan.source.join(dir))? {
+        let path = join_relative(dir, &entry.file_name().to_string_lossy());
+        let file_type = entry.file_type()?;
+        if is_excluded(plan, &path, file_type.is_dir()) {
+            continue;
+        }
+
+        let destination = plan.target.join(&path);
+        let existing = destination.symlink_metadata().ok();
+        if file_type.is_dir() {
+            if !existing.as_ref().is_some_and(fs::Metadata::is_dir) {
+                if existing.is_some() {
+                    return Err(format!("{path}: the target has a file where the template has a directory").into());
+                }
+                if !plan.dry_run {
+                    fs::create_dir(&destination)?;
+                    set_mode(&destination, entry.metadata()?.permissions(), plan.mode_mask)?;
+                    set_owner(&destination, &entry, &plan.ownership, owner)?;
+                }
+                report(Change::Copy(format!("{path}/")))?;
+            }
+            copy_dir(plan, &path, owner, report)?;
+            continue;
+        }
+
+        match existing {
+            Some(_) if !plan.overwrite => continue,
+            Some(metadata) if metadata.is_dir() => {
+                return Err(format!("{path}: the target has a directory where the template has a file").into());
+            }
+            _ => {}
+        }
+        if !plan.dry_run {
+            if existing.as_ref().is_some_and(|m| m.file_type().is_symlink()) {
+                // Replace the link itself instead of writing through it
+                fs::remove_file(&destination)?;
+            }
+            copy_entry(&entry.path(), &destination, file_type.is_symlink())?;
+            if !file_type.is_symlink() {
+                let metadata = entry.metadata()?;
+                set_mode(&destination, metadata.permissions(), plan.mode_mask)?;
+                if plan.timestamps == Timestamps::Preserve {
+                    fs::File::options().write(true).open(&destination)?.set_modified(metadata.modified()?)?;
+                }
+            }
+            set_owner(&destination, &entry, &plan.ownership, owner)?;
+        }
+        report(Change::Copy(path))?;
+    }
+    Ok(())
+}
+
+/// Copies a file, or recreates a symlink as a symlink where the platform allows it
+fn copy_entry(source: &Path, destination: &Path, is_symlink: bool) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)]
+    if is_symlink {
+        std::os::unix::fs::symlink(fs::read_link(source)?, destination)?;
+        return Ok(());
+    }
+    #[cfg(not(unix))]
+    let _ = is_symlink;
+
+    // fs::copy carries the permission bits over
+    fs::copy(source, destination)?;
+    Ok(())
+}
+
+/// Writes content made from the template file `source` the way copying creates files: with the
+/// template file's permissions minus the masked bits, so scripts stay executable
+pub(crate) fn write_with_mode(destination: &Path, content: &[u8], source: &Path, mask: u32) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(destination, content)?;
+    set_mode(destination, fs::metadata(source)?.permissions(), mask)
+}
+
+/// Gives a created file or directory the template's permissions minus the masked bits
+fn set_mode(destination: &Path, permissions: fs::Permissions, mask: u32) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)]
+    let permissions = {
+        use std::os::unix::fs::PermissionsExt;
+        fs::Permissions::from_mode(permissions.mode() & 0o7777 & !mask)
+    };
+    #[cfg(not(unix))]
+    let _ = mask;
+
+    fs::set_permissions(destination, permissions)?;
+    Ok(())
+}
+
+/// Parses a permission mask written in octal like a umask: `027`, `0o027` or `0027`
+pub fn parse_mode_mask(text: &str) -> Result<u32, String> {
+    let digits = text.strip_prefix("0o").unwrap_or(text);
+    u32::from_str_radix(digits, 8).ok()
+        .filter(|mask| !digits.is_empty() && *mask <= 0o777)
+        .ok_or_else(|| format!("'{text}' is not a permission mask (octal, like 022 or 027)"))
+}
+
+/// The umask of this process, read from /proc where that exists. Elsewhere the common 022
+/// is assumed; pass `--mode-mask` or set `mode_mask` in config.toml when it differs.
+pub fn process_umask() -> u32 {
+    fs::read_to_string("/proc/self/status").ok()
+        .and_then(|status| {
+            status.lines()
+                .find_map(|line| line.strip_prefix("Umask:"))
+                .and_then(|mask| parse_mode_mask(mask.trim()).ok())
+        })
+        .unwrap_or(0o022)
+}
+
+/// Writes a mask as an rsync `--chmod` spec clearing the same bits: 027 is `g-w,o-rwx`
+fn chmod_spec(mask: u32) -> String {
+    [('u', 6), ('g', 3), ('o', 0)].iter()
+        .filter_map(|(class, shift)| {
+            let bits = (mask >> shift) & 0o7;
+            let letters: String = [('r', 4), ('w', 2), ('x', 1)].iter()
+                .filter(|(_, bit)| bits & bit != 0)
+                .map(|(letter, _)| *letter)
+                .collect();
+            (!letters.is_empty()).then(|| format!("{class}-{letters}"))
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Removes target entries below `dir` that the source doesn't have, unless excluded or
+/// protected. Returns whether everything below `dir` that wasn't in the source is gone.
+fn delete_extraneous(
+    plan: &CopyPlan,
+    dir: &str,
+    report: &mut dyn FnMut(Change) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let target_dir = plan.target.join(dir);
+    if !target_dir.is_dir() {
+        return Ok(true);
+    }
+
+    let mut emptied = true;
+    for entry in sorted_entries(&target_dir)? {
+        let path = join_relative(dir, &entry.file_name().to_string_lossy());
+        let is_dir = entry.file_type()?.is_dir();
+        if is_excluded(plan, &path, is_dir) || is_protected(plan, &path, is_dir) {
+            emptied = false;
+            continue;
+        }
+
+        let source = plan.source.join(&path).symlink_metadata().ok();
+        if source.as_ref().is_some_and(|s| s.is_dir() == is_dir) {
+            if is_dir {
+                delete_extraneous(plan, &path, report)?;
+            }
+            emptied = false;
+            continue;
+        }
+
+        if is_dir {
+            // Everything inside goes too, except what is protected
+            if !delete_extraneous(plan, &path, report)? {
+                emptied = false;
+                continue;
+            }
+            if !plan.dry_run {
+                fs::remove_dir(entry.path())?;
+            }
+            report(Change::Delete(format!("{path}/")))?;
+        } else {
+            if !plan.dry_run {
+                fs::remove_file(entry.path())?;
+            }
+            report(Change::Delete(path))?;
+        }
+    }
+    Ok(emptied)
+}
+
+/// Numeric user and group ids, None for the ones left as they are
+type OwnerIds = (Option<u32>, Option<u32>);
+
+/// Resolves `Ownership::Set` to numeric user and group ids before anything is copied
+fn owner_ids(ownership: &Ownership) -> Result<Option<OwnerIds>, Box<dyn std::error::Error>> {
+    let Ownership::Set(spec) = ownership else {
+        return Ok(None);
+    };
+    let (user, group) = match spec.split_once(':') {
+        Some((user, group)) => (Some(user).filter(|u| !u.is_empty()), Some(group)),
+        None => (Some(spec.as_str()), None),
+    };
+    let uid = user.map(|user| lookup_id(user, IdKind::User)).transpose()?;
+    let gid = group.map(|group| lookup_id(group, IdKind::Group)).transpose()?;
+    Ok(Some((uid, gid)))
+}
+
+/// Finds the numeric id of a user or group by name through the system's user database (NSS,
+/// so LDAP and other directory users resolve too). Numeric ids are taken as they are.
+#[cfg(unix)]
+fn lookup_id(name: &str, kind: IdKind) -> Result<u32, Box<dyn std::error::Error>> {
+    use nix::unistd::{Group, User};
+
+    if let Ok(id) = name.parse() {
+        return Ok(id);
+    }
+    let found = match kind {
+        IdKind::User => User::from_name(name).map(|user| user.map(|user| user.uid.as_raw())),
+        IdKind::Group => Group::from_name(name).map(|group| group.map(|group| group.gid.as_raw())),
+    };
+    found
+        .map_err(|e| format!("Cannot look up {} '{name}': {e}", kind.label()))?
+        .ok_or_else(|| format!("Unknown {} '{name}'; use a numeric id, or set copy_engine = \"rsync\"", kind.label()).into())
+}
+
+/// Takes numeric ids as they are; names can't be resolved on this platform
+#[cfg(not(unix))]
+fn lookup_id(name: &str, kind: IdKind) -> Result<u32, Box<dyn std::error::Error>> {
+    name.parse().map_err(|_| format!("Unknown {} '{name}'; use a numeric id", kind.label()).into())
+}
+
+/// Which user database `lookup_id` searches
+#[derive(Debug, Clone, Copy)]
+enum IdKind {
+    User,
+    Group,
+}
+
+impl IdKind {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Group => "group",
+        }
+    }
+}
+
+#[cfg(unix)]
+fn set_owner(
+    destination: &Path,
+    source: &fs::DirEntry,
+    ownership: &Ownership,
+    ids: Option<OwnerIds>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::MetadataExt;
+
+    let (uid, gid) = match (ownership, ids) {
+        (Ownership::Invoker, _) => return Ok(()),
+        (Ownership::Preserve, _) => {
+            let metadata = source.metadata()?;
+            (Some(metadata.uid()), Some(metadata.gid()))
+        }
+        (Ownership::Set(_), ids) => ids.unwrap_or_default(),
+    };
+    std::os::unix::fs::lchown(destination, uid, gid)
+        .map_err(|e| format!("Cannot change the owner of {}: {e}", destination.display()))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_owner(
+    _destination: &Path,
+    _source: &fs::DirEntry,
+    ownership: &Ownership,
+    _ids: Option<OwnerIds>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if *ownership == Ownership::Invoker {
+        Ok(())
+    } else {
+        Err("Setting file owners is not supported on this platform".into())
+    }
+}
+
+/// Copy engine running the `rsync` binary on PATH
+pub struct RsyncCopy;
+
+impl CopyEngine for RsyncCopy {
+    fn copy(&self, plan: &CopyPlan, on_change: &mut ChangeHandler) -> Result<Vec<Change>, Box<dyn std::error::Error>> {
+        let _timing = span(Phase::Io);
+        let mut cmd = Command::new("rsync");
+        // -r recursive, -l copy symlinks, -p preserve permissions; -t only when timestamps
+        // are preserved, otherwise files get the current time
+        cmd.arg("-rlp").arg("--itemize-changes");
+        if plan.timestamps == Timestamps::Preserve {
+            cmd.arg("--times");
+            if plan.overwrite {
+                // With matching times rsync would take a changed file for unchanged
+                cmd.arg("--ignore-times");
+            }
+        }
+        for pattern in &plan.excludes {
+            cmd.arg(format!("--exclude={pattern}"));
+        }
+        if plan.dry_run {
+            cmd.arg("--dry-run");
+        }
+        if !plan.overwrite {
+            cmd.arg("--ignore-existing");
+        }
+        if plan.mode_mask != 0 {
+            cmd.arg(format!("--chmod={}", chmod_spec(plan.mode_mask)));
+        }
+
+        match &plan.ownership {
+            Ownership::Invoker => {}
+            Ownership::Preserve => {
+                cmd.arg("--owner").arg("--group");
+            }
+            Ownership::Set(owner) => {
+                // rsync only applies --chown when it is asked to set owners and groups
+                cmd.arg(if owner.starts_with(':') { "--group" } else { "--owner" });
+                if owner.contains(':') {
+                    cmd.arg("--group");
+                }
+                cmd.arg(format!("--chown={owner}"));
+            }
+        }
+
+        if plan.delete {
+            cmd.arg("--delete");
+            for pattern in &plan.protected {
+                cmd.arg(format!("--filter=P {pattern}"));
+            }
+        }
+
+        // Add trailing slash to source for proper rsync behavior
+        cmd.arg(format!("{}/", plan.source.display()));
+        cmd.arg(plan.target);
+
+        let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()
+            .map_err(|e| format!("Cannot run rsync (copy_engine = \"rsync\" in config.toml): {e}"))?;
+
+        let mut changes = Vec::new();
+        if let Some(child_stdout) = child.stdout.take() {
+            for line in BufReader::new(child_stdout).lines() {
+                if let Some(change) = parse_itemized_line(&line?) {
+                    on_change(&change)?;
+                    changes.push(change);
+                }
+            }
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("rsync failed: {stderr}").into());
+        }
+        Ok(changes)
+    }
+}
+
+/// Parses one line of itemized rsync output, None for anything that isn't a change
+fn parse_itemized_line(line: &str) -> Option<Change> {
+    if let Some(path) = line.strip_prefix("*deleting") {
+        Some(Change::Delete(path.trim().to_string()))
+    } else if let Some((code, path)) = line.split_once(' ')
+        && code.len() == 11
+        && code.starts_with(['<', '>', 'c', 'h', '.'])
+    {
+        Some(Change::Copy(path.trim().to_string()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    #[test]
+    fn test_parse_itemized() {
+        let output = "sending incremental file list\n\
+            *deleting   old.txt\n\
+            cd+++++++++ src/\n\
+            >f+++++++++ src/main.rs\n\
+            \n\
+            sent 123 bytes  received 45 bytes  336.00 bytes/sec\n";
+
+        let changes: Vec<Change> = output.lines().filter_map(parse_itemized_line).collect();
+        assert_eq!(
+            changes,
+            vec![
+                Change::Delete("old.txt".to_string()),
+                Change::Copy("src/".to_string()),
+                Change::Copy("src/main.rs".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mode_mask() {
+        assert_eq!(parse_mode_mask("027"), Ok(0o027));
+        assert_eq!(parse_mode_mask("0o022"), Ok(0o022));
+        assert_eq!(parse_mode_mask("0000"), Ok(0));
+        assert!(parse_mode_mask("1000").is_err());
+        assert!(parse_mode_mask("8").is_err());
+        assert!(parse_mode_mask("").is_err());
+
+        assert_eq!(chmod_spec(0o027), "g-w,o-rwx");
+        assert_eq!(chmod_spec(0o700), "u-rwx");
+    }
+
+    #[test]
+    fn test_native_copy() {
+        let root = env::temp_dir().join(format!("template-rs-copy-{}", std::process::id()));
+        let (source, target) = (root.join("source"), root.join("target"));
+        for (path, content) in [("src/main.rs", "new"), ("README.md", "new"), ("skip.log", "new")] {
+            fs::create_dir_all(source.join(path).parent().unwrap()).unwrap();
+            fs::write(source.join(path), content).unwrap();
+        }
+        let template_time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+        fs::File::options().write(true).open(source.join("src/main.rs")).unwrap().set_modified(template_time).unwrap();
+        for (path, content) in [("README.md", "old"), ("old/a.txt", "old"), ("old/.env", "secret"), ("stale.txt", "old")] {
+            fs::create_dir_all(target.join(path).parent().unwrap()).unwrap();
+            fs::write(target.join(path), content).unwrap();
+        }
+
+        let mut plan = CopyPlan {
+            source: &source,
+            target: &target,
+            excludes: vec!["*.log".to_string()],
+            protected: vec![".env".to_string()],
+            overwrite: false,
+            delete: true,
+            dry_run: true,
+            ownership: Ownership::Invoker,
+            mode_mask: 0o027,
+            timestamps: Timestamps::Preserve,
+        };
+        let expected = vec![
+            Change::Delete("old/a.txt".to_string()),
+            Change::Delete("stale.txt".to_string()),
+            Change::Copy("src/".to_string()),
+            Change::Copy("src/main.rs".to_string()),
+        ];
+        assert_eq!(NativeCopy.copy(&plan, &mut |_| Ok(())).unwrap(), expected);
+        assert!(target.join("stale.txt").exists());
+
+        plan.dry_run = false;
+        assert_eq!(NativeCopy.copy(&plan, &mut |_| Ok(())).unwrap(), expected);
+        assert_eq!(fs::read_to_string(target.join("README.md")).unwrap(), "old");
+        assert_eq!(fs::read_to_string(target.join("src/main.rs")).unwrap(), "new");
+        assert!(target.join("old/.env").exists() && !target.join("stale.txt").exists());
+        assert!(!target.join("skip.log").exists());
+        assert_eq!(fs::metadata(target.join("src/main.rs")).unwrap().modified().unwrap(), template_time);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(target.join("src/main.rs")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o027, 0);
+        }
+
+        plan.overwrite = true;
+        let changes = NativeCopy.copy(&plan, &mut |_| Ok(())).unwrap();
+        assert_eq!(changes, vec![Change::Copy("README.md".to_string()), Change::Copy("src/main.rs".to_string())]);
+        assert_eq!(fs::read_to_string(target.join("README.md")).unwrap(), "new");
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lookup_id() {
+        assert_eq!(lookup_id("root", IdKind::User).unwrap(), 0);
+        assert_eq!(lookup_id("1234", IdKind::Group).unwrap(), 1234);
+        assert!(lookup_id("no-such-user-template-rs", IdKind::User).is_err());
+    }
+}
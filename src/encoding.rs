@@ -0,0 +1,96 @@
+use crate::merge::is_binary;
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+/// How a text file was encoded, so transformed text can be written back the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextEncoding {
+    encoding: &'static Encoding,
+    bom: bool,
+}
+
+impl TextEncoding {
+    /// Check if the file is plain UTF-8 without a byte order mark
+    pub fn is_plain_utf8(self) -> bool {
+        self.encoding == UTF_8 && !self.bom
+    }
+
+    /// Encodes text back into this encoding, None if it has characters the encoding can't represent
+    pub fn encode(self, text: &str) -> Option<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(text.len());
+        if self.bom {
+            bytes.extend_from_slice(bom(self.encoding));
+        }
+
+        // encoding_rs follows the WHATWG standard, which never produces UTF-16
+        if self.encoding == UTF_16LE {
+            bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        } else if self.encoding == UTF_16BE {
+            bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+        } else {
+            let (encoded, _, unmappable) = self.encoding.encode(text);
+            if unmappable {
+                return None;
+            }
+            bytes.extend_from_slice(&encoded);
+        }
+        Some(bytes)
+    }
+}
+
+/// Decodes a text file to UTF-8 for processing. A byte order mark wins, then the encoding the
+/// template declares for the file (e.g. "latin1", "windows-1252", "utf-16le"), then UTF-8.
+/// Returns None for binary files and text that isn't valid in that encoding: those are copied verbatim.
+pub fn decode(content: &[u8], declared: Option<&str>) -> Option<(String, TextEncoding)> {
+    let (encoding, bom_len) = match Encoding::for_bom(content) {
+        Some((encoding, bom_len)) => (encoding, bom_len),
+        None => match declared {
+            Some(label) => (Encoding::for_label(label.as_bytes())?, 0),
+            None if is_binary(content) => return None,
+            None => (UTF_8, 0),
+        },
+    };
+
+    let text = encoding.decode_without_bom_handling_and_without_replacement(&content[bom_len..])?;
+    Some((text.into_owned(), TextEncoding { encoding, bom: bom_len > 0 }))
+}
+
+/// Check if an encoding label (as used in `encoding = "..."`) is one this build can decode
+pub fn is_known_encoding(label: &str) -> bool {
+    Encoding::for_label(label.as_bytes()).is_some()
+}
+
+fn bom(encoding: &'static Encoding) -> &'static [u8] {
+    if encoding == UTF_16LE {
+        b"\xFF\xFE"
+    } else if encoding == UTF_16BE {
+        b"\xFE\xFF"
+    } else {
+        b"\xEF\xBB\xBF"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf16_round_trip() {
+        let content = b"\xFF\xFEh\0i\0\n\0";
+        let (text, encoding) = decode(content, None).unwrap();
+        assert_eq!(text, "hi\n");
+        assert!(!encoding.is_plain_utf8());
+        assert_eq!(encoding.encode(&text).as_deref(), Some(&content[..]));
+    }
+
+    #[test]
+    fn test_declared_latin1() {
+        let content = b"caf\xE9\n";
+        assert!(decode(content, None).is_none());
+
+        let (text, encoding) = decode(content, Some("latin1")).unwrap();
+        assert_eq!(text, "caf\u{e9}\n");
+        assert_eq!(encoding.encode(&text.to_uppercase()).as_deref(), Some(&b"CAF\xC9\n"[..]));
+        assert_eq!(encoding.encode("\u{1F600}"), None);
+    }
+}
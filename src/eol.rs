@@ -0,0 +1,155 @@
+use crate::config::UserConfig;
+use crate::encoding::decode;
+use crate::patterns::matches_pattern;
+use crate::template::Template;
+use crate::timing::{Phase, span};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Bytes checked for a NUL to tell binary files apart, like git does
+const BINARY_SNIFF_LEN: usize = 8000;
+
+/// Line endings written into projects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Eol {
+    Lf,
+    Crlf,
+    /// CRLF on Windows, LF elsewhere
+    Native,
+    /// Copy files exactly as the template has them
+    #[default]
+    Keep,
+}
+
+/// A per-glob line ending from `eol_overrides` in .template.toml, like a .gitattributes line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EolOverride {
+    pub pattern: String,
+    pub eol: Eol,
+}
+
+/// Line ending rules for one template application. The template's `eol_overrides` (last match
+/// wins) come first, then the template's `eol`, then the user's `eol`, and otherwise files are kept as-is.
+pub struct EolPolicy {
+    overrides: Vec<EolOverride>,
+    default: Eol,
+    /// Declared encodings of files that aren't UTF-8
+    encodings: BTreeMap<String, String>,
+}
+
+impl EolPolicy {
+    pub fn new(template: &Template, user_config: &UserConfig) -> Self {
+        Self {
+            overrides: template.config.eol_overrides.clone().unwrap_or_default(),
+            default: template.config.eol.or(user_config.eol).unwrap_or_default(),
+            encodings: template.config.files.iter()
+                .flatten()
+                .filter_map(|(file, config)| Some((file.clone(), config.encoding.clone()?)))
+                .collect(),
+        }
+    }
+
+    /// The line ending that applies to a file (relative path, forward slashes)
+    pub fn for_file(&self, file: &str) -> Eol {
+        self.overrides.iter()
+            .rev()
+            .find(|o| matches_pattern(file, &o.pattern))
+            .map_or(self.default, |o| o.eol)
+    }
+
+    /// Converts a file's content for the target, None when it stays unchanged.
+    /// UTF-16 and other declared encodings are converted as text and written back in their
+    /// encoding; other text is converted byte-wise, which is safe for ASCII-compatible encodings.
+    pub fn apply(&self, file: &str, content: &[u8]) -> Option<Vec<u8>> {
+        let eol = self.for_file(file);
+        let declared = self.encodings.get(file).map(String::as_str);
+        if let Some((text, encoding)) = decode(content, declared)
+            && !encoding.is_plain_utf8()
+        {
+            let converted = String::from_utf8(convert(text.as_bytes(), eol)?).ok()?;
+            return encoding.encode(&converted).filter(|bytes| bytes != content);
+        }
+        convert(content, eol)
+    }
+
+    /// Rewrites files in the target that need different line endings
+    pub fn apply_to_files(&self, target_path: &Path, files: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        let _timing = span(Phase::Render);
+        if self.default == Eol::Keep && self.overrides.is_empty() {
+            return Ok(());
+        }
+        for file in files {
+            let path = target_path.join(file);
+            if let Some(converted) = self.apply(file, &fs::read(&path)?) {
+                fs::write(&path, converted)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Normalizes every line ending in text content to `eol`. Binary content (a NUL byte near the
+/// start) and `Keep` are left alone. Returns None when nothing changes.
+pub fn convert(content: &[u8], eol: Eol) -> Option<Vec<u8>> {
+    let crlf = match eol {
+        Eol::Keep => return None,
+        Eol::Lf => false,
+        Eol::Crlf => true,
+        Eol::Native => cfg!(windows),
+    };
+    if content[..content.len().min(BINARY_SNIFF_LEN)].contains(&0) {
+        return None;
+    }
+
+    let mut converted = Vec::with_capacity(content.len());
+    for (index, &byte) in content.iter().enumerate() {
+        match byte {
+            b'\r' if content.get(index + 1) == Some(&b'\n') => {}
+            b'\n' if crlf => converted.extend_from_slice(b"\r\n"),
+            _ => converted.push(byte),
+        }
+    }
+
+    (converted != content).then_some(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(convert(b"a\r\nb\n", Eol::Lf).as_deref(), Some(&b"a\nb\n"[..]));
+        assert_eq!(convert(b"a\r\nb\n", Eol::Crlf).as_deref(), Some(&b"a\r\nb\r\n"[..]));
+        assert_eq!(convert(b"a\nb\n", Eol::Lf), None);
+        assert_eq!(convert(b"a\nb\n", Eol::Keep), None);
+        assert_eq!(convert(b"\0a\nb", Eol::Crlf), None);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let policy = EolPolicy {
+            overrides: vec![
+                EolOverride { pattern: "*.bat".to_string(), eol: Eol::Crlf },
+                EolOverride { pattern: "/scripts/".to_string(), eol: Eol::Lf },
+            ],
+            default: Eol::Keep,
+            encodings: BTreeMap::new(),
+        };
+        assert_eq!(policy.for_file("run.bat"), Eol::Crlf);
+        assert_eq!(policy.for_file("scripts/run.bat"), Eol::Lf);
+        assert_eq!(policy.for_file("README.md"), Eol::Keep);
+    }
+
+    #[test]
+    fn test_utf16_line_endings() {
+        let policy = EolPolicy { overrides: Vec::new(), default: Eol::Crlf, encodings: BTreeMap::new() };
+        let converted = policy.apply("notes.txt", b"\xFF\xFEa\0\n\0").unwrap();
+        assert_eq!(converted, b"\xFF\xFEa\0\r\0\n\0");
+    }
+}
@@ -0,0 +1,57 @@
+use crate::BIN_NAME;
+
+use thiserror::Error;
+
+use std::io;
+
+/// Failures that scripts may want to tell apart; the binary exits with a status of its own for each:
+///
+/// | Status | Error |
+/// |--------|-------|
+/// | 1 | anything else |
+/// | 2 | invalid command-line arguments |
+/// | 3 | [`TemplateError::NotFound`] |
+/// | 4 | [`TemplateError::Config`] |
+/// | 5 | [`TemplateError::Io`] |
+/// | 6 | [`TemplateError::Copy`] |
+/// | 7 | [`TemplateError::Aborted`] |
+/// | 8 | [`TemplateError::Prerequisites`] |
+/// | 9 | [`TemplateError::CheckFailed`] |
+///
+/// Functions still return `Box<dyn Error>`; these are boxed like any other error and picked back
+/// out by downcasting.
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    /// Reading or writing a file failed
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// A .template.toml or config.toml that can't be parsed or isn't supported
+    #[error("{0}")]
+    Config(String),
+    /// No template has the given name
+    #[error("Template '{0}' not found. Use '{BIN_NAME} list' to see available templates.")]
+    NotFound(String),
+    /// Copying the template's files into the target failed part way
+    #[error("Copying the template failed: {0}")]
+    Copy(String),
+    /// A confirmation was declined, nothing was changed
+    #[error("{0}")]
+    Aborted(String),
+    /// The template's prerequisites aren't met
+    #[error("{0}")]
+    Prerequisites(String),
+    /// The generated project failed its language's sanity check
+    #[error("{0}")]
+    CheckFailed(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_not_found_message() {
+        let error = TemplateError::NotFound("rust/cli".to_string());
+        assert_eq!(error.to_string(), format!("Template 'rust/cli' not found. Use '{BIN_NAME} list' to see available templates."));
+    }
+}
@@ -0,0 +1,72 @@
+use serde::Serialize;
+
+use std::io::{self, Write};
+
+/// Progress event emitted by `init`/`new --json-events`, one JSON object per line on stdout
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event<'a> {
+    /// The template was found and the copy is being planned
+    Planning {
+        template: &'a str,
+        target: &'a str,
+        dry_run: bool,
+    },
+    /// A file was copied (or would be, in a dry run)
+    FileCopied { path: &'a str },
+    /// A file was deleted (or would be, in a dry run)
+    FileDeleted { path: &'a str },
+    /// A template file was not copied because the target already has one
+    Conflict {
+        path: &'a str,
+        resolution: &'a str,
+    },
+    /// A `{{ ... }}` placeholder was left in an applied file because its variable has no value
+    UnrenderedPlaceholder {
+        path: &'a str,
+        line: usize,
+        placeholder: &'a str,
+    },
+    /// A hook command from the template's [hooks] table is starting
+    HookStarted {
+        stage: &'a str,
+        command: &'a str,
+    },
+    /// The sanity check of the generated project finished
+    CheckFinished {
+        command: &'a str,
+        passed: bool,
+    },
+    /// The run finished successfully
+    Done {
+        copied: usize,
+        deleted: usize,
+        conflicts: usize,
+        dry_run: bool,
+    },
+    /// The run failed
+    Error { message: &'a str },
+}
+
+/// Writes an event as a single line and flushes so consumers see it immediately
+pub fn emit(event: &Event) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer(&mut stdout, event)?;
+    writeln!(stdout)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_shape() {
+        let json = serde_json::to_string(&Event::FileCopied { path: "src/main.rs" }).unwrap();
+        assert_eq!(json, r#"{"event":"file-copied","path":"src/main.rs"}"#);
+
+        let json = serde_json::to_string(&Event::Done { copied: 2, deleted: 0, conflicts: 1, dry_run: false }).unwrap();
+        assert_eq!(json, r#"{"event":"done","copied":2,"deleted":0,"conflicts":1,"dry_run":false}"#);
+    }
+}
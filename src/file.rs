@@ -1,4 +1,6 @@
-use crate::path::{PERSISTENT_STORAGE, TEMPLATE_STORAGE, resolve_path};
+use crate::objects::is_executable;
+use crate::path::{BACKUP_STORAGE, CACHE_STORAGE, OBJECT_STORAGE, TEMPLATE_STORAGE, VERSION_STORAGE, storage_dirs};
+use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -13,21 +15,120 @@ pub fn create_dir_if_missing<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std:
 
 /// Ensures the persistent storage directory exists, returns the resolved path
 pub fn ensure_persistent_storage_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let storage_dir = resolve_path(PERSISTENT_STORAGE, None)?;
+    let storage_dir = storage_dirs().data.clone();
     create_dir_if_missing(&storage_dir)?;
     Ok(storage_dir)
 }
 
+/// Ensures the directory holding config.toml exists, returns the resolved path
+pub fn ensure_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let config_dir = storage_dirs().config.clone();
+    create_dir_if_missing(&config_dir)?;
+    Ok(config_dir)
+}
+
 /// Ensures the template storage directory exists, returns the resolved path
 pub fn ensure_template_storage_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let template_dir = resolve_path(TEMPLATE_STORAGE, None)?;
+    let template_dir = storage_dirs().data.join(TEMPLATE_STORAGE);
     create_dir_if_missing(&template_dir)?;
     Ok(template_dir)
 }
 
-/// Ensures both storage directories exist
+/// Ensures the object storage directory exists, returns the resolved path
+pub fn ensure_object_storage_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let object_dir = storage_dirs().data.join(OBJECT_STORAGE);
+    create_dir_if_missing(&object_dir)?;
+    Ok(object_dir)
+}
+
+/// Ensures the cache directory exists, returns the resolved path
+pub fn ensure_cache_storage_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let cache_dir = storage_dirs().data.join(CACHE_STORAGE);
+    create_dir_if_missing(&cache_dir)?;
+    Ok(cache_dir)
+}
+
+/// Ensures the version record directory exists, returns the resolved path
+pub fn ensure_version_storage_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let version_dir = storage_dirs().data.join(VERSION_STORAGE);
+    create_dir_if_missing(&version_dir)?;
+    Ok(version_dir)
+}
+
+/// Ensures the directory for automatic backups (taken before migrations) exists, returns the resolved path
+pub fn ensure_backup_storage_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let backup_dir = storage_dirs().data.join(BACKUP_STORAGE);
+    create_dir_if_missing(&backup_dir)?;
+    Ok(backup_dir)
+}
+
+/// Ensures all storage directories exist
 pub fn ensure_all_storage_dirs() -> Result<(), Box<dyn std::error::Error>> {
     ensure_persistent_storage_dir()?;
+    ensure_config_dir()?;
     ensure_template_storage_dir()?;
+    ensure_object_storage_dir()?;
+    Ok(())
+}
+
+/// Removes directories left empty below (but not including) a directory
+pub fn remove_empty_dirs<P: AsRef<Path>>(dir: P) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            let path = entry.path();
+            remove_empty_dirs(&path)?;
+            if fs::read_dir(&path)?.next().is_none() {
+                fs::remove_dir(&path)?;
+            }
+        }
+    }
     Ok(())
 }
+
+/// Recursively sums the size of regular files below a directory
+pub fn dir_size<P: AsRef<Path>>(path: P) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            total += dir_size(entry.path())?;
+        } else if file_type.is_file() {
+            total += entry.metadata()?.len();
+        }
+    }
+    Ok(total)
+}
+
+/// Formats a byte count for humans (e.g. "1.5 MiB")
+#[allow(clippy::cast_precision_loss)]
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Looks for an executable in the directories on PATH (adding .exe on Windows)
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+    let file_name = format!("{name}{}", env::consts::EXE_SUFFIX);
+    let path = env::var_os("PATH")?;
+
+    env::split_paths(&path)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| {
+            // Only unix has an executable bit to check; elsewhere the .exe suffix has to do
+            candidate.is_file() && (cfg!(not(unix)) || is_executable(candidate).unwrap_or(false))
+        })
+}
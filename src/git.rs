@@ -0,0 +1,417 @@
+use thiserror::Error;
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Output, Stdio};
+
+/// Why a git operation failed
+#[derive(Debug, Error)]
+pub enum GitError {
+    /// The git command is needed but not installed
+    #[error("git is not installed")]
+    NotInstalled,
+    /// Running the git command failed
+    #[error("Cannot run git: {0}")]
+    Io(#[from] io::Error),
+    /// The repository has no branch, tag or commit by that name
+    #[error("The repository has no branch, tag or commit '{0}'")]
+    UnknownReference(String),
+    /// libgit2 failed; `operation` is what it was doing
+    #[cfg(feature = "git-library")]
+    #[error("libgit2 could not {operation}: {}", .source.message())]
+    Library { operation: String, source: git2::Error },
+    /// The git command exited with a failure; `stderr` is what it printed
+    #[error("`git {command}` failed ({status}): {stderr}")]
+    Command { command: String, status: ExitStatus, stderr: String },
+}
+
+/// Lists uncommitted changes (`git status --porcelain` lines) of the git worktree containing `path`.
+/// Returns None when the path is not inside a git repository or git can't be used.
+pub fn uncommitted_changes(path: &Path) -> Option<Vec<String>> {
+    with_fallback(|git| git.uncommitted_changes(path)).ok().flatten()
+}
+
+/// Clones a repository into `destination`, checked out at `reference` (a branch, tag or commit)
+/// when one is given. References a clone doesn't bring along, such as `refs/pull/1/head`, are
+/// fetched. Built with the `git-library` feature this goes through libgit2, so git needn't be
+/// installed; the git command is used otherwise, and for URLs libgit2 can't fetch.
+pub fn clone(url: &str, reference: Option<&str>, destination: &Path) -> Result<(), GitError> {
+    with_fallback(|git| clone_with(git, url, reference, destination))
+}
+
+/// Describes the checked out commit by the nearest tag (`v1.2.0-3-g1a2b3c4`), or by its
+/// abbreviated id without tags. None for a repository without commits.
+pub fn describe(repository: &Path) -> Result<Option<String>, GitError> {
+    with_fallback(|git| git.describe(repository))
+}
+
+/// Clones with one backend, removing what a failed attempt leaves behind
+fn clone_with(git: &dyn GitBackend, url: &str, reference: Option<&str>, destination: &Path) -> Result<(), GitError> {
+    let result = git.clone_repository(url, reference.is_none(), destination).and_then(|()| match reference {
+        Some(reference) => checkout_or_fetch(git, destination, reference),
+        None => Ok(()),
+    });
+    if result.is_err() {
+        let _ = fs::remove_dir_all(destination);
+    }
+    result
+}
+
+/// Checks out `reference`, fetching it first when the repository doesn't have it
+fn checkout_or_fetch(git: &dyn GitBackend, repository: &Path, reference: &str) -> Result<(), GitError> {
+    match git.checkout(repository, reference) {
+        // The clone just came from origin, so a fetch failing means origin doesn't have it either
+        Err(GitError::UnknownReference(_)) => {
+            let commit = git.fetch(repository, reference).map_err(|_| GitError::UnknownReference(reference.to_string()))?;
+            git.checkout(repository, &commit)
+        }
+        result => result,
+    }
+}
+
+/// Runs an operation with libgit2 when the build has it, then with the git command if libgit2
+/// failed for a reason other than a missing reference and git is installed
+fn with_fallback<T>(mut operation: impl FnMut(&dyn GitBackend) -> Result<T, GitError>) -> Result<T, GitError> {
+    #[cfg(feature = "git-library")]
+    match operation(&Library) {
+        Ok(value) => return Ok(value),
+        Err(e @ GitError::UnknownReference(_)) => return Err(e),
+        Err(e) if crate::file::find_in_path("git").is_none() => return Err(e),
+        Err(e) => eprintln!("{} {e}, trying the git command", crate::theme::Icon::Warning),
+    }
+    operation(&Cli)
+}
+
+/// One way of running git operations: libgit2 in-process, or the git command
+trait GitBackend {
+    /// Clones `url` into `destination`; `shallow` allows leaving out the history
+    fn clone_repository(&self, url: &str, shallow: bool, destination: &Path) -> Result<(), GitError>;
+
+    /// Checks out `reference` as a detached HEAD, `UnknownReference` when the repository has no
+    /// such branch (remote-tracking ones included), tag or commit
+    fn checkout(&self, repository: &Path, reference: &str) -> Result<(), GitError>;
+
+    /// Fetches `reference` from `origin`, returning the commit it points to
+    fn fetch(&self, repository: &Path, reference: &str) -> Result<String, GitError>;
+
+    /// See `describe`
+    fn describe(&self, repository: &Path) -> Result<Option<String>, GitError>;
+
+    /// See `uncommitted_changes`
+    fn uncommitted_changes(&self, path: &Path) -> Result<Option<Vec<String>>, GitError>;
+}
+
+/// Git operations through libgit2
+#[cfg(feature = "git-library")]
+struct Library;
+
+#[cfg(feature = "git-library")]
+impl Library {
+    fn error(operation: impl Into<String>) -> impl FnOnce(git2::Error) -> GitError {
+        let operation = operation.into();
+        move |source| GitError::Library { operation, source }
+    }
+
+    fn open(repository: &Path) -> Result<git2::Repository, GitError> {
+        git2::Repository::open(repository).map_err(Self::error(format!("open {}", repository.display())))
+    }
+}
+
+#[cfg(feature = "git-library")]
+impl GitBackend for Library {
+    fn clone_repository(&self, url: &str, _shallow: bool, destination: &Path) -> Result<(), GitError> {
+        // Always the whole history: libgit2 can't fetch shallow over every transport (local paths)
+        git2::Repository::clone(url, destination).map_err(Self::error(format!("clone {url}")))?;
+        Ok(())
+    }
+
+    fn checkout(&self, repository: &Path, reference: &str) -> Result<(), GitError> {
+        let repository = Self::open(repository)?;
+        // Branches other than the default one only exist as remote-tracking branches after a clone
+        let object = repository.revparse_single(reference)
+            .or_else(|_| repository.revparse_single(&format!("origin/{reference}")))
+            .map_err(|e| match e.code() {
+                git2::ErrorCode::NotFound | git2::ErrorCode::InvalidSpec | git2::ErrorCode::Ambiguous => {
+                    GitError::UnknownReference(reference.to_string())
+                }
+                _ => Self::error(format!("resolve {reference}"))(e),
+            })?;
+        let commit = object.peel_to_commit().map_err(|_| GitError::UnknownReference(reference.to_string()))?;
+        let operation = format!("check out {reference}");
+        repository.checkout_tree(commit.as_object(), None).map_err(Self::error(operation.clone()))?;
+        repository.set_head_detached(commit.id()).map_err(Self::error(operation))
+    }
+
+    fn fetch(&self, repository: &Path, reference: &str) -> Result<String, GitError> {
+        let repository = Self::open(repository)?;
+        let operation = format!("fetch {reference}");
+        let mut origin = repository.find_remote("origin").map_err(Self::error(operation.clone()))?;
+        origin.fetch(&[reference], None, None).map_err(|e| match e.code() {
+            git2::ErrorCode::NotFound => GitError::UnknownReference(reference.to_string()),
+            _ => Self::error(operation.clone())(e),
+        })?;
+        let fetched = repository.find_reference("FETCH_HEAD")
+            .and_then(|fetch_head| fetch_head.peel_to_commit())
+            .map_err(Self::error(operation))?;
+        Ok(fetched.id().to_string())
+    }
+
+    fn describe(&self, repository: &Path) -> Result<Option<String>, GitError> {
+        let repository = Self::open(repository)?;
+        if repository.head().is_err_and(|e| matches!(e.code(), git2::ErrorCode::UnbornBranch | git2::ErrorCode::NotFound)) {
+            return Ok(None);
+        }
+        let description = repository.describe(git2::DescribeOptions::new().describe_tags().show_commit_oid_as_fallback(true))
+            .and_then(|description| description.format(None))
+            .map_err(Self::error("describe HEAD"))?;
+        Ok(Some(description))
+    }
+
+    fn uncommitted_changes(&self, path: &Path) -> Result<Option<Vec<String>>, GitError> {
+        let repository = match git2::Repository::discover(path) {
+            Ok(repository) if !repository.is_bare() => repository,
+            Ok(_) => return Ok(None),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(Self::error(format!("open the repository at {}", path.display()))(e)),
+        };
+        let mut options = git2::StatusOptions::new();
+        options.include_untracked(true).include_ignored(false);
+        let statuses = repository.statuses(Some(&mut options)).map_err(Self::error("list changes"))?;
+        Ok(Some(statuses.iter()
+            .map(|entry| porcelain_line(entry.status(), &String::from_utf8_lossy(entry.path_bytes())))
+            .collect()))
+    }
+}
+
+/// A `git status --porcelain` line for a libgit2 status: the index and worktree state, then the path
+#[cfg(feature = "git-library")]
+fn porcelain_line(status: git2::Status, path: &str) -> String {
+    use git2::Status;
+
+    if status.is_conflicted() {
+        return format!("UU {path}");
+    }
+    if status == Status::WT_NEW {
+        return format!("?? {path}");
+    }
+    let state = |new: Status, modified: Status, deleted: Status, renamed: Status, typechange: Status| {
+        [(new, 'A'), (modified, 'M'), (deleted, 'D'), (renamed, 'R'), (typechange, 'T')].into_iter()
+            .find(|(flag, _)| status.contains(*flag))
+            .map_or(' ', |(_, state)| state)
+    };
+    let index = state(Status::INDEX_NEW, Status::INDEX_MODIFIED, Status::INDEX_DELETED, Status::INDEX_RENAMED, Status::INDEX_TYPECHANGE);
+    let worktree = state(Status::WT_NEW, Status::WT_MODIFIED, Status::WT_DELETED, Status::WT_RENAMED, Status::WT_TYPECHANGE);
+    format!("{index}{worktree} {path}")
+}
+
+/// Git operations through the git command
+struct Cli;
+
+impl Cli {
+    /// Resolves a reference to a commit id, None when there is no such commit
+    fn resolve(repository: &Path, reference: &str) -> Result<Option<String>, GitError> {
+        let output = git(Some(repository), ["rev-parse", "--verify", "--quiet", format!("{reference}^{{commit}}").as_str()])?;
+        // --verify --quiet exits with 1, printing nothing, for anything that isn't a commit
+        Ok(output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+    }
+}
+
+impl GitBackend for Cli {
+    fn clone_repository(&self, url: &str, shallow: bool, destination: &Path) -> Result<(), GitError> {
+        let mut args = vec![OsStr::new("clone"), OsStr::new("--quiet")];
+        if shallow {
+            args.extend([OsStr::new("--depth"), OsStr::new("1")]);
+        }
+        args.extend([OsStr::new(url), destination.as_os_str()]);
+        run_git(None, args)?;
+        Ok(())
+    }
+
+    fn checkout(&self, repository: &Path, reference: &str) -> Result<(), GitError> {
+        // Branches other than the default one only exist as remote-tracking branches after a clone
+        let commit = match Self::resolve(repository, reference)? {
+            Some(commit) => commit,
+            None => Self::resolve(repository, &format!("origin/{reference}"))?
+                .ok_or_else(|| GitError::UnknownReference(reference.to_string()))?,
+        };
+        run_git(Some(repository), ["checkout", "--quiet", "--detach", commit.as_str()])?;
+        Ok(())
+    }
+
+    fn fetch(&self, repository: &Path, reference: &str) -> Result<String, GitError> {
+        run_git(Some(repository), ["fetch", "--quiet", "origin", reference])?;
+        Self::resolve(repository, "FETCH_HEAD")?.ok_or_else(|| GitError::UnknownReference(reference.to_string()))
+    }
+
+    fn describe(&self, repository: &Path) -> Result<Option<String>, GitError> {
+        if Self::resolve(repository, "HEAD")?.is_none() {
+            return Ok(None);
+        }
+        let description = run_git(Some(repository), ["describe", "--tags", "--always"])?;
+        Ok(Some(description.trim().to_string()))
+    }
+
+    fn uncommitted_changes(&self, path: &Path) -> Result<Option<Vec<String>>, GitError> {
+        let output = git(Some(path), ["status", "--porcelain"])?;
+        // Outside a worktree, or in a directory that doesn't exist yet
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect()))
+    }
+}
+
+/// Runs git, in `directory` when given, with its input closed so it can't prompt
+fn git<I, S>(directory: Option<&Path>, args: I) -> Result<Output, GitError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = Command::new("git");
+    if let Some(directory) = directory {
+        command.arg("-C").arg(directory);
+    }
+    command.args(args).stdin(Stdio::null()).output().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => GitError::NotInstalled,
+        _ => GitError::Io(e),
+    })
+}
+
+/// Runs git like `git`, returning its output or failing with its exit status
+fn run_git<I, S>(directory: Option<&Path>, args: I) -> Result<String, GitError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let args: Vec<S> = args.into_iter().collect();
+    let output = git(directory, &args)?;
+    if !output.status.success() {
+        return Err(GitError::Command {
+            command: args.first().map(|arg| arg.as_ref().to_string_lossy().into_owned()).unwrap_or_default(),
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::find_in_path;
+
+    use tempfile::TempDir;
+
+    fn backends() -> Vec<&'static dyn GitBackend> {
+        vec![
+            #[cfg(feature = "git-library")]
+            &Library,
+            &Cli,
+        ]
+    }
+
+    fn run(directory: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com", "-c", "init.defaultBranch=main", "-C"])
+            .arg(directory)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?}: {}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    /// A repository whose main branch has two commits, the first tagged v1, a `feature` branch
+    /// and a commit only `refs/custom/hidden` points to, which clones don't bring along
+    fn fixture() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path();
+        run(repository, &["init", "--quiet"]);
+        for (content, tag) in [("one", Some("v1")), ("two", None)] {
+            fs::write(repository.join("file.txt"), content).unwrap();
+            run(repository, &["add", "file.txt"]);
+            run(repository, &["commit", "--quiet", "-m", content]);
+            if let Some(tag) = tag {
+                run(repository, &["tag", tag]);
+            }
+        }
+        for (branch, content) in [("feature", "feature"), ("hidden", "hidden")] {
+            run(repository, &["checkout", "--quiet", "-b", branch, "main"]);
+            fs::write(repository.join("file.txt"), content).unwrap();
+            run(repository, &["commit", "--quiet", "-am", content]);
+        }
+        run(repository, &["update-ref", "refs/custom/hidden", "hidden"]);
+        run(repository, &["checkout", "--quiet", "main"]);
+        run(repository, &["branch", "--quiet", "-D", "hidden"]);
+        dir
+    }
+
+    #[test]
+    fn test_clone_at_references() {
+        if find_in_path("git").is_none() {
+            return;
+        }
+        let origin = fixture();
+        let url = origin.path().to_str().unwrap();
+        for git in backends() {
+            let scratch = tempfile::tempdir().unwrap();
+            for (reference, expected) in [(None, "two"), (Some("v1"), "one"), (Some("feature"), "feature"), (Some("refs/custom/hidden"), "hidden")] {
+                let destination = scratch.path().join(reference.unwrap_or("default").replace('/', "-"));
+                clone_with(git, url, reference, &destination).unwrap();
+                assert_eq!(fs::read_to_string(destination.join("file.txt")).unwrap(), expected, "{reference:?}");
+            }
+
+            let destination = scratch.path().join("unknown");
+            let error = clone_with(git, url, Some("nope"), &destination).unwrap_err();
+            assert!(matches!(&error, GitError::UnknownReference(reference) if reference == "nope"), "{error}");
+            assert!(!destination.exists());
+        }
+    }
+
+    #[test]
+    fn test_describe() {
+        if find_in_path("git").is_none() {
+            return;
+        }
+        let origin = fixture();
+        let head = run(origin.path(), &["rev-parse", "--short=7", "HEAD"]);
+        let empty = tempfile::tempdir().unwrap();
+        run(empty.path(), &["init", "--quiet"]);
+        for git in backends() {
+            assert_eq!(git.describe(origin.path()).unwrap(), Some(format!("v1-1-g{}", head.trim())));
+            git.checkout(origin.path(), "v1").unwrap();
+            assert_eq!(git.describe(origin.path()).unwrap().as_deref(), Some("v1"));
+            run(origin.path(), &["checkout", "--quiet", "main"]);
+            assert_eq!(git.describe(empty.path()).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn test_uncommitted_changes() {
+        if find_in_path("git").is_none() {
+            return;
+        }
+        let repository = fixture();
+        let outside = tempfile::tempdir().unwrap();
+        for git in backends() {
+            assert_eq!(git.uncommitted_changes(repository.path()).unwrap(), Some(Vec::new()));
+            assert_eq!(git.uncommitted_changes(&outside.path().join("missing")).unwrap(), None);
+        }
+
+        fs::write(repository.path().join("file.txt"), "changed").unwrap();
+        fs::write(repository.path().join("untracked.txt"), "").unwrap();
+        fs::write(repository.path().join("staged.txt"), "").unwrap();
+        run(repository.path(), &["add", "staged.txt"]);
+        for git in backends() {
+            let mut changes = git.uncommitted_changes(repository.path()).unwrap().unwrap();
+            changes.sort();
+            assert_eq!(changes, [" M file.txt", "?? untracked.txt", "A  staged.txt"]);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::error::TemplateError;
+use crate::file::ensure_persistent_storage_dir;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const HISTORY_FILE: &str = "history.toml";
+
+/// Most recent uses kept in the log of history.toml; older ones only remain in the counts
+const MAX_LOGGED_USES: usize = 1000;
+
+/// Per-template usage history from history.toml in the storage directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageHistory {
+    /// Template name mapped to its usage
+    #[serde(default)]
+    pub templates: BTreeMap<String, UsageEntry>,
+    /// Individual applications, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uses: Vec<UseRecord>,
+}
+
+/// How often and how recently a template was applied
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageEntry {
+    /// Number of successful applications
+    pub count: u64,
+    /// Unix timestamp (seconds) of the last application
+    pub last_used: u64,
+}
+
+/// One application of a template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UseRecord {
+    pub template: String,
+    /// Template version that was applied
+    pub version: Option<String>,
+    /// Unix timestamp (seconds)
+    pub at: u64,
+}
+
+/// Current Unix time in seconds
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Time stamped into generated projects (.template.lock, receipts, file times): SOURCE_DATE_EPOCH
+/// when set, so repeated runs produce identical output, otherwise the current time
+pub fn generation_secs() -> u64 {
+    source_date_epoch().unwrap_or_else(now_secs)
+}
+
+/// SOURCE_DATE_EPOCH as reproducible builds define it, None when unset or not a timestamp
+pub fn source_date_epoch() -> Option<u64> {
+    env::var("SOURCE_DATE_EPOCH").ok()?.trim().parse().ok()
+}
+
+impl UsageHistory {
+    /// Loads the usage history, empty when there is none yet
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let history_path = ensure_persistent_storage_dir()?.join(HISTORY_FILE);
+        if !history_path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&history_path)?;
+        let history = toml::from_str(&content)
+            .map_err(|e| TemplateError::Config(format!("Failed to parse {}: {e}", history_path.display())))?;
+        Ok(history)
+    }
+
+    /// Writes the usage history
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let history_path = ensure_persistent_storage_dir()?.join(HISTORY_FILE);
+        fs::write(history_path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Records one use of a template and saves the history
+    pub fn record_use(template: &str, version: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        let mut history = Self::load()?;
+        history.add_use(template, version, now_secs());
+        history.save()
+    }
+
+    fn add_use(&mut self, template: &str, version: Option<&str>, at: u64) {
+        let entry = self.templates.entry(template.to_string()).or_default();
+        entry.count += 1;
+        entry.last_used = at;
+
+        self.uses.push(UseRecord { template: template.to_string(), version: version.map(String::from), at });
+        if self.uses.len() > MAX_LOGGED_USES {
+            self.uses.drain(..self.uses.len() - MAX_LOGGED_USES);
+        }
+    }
+
+    /// Moves the usage of a renamed template to its new name, merging with any usage recorded
+    /// there. Returns whether anything changed.
+    pub fn rename(&mut self, old: &str, new: &str) -> bool {
+        let Some(moved) = self.templates.remove(old) else {
+            return false;
+        };
+        let entry = self.templates.entry(new.to_string()).or_default();
+        entry.count += moved.count;
+        entry.last_used = entry.last_used.max(moved.last_used);
+        for record in self.uses.iter_mut().filter(|record| record.template == old) {
+            record.template = new.to_string();
+        }
+        true
+    }
+
+    /// Unix timestamp of the last use of a template, if it was ever used
+    pub fn last_used(&self, template: &str) -> Option<u64> {
+        self.templates.get(template).map(|e| e.last_used)
+    }
+}
+
+/// Formats a Unix timestamp as a UTC date (YYYY-MM-DD)
+pub fn format_date(secs: u64) -> String {
+    // Civil-from-days, after Howard Hinnant's date algorithms
+    let days = i64::try_from(secs / 86_400).unwrap_or(0) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_792_290_948), "2026-10-18");
+    }
+
+    #[test]
+    fn test_add_use() {
+        let mut history = UsageHistory::default();
+        for at in 0..=MAX_LOGGED_USES as u64 {
+            history.add_use("rust/cli", Some("1.0.0"), at);
+        }
+        history.add_use("web", None, 5000);
+
+        assert_eq!(history.templates["rust/cli"].count, MAX_LOGGED_USES as u64 + 1);
+        assert_eq!(history.last_used("web"), Some(5000));
+        assert_eq!(history.uses.len(), MAX_LOGGED_USES);
+        assert_eq!(history.uses[0].at, 2);
+        assert_eq!(history.uses.last().map(|u| u.template.as_str()), Some("web"));
+
+        assert!(history.rename("web", "rust/cli"));
+        assert!(!history.rename("web", "other"));
+        assert_eq!(history.templates["rust/cli"].count, MAX_LOGGED_USES as u64 + 2);
+        assert_eq!(history.last_used("rust/cli"), Some(5000));
+        assert!(history.uses.iter().all(|u| u.template == "rust/cli"));
+    }
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+use std::process::Command;
+
+/// Shell commands a template runs in the target directory around `init` and `new`, from the
+/// [hooks] table of .template.toml. Commands may use `{{ variable }}` placeholders.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Hooks {
+    /// Run before any file is copied (e.g. checking a toolchain version)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_init: Vec<String>,
+    /// Run after the template was applied (e.g. "git init", "cargo check")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_init: Vec<String>,
+}
+
+impl Hooks {
+    /// Check if there is no command to run
+    pub fn is_empty(&self) -> bool {
+        self.pre_init.is_empty() && self.post_init.is_empty()
+    }
+
+    /// Every command with the stage it runs in, in the order they run
+    pub fn commands(&self) -> impl Iterator<Item = (HookStage, &str)> {
+        let pre = self.pre_init.iter().map(|command| (HookStage::PreInit, command.as_str()));
+        let post = self.post_init.iter().map(|command| (HookStage::PostInit, command.as_str()));
+        pre.chain(post)
+    }
+}
+
+/// When a hook runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    PreInit,
+    PostInit,
+}
+
+impl HookStage {
+    /// Key of the stage in the [hooks] table
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::PreInit => "pre_init",
+            Self::PostInit => "post_init",
+        }
+    }
+}
+
+/// Runs a hook command through the platform shell in `target_path`, failing when it can't be
+/// started or exits unsuccessfully. Its output goes straight to the terminal.
+pub fn run_hook(command: &str, target_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    // Hooks must not write into stdout, which holds plain records or JSON events
+    let status = shell.arg(command)
+        .current_dir(target_path)
+        .stdout(std::io::stderr())
+        .status()
+        .map_err(|e| format!("Cannot run hook '{command}': {e}"))?;
+    if !status.success() {
+        return Err(format!("Hook '{command}' failed ({status})").into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_in_order() {
+        let hooks: Hooks = toml::from_str("post_init = [\"git init\"]\npre_init = [\"true\"]").unwrap();
+        let commands: Vec<_> = hooks.commands().collect();
+        assert_eq!(commands, [(HookStage::PreInit, "true"), (HookStage::PostInit, "git init")]);
+        assert!(Hooks::default().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_hook() {
+        let dir = std::env::temp_dir();
+        assert!(run_hook("test -d .", &dir).is_ok());
+        let error = run_hook("exit 3", &dir).unwrap_err().to_string();
+        assert!(error.starts_with("Hook 'exit 3' failed"));
+    }
+}
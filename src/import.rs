@@ -0,0 +1,452 @@
+use crate::error::TemplateError;
+use crate::merge::is_binary;
+use crate::patterns::{matches_any, matches_pattern, parse_ignore_file};
+use crate::render::{RenderEngine, referenced_names};
+use crate::template::{TEMPLATE_CONFIG_FILE, Template, TemplateConfig, collect_files};
+use crate::variables::{VariableKind, VariableSpec, is_valid_name};
+
+use clap::ValueEnum;
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Variables of a cookiecutter template, with their defaults, in its root
+pub const COOKIECUTTER_FILE: &str = "cookiecutter.json";
+
+/// Placeholders and settings of a cargo-generate template, in its root
+pub const CARGO_GENERATE_FILE: &str = "cargo-generate.toml";
+
+/// Gitignore-style patterns of files cargo-generate leaves out of generated projects
+const GENIGNORE_FILE: &str = ".genignore";
+
+/// Extension cargo-generate strips from file names, for files that must not look like what
+/// they are inside the template repository
+const LIQUID_EXTENSION: &str = ".liquid";
+
+/// Template formats of other tools that `import` converts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// cookiecutter.json with `{{ cookiecutter.name }}` placeholders
+    Cookiecutter,
+    /// Optional cargo-generate.toml with Liquid `{{project-name}}` placeholders
+    CargoGenerate,
+}
+
+/// A template converted to the native format, not yet stored anywhere
+#[derive(Debug, Default)]
+pub struct Conversion {
+    pub config: TemplateConfig,
+    /// Files of the converted template: (relative path, content)
+    pub files: Vec<(String, Vec<u8>)>,
+    /// What couldn't be carried over and has to be looked at by hand
+    pub warnings: Vec<String>,
+}
+
+impl Conversion {
+    /// Writes the files and .template.toml into a new template directory
+    pub fn write_to(&self, dest: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        for (path, content) in &self.files {
+            let destination = dest.join(path);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(destination, content)?;
+        }
+        Template::save_config(&self.config, dest.join(TEMPLATE_CONFIG_FILE), true)
+    }
+}
+
+/// Converts a cookiecutter template: the variables of cookiecutter.json become declared
+/// variables (lists become choices), and the single `{{cookiecutter.*}}` directory becomes the
+/// template's content, with `{{ cookiecutter.name }}` placeholders in paths and files rewritten
+/// to `{{ name }}`. Jinja logic (`{% if %}`, method calls, ...) has no equivalent and is left
+/// as written, with a warning; hooks aren't converted.
+pub fn convert_cookiecutter(source: &Path) -> Result<Conversion, Box<dyn std::error::Error>> {
+    let manifest = source.join(COOKIECUTTER_FILE);
+    if !manifest.is_file() {
+        return Err(format!("{} has no {COOKIECUTTER_FILE}, so it isn't a cookiecutter template", source.display()).into());
+    }
+    let context: serde_json::Map<String, Value> = serde_json::from_str(&fs::read_to_string(&manifest)?)
+        .map_err(|e| TemplateError::Config(format!("{COOKIECUTTER_FILE} is invalid: {e}")))?;
+
+    let project_dirs: Vec<String> = fs::read_dir(source)?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.contains("{{") && name.contains("cookiecutter"))
+        .collect();
+    let [project_dir] = project_dirs.as_slice() else {
+        return Err(format!(
+            "A cookiecutter template has one {{{{cookiecutter.*}}}} directory in its root, {} has {}",
+            source.display(),
+            project_dirs.len()
+        ).into());
+    };
+
+    let mut conversion = Conversion::default();
+    let prompts = context.get("__prompts__").and_then(Value::as_object);
+    let mut variables = BTreeMap::new();
+    let mut verbatim = Vec::new();
+    for (name, value) in &context {
+        match name.as_str() {
+            "__prompts__" => continue,
+            "_copy_without_render" => {
+                verbatim = value.as_array().into_iter().flatten()
+                    .filter_map(Value::as_str)
+                    .map(|pattern| pattern.strip_prefix(&format!("{project_dir}/")).unwrap_or(pattern).to_string())
+                    .collect();
+                continue;
+            }
+            _ if name.starts_with('_') && !name.starts_with("__") => {
+                conversion.warnings.push(format!("{COOKIECUTTER_FILE}: the '{name}' setting isn't supported and was dropped"));
+                continue;
+            }
+            _ => {}
+        }
+        if !is_valid_name(name) {
+            conversion.warnings.push(format!("{COOKIECUTTER_FILE}: '{name}' isn't a valid variable name and was dropped"));
+            continue;
+        }
+        let description = prompts.and_then(|prompts| prompts.get(name)).and_then(Value::as_str).map(String::from);
+        let spec = match value {
+            Value::Bool(default) => VariableSpec { kind: VariableKind::Bool, default: Some(default.to_string()), ..VariableSpec::default() },
+            Value::Number(default) if default.is_i64() => VariableSpec { kind: VariableKind::Integer, default: Some(default.to_string()), ..VariableSpec::default() },
+            Value::Array(choices) => {
+                let choices: Vec<String> = choices.iter().map(|choice| choice.as_str().map_or_else(|| choice.to_string(), String::from)).collect();
+                VariableSpec { default: choices.first().cloned(), choices: Some(choices), ..VariableSpec::default() }
+            }
+            Value::Object(_) | Value::Null => {
+                conversion.warnings.push(format!("{COOKIECUTTER_FILE}: '{name}' has no plain value and was dropped"));
+                continue;
+            }
+            other => {
+                let default = other.as_str().map_or_else(|| other.to_string(), String::from);
+                let converted = rewrite_tags(&default, |_, inner| cookiecutter_expression(inner));
+                if unsupported_tags(&converted) > 0 {
+                    conversion.warnings.push(format!("{COOKIECUTTER_FILE}: the default of '{name}' ({default}) can't be converted, the value is asked for instead"));
+                    VariableSpec::default()
+                } else {
+                    VariableSpec { default: Some(converted), ..VariableSpec::default() }
+                }
+            }
+        };
+        variables.insert(name.clone(), VariableSpec { description, ..spec });
+    }
+
+    let content_dir = source.join(project_dir);
+    let mut files = Vec::new();
+    collect_files(&content_dir, &content_dir, &mut files)?;
+    files.sort();
+    for file in files {
+        let path = rewrite_tags(&file, |_, inner| cookiecutter_expression(inner));
+        let content = fs::read(content_dir.join(&file))?;
+        let copied_as_is = verbatim.iter().any(|pattern| matches_pattern(&file, pattern));
+        let content = match std::str::from_utf8(&content) {
+            Ok(text) if !copied_as_is && !is_binary(&content) => {
+                let converted = rewrite_tags(text, |_, inner| cookiecutter_expression(inner));
+                let unsupported = unsupported_tags(&converted);
+                if unsupported > 0 {
+                    conversion.warnings.push(format!("{path}: {unsupported} Jinja expression(s) without an equivalent are left as written"));
+                }
+                converted.into_bytes()
+            }
+            _ => content,
+        };
+        conversion.files.push((path, content));
+    }
+    if source.join("hooks").is_dir() {
+        conversion.warnings.push("hooks/: cookiecutter hooks aren't converted; port them to [hooks] in .template.toml".to_string());
+    }
+
+    conversion.config = TemplateConfig {
+        variables: (!variables.is_empty()).then_some(variables),
+        verbatim: (!verbatim.is_empty()).then_some(verbatim),
+        engine: Some(RenderEngine::Tera),
+        ..TemplateConfig::default()
+    };
+    Ok(conversion)
+}
+
+/// Converts a cargo-generate template: the whole repository is the template, minus
+/// cargo-generate.toml, .genignore and what they ignore. The [placeholders] of
+/// cargo-generate.toml become declared variables, and so do the built-in placeholders the files
+/// use (`project-name`, `crate_name`, `authors`, `crate_type`). Names are written with '_'
+/// instead of '-', `.liquid` extensions are dropped, and the Liquid engine renders the rest,
+/// logic tags included. Rhai hooks have no equivalent and are reported.
+pub fn convert_cargo_generate(source: &Path) -> Result<Conversion, Box<dyn std::error::Error>> {
+    let manifest_path = source.join(CARGO_GENERATE_FILE);
+    let manifest: toml::Table = if manifest_path.is_file() {
+        toml::from_str(&fs::read_to_string(&manifest_path)?).map_err(|e| TemplateError::Config(format!("{CARGO_GENERATE_FILE} is invalid: {e}")))?
+    } else if source.join("Cargo.toml").is_file() {
+        toml::Table::new()
+    } else {
+        return Err(format!("{} has neither {CARGO_GENERATE_FILE} nor Cargo.toml, so it isn't a cargo-generate template", source.display()).into());
+    };
+    let mut conversion = Conversion::default();
+    let settings = manifest.get("template").and_then(toml::Value::as_table);
+    let patterns = |key: &str| -> Vec<String> {
+        settings.and_then(|settings| settings.get(key)).and_then(toml::Value::as_array).into_iter().flatten()
+            .filter_map(toml::Value::as_str)
+            .map(String::from)
+            .collect()
+    };
+    let mut ignored = patterns("ignore");
+    if source.join(GENIGNORE_FILE).is_file() {
+        ignored.extend(parse_ignore_file(&fs::read_to_string(source.join(GENIGNORE_FILE))?));
+    }
+    let verbatim = patterns("exclude");
+    if !patterns("include").is_empty() {
+        conversion.warnings.push(format!("{CARGO_GENERATE_FILE}: 'include' isn't supported, every file is rendered; list the others in verbatim"));
+    }
+    for (key, what) in [("hooks", "rhai hooks aren't converted; port them to [hooks] in .template.toml"), ("conditional", "conditional placeholders and files aren't converted")] {
+        if manifest.contains_key(key) {
+            conversion.warnings.push(format!("{CARGO_GENERATE_FILE}: {what}"));
+        }
+    }
+
+    let mut variables = BTreeMap::new();
+    let placeholders = manifest.get("placeholders").and_then(toml::Value::as_table).cloned().unwrap_or_default();
+    for (name, placeholder) in &placeholders {
+        let variable = liquid_name(name);
+        if !is_valid_name(&variable) {
+            conversion.warnings.push(format!("{CARGO_GENERATE_FILE}: '{name}' isn't a valid variable name and was dropped"));
+            continue;
+        }
+        let field = |key: &str| placeholder.get(key);
+        let kind = match field("type").and_then(toml::Value::as_str) {
+            Some("bool") => VariableKind::Bool,
+            _ => VariableKind::String,
+        };
+        let default = field("default").map(|value| value.as_str().map_or_else(|| value.to_string(), String::from));
+        let choices = field("choices").and_then(toml::Value::as_array).map(|choices| {
+            choices.iter().map(|choice| choice.as_str().map_or_else(|| choice.to_string(), String::from)).collect()
+        });
+        if field("regex").is_some() {
+            conversion.warnings.push(format!("{CARGO_GENERATE_FILE}: the regex of '{name}' isn't checked"));
+        }
+        let description = field("prompt").and_then(toml::Value::as_str).map(String::from);
+        variables.insert(variable, VariableSpec { description, default, kind, choices, ..VariableSpec::default() });
+    }
+
+    let mut files = Vec::new();
+    collect_files(source, source, &mut files)?;
+    files.sort();
+    files.retain(|file| {
+        !file.starts_with(".git/") && file != CARGO_GENERATE_FILE && file != GENIGNORE_FILE && !matches_any(file, &ignored)
+    });
+    let mut referenced = Vec::new();
+    for file in files {
+        let converted_path = rewrite_tags(&file, |_, inner| liquid_name(inner));
+        let path = converted_path.strip_suffix(LIQUID_EXTENSION).unwrap_or(&converted_path).to_string();
+        referenced.extend(referenced_names(&path));
+        let content = fs::read(source.join(&file))?;
+        let copied_as_is = verbatim.iter().any(|pattern| matches_pattern(&file, pattern));
+        let content = match std::str::from_utf8(&content) {
+            Ok(text) if !copied_as_is && !is_binary(&content) => {
+                let converted = rewrite_tags(text, |_, inner| liquid_name(inner));
+                referenced.extend(referenced_names(&converted));
+                converted.into_bytes()
+            }
+            _ => content,
+        };
+        conversion.files.push((path, content));
+    }
+
+    referenced.sort();
+    referenced.dedup();
+    // The placeholders cargo-generate fills in itself, for the ones the files use
+    let builtin = |description: &str, default: Option<&str>, choices: Option<&[&str]>| VariableSpec {
+        description: Some(description.to_string()),
+        default: default.map(String::from),
+        choices: choices.map(|choices| choices.iter().map(|choice| (*choice).to_string()).collect()),
+        ..VariableSpec::default()
+    };
+    variables.entry("project_name".to_string()).or_insert_with(|| builtin("Name of the project", None, None));
+    for name in &referenced {
+        if variables.contains_key(name) {
+            continue;
+        }
+        let spec = match name.as_str() {
+            "crate_name" => builtin("Name of the crate", Some("{{ project_name | snake_case }}"), None),
+            "authors" => builtin("Authors of the crate", None, None),
+            "username" => builtin("Your user name", None, None),
+            "crate_type" => builtin("Kind of crate", Some("bin"), Some(&["bin", "lib"])),
+            _ => {
+                conversion.warnings.push(format!("'{name}' is used but not declared; declare it in .template.toml"));
+                continue;
+            }
+        };
+        variables.insert(name.clone(), spec);
+    }
+
+    conversion.config = TemplateConfig {
+        variables: Some(variables),
+        verbatim: (!verbatim.is_empty()).then_some(verbatim),
+        engine: Some(RenderEngine::Liquid),
+        ..TemplateConfig::default()
+    };
+    Ok(conversion)
+}
+
+/// A Liquid expression with '-' in variable names written as '_': `project-name` is `project_name`
+fn liquid_name(expression: &str) -> String {
+    let chars: Vec<char> = expression.chars().collect();
+    chars.iter().enumerate()
+        .map(|(index, c)| {
+            let between_names = index > 0
+                && chars.get(index + 1).is_some_and(|next| next.is_ascii_alphanumeric())
+                && chars[index - 1].is_ascii_alphanumeric();
+            if *c == '-' && between_names { '_' } else { *c }
+        })
+        .collect()
+}
+
+/// Rewrites what is inside every `{{ ... }}` and `{% ... %}` tag of some text; `rewrite` gets
+/// the opening braces and the inside
+fn rewrite_tags(text: &str, mut rewrite: impl FnMut(&str, &str) -> String) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{").into_iter().chain(rest.find("{%")).min() {
+        let close = if rest[start..].starts_with("{{") { "}}" } else { "%}" };
+        let Some(len) = rest[start + 2..].find(close) else {
+            break;
+        };
+        output.push_str(&rest[..start + 2]);
+        output.push_str(&rewrite(&rest[start..start + 2], &rest[start + 2..start + 2 + len]));
+        output.push_str(close);
+        rest = &rest[start + 2 + len + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// A Jinja expression of cookiecutter without its `cookiecutter.` namespace, and with the
+/// `.lower()` and `.upper()` methods as the filters of the same name
+fn cookiecutter_expression(expression: &str) -> String {
+    const NAMESPACE: &str = "cookiecutter.";
+    let mut output = String::with_capacity(expression.len());
+    let mut rest = expression;
+    while let Some(start) = rest.find(NAMESPACE) {
+        let preceded_by_name = rest[..start].chars().next_back().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+        output.push_str(&rest[..start]);
+        if preceded_by_name {
+            output.push_str(NAMESPACE);
+        }
+        rest = &rest[start + NAMESPACE.len()..];
+    }
+    output.push_str(rest);
+    output.replace(".lower()", " | lower").replace(".upper()", " | upper")
+}
+
+/// Counts the tags the renderer can't evaluate: logic tags other than raw blocks, and output
+/// tags that aren't a variable with filters
+fn unsupported_tags(text: &str) -> usize {
+    let mut count = 0;
+    rewrite_tags(text, |open, inner| {
+        let supported = if open == "{%" {
+            ["raw", "endraw"].contains(&inner.trim())
+        } else {
+            let mut parts = inner.split('|').map(str::trim);
+            parts.next().is_some_and(is_valid_name) && parts.all(is_valid_name)
+        };
+        if !supported {
+            count += 1;
+        }
+        inner.to_string()
+    });
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_cookiecutter_expression() {
+        let convert = |text: &str| rewrite_tags(text, |_, inner| cookiecutter_expression(inner));
+        assert_eq!(convert("name = \"{{ cookiecutter.project_name }}\""), "name = \"{{ project_name }}\"");
+        assert_eq!(convert("{{cookiecutter.slug.upper()}}/{{ cookiecutter.a|lower }}"), "{{slug | upper}}/{{ a|lower }}");
+        assert_eq!(convert("{% if cookiecutter.ci == 'y' %}x{% endif %}"), "{% if ci == 'y' %}x{% endif %}");
+        assert_eq!(convert("{{ my.cookiecutter.x }} {{ unclosed"), "{{ my.cookiecutter.x }} {{ unclosed");
+
+        assert_eq!(unsupported_tags("{{ a }} {{ b | lower }} {% raw %}{{ x }}{% endraw %} {{ c() }}"), 1);
+        assert_eq!(unsupported_tags("{% if ci %}{{ name.replace('-', '_') }}{% endif %}"), 3);
+    }
+
+    #[test]
+    fn test_convert_cookiecutter() {
+        let root = env::temp_dir().join(format!("template-rs-cookiecutter-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let project = root.join("{{cookiecutter.project_slug}}");
+        fs::create_dir_all(project.join("{{ cookiecutter.module }}")).unwrap();
+        fs::write(root.join(COOKIECUTTER_FILE), r#"{
+            "project_name": "My App",
+            "project_slug": "{{ cookiecutter.project_name.lower() }}",
+            "module": "{{ cookiecutter.project_name.lower().replace(' ', '_') }}",
+            "license": ["MIT", "Apache-2.0"],
+            "docker": true,
+            "_copy_without_render": ["*.html"],
+            "__prompts__": {"project_name": "Name of the project"}
+        }"#).unwrap();
+        fs::write(project.join("README.md"), "# {{ cookiecutter.project_name }}\n").unwrap();
+        fs::write(project.join("{{ cookiecutter.module }}/page.html"), "{{ cookiecutter.keep }}").unwrap();
+
+        let conversion = convert_cookiecutter(&root).unwrap();
+        let variables = conversion.config.variables.as_ref().unwrap();
+        assert_eq!(variables["project_name"].description.as_deref(), Some("Name of the project"));
+        assert_eq!(variables["project_slug"].default.as_deref(), Some("{{ project_name | lower }}"));
+        assert_eq!(variables["module"].default, None);
+        assert_eq!(variables["license"].choices.as_deref(), Some(&["MIT".to_string(), "Apache-2.0".to_string()][..]));
+        assert_eq!(variables["docker"].kind, VariableKind::Bool);
+        assert_eq!(conversion.config.engine, Some(RenderEngine::Tera));
+        assert_eq!(conversion.files, vec![
+            ("README.md".to_string(), b"# {{ project_name }}\n".to_vec()),
+            ("{{ module }}/page.html".to_string(), b"{{ cookiecutter.keep }}".to_vec()),
+        ]);
+        assert_eq!(conversion.warnings.len(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_convert_cargo_generate() {
+        let root = env::temp_dir().join(format!("template-rs-cargo-generate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join(CARGO_GENERATE_FILE), r#"
+            [template]
+            ignore = ["notes.md"]
+            [placeholders.use-serde]
+            type = "bool"
+            prompt = "Use serde?"
+            default = false
+            [placeholders.license]
+            type = "string"
+            choices = ["MIT", "Apache-2.0"]
+            default = "MIT"
+        "#).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"{{project-name}}\"\nauthors = [\"{{authors}}\"]\n").unwrap();
+        fs::write(root.join("src/main.rs.liquid"), "// {{ crate_name | upcase }} {{ license }}\n").unwrap();
+        fs::write(root.join("notes.md"), "not part of projects").unwrap();
+
+        let conversion = convert_cargo_generate(&root).unwrap();
+        let variables = conversion.config.variables.as_ref().unwrap();
+        assert_eq!(variables.keys().collect::<Vec<_>>(), ["authors", "crate_name", "license", "project_name", "use_serde"]);
+        assert_eq!(variables["use_serde"].kind, VariableKind::Bool);
+        assert_eq!(variables["use_serde"].description.as_deref(), Some("Use serde?"));
+        assert_eq!(variables["crate_name"].default.as_deref(), Some("{{ project_name | snake_case }}"));
+        assert_eq!(conversion.config.engine, Some(RenderEngine::Liquid));
+        assert_eq!(conversion.files, vec![
+            ("Cargo.toml".to_string(), b"[package]\nname = \"{{project_name}}\"\nauthors = [\"{{authors}}\"]\n".to_vec()),
+            ("src/main.rs".to_string(), b"// {{ crate_name | upcase }} {{ license }}\n".to_vec()),
+        ]);
+        assert!(conversion.warnings.is_empty());
+
+        assert_eq!(liquid_name(" project-name | kebab-case "), " project_name | kebab_case ");
+        assert_eq!(liquid_name(" a - b "), " a - b ");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+use crate::file::ensure_cache_storage_dir;
+use crate::template::TemplateConfig;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// File in the cache directory holding the parsed .template.toml of every template discovered
+pub const INDEX_FILE: &str = "index.json";
+
+/// Lock file serializing updates of the index between processes
+const INDEX_LOCK_FILE: &str = "index.lock";
+
+/// Version of the index format; an index of another version is rebuilt
+const INDEX_SCHEMA_VERSION: u32 = 1;
+
+/// Parsed template configs by .template.toml path, so discovery only reparses configs that
+/// changed. Several processes (a foreground command, shell completions, ...) can use it at once:
+/// the file is only ever replaced whole by a rename, so readers never see half of it, and
+/// updates hold a lock while they merge with what other processes wrote meanwhile.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DiscoveryIndex {
+    schema_version: u32,
+    entries: BTreeMap<PathBuf, IndexEntry>,
+    /// Entries added by this process, written back by `save`
+    #[serde(skip)]
+    added: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Stamp {
+    modified_nanos: u128,
+    size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    stamp: Stamp,
+    config: TemplateConfig,
+}
+
+impl Stamp {
+    /// The modification time and size of a file, None when it can't be read
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified_nanos = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+        Some(Self { modified_nanos, size: metadata.len() })
+    }
+}
+
+impl DiscoveryIndex {
+    /// The index in the cache directory; empty when there is none yet or it can't be used
+    pub fn load() -> Self {
+        ensure_cache_storage_dir().map(|dir| Self::load_from(&dir)).unwrap_or_default()
+    }
+
+    /// Writes the entries added since loading back to the cache directory. The index is only
+    /// a cache, so failing to write it is not an error.
+    pub fn save(&self) {
+        if !self.added.is_empty()
+            && let Ok(dir) = ensure_cache_storage_dir()
+        {
+            let _ = self.save_to(&dir);
+        }
+    }
+
+    fn load_from(dir: &Path) -> Self {
+        fs::read(dir.join(INDEX_FILE)).ok()
+            .and_then(|content| serde_json::from_slice::<Self>(&content).ok())
+            .filter(|index| index.schema_version == INDEX_SCHEMA_VERSION)
+            .unwrap_or_default()
+    }
+
+    /// The config parsed from `config_path` before, if the file hasn't changed since
+    pub fn get(&self, config_path: &Path) -> Option<TemplateConfig> {
+        let entry = self.entries.get(config_path)?;
+        (Stamp::of(config_path)? == entry.stamp).then(|| entry.config.clone())
+    }
+
+    /// Records the config just parsed from `config_path`
+    pub fn insert(&mut self, config_path: &Path, config: &TemplateConfig) {
+        let Some(stamp) = Stamp::of(config_path) else {
+            return;
+        };
+        self.entries.insert(config_path.to_path_buf(), IndexEntry { stamp, config: config.clone() });
+        self.added.push(config_path.to_path_buf());
+    }
+
+    /// Merges this process's additions into the index on disk under the lock, dropping entries
+    /// of configs that no longer exist, and swaps the result in with a rename
+    fn save_to(&self, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let lock = File::create(dir.join(INDEX_LOCK_FILE))?;
+        lock.lock()?;
+
+        let mut current = Self::load_from(dir);
+        current.schema_version = INDEX_SCHEMA_VERSION;
+        for path in &self.added {
+            if let Some(entry) = self.entries.get(path) {
+                current.entries.insert(path.clone(), entry.clone());
+            }
+        }
+        current.entries.retain(|path, _| path.is_file());
+
+        let scratch = dir.join(format!("{INDEX_FILE}.{}.tmp", std::process::id()));
+        fs::write(&scratch, serde_json::to_vec(&current)?)?;
+        let result = fs::rename(&scratch, dir.join(INDEX_FILE));
+        if result.is_err() {
+            let _ = fs::remove_file(&scratch);
+        }
+        lock.unlock()?;
+        Ok(result?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::thread;
+
+    #[test]
+    fn test_concurrent_updates_are_merged() {
+        let root = env::temp_dir().join(format!("template-rs-index-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        // Each writer adds its own template; none may be lost or leave a torn file behind
+        let writers: Vec<_> = (0..8).map(|n| {
+            let root = root.clone();
+            thread::spawn(move || {
+                let config_path = root.join(format!("t{n}.toml"));
+                fs::write(&config_path, format!("name = \"t{n}\"\ncreated = 2024-01-01T00:00:00Z\n")).unwrap();
+                let config: TemplateConfig = toml::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+                let mut index = DiscoveryIndex::load_from(&root);
+                index.insert(&config_path, &config);
+                index.save_to(&root).unwrap();
+            })
+        }).collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let index = DiscoveryIndex::load_from(&root);
+        assert_eq!(index.entries.len(), 8);
+        let config = index.get(&root.join("t3.toml")).unwrap();
+        assert_eq!(config.name.as_deref(), Some("t3"));
+        assert!(config.metadata.unwrap().contains_key("created"));
+
+        // A changed config is no longer served from the index
+        fs::write(root.join("t3.toml"), "name = \"renamed\"\n").unwrap();
+        assert!(index.get(&root.join("t3.toml")).is_none());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::path::Path;
 
 /// Language definition with display name and aliases
 pub struct Language {
@@ -92,6 +93,158 @@ pub fn is_known_language(identifier: &str) -> bool {
     map.contains_key(lower.as_str())
 }
 
+/// File extensions (lowercase, without the dot) mapped to the language display name
+static EXTENSIONS: &[(&str, &str)] = &[
+    ("bash", "Bash"), ("sh", "Bash"), ("zsh", "Bash"),
+    ("c", "C"), ("h", "C"),
+    ("cs", "C#"), ("csx", "C#"), ("cake", "C#"),
+    ("cc", "C++"), ("cpp", "C++"), ("cxx", "C++"), ("hh", "C++"), ("hpp", "C++"), ("hxx", "C++"),
+    ("clj", "Clojure"), ("cljs", "Clojure"), ("cljc", "Clojure"), ("edn", "Clojure"),
+    ("cmake", "CMake"),
+    ("coffee", "CoffeeScript"),
+    ("css", "CSS"),
+    ("dart", "Dart"),
+    ("ex", "Elixir"), ("exs", "Elixir"),
+    ("erl", "Erlang"), ("hrl", "Erlang"),
+    ("fs", "F#"), ("fsi", "F#"), ("fsx", "F#"),
+    ("go", "Go"),
+    ("groovy", "Groovy"), ("gradle", "Groovy"),
+    ("hs", "Haskell"), ("lhs", "Haskell"),
+    ("htm", "HTML"), ("html", "HTML"), ("xhtml", "HTML"),
+    ("java", "Java"),
+    ("cjs", "JavaScript"), ("js", "JavaScript"), ("jsx", "JavaScript"), ("mjs", "JavaScript"),
+    ("geojson", "JSON"), ("json", "JSON"), ("jsonl", "JSON"),
+    ("just", "Just"),
+    ("kt", "Kotlin"), ("kts", "Kotlin"),
+    ("lua", "Lua"),
+    ("mk", "Makefile"), ("mak", "Makefile"),
+    ("markdown", "Markdown"), ("md", "Markdown"),
+    ("nix", "Nix"),
+    ("m", "Objective-C"),
+    ("mm", "Objective-C++"),
+    ("ml", "OCaml"), ("mli", "OCaml"),
+    ("pl", "Perl"), ("pm", "Perl"),
+    ("php", "PHP"),
+    ("ps1", "PowerShell"), ("psd1", "PowerShell"), ("psm1", "PowerShell"),
+    ("py", "Python"), ("pyi", "Python"), ("pyw", "Python"),
+    ("r", "R"),
+    ("gemspec", "Ruby"), ("rake", "Ruby"), ("rb", "Ruby"),
+    ("rs", "Rust"),
+    ("sass", "Sass"),
+    ("sc", "Scala"), ("scala", "Scala"),
+    ("scss", "SCSS"),
+    ("sql", "SQL"),
+    ("svelte", "Svelte"),
+    ("swift", "Swift"),
+    ("toml", "TOML"),
+    ("cts", "TypeScript"), ("mts", "TypeScript"), ("ts", "TypeScript"), ("tsx", "TypeScript"),
+    ("vue", "Vue"),
+    ("xml", "XML"),
+    ("yaml", "YAML"), ("yml", "YAML"),
+];
+
+/// Whole file names (lowercase) that identify a language regardless of extension
+static FILE_NAMES: &[(&str, &str)] = &[
+    ("cmakelists.txt", "CMake"),
+    ("containerfile", "Dockerfile"),
+    ("dockerfile", "Dockerfile"),
+    ("gemfile", "Ruby"),
+    ("gnumakefile", "Makefile"),
+    ("justfile", "Just"),
+    ("makefile", "Makefile"),
+    ("rakefile", "Ruby"),
+];
+
+/// Detects the language of a file from its name or extension (case-insensitive)
+pub fn detect_language_for_path<P: AsRef<Path>>(path: P) -> Option<&'static str> {
+    let path = path.as_ref();
+    let file_name = path.file_name()?.to_str()?.to_lowercase();
+
+    if let Some((_, language)) = FILE_NAMES.iter().find(|(name, _)| *name == file_name) {
+        return Some(language);
+    }
+
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    EXTENSIONS.iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, language)| *language)
+}
+
+/// Check if a language is data or markup (JSON, YAML, Markdown, ...) rather than a
+/// programming language, so it shouldn't decide what a project is written in
+pub fn is_data_language(display_name: &str) -> bool {
+    matches!(display_name, "JSON" | "Markdown" | "TOML" | "XML" | "YAML")
+}
+
+/// Picks the language most files are written in, ignoring data and markup files unless
+/// nothing else was recognized. Ties go to the alphabetically first language.
+pub fn detect_primary_language<I, P>(paths: I) -> Option<&'static str>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut counts: HashMap<&'static str, usize> = HashMap::new();
+    for path in paths {
+        if let Some(language) = detect_language_for_path(path) {
+            *counts.entry(language).or_default() += 1;
+        }
+    }
+
+    let pick = |data: bool| {
+        counts.iter()
+            .filter(|(language, _)| is_data_language(language) == data)
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(language, _)| *language)
+    };
+    pick(false).or_else(|| pick(true))
+}
+
+/// Suggests known languages close to an unrecognized identifier (typos, partial names),
+/// closest first
+pub fn suggest_languages(identifier: &str) -> Vec<&'static str> {
+    const MAX_SUGGESTIONS: usize = 3;
+
+    let lower = identifier.to_lowercase();
+    let max_distance = if lower.chars().count() <= 3 { 1 } else { 2 };
+
+    let mut candidates: Vec<(usize, &'static str)> = get_language_map()
+        .into_iter()
+        .flat_map(|(alias, lang)| [(alias.to_string(), lang.display_name), (lang.display_name.to_lowercase(), lang.display_name)])
+        .filter_map(|(name, display_name)| {
+            let distance = edit_distance(&lower, &name);
+            let is_prefix = lower.len() >= 2 && name.starts_with(&lower);
+            (distance <= max_distance || is_prefix).then_some((distance, display_name))
+        })
+        .collect();
+
+    candidates.sort_unstable();
+    let mut suggestions = Vec::new();
+    for (_, display_name) in candidates {
+        if !suggestions.contains(&display_name) {
+            suggestions.push(display_name);
+        }
+    }
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+/// Levenshtein distance between two strings
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,4 +274,37 @@ mod tests {
         assert_eq!(get_display_name("unknown-lang"), "unknown-lang");
         assert!(!is_known_language("unknown-lang"));
     }
+
+    #[test]
+    fn test_suggestions() {
+        assert_eq!(suggest_languages("rsut").first(), Some(&"Rust"));
+        assert_eq!(suggest_languages("pyhton").first(), Some(&"Python"));
+        assert!(suggest_languages("typesc").contains(&"TypeScript"));
+        assert!(suggest_languages("zzzzzz").is_empty());
+    }
+
+    #[test]
+    fn test_detect_language_for_path() {
+        assert_eq!(detect_language_for_path("src/main.rs"), Some("Rust"));
+        assert_eq!(detect_language_for_path("app/Main.KT"), Some("Kotlin"));
+        assert_eq!(detect_language_for_path("Dockerfile"), Some("Dockerfile"));
+        assert_eq!(detect_language_for_path("build/CMakeLists.txt"), Some("CMake"));
+        assert_eq!(detect_language_for_path("notes.txt"), None);
+        assert_eq!(detect_language_for_path("LICENSE"), None);
+    }
+
+    #[test]
+    fn test_detected_languages_are_known() {
+        for (_, language) in EXTENSIONS.iter().chain(FILE_NAMES) {
+            assert!(is_known_language(language), "{language} is not in the language map");
+        }
+    }
+
+    #[test]
+    fn test_detect_primary_language() {
+        let files = ["Cargo.toml", "README.md", "docs/a.md", "docs/b.md", "src/main.rs", "src/lib.rs"];
+        assert_eq!(detect_primary_language(files), Some("Rust"));
+        assert_eq!(detect_primary_language(["a.yaml", "b.yml", "c.json"]), Some("YAML"));
+        assert_eq!(detect_primary_language(["LICENSE"]), None);
+    }
 }
@@ -0,0 +1,84 @@
+//! Discover and apply templates from other tools (editors, GUIs, build scripts) without shelling
+//! out to the `template` command.
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use template_rs::{ApplyOptions, TemplateStore};
+//!
+//! let store = TemplateStore::open()?;
+//! for template in store.templates()? {
+//!     println!("{}: {}", template.name, template.description().unwrap_or_default());
+//! }
+//!
+//! let options = ApplyOptions { create_dir: true, yes: true, ..ApplyOptions::default() };
+//! let report = store.apply("builtin/rust-bin", Path::new("my-app"), &options)?;
+//! println!("Wrote {} file(s)", report.written.len());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod cli;
+
+mod path;
+mod config;
+mod copy;
+mod file;
+mod git;
+mod archive;
+mod builtin;
+mod categories;
+mod checks;
+mod history;
+mod import;
+mod hooks;
+mod index;
+mod commands;
+mod compat;
+mod encoding;
+mod eol;
+mod error;
+mod events;
+mod template;
+mod languages;
+mod lock;
+mod markdown;
+mod merge;
+mod names;
+mod migrate;
+mod objects;
+mod oci;
+mod onboarding;
+mod origin;
+mod overrides;
+mod output;
+mod patch;
+mod patterns;
+mod placeholders;
+mod plugins;
+mod preset;
+mod prerequisites;
+mod prompt;
+mod receipt;
+mod regions;
+mod remote;
+mod render;
+mod registry;
+mod search;
+mod store;
+mod tags;
+mod theme;
+mod timing;
+mod tui;
+mod variables;
+mod versions;
+mod workspace;
+
+pub use commands::init::{ApplyOptions, ApplyReport};
+pub use copy::Timestamps;
+pub use error::TemplateError;
+pub use path::resolve_path;
+pub use receipt::ReceiptTarget;
+pub use store::TemplateStore;
+pub use template::{Template, TemplateConfig};
+
+/// Name of the command-line binary, for messages suggesting a command to run
+pub(crate) const BIN_NAME: &str = "template";
@@ -0,0 +1,156 @@
+use crate::error::TemplateError;
+use crate::history::generation_secs;
+use crate::timing::{Phase, span};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const TEMPLATE_LOCK_FILE: &str = ".template.lock";
+
+/// Version of the lock format written by this build
+pub const LOCK_SCHEMA_VERSION: u32 = 1;
+
+/// Record of a template application, stored as .template.lock in the target directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateLock {
+    /// Lock format version (0 for files written before versioning, upgraded when loaded)
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Template name (storage path) that was applied
+    pub template: String,
+    /// Template version at the time it was applied
+    pub version: Option<String>,
+    /// Version of this tool that applied the template
+    pub tool_version: String,
+    /// Unix timestamp (seconds) of the last application
+    pub applied_at: u64,
+    /// Options the template was applied with
+    #[serde(default)]
+    pub options: LockOptions,
+    /// Variable values the template was applied with
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    /// Relative file paths mapped to the SHA-256 of the template content that was applied
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+/// Apply options recorded in the lock file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockOptions {
+    pub force: bool,
+    pub delete: bool,
+    #[serde(default = "default_true")]
+    pub default_excludes: bool,
+}
+
+impl Default for LockOptions {
+    fn default() -> Self {
+        Self { force: false, delete: false, default_excludes: true }
+    }
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+impl TemplateLock {
+    /// Creates a new lock record stamped with the current time and tool version
+    pub fn new(template: &str, version: Option<&str>) -> Self {
+        let applied_at = generation_secs();
+
+        Self {
+            schema_version: LOCK_SCHEMA_VERSION,
+            template: template.to_string(),
+            version: version.map(String::from),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            applied_at,
+            options: LockOptions::default(),
+            variables: BTreeMap::new(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Loads the lock file from a target directory, if one exists
+    pub fn load<P: AsRef<Path>>(target_dir: P) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let _timing = span(Phase::Config);
+        let lock_path = target_dir.as_ref().join(TEMPLATE_LOCK_FILE);
+        if !lock_path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&lock_path)?;
+        let mut lock: Self = toml::from_str(&content)
+            .map_err(|e| TemplateError::Config(format!("Failed to parse {}: {e}", lock_path.display())))?;
+        if lock.schema_version > LOCK_SCHEMA_VERSION {
+            return Err(TemplateError::Config(format!(
+                "{} was written by a newer version of {} (lock schema version {}); upgrade to use it",
+                lock_path.display(),
+                env!("CARGO_PKG_NAME"),
+                lock.schema_version
+            )).into());
+        }
+        // Version 0 locks only lack the stamp, they are rewritten as current on the next save
+        lock.schema_version = LOCK_SCHEMA_VERSION;
+        Ok(Some(lock))
+    }
+
+    /// Writes the lock file into a target directory
+    pub fn save<P: AsRef<Path>>(&self, target_dir: P) -> Result<(), Box<dyn std::error::Error>> {
+        let lock_path = target_dir.as_ref().join(TEMPLATE_LOCK_FILE);
+        fs::write(lock_path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    /// The lock file's content
+    pub fn to_toml(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+}
+
+/// Computes the hex-encoded SHA-256 digest of some content
+pub fn hash_bytes(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_bytes() {
+        assert_eq!(
+            hash_bytes(b"hello"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[test]
+    fn test_lock_roundtrip() {
+        let mut lock = TemplateLock::new("rust/cli", Some("1.2.0"));
+        lock.options.force = true;
+        lock.variables.insert("project_name".to_string(), "demo".to_string());
+        lock.files.insert("src/main.rs".to_string(), hash_bytes(b"fn main() {}"));
+
+        let content = toml::to_string_pretty(&lock).unwrap();
+        let parsed: TemplateLock = toml::from_str(&content).unwrap();
+
+        assert_eq!(parsed.template, "rust/cli");
+        assert_eq!(parsed.version.as_deref(), Some("1.2.0"));
+        assert!(parsed.options.force);
+        assert_eq!(parsed.variables.get("project_name").map(String::as_str), Some("demo"));
+        assert_eq!(parsed.files.len(), 1);
+    }
+
+    #[test]
+    fn test_broken_lock_is_config_error() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(TEMPLATE_LOCK_FILE), "template = [").unwrap();
+        let error = TemplateLock::load(dir.path()).unwrap_err();
+        assert!(matches!(error.downcast_ref::<TemplateError>(), Some(TemplateError::Config(_))), "{error}");
+    }
+}
@@ -1,82 +1,55 @@
-use clap::Parser;
-use clap::builder::styling::{AnsiColor, Effects, Styles};
+use template_rs::TemplateError;
 
-// Declare modules
-mod path;
-mod file;
-mod commands;
-mod template;
-mod languages;
-
-// Import from modules
-use file::ensure_all_storage_dirs;
-use commands::{Commands, handle_command};
-
-const VERSION: &str = env!("CARGO_PKG_VERSION");
-const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
-const LICENSE: &str = include_str!("../LICENSE");
-
-fn custom_styles() -> Styles {
-    Styles::styled()
-        .header(AnsiColor::Green.on_default() | Effects::BOLD)
-        .usage(AnsiColor::Green.on_default() | Effects::BOLD)
-        .literal(AnsiColor::Cyan.on_default() | Effects::BOLD)
-        .placeholder(AnsiColor::Blue.on_default())
-        .error(AnsiColor::Red.on_default() | Effects::BOLD)
-        .valid(AnsiColor::Cyan.on_default() | Effects::BOLD)
-        .invalid(AnsiColor::Yellow.on_default() | Effects::BOLD)
-}
-
-#[derive(Parser)]
-#[command(name = env!("CARGO_BIN_NAME"))]
-#[command(version = VERSION)]
-#[command(author = AUTHORS)]
-#[command(about = "A Rust CLI tool for managing programming templates")]
-#[command(arg_required_else_help = true)]
-#[command(styles = custom_styles())]
-struct Cli {
-    /// Display detailed information about this tool
-    #[arg(long)]
-    about: bool,
-
-    /// Display the license information
-    #[arg(long)]
-    license: bool,
-    
-    #[command(subcommand)]
-    command: Option<Commands>,
-}
+use std::error::Error;
+use std::io;
 
 fn main() {
-    // Ensure storage directories exist at startup
-    if let Err(e) = ensure_all_storage_dirs() {
-        eprintln!("Error creating storage directories: {e}");
-        std::process::exit(1);
-    }
-
-    let cli = Cli::parse();
-
-    // Handle about flag first
-    if cli.about {
-        println!("template-rs v{VERSION}");
-        println!("Created by: {AUTHORS}\n");
-        println!("🌐 Website: https://matthewgreen.gg/");
-        println!("📦 GitHub: https://github.com/greenmatthew/template-rs");
-        println!("🔧 Gitea: https://git.matthewgreen.gg/mgreen/template-rs\n");
-        println!("Use `{} --license` to view the license", env!("CARGO_BIN_NAME"));
-        return;
+    if let Err(e) = template_rs::cli::run() {
+        eprintln!("Error: {e}");
+        std::process::exit(exit_code(e.as_ref()));
     }
+}
 
-    // Handle license flag first
-    if cli.license {
-        println!("{LICENSE}");
-        return;
+/// The exit status of a failed command, see [`TemplateError`]. IO errors that aren't wrapped in
+/// one count as [`TemplateError::Io`].
+fn exit_code(error: &(dyn Error + 'static)) -> i32 {
+    let Some(error) = error.downcast_ref::<TemplateError>() else {
+        return if error.is::<io::Error>() { 5 } else { 1 };
+    };
+    match error {
+        TemplateError::NotFound(_) => 3,
+        TemplateError::Config(_) => 4,
+        TemplateError::Io(_) => 5,
+        TemplateError::Copy(_) => 6,
+        TemplateError::Aborted(_) => 7,
+        TemplateError::Prerequisites(_) => 8,
+        TemplateError::CheckFailed(_) => 9,
     }
+}
 
-    // Handle subcommands
-    if let Some(command) = cli.command
-        && let Err(e) = handle_command(command) {
-            eprintln!("Error: {e}");
-            std::process::exit(1);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        let codes = [
+            (TemplateError::NotFound("app".to_string()), 3),
+            (TemplateError::Config("bad".to_string()), 4),
+            (TemplateError::Io(io::Error::other("disk")), 5),
+            (TemplateError::Copy("partial".to_string()), 6),
+            (TemplateError::Aborted("declined".to_string()), 7),
+            (TemplateError::Prerequisites("missing".to_string()), 8),
+            (TemplateError::CheckFailed("cargo check".to_string()), 9),
+        ];
+        for (error, code) in codes {
+            let boxed: Box<dyn Error> = error.into();
+            assert_eq!(exit_code(boxed.as_ref()), code, "{boxed}");
         }
+
+        let io_error: Box<dyn Error> = io::Error::other("disk").into();
+        assert_eq!(exit_code(io_error.as_ref()), 5);
+        let other: Box<dyn Error> = "something else".into();
+        assert_eq!(exit_code(other.as_ref()), 1);
+    }
 }
@@ -0,0 +1,161 @@
+use clap::builder::styling::{AnsiColor, Style};
+
+/// Renders markdown for the terminal: headings, lists, block quotes, rules, code blocks and
+/// inline code, emphasis and links. Without `styled` no escape codes are written and headings
+/// are underlined with '=' or '-' instead.
+pub fn render_markdown(text: &str, styled: bool) -> String {
+    let code_style = Style::new().fg_color(Some(AnsiColor::Cyan.into()));
+    let quote_style = Style::new().dimmed();
+    let mut lines = Vec::new();
+    let mut in_code = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.push(format!("    {}", paint(line, code_style, styled)));
+            continue;
+        }
+
+        let indent = &line[..line.len() - trimmed.len()];
+        if let Some((level, heading)) = heading(trimmed) {
+            let heading = render_inline(heading, styled);
+            if styled {
+                let style = if level == 1 { Style::new().bold().underline() } else { Style::new().bold() };
+                lines.push(paint(&heading, style, true));
+            } else {
+                let width = heading.chars().count();
+                lines.push(heading);
+                match level {
+                    1 => lines.push("=".repeat(width)),
+                    2 => lines.push("-".repeat(width)),
+                    _ => {}
+                }
+            }
+        } else if is_rule(trimmed) {
+            lines.push("─".repeat(40));
+        } else if let Some(quoted) = trimmed.strip_prefix('>') {
+            let quoted = render_inline(quoted.trim_start(), styled);
+            lines.push(format!("{indent}│ {}", paint(&quoted, quote_style, styled)));
+        } else if let Some(item) = ["- ", "* ", "+ "].iter().find_map(|marker| trimmed.strip_prefix(marker)) {
+            lines.push(format!("{indent}• {}", render_inline(item, styled)));
+        } else {
+            lines.push(format!("{indent}{}", render_inline(trimmed, styled)));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Level and text of an ATX heading (`## Usage`)
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let text = line[level..].strip_prefix(' ')?;
+    Some((level, text.trim().trim_end_matches('#').trim_end()))
+}
+
+/// Thematic break: three or more '-', '*' or '_' (spaces allowed)
+fn is_rule(line: &str) -> bool {
+    let chars: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    chars.len() >= 3 && ['-', '*', '_'].iter().any(|&marker| chars.iter().all(|&c| c == marker))
+}
+
+/// Renders code spans, strong and emphasized text and links within one line. Unstyled, code
+/// spans keep their backticks and links are followed by their target in parentheses.
+fn render_inline(text: &str, styled: bool) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let span = match c {
+            '`' => delimited(rest, "`").map(|(inner, len)| {
+                let code = if styled { inner.to_string() } else { format!("`{inner}`") };
+                (paint(&code, Style::new().fg_color(Some(AnsiColor::Cyan.into())), styled), len)
+            }),
+            '*' | '_' if rest[1..].starts_with(c) => delimited(rest, &rest[..2])
+                .map(|(inner, len)| (paint(&render_inline(inner, styled), Style::new().bold(), styled), len)),
+            '*' | '_' if !output.ends_with(|p: char| p.is_alphanumeric()) => delimited(rest, &rest[..1])
+                .map(|(inner, len)| (paint(&render_inline(inner, styled), Style::new().italic(), styled), len)),
+            '[' => link(rest).map(|(label, target, len)| {
+                let label = paint(&render_inline(label, styled), Style::new().underline(), styled);
+                (format!("{label} ({target})"), len)
+            }),
+            _ => None,
+        };
+        match span {
+            Some((rendered, len)) => {
+                output.push_str(&rendered);
+                rest = &rest[len..];
+            }
+            None => {
+                output.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    output
+}
+
+/// Text between `delimiter` at the start of `text` and its next occurrence, with the length
+/// consumed; `None` when unclosed or empty
+fn delimited<'a>(text: &'a str, delimiter: &str) -> Option<(&'a str, usize)> {
+    let inner_start = delimiter.len();
+    let inner_len = text[inner_start..].find(delimiter)?;
+    if inner_len == 0 {
+        return None;
+    }
+    Some((&text[inner_start..inner_start + inner_len], inner_start + inner_len + delimiter.len()))
+}
+
+/// Label and target of an inline link `[label](target)`, with the length consumed
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = text.find("](")?;
+    let target_end = label_end + 2 + text[label_end + 2..].find(')')?;
+    Some((&text[1..label_end], &text[label_end + 2..target_end], target_end + 1))
+}
+
+fn paint(text: &str, style: Style, styled: bool) -> String {
+    if styled {
+        format!("{style}{text}{style:#}")
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_blocks_unstyled() {
+        let markdown = "# Usage\n\nRun it:\n\n```sh\ncargo run\n```\n\n- one\n  * two\n> note\n---\n### Notes ###";
+        assert_eq!(
+            render_markdown(markdown, false),
+            "Usage\n=====\n\nRun it:\n\n    cargo run\n\n• one\n  • two\n│ note\n────────────────────────────────────────\nNotes"
+        );
+    }
+
+    #[test]
+    fn test_render_inline_unstyled() {
+        assert_eq!(
+            render_inline("Use `make` with **care**, see [docs](https://example.com) and *this*", false),
+            "Use `make` with care, see docs (https://example.com) and this"
+        );
+        // Intraword underscores and unclosed markers stay as written
+        assert_eq!(render_inline("snake_case_name costs 2 * 3", false), "snake_case_name costs 2 * 3");
+        assert_eq!(render_inline("#hashtag", false), "#hashtag");
+    }
+
+    #[test]
+    fn test_render_styled() {
+        let rendered = render_markdown("## Title\nsome `code`", true);
+        assert!(rendered.contains("\u{1b}["));
+        assert!(!rendered.contains('`'));
+        assert!(!rendered.contains("##"));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::lock::hash_bytes;
+use crate::regions::regenerate_regions;
+
+use std::fmt;
+
+/// How a single file will be affected by an upgrade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeOutcome {
+    /// Template changed and the local file is untouched (or missing), so it can be replaced
+    CleanUpdate,
+    /// Nothing to do: the local file already matches, or the template didn't change it
+    AlreadyCurrent,
+    /// Both the template and the local file changed, and a three-way merge succeeds
+    LocallyModified,
+    /// Both changed and the changes overlap (or no merge base is available)
+    Conflict,
+}
+
+impl MergeOutcome {
+    /// Short label used in upgrade reports
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::CleanUpdate => "clean-update",
+            Self::AlreadyCurrent => "already-current",
+            Self::LocallyModified => "locally-modified",
+            Self::Conflict => "conflict",
+        }
+    }
+}
+
+impl fmt::Display for MergeOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Result of planning a file upgrade: its outcome and the content to write, if any.
+/// For conflicts the content holds the merge with conflict markers, when one could be produced.
+#[derive(Debug, Clone)]
+pub struct FileMerge {
+    pub outcome: MergeOutcome,
+    pub content: Option<Vec<u8>>,
+}
+
+impl FileMerge {
+    const fn new(outcome: MergeOutcome, content: Option<Vec<u8>>) -> Self {
+        Self { outcome, content }
+    }
+}
+
+/// Classifies a file for upgrade using the recorded base, the local copy, and the new template content.
+/// When both sides changed and the file has managed regions, the regions are regenerated and
+/// the template's changes outside them are merged like any other file's.
+///
+/// # Arguments
+/// * `base_hash` - Hash of the template content last applied (from .template.lock)
+/// * `base` - The last applied content itself, if it is still in the object store
+/// * `local` - The file currently in the project, if it exists
+/// * `upstream` - The file in the current version of the template
+pub fn plan_file_merge(
+    base_hash: Option<&str>,
+    base: Option<&[u8]>,
+    local: Option<&[u8]>,
+    upstream: &[u8],
+) -> FileMerge {
+    let Some(local) = local else {
+        return FileMerge::new(MergeOutcome::CleanUpdate, Some(upstream.to_vec()));
+    };
+
+    if local == upstream {
+        return FileMerge::new(MergeOutcome::AlreadyCurrent, None);
+    }
+
+    if let Some(base_hash) = base_hash {
+        if hash_bytes(local) == base_hash {
+            return FileMerge::new(MergeOutcome::CleanUpdate, Some(upstream.to_vec()));
+        }
+
+        if hash_bytes(upstream) == base_hash {
+            // Only the local copy changed; keep the user's edits
+            return FileMerge::new(MergeOutcome::AlreadyCurrent, None);
+        }
+    }
+
+    // Files with managed regions get the region bodies rewritten, then the rest is merged with
+    // the base's regions regenerated too, so only changes outside the regions can conflict
+    if let (Ok(local_text), Ok(upstream_text)) = (str::from_utf8(local), str::from_utf8(upstream))
+        && let Some(regenerated) = regenerate_regions(local_text, upstream_text)
+    {
+        let merged = if regenerated == upstream_text {
+            regenerated
+        } else {
+            let Some(base) = base.and_then(|base| str::from_utf8(base).ok()) else {
+                // Without a base there is no telling whose the differences outside the regions are
+                return FileMerge::new(MergeOutcome::Conflict, None);
+            };
+            let base = regenerate_regions(base, upstream_text).unwrap_or_else(|| base.to_string());
+            match diffy::merge(&base, &regenerated, upstream_text) {
+                Ok(merged) => merged,
+                Err(marked) => return FileMerge::new(MergeOutcome::Conflict, Some(marked.into_bytes())),
+            }
+        };
+        return if merged.as_bytes() == local {
+            FileMerge::new(MergeOutcome::AlreadyCurrent, None)
+        } else {
+            FileMerge::new(MergeOutcome::LocallyModified, Some(merged.into_bytes()))
+        };
+    }
+
+    if base_hash.is_none() {
+        // File is new in the template but something else already lives at that path
+        return FileMerge::new(MergeOutcome::Conflict, None);
+    }
+
+    match base {
+        Some(base) if !is_binary(base) && !is_binary(local) && !is_binary(upstream) => {
+            match diffy::merge_bytes(base, local, upstream) {
+                Ok(merged) => FileMerge::new(MergeOutcome::LocallyModified, Some(merged)),
+                Err(marked) => FileMerge::new(MergeOutcome::Conflict, Some(marked)),
+            }
+        }
+        _ => FileMerge::new(MergeOutcome::Conflict, None),
+    }
+}
+
+/// Heuristic binary detection: any NUL byte in the first 8 KiB
+pub fn is_binary(content: &[u8]) -> bool {
+    content.iter().take(8192).any(|&b| b == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &[u8] = b"one\ntwo\nthree\nfour\nfive\n";
+
+    #[test]
+    fn test_missing_local_is_clean_update() {
+        let plan = plan_file_merge(None, None, None, b"new\n");
+        assert_eq!(plan.outcome, MergeOutcome::CleanUpdate);
+        assert_eq!(plan.content.as_deref(), Some(&b"new\n"[..]));
+    }
+
+    #[test]
+    fn test_identical_is_already_current() {
+        let plan = plan_file_merge(Some(&hash_bytes(BASE)), Some(BASE), Some(b"x\n"), b"x\n");
+        assert_eq!(plan.outcome, MergeOutcome::AlreadyCurrent);
+    }
+
+    #[test]
+    fn test_unmodified_local_is_clean_update() {
+        let plan = plan_file_merge(Some(&hash_bytes(BASE)), Some(BASE), Some(BASE), b"changed\n");
+        assert_eq!(plan.outcome, MergeOutcome::CleanUpdate);
+    }
+
+    #[test]
+    fn test_unchanged_template_keeps_local_edits() {
+        let plan = plan_file_merge(Some(&hash_bytes(BASE)), Some(BASE), Some(b"mine\n"), BASE);
+        assert_eq!(plan.outcome, MergeOutcome::AlreadyCurrent);
+        assert!(plan.content.is_none());
+    }
+
+    #[test]
+    fn test_non_overlapping_changes_merge() {
+        let local = b"ONE\ntwo\nthree\nfour\nfive\n";
+        let upstream = b"one\ntwo\nthree\nfour\nFIVE\n";
+        let plan = plan_file_merge(Some(&hash_bytes(BASE)), Some(BASE), Some(local), upstream);
+        assert_eq!(plan.outcome, MergeOutcome::LocallyModified);
+        assert_eq!(plan.content.as_deref(), Some(&b"ONE\ntwo\nthree\nfour\nFIVE\n"[..]));
+    }
+
+    #[test]
+    fn test_overlapping_changes_conflict() {
+        let local = b"one\nTWO\nthree\nfour\nfive\n";
+        let upstream = b"one\n2\nthree\nfour\nfive\n";
+        let plan = plan_file_merge(Some(&hash_bytes(BASE)), Some(BASE), Some(local), upstream);
+        assert_eq!(plan.outcome, MergeOutcome::Conflict);
+
+        let marked = String::from_utf8(plan.content.unwrap()).unwrap();
+        assert!(marked.contains("<<<<<<< ours\nTWO\n"));
+        assert!(marked.contains("=======\n2\n>>>>>>> theirs\n"));
+    }
+
+    #[test]
+    fn test_managed_regions_are_regenerated() {
+        let base = b"# template-rs:begin\nold\n# template-rs:end\n";
+        let local = b"mine\n# template-rs:begin\nold\n# template-rs:end\n";
+        let upstream = b"# template-rs:begin\nnew\n# template-rs:end\n";
+        let plan = plan_file_merge(Some(&hash_bytes(base)), Some(base), Some(local), upstream);
+        assert_eq!(plan.outcome, MergeOutcome::LocallyModified);
+        assert_eq!(plan.content.as_deref(), Some(&b"mine\n# template-rs:begin\nnew\n# template-rs:end\n"[..]));
+    }
+
+    #[test]
+    fn test_changes_outside_managed_regions_merge() {
+        let base = b"head\n# template-rs:begin\nold\n# template-rs:end\ntail\n";
+        let local = b"mine\n# template-rs:begin\nold\n# template-rs:end\ntail\n";
+        let upstream = b"head\n# template-rs:begin\nnew\n# template-rs:end\nTAIL\n";
+        let plan = plan_file_merge(Some(&hash_bytes(base)), Some(base), Some(local), upstream);
+        assert_eq!(plan.outcome, MergeOutcome::LocallyModified);
+        assert_eq!(plan.content.as_deref(), Some(&b"mine\n# template-rs:begin\nnew\n# template-rs:end\nTAIL\n"[..]));
+
+        // Overlapping changes outside the regions conflict, and so does a missing base
+        let upstream = b"theirs\n# template-rs:begin\nnew\n# template-rs:end\ntail\n";
+        let plan = plan_file_merge(Some(&hash_bytes(base)), Some(base), Some(local), upstream);
+        assert_eq!(plan.outcome, MergeOutcome::Conflict);
+        assert!(String::from_utf8(plan.content.unwrap()).unwrap().contains("<<<<<<< ours\nmine\n"));
+        let plan = plan_file_merge(Some(&hash_bytes(base)), None, Some(local), upstream);
+        assert_eq!(plan.outcome, MergeOutcome::Conflict);
+    }
+
+    #[test]
+    fn test_missing_base_conflicts() {
+        let plan = plan_file_merge(Some(&hash_bytes(BASE)), None, Some(b"mine\n"), b"theirs\n");
+        assert_eq!(plan.outcome, MergeOutcome::Conflict);
+        assert!(plan.content.is_none());
+    }
+}
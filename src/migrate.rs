@@ -0,0 +1,160 @@
+use crate::archive::{ArchiveFormat, create_archive_from};
+use crate::commands::backup::{library_entries, library_files};
+use crate::config::{CONFIG_SCHEMA_VERSION, USER_CONFIG_FILE, user_config_path};
+use crate::error::TemplateError;
+use crate::file::{create_dir_if_missing, ensure_backup_storage_dir, ensure_persistent_storage_dir};
+use crate::history::now_secs;
+use crate::path::{legacy_storage_dir, platform_dirs, relocated_dirs};
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::Path;
+
+/// Version of the storage layout written by this build
+pub const STORAGE_SCHEMA_VERSION: u32 = 1;
+/// File in the storage directory recording its layout version
+pub const STORAGE_SCHEMA_FILE: &str = "storage.toml";
+
+/// Contents of storage.toml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StorageSchema {
+    schema_version: u32,
+}
+
+/// A step upgrading storage to the version it is listed with
+type Migration = fn(&Path) -> Result<(), Box<dyn std::error::Error>>;
+
+/// Every migration in order; storage without storage.toml predates versioning and counts as version 0
+const MIGRATIONS: &[(u32, Migration)] = &[
+    (1, stamp_config_schema),
+];
+
+/// Moves storage from the legacy ~/.template-rs to the platform directories (see `path::platform_dirs`)
+/// the first time this build runs, with config.toml going to the config directory. Run at startup
+/// before anything resolves the storage directories; if the move fails, storage stays where it is
+/// and keeps being used from there. Storage relocated by $TEMPLATE_RS_HOME or the `storage`
+/// setting is never moved.
+pub fn migrate_legacy_storage() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(legacy) = legacy_storage_dir().filter(|_| relocated_dirs().is_none()) else {
+        return Ok(());
+    };
+    let platform = platform_dirs();
+    if !legacy.is_dir() || platform.data.exists() {
+        return Ok(());
+    }
+
+    if let Some(parent) = platform.data.parent() {
+        create_dir_if_missing(parent)?;
+    }
+    if let Err(e) = fs::rename(&legacy, &platform.data) {
+        eprintln!(
+            "Warning: could not move {} to {} ({e}), still using it from there",
+            legacy.display(),
+            platform.data.display()
+        );
+        return Ok(());
+    }
+
+    let legacy_config = platform.data.join(USER_CONFIG_FILE);
+    if platform.config != platform.data && legacy_config.exists() {
+        create_dir_if_missing(&platform.config)?;
+        fs::rename(&legacy_config, platform.config.join(USER_CONFIG_FILE))?;
+    }
+
+    eprintln!(
+        "Moved storage from {} to {} (config: {})",
+        legacy.display(),
+        platform.data.display(),
+        platform.config.display()
+    );
+    Ok(())
+}
+
+/// Brings the storage layout up to date, run on every startup. Storage with content is backed up
+/// to its backups directory first; storage written by a newer build is refused rather than damaged.
+pub fn migrate_storage() -> Result<(), Box<dyn std::error::Error>> {
+    let storage_dir = ensure_persistent_storage_dir()?;
+    let schema_path = storage_dir.join(STORAGE_SCHEMA_FILE);
+    let current = read_schema_version(&schema_path)?;
+
+    if current == STORAGE_SCHEMA_VERSION {
+        return Ok(());
+    }
+    if current > STORAGE_SCHEMA_VERSION {
+        return Err(format!(
+            "{} uses storage schema version {current}, but this build only understands up to {STORAGE_SCHEMA_VERSION}. Upgrade {} to use it.",
+            storage_dir.display(),
+            env!("CARGO_PKG_NAME")
+        ).into());
+    }
+
+    // A fresh install has nothing to back up or announce
+    let files = library_files(&storage_dir)?;
+    if !files.is_empty() {
+        let backup_path = ensure_backup_storage_dir()?
+            .join(format!("pre-migration-v{current}-{}.tar.zst", now_secs()));
+        create_archive_from(ArchiveFormat::Zstd, &library_entries(&storage_dir, &files)?, &backup_path)?;
+        eprintln!(
+            "Migrating {} from schema version {current} to {STORAGE_SCHEMA_VERSION} (backup: {})",
+            storage_dir.display(),
+            backup_path.display()
+        );
+    }
+
+    for (version, migration) in MIGRATIONS.iter().filter(|(version, _)| *version > current) {
+        migration(&storage_dir)
+            .map_err(|e| format!("Migrating storage to schema version {version} failed: {e}"))?;
+        write_schema_version(&schema_path, *version)?;
+    }
+    Ok(())
+}
+
+fn read_schema_version(schema_path: &Path) -> Result<u32, Box<dyn std::error::Error>> {
+    if !schema_path.exists() {
+        return Ok(0);
+    }
+    let content = fs::read_to_string(schema_path)?;
+    let schema: StorageSchema = toml::from_str(&content)
+        .map_err(|e| TemplateError::Config(format!("Failed to parse {}: {e}", schema_path.display())))?;
+    Ok(schema.schema_version)
+}
+
+fn write_schema_version(schema_path: &Path, schema_version: u32) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(schema_path, toml::to_string(&StorageSchema { schema_version })?)?;
+    Ok(())
+}
+
+/// v1: stamp `schema_version` into an existing config.toml. The key is prepended as text so
+/// the user's comments and formatting survive (top-level keys must come before any table).
+fn stamp_config_schema(_storage_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let config_path = user_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&config_path)?;
+    let table: toml::Table = toml::from_str(&content)
+        .map_err(|e| TemplateError::Config(format!("Failed to parse {}: {e}", config_path.display())))?;
+    if !table.contains_key("schema_version") {
+        fs::write(&config_path, stamp(&content, CONFIG_SCHEMA_VERSION))?;
+    }
+    Ok(())
+}
+
+fn stamp(content: &str, schema_version: u32) -> String {
+    format!("schema_version = {schema_version}\n{content}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stamp_keeps_tables_valid() {
+        let content = "# my config\n[theme]\nascii = true\n";
+        let stamped: toml::Table = toml::from_str(&stamp(content, 1)).unwrap();
+        assert_eq!(stamped["schema_version"].as_integer(), Some(1));
+        assert_eq!(stamped["theme"]["ascii"].as_bool(), Some(true));
+    }
+}
@@ -0,0 +1,79 @@
+use crate::builtin::BUILTIN_PREFIX;
+
+/// Checks that `name` can be a template's name in storage: relative path components separated
+/// by '/', none of them empty, '.' or '..', and not under the built-in prefix
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.split('/').any(|part| part.is_empty() || part == "." || part == "..") {
+        return Err(format!("Invalid template name '{name}'"));
+    }
+    if format!("{name}/").starts_with(BUILTIN_PREFIX) {
+        return Err(format!("'{BUILTIN_PREFIX}' is reserved for the built-in templates, choose another name"));
+    }
+    Ok(())
+}
+
+/// Applies the naming policy to a name given for a new template: lowercase components of
+/// letters, digits, '-', '_' and '.', separated by '/'. Other characters and spaces become '-',
+/// so `Rust CLI/My App` becomes `rust-cli/my-app`. With `normalize` off the name is only checked
+/// with [`check_name`], for templates that need to keep an existing spelling.
+pub fn template_name(name: &str, normalize: bool) -> Result<String, Box<dyn std::error::Error>> {
+    let name = name.replace('\\', "/").trim_matches('/').to_string();
+    if !normalize {
+        check_name(&name)?;
+        return Ok(name);
+    }
+
+    let normalized = normalize_name(&name);
+    if normalized == name {
+        check_name(&name)?;
+    } else {
+        check_name(&normalized).map_err(|e| format!("'{name}' normalizes to '{normalized}': {e}"))?;
+        eprintln!("Using '{normalized}' as the template name, pass --no-normalize to keep '{name}'");
+    }
+    Ok(normalized)
+}
+
+/// Slugifies every component of a template name, dropping components left empty
+pub fn normalize_name(name: &str) -> String {
+    name.split(['/', '\\'])
+        .map(slugify)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn slugify(part: &str) -> String {
+    let mut slug = String::new();
+    for c in part.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    // Leading dots would hide the template, and '..' must not survive as a component
+    slug.trim_start_matches(['.', '-']).trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("Rust CLI/My  App"), "rust-cli/my-app");
+        assert_eq!(normalize_name("web//api_v2.1"), "web/api_v2.1");
+        assert_eq!(normalize_name("../.hidden/Ünïcode!"), "hidden/n-code");
+        assert_eq!(normalize_name("rust\\lib"), "rust/lib");
+    }
+
+    #[test]
+    fn test_template_name() {
+        assert_eq!(template_name("/Rust/Binary Crate/", true).unwrap(), "rust/binary-crate");
+        assert_eq!(template_name("Rust/Binary", false).unwrap(), "Rust/Binary");
+        assert!(template_name("Builtin/rust-bin", true).is_err());
+        assert!(template_name("builtin", false).is_err());
+        assert!(template_name("rust/../lib", false).is_err());
+        assert!(template_name("!!!", true).is_err());
+    }
+}
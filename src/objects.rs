@@ -0,0 +1,223 @@
+use crate::error::TemplateError;
+use crate::file::{create_dir_if_missing, ensure_cache_storage_dir, ensure_object_storage_dir};
+use crate::lock::hash_bytes;
+use crate::path::is_contained;
+use crate::timing::{Phase, span};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// File list of a packed template, whose contents live in the object store
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Relative file paths mapped to their stored content
+    #[serde(default)]
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+/// A file in a packed template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// SHA-256 of the content in the object store
+    pub hash: String,
+    /// Whether the file should be created executable
+    #[serde(default)]
+    pub executable: bool,
+}
+
+impl Manifest {
+    /// Loads a manifest file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let manifest = toml::from_str(&content)
+            .map_err(|e| TemplateError::Config(format!("Failed to parse {}: {e}", path.display())))?;
+        Ok(manifest)
+    }
+
+    /// Writes the manifest to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Writes every file in the manifest below a directory
+    pub fn materialize<P: AsRef<Path>>(&self, dest_dir: P) -> Result<(), Box<dyn std::error::Error>> {
+        let dest_dir = dest_dir.as_ref();
+        for (file, entry) in &self.files {
+            if !is_contained(file) {
+                return Err(format!("Manifest entry '{file}' escapes the template directory").into());
+            }
+            let content = load_blob(&entry.hash)?
+                .ok_or_else(|| format!("Object {} for '{file}' is missing from the object store", entry.hash))?;
+
+            let destination = dest_dir.join(file);
+            if let Some(parent) = destination.parent() {
+                create_dir_if_missing(parent)?;
+            }
+            fs::write(&destination, content)?;
+            if entry.executable {
+                set_executable(&destination)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Materializes the manifest into the cache (once per distinct manifest), returns the directory
+    pub fn materialize_cached(&self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let _timing = span(Phase::Io);
+        let key = hash_bytes(toml::to_string(self)?.as_bytes());
+        let cache_dir = ensure_cache_storage_dir()?.join("materialized");
+        let dest_dir = cache_dir.join(&key);
+        if dest_dir.exists() {
+            return Ok(dest_dir);
+        }
+
+        // Build in a scratch directory and move it into place so a partial copy is never reused
+        let scratch_dir = cache_dir.join(format!("{key}.tmp-{}", std::process::id()));
+        if scratch_dir.exists() {
+            fs::remove_dir_all(&scratch_dir)?;
+        }
+        create_dir_if_missing(&scratch_dir)?;
+        self.materialize(&scratch_dir)?;
+        fs::rename(&scratch_dir, &dest_dir)?;
+
+        Ok(dest_dir)
+    }
+}
+
+/// Check if a file has any executable permission bit set
+#[cfg(unix)]
+pub fn is_executable(path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(fs::metadata(path)?.permissions().mode() & 0o111 != 0)
+}
+
+/// Check if a file has any executable permission bit set
+#[cfg(not(unix))]
+pub fn is_executable(_path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+    Ok(false)
+}
+
+/// Marks a file executable for everyone who can read it
+#[cfg(unix)]
+fn set_executable(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    let mode = permissions.mode();
+    permissions.set_mode(mode | ((mode & 0o444) >> 2));
+    fs::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+/// Marks a file executable for everyone who can read it
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    Ok(())
+}
+
+/// Returns the path of a blob in the object store (objects/ab/cdef...)
+fn blob_path(hash: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if hash.len() < 3 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid object hash: {hash}").into());
+    }
+    let object_dir = ensure_object_storage_dir()?;
+    Ok(object_dir.join(&hash[..2]).join(&hash[2..]))
+}
+
+/// Stores content in the object store keyed by its SHA-256, returns the hash
+pub fn store_blob(content: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+    let _timing = span(Phase::Io);
+    let hash = hash_bytes(content);
+    write_blob(&blob_path(&hash)?, &hash, content)?;
+    Ok(hash)
+}
+
+/// Writes a blob unless an intact copy is already there. The content goes to a temporary file
+/// next to the blob and is renamed into place, so readers never see a partial object.
+fn write_blob(path: &Path, hash: &str, content: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    // Identical content is already stored under the same key, unless it was truncated or corrupted
+    if path.exists() && fs::read(path).is_ok_and(|existing| existing.len() == content.len() && hash_bytes(&existing) == hash) {
+        return Ok(());
+    }
+
+    let parent = path.parent().ok_or_else(|| format!("Invalid object path: {}", path.display()))?;
+    create_dir_if_missing(parent)?;
+    let mut staged = tempfile::NamedTempFile::new_in(parent)?;
+    staged.write_all(content)?;
+    staged.persist(path)?;
+    Ok(())
+}
+
+/// Check if a blob with this hash is in the object store
+pub fn has_blob(hash: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    Ok(blob_path(hash)?.exists())
+}
+
+/// Loads content from the object store, if a blob with this hash exists
+pub fn load_blob(hash: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+    let _timing = span(Phase::Io);
+    let path = blob_path(hash)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(fs::read(path)?))
+}
+
+/// Lists the hashes of every blob in the object store
+pub fn list_blobs() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let object_dir = ensure_object_storage_dir()?;
+    let mut hashes = Vec::new();
+    for prefix in fs::read_dir(object_dir)? {
+        let prefix = prefix?;
+        if !prefix.file_type()?.is_dir() {
+            continue;
+        }
+        let prefix_name = prefix.file_name().to_string_lossy().into_owned();
+        for blob in fs::read_dir(prefix.path())? {
+            let blob_name = blob?.file_name().to_string_lossy().into_owned();
+            // Skip blobs still being written by store_blob
+            if blob_name.chars().all(|c| c.is_ascii_hexdigit()) {
+                hashes.push(format!("{prefix_name}{blob_name}"));
+            }
+        }
+    }
+    hashes.sort();
+    Ok(hashes)
+}
+
+/// Deletes a blob from the object store, returns the number of bytes freed
+pub fn remove_blob(hash: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let path = blob_path(hash)?;
+    if !path.exists() {
+        return Ok(0);
+    }
+    let size = fs::metadata(&path)?.len();
+    fs::remove_file(&path)?;
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_blob_replaces_damaged_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = b"fn main() {}\n";
+        let hash = hash_bytes(content);
+        let path = dir.path().join(&hash[..2]).join(&hash[2..]);
+
+        write_blob(&path, &hash, content).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), content);
+
+        fs::write(&path, b"fn main").unwrap();
+        write_blob(&path, &hash, content).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), content);
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+}
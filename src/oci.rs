@@ -0,0 +1,111 @@
+use crate::archive::{ArchiveFormat, extract_archive};
+use crate::error::TemplateError;
+use crate::file::find_in_path;
+use crate::template::{Template, TemplateConfig, TEMPLATE_CONFIG_FILE};
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use tempfile::TempDir;
+
+/// Prefix of template names that are pulled from an OCI registry: `oci://registry/org/template:tag`
+pub const OCI_SCHEME: &str = "oci://";
+
+/// Directory of a container image that holds the template
+const IMAGE_TEMPLATE_DIR: &str = "/template";
+
+/// Container engines that can pull images, in order of preference
+const ENGINES: &[&str] = &["docker", "podman"];
+
+/// The registry reference of an `oci://` template name, None for other names
+pub fn oci_reference(name: &str) -> Option<&str> {
+    name.strip_prefix(OCI_SCHEME).filter(|reference| !reference.is_empty())
+}
+
+/// A template pulled from a registry into a temporary directory, which is removed when it is dropped
+pub struct PulledTemplate {
+    pub template: Template,
+    dir: TempDir,
+}
+
+/// Pulls the template published as `name` (`oci://...`). With oras installed the reference is
+/// pulled as an artifact, whose files are the template or a single .tar.gz/.tar.zst package of it;
+/// otherwise docker or podman pulls it as an image with the template in /template.
+pub fn pull_template(name: &str) -> Result<PulledTemplate, Box<dyn std::error::Error>> {
+    let reference = oci_reference(name).ok_or_else(|| format!("Invalid OCI reference '{name}'"))?;
+    // Cleans up the directory on every error below
+    let dir = tempfile::Builder::new().prefix("template-rs-oci-").tempdir()?;
+    let template = Template { name: name.to_string(), path: dir.path().to_path_buf(), config: TemplateConfig::default() };
+    let mut pulled = PulledTemplate { template, dir };
+
+    eprintln!("Pulling {reference}");
+    if find_in_path("oras").is_some() {
+        run(Command::new("oras").args(["pull", "--output"]).arg(pulled.dir.path()).arg(reference), "oras")?;
+        unpack_package(pulled.dir.path())?;
+    } else if let Some(engine) = ENGINES.iter().find(|engine| find_in_path(engine).is_some()) {
+        copy_from_image(engine, reference, pulled.dir.path())?;
+    } else {
+        return Err("Pulling oci:// templates needs oras, docker or podman on PATH".into());
+    }
+
+    let config_path = pulled.dir.path().join(TEMPLATE_CONFIG_FILE);
+    if !config_path.is_file() {
+        return Err(format!("{reference} has no {TEMPLATE_CONFIG_FILE}, so it isn't a template").into());
+    }
+    pulled.template.config = Template::parse_config(config_path)
+        .map_err(|e| TemplateError::Config(format!("{TEMPLATE_CONFIG_FILE} of {reference} is invalid: {e}")))?;
+    Ok(pulled)
+}
+
+/// Copies the template directory out of an image through a container that is never started
+fn copy_from_image(engine: &str, reference: &str, destination: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // The command is never run, it just lets images without one be created
+    let output = Command::new(engine).args(["create", reference, "template"]).stdin(Stdio::null()).output()
+        .map_err(|e| format!("Cannot run {engine}: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("{engine} failed: {}", String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    let container = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let result = run(
+        Command::new(engine).arg("cp").arg(format!("{container}:{IMAGE_TEMPLATE_DIR}/.")).arg(destination),
+        engine,
+    );
+    let _ = Command::new(engine).args(["rm", &container]).output();
+    result
+}
+
+/// Unpacks an artifact that holds nothing but a template package
+fn unpack_package(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    let [entry] = entries.as_slice() else {
+        return Ok(());
+    };
+    let path = entry.path();
+    if let Some(format) = ArchiveFormat::from_path(&path) {
+        extract_archive(format, &path, dir)?;
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+fn run(command: &mut Command, program: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let output = command.stdin(Stdio::null()).output().map_err(|e| format!("Cannot run {program}: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("{program} failed: {}", String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oci_reference() {
+        assert_eq!(oci_reference("oci://ghcr.io/org/rust-cli:1.2"), Some("ghcr.io/org/rust-cli:1.2"));
+        assert_eq!(oci_reference("oci://"), None);
+        assert_eq!(oci_reference("rust/cli"), None);
+    }
+}
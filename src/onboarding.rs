@@ -0,0 +1,127 @@
+use crate::builtin::{builtin_names, install_builtin_templates};
+use crate::commands::Commands;
+use crate::commands::completions::install_registration;
+use crate::config::{CONFIG_SCHEMA_VERSION, USER_CONFIG_FILE};
+use crate::file::{ensure_template_storage_dir, find_in_path};
+use crate::path::{legacy_storage_dir, platform_dirs, relocated_dirs, resolve_path};
+use crate::prompt::{ask, confirm};
+use crate::theme::Icon;
+
+use clap_complete::aot::Shell;
+use toml_edit::{DocumentMut, Item, Table, value};
+
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::process::Command;
+
+/// Variables the wizard fills in config.toml for every template that declares them
+const AUTHOR_VARIABLE: &str = "author";
+const EMAIL_VARIABLE: &str = "email";
+
+/// What the user chose during onboarding that can only be done once storage exists
+pub struct Onboarding {
+    install_starters: bool,
+    completions: Option<Shell>,
+}
+
+/// Check if `command` should set up this machine first: it needs storage, nothing is in storage,
+/// there is no config.toml, storage isn't relocated, and someone at a terminal asked for
+/// interactive output. Must be called before storage is resolved.
+pub fn is_first_run(command: Option<&Commands>, plain: bool) -> bool {
+    // Help, version and about output, completion scripts and the like never need storage
+    if !command.is_some_and(Commands::needs_storage) || plain {
+        return false;
+    }
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() || !io::stderr().is_terminal() {
+        return false;
+    }
+    let platform = platform_dirs();
+    relocated_dirs().is_none()
+        && !platform.data.exists()
+        && !platform.config.join(USER_CONFIG_FILE).exists()
+        && !legacy_storage_dir().is_some_and(|legacy| legacy.is_dir())
+}
+
+/// Asks where storage goes and who the user is, and writes the answers to a new config.toml.
+/// The rest (starter templates, completions) is asked now and done by `Onboarding::finish`
+/// once storage exists. End of input skips the remaining questions.
+pub fn run_wizard() -> Result<Onboarding, Box<dyn std::error::Error>> {
+    let platform = platform_dirs();
+    eprintln!("{} Welcome to {}! Nothing is set up yet, so a few questions first.", Icon::Package, env!("CARGO_PKG_NAME"));
+    eprintln!("Press Enter to take the suggestion in brackets, Ctrl-D to skip the rest.\n");
+
+    let default_storage = platform.data.display().to_string();
+    let storage = ask("Where should templates be stored", &default_storage)?;
+    let author = if storage.is_some() { ask("Your name, for templates' author variable", &git_identity("user.name"))? } else { None };
+    let email = if author.is_some() { ask("Your email, for templates' email variable", &git_identity("user.email"))? } else { None };
+    // The setting is relative to the config directory, the answer to where the user is
+    let storage = storage.filter(|storage| *storage != default_storage)
+        .map(|storage| resolve_path(&storage, None).map(|path| path.display().to_string()))
+        .transpose()?;
+    write_config(&platform.config, storage.as_deref(), author.as_deref(), email.as_deref())?;
+    if email.is_none() {
+        eprintln!();
+        return Ok(Onboarding { install_starters: false, completions: None });
+    }
+
+    let install_starters = confirm(&format!("Install the starter templates ({}) into storage?", builtin_names().join(", ")))?;
+    let completions = match Shell::from_env() {
+        Some(shell) if confirm(&format!("Set up {shell} completions?"))? => Some(shell),
+        _ => None,
+    };
+    Ok(Onboarding { install_starters, completions })
+}
+
+impl Onboarding {
+    /// Carries out the choices that need storage, reporting what was done
+    pub fn finish(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.install_starters {
+            let installed = install_builtin_templates(&ensure_template_storage_dir()?)?;
+            eprintln!("{} Installed {} starter templates: {}", Icon::Success, installed.len(), installed.join(", "));
+        }
+        if let Some(shell) = self.completions {
+            let bin = crate::BIN_NAME;
+            match install_registration(shell)? {
+                Some(path) => eprintln!("{} Wrote {shell} completions to {}", Icon::Success, path.display()),
+                None => eprintln!("{} Add this to your {shell} startup file: source <(COMPLETE={shell} {bin})", Icon::Note),
+            }
+        }
+        eprintln!("Setup done. Change it any time in {USER_CONFIG_FILE}; `{} list` shows your templates.\n", crate::BIN_NAME);
+        Ok(())
+    }
+}
+
+/// Writes the first config.toml, which also marks onboarding as done. Answers left empty are not written.
+fn write_config(config_dir: &Path, storage: Option<&str>, author: Option<&str>, email: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut document = DocumentMut::new();
+    document["schema_version"] = value(i64::from(CONFIG_SCHEMA_VERSION));
+    if let Some(storage) = storage {
+        document["storage"] = value(storage);
+    }
+    let mut variables = Table::new();
+    for (name, answer) in [(AUTHOR_VARIABLE, author), (EMAIL_VARIABLE, email)] {
+        if let Some(answer) = answer.filter(|answer| !answer.is_empty()) {
+            variables[name] = value(answer);
+        }
+    }
+    if !variables.is_empty() {
+        document["variables"] = Item::Table(variables);
+    }
+
+    fs::create_dir_all(config_dir)?;
+    fs::write(config_dir.join(USER_CONFIG_FILE), document.to_string())?;
+    Ok(())
+}
+
+/// A value of the user's git identity, to suggest; empty when git or the value is missing
+fn git_identity(key: &str) -> String {
+    if find_in_path("git").is_none() {
+        return String::new();
+    }
+    Command::new("git").args(["config", "--global", key]).output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
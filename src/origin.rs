@@ -0,0 +1,61 @@
+use crate::error::TemplateError;
+use crate::history::now_secs;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const TEMPLATE_ORIGIN_FILE: &str = ".template-origin.toml";
+
+/// Where an ejected template came from, stored as .template-origin.toml in the copy. The file
+/// hashes point into the object store, so the upstream content stays available for a diff even
+/// after the source changes or the binary is upgraded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateOrigin {
+    /// Template the copy was made from (e.g. builtin/rust-bin)
+    pub source: String,
+    /// Source template version at the time of the copy
+    pub version: Option<String>,
+    /// Version of this tool that made the copy
+    pub tool_version: String,
+    /// Unix timestamp (seconds) of the copy
+    pub ejected_at: u64,
+    /// Relative file paths mapped to the SHA-256 of the upstream content
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl TemplateOrigin {
+    /// Creates a new origin record stamped with the current time and tool version
+    pub fn new(source: &str, version: Option<&str>) -> Self {
+        Self {
+            source: source.to_string(),
+            version: version.map(String::from),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            ejected_at: now_secs(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Loads the origin record of a template directory, if it has one
+    pub fn load<P: AsRef<Path>>(template_dir: P) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let origin_path = template_dir.as_ref().join(TEMPLATE_ORIGIN_FILE);
+        if !origin_path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&origin_path)?;
+        let origin = toml::from_str(&content)
+            .map_err(|e| TemplateError::Config(format!("Failed to parse {}: {e}", origin_path.display())))?;
+        Ok(Some(origin))
+    }
+
+    /// Writes the origin record into a template directory
+    pub fn save<P: AsRef<Path>>(&self, template_dir: P) -> Result<(), Box<dyn std::error::Error>> {
+        let origin_path = template_dir.as_ref().join(TEMPLATE_ORIGIN_FILE);
+        fs::write(origin_path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether output is restricted to stable, unstyled, line-oriented records (`--plain`)
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Switches plain output on or off for the rest of the run
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+}
+
+/// Check if plain output was requested. Commands then print one tab-separated record
+/// per line on stdout and skip headers, blank spacer lines and hints.
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// Prints a plain record: fields joined by tabs, with tabs and line breaks inside fields
+/// replaced by spaces so every record stays on one line
+pub fn print_record(fields: &[&str]) {
+    println!("{}", format_record(fields));
+}
+
+fn format_record(fields: &[&str]) -> String {
+    fields.iter()
+        .map(|field| field.replace(['\t', '\n', '\r'], " "))
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_record() {
+        assert_eq!(format_record(&["ok", "rust/cli", "1024"]), "ok\trust/cli\t1024");
+        assert_eq!(format_record(&["a\tb", "multi\nline"]), "a b\tmulti line");
+    }
+}
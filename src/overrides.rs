@@ -0,0 +1,78 @@
+use crate::template::collect_files;
+
+use std::fs;
+use std::path::Path;
+
+/// Directory of a project holding its own versions of generated files. They are layered over the
+/// template on every apply and upgrade, so customizations survive refreshes.
+pub const TEMPLATE_OVERRIDES_DIR: &str = ".template-overrides";
+
+/// The project's overrides as paths relative to the project, sorted
+pub fn override_files(target_path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let overrides_dir = target_path.join(TEMPLATE_OVERRIDES_DIR);
+    let mut files = Vec::new();
+    if overrides_dir.is_dir() {
+        collect_files(&overrides_dir, &overrides_dir, &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The project's override of the file at `path` (relative to the project), if it has one
+pub fn override_content(target_path: &Path, path: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+    let override_path = target_path.join(TEMPLATE_OVERRIDES_DIR).join(path);
+    Ok(if override_path.is_file() { Some(fs::read(override_path)?) } else { None })
+}
+
+/// Copies the overrides over the project files, except the `skipped` paths, and returns the paths
+/// that changed. Files already matching their override are left alone.
+pub fn apply_overrides(target_path: &Path, skipped: &[String], dry_run: bool) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut changed = Vec::new();
+    for path in override_files(target_path)? {
+        if skipped.contains(&path) {
+            continue;
+        }
+        let content = fs::read(target_path.join(TEMPLATE_OVERRIDES_DIR).join(&path))?;
+        let destination = target_path.join(&path);
+        if fs::read(&destination).is_ok_and(|current| current == content) {
+            continue;
+        }
+        if !dry_run {
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&destination, content)?;
+        }
+        changed.push(path);
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_apply_overrides() {
+        let root = env::temp_dir().join(format!("template-rs-overrides-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join(TEMPLATE_OVERRIDES_DIR).join("ci")).unwrap();
+        fs::write(root.join(TEMPLATE_OVERRIDES_DIR).join("README.md"), "custom").unwrap();
+        fs::write(root.join(TEMPLATE_OVERRIDES_DIR).join("ci/build.yml"), "steps: []").unwrap();
+        fs::write(root.join(TEMPLATE_OVERRIDES_DIR).join("same.txt"), "same").unwrap();
+        fs::write(root.join("README.md"), "generated").unwrap();
+        fs::write(root.join("same.txt"), "same").unwrap();
+
+        assert_eq!(apply_overrides(&root, &[], true).unwrap(), ["README.md", "ci/build.yml"]);
+        assert_eq!(fs::read_to_string(root.join("README.md")).unwrap(), "generated");
+
+        let skipped = ["ci/build.yml".to_string()];
+        assert_eq!(apply_overrides(&root, &skipped, false).unwrap(), ["README.md"]);
+        assert_eq!(fs::read_to_string(root.join("README.md")).unwrap(), "custom");
+        assert!(!root.join("ci").exists());
+        assert_eq!(override_content(&root, "same.txt").unwrap().as_deref(), Some(&b"same"[..]));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
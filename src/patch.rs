@@ -0,0 +1,92 @@
+use diffy::DiffOptions;
+
+use std::fmt::Write;
+
+/// One file in a patch: content before (None when the file is created) and after (None when
+/// it is deleted)
+#[derive(Debug, Clone)]
+pub struct FileChange {
+    /// Relative path, forward slashes
+    pub path: String,
+    pub old: Option<Vec<u8>>,
+    pub new: Option<Vec<u8>>,
+    /// Whether the file is executable, for the mode of created and deleted files
+    pub executable: bool,
+}
+
+/// Formats changes as a git-style patch that `git apply` (or `patch -p1`) accepts. Files that
+/// aren't UTF-8 are only named, as git does for binary files without `--binary`.
+pub fn format_git_patch(changes: &[FileChange]) -> String {
+    let mut patch = String::new();
+    for change in changes {
+        let path = &change.path;
+        let mode = if change.executable { "100755" } else { "100644" };
+        let _ = writeln!(patch, "diff --git a/{path} b/{path}");
+        match (&change.old, &change.new) {
+            (None, _) => {
+                let _ = writeln!(patch, "new file mode {mode}");
+            }
+            (_, None) => {
+                let _ = writeln!(patch, "deleted file mode {mode}");
+            }
+            _ => {}
+        }
+
+        let old = change.old.as_deref().unwrap_or_default();
+        let new = change.new.as_deref().unwrap_or_default();
+        let (Ok(old_text), Ok(new_text)) = (std::str::from_utf8(old), std::str::from_utf8(new)) else {
+            let original = if change.old.is_some() { format!("a/{path}") } else { "/dev/null".to_string() };
+            let modified = if change.new.is_some() { format!("b/{path}") } else { "/dev/null".to_string() };
+            let _ = writeln!(patch, "Binary files {original} and {modified} differ");
+            continue;
+        };
+
+        let mut options = DiffOptions::new();
+        options.set_original_filename(if change.old.is_some() { format!("a/{path}") } else { "/dev/null".to_string() });
+        options.set_modified_filename(if change.new.is_some() { format!("b/{path}") } else { "/dev/null".to_string() });
+        let diff = options.create_patch(old_text, new_text);
+        // Empty files have no hunks, and git wants no ---/+++ lines without hunks
+        if !diff.hunks().is_empty() {
+            patch.push_str(&diff.to_string());
+        }
+    }
+    patch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(path: &str, old: Option<&str>, new: Option<&str>) -> FileChange {
+        FileChange {
+            path: path.to_string(),
+            old: old.map(|text| text.as_bytes().to_vec()),
+            new: new.map(|text| text.as_bytes().to_vec()),
+            executable: false,
+        }
+    }
+
+    #[test]
+    fn test_format_git_patch() {
+        let patch = format_git_patch(&[
+            change("README.md", None, Some("# Demo\n")),
+            change("old.txt", Some("gone\n"), None),
+            change("src/main.rs", Some("a\nb\n"), Some("a\nc\n")),
+            change(".keep", None, Some("")),
+        ]);
+        assert_eq!(
+            patch,
+            "diff --git a/README.md b/README.md\nnew file mode 100644\n--- /dev/null\n+++ b/README.md\n@@ -0,0 +1 @@\n+# Demo\n\
+             diff --git a/old.txt b/old.txt\ndeleted file mode 100644\n--- a/old.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-gone\n\
+             diff --git a/src/main.rs b/src/main.rs\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n\
+             diff --git a/.keep b/.keep\nnew file mode 100644\n"
+        );
+    }
+
+    #[test]
+    fn test_format_binary() {
+        let mut binary = change("logo.png", None, None);
+        binary.new = Some(vec![0x89, 0xff, 0x00]);
+        assert!(format_git_patch(&[binary]).ends_with("Binary files /dev/null and b/logo.png differ\n"));
+    }
+}
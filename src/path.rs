@@ -1,8 +1,163 @@
+use crate::config::USER_CONFIG_FILE;
+
 use std::env;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Where storage lived before it followed the XDG base directories
+pub const LEGACY_STORAGE: &str = "~/.template-rs";
+
+/// Directory below the platform data and config directories
+const APP_DIR: &str = "template-rs";
+
+/// Environment variable naming one directory for all of storage and config.toml, so CI jobs and
+/// tests can run without touching the real home directory
+pub const HOME_ENV: &str = "TEMPLATE_RS_HOME";
+
+/// Key of config.toml that moves storage (but not config.toml itself) elsewhere
+const STORAGE_SETTING: &str = "storage";
+
+pub const TEMPLATE_STORAGE: &str = "templates";
+pub const OBJECT_STORAGE: &str = "objects";
+pub const CACHE_STORAGE: &str = "cache";
+pub const VERSION_STORAGE: &str = "versions";
+pub const BACKUP_STORAGE: &str = "backups";
+
+/// Where the tool keeps its files: `data` holds templates, objects, history and the rest of
+/// storage (the directories above are relative to it), `config` holds config.toml
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageDirs {
+    pub data: PathBuf,
+    pub config: PathBuf,
+}
+
+static STORAGE_DIRS: OnceLock<StorageDirs> = OnceLock::new();
+
+#[cfg(test)]
+thread_local! {
+    /// Storage of the test running on this thread, see `TestStorage`
+    static TEST_STORAGE: std::cell::Cell<Option<&'static StorageDirs>> = const { std::cell::Cell::new(None) };
+}
+
+/// A scratch directory holding the storage and config.toml of the test that created it, the way
+/// $TEMPLATE_RS_HOME would, until it is dropped. Tests run on parallel threads in one process,
+/// so this is kept per thread rather than in the environment.
+#[cfg(test)]
+pub(crate) struct TestStorage {
+    home: tempfile::TempDir,
+}
+
+#[cfg(test)]
+impl TestStorage {
+    pub(crate) fn new() -> Self {
+        let home = tempfile::tempdir().unwrap();
+        let dirs = StorageDirs { data: home.path().to_path_buf(), config: home.path().to_path_buf() };
+        TEST_STORAGE.set(Some(Box::leak(Box::new(dirs))));
+        Self { home }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        self.home.path()
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestStorage {
+    fn drop(&mut self) {
+        TEST_STORAGE.set(None);
+    }
+}
+
+/// The storage directories in use, resolved once per run: $TEMPLATE_RS_HOME when set, else
+/// the `storage` setting of config.toml, else the platform directories. Storage left in the
+/// legacy ~/.template-rs (because it could not be moved) keeps being used there.
+pub fn storage_dirs() -> &'static StorageDirs {
+    #[cfg(test)]
+    if let Some(dirs) = TEST_STORAGE.get() {
+        return dirs;
+    }
+    STORAGE_DIRS.get_or_init(|| {
+        relocated_dirs().unwrap_or_else(|| {
+            let platform = platform_dirs();
+            match legacy_storage_dir() {
+                Some(legacy) if legacy.is_dir() && !platform.data.exists() => {
+                    StorageDirs { data: legacy.clone(), config: legacy }
+                }
+                _ => platform,
+            }
+        })
+    })
+}
+
+/// Storage moved away from the defaults by $TEMPLATE_RS_HOME (data and config) or by the
+/// `storage` setting of config.toml (data only), None when it is in the default place
+pub fn relocated_dirs() -> Option<StorageDirs> {
+    #[cfg(test)]
+    if let Some(dirs) = TEST_STORAGE.get() {
+        return Some(dirs.clone());
+    }
+    if let Some(home) = env::var_os(HOME_ENV).filter(|home| !home.is_empty()) {
+        let home = resolve_path(&home.to_string_lossy(), None).unwrap_or_else(|_| PathBuf::from(home));
+        return Some(StorageDirs { data: home.clone(), config: home });
+    }
+
+    let platform = platform_dirs();
+    let config = match legacy_storage_dir() {
+        Some(legacy) if legacy.is_dir() && !platform.data.exists() => legacy,
+        _ => platform.config,
+    };
+    configured_storage(&config).map(|data| StorageDirs { data, config })
+}
+
+/// The `storage` setting of the config.toml in `config_dir`, relative to that directory.
+/// A config.toml that can't be read is left for `UserConfig::load` to report.
+fn configured_storage(config_dir: &Path) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(config_dir.join(USER_CONFIG_FILE)).ok()?;
+    let table: toml::Table = toml::from_str(&content).ok()?;
+    let storage = table.get(STORAGE_SETTING)?.as_str().filter(|storage| !storage.trim().is_empty())?;
+    resolve_path(storage, Some(config_dir)).ok()
+}
+
+/// `$XDG_DATA_HOME/template-rs` and `$XDG_CONFIG_HOME/template-rs`, or one directory for both:
+/// `%APPDATA%\template-rs` on Windows, `~/Library/Application Support/template-rs` on macOS
+pub fn platform_dirs() -> StorageDirs {
+    if cfg!(windows)
+        && let Some(app_data) = env::var_os("APPDATA")
+    {
+        let dir = PathBuf::from(app_data).join(APP_DIR);
+        return StorageDirs { data: dir.clone(), config: dir };
+    }
+    if cfg!(target_os = "macos")
+        && let Ok(home) = resolve_path("~", None)
+    {
+        let dir = home.join("Library/Application Support").join(APP_DIR);
+        return StorageDirs { data: dir.clone(), config: dir };
+    }
+
+    StorageDirs {
+        data: xdg_dir("XDG_DATA_HOME", ".local/share"),
+        config: xdg_dir("XDG_CONFIG_HOME", ".config"),
+    }
+}
+
+/// The legacy ~/.template-rs, None if the home directory is unknown
+pub fn legacy_storage_dir() -> Option<PathBuf> {
+    resolve_path(LEGACY_STORAGE, None).ok().filter(|p| p.is_absolute())
+}
 
-pub const PERSISTENT_STORAGE: &str = "~/.template-rs";
-pub const TEMPLATE_STORAGE: &str = "~/.template-rs/templates";
+/// An XDG base directory for this tool. Unset, empty or relative values fall back to the
+/// default below the home directory, as the spec requires.
+fn xdg_dir(var: &str, default: &str) -> PathBuf {
+    xdg_home(var, default).join(APP_DIR)
+}
+
+/// An XDG base directory itself (`$XDG_DATA_HOME`, ...), or `default` below the home directory
+pub fn xdg_home(var: &str, default: &str) -> PathBuf {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| resolve_path("~", None).unwrap_or_default().join(default))
+}
 
 /// Resolves a user-provided path string into an absolute `PathBuf`.
 /// Handles relative paths, absolute paths, home directory expansion (~),
@@ -15,6 +170,8 @@ pub const TEMPLATE_STORAGE: &str = "~/.template-rs/templates";
 ///
 /// # Examples
 /// ```
+/// use template_rs::resolve_path;
+///
 /// // Absolute path
 /// let resolved = resolve_path("/home/user/file.txt", None);
 /// 
@@ -72,12 +229,87 @@ pub fn resolve_path(path_str: &str, invocation_dir: Option<&Path>) -> Result<Pat
     Ok(final_path)
 }
 
+/// Check if a relative path stays inside the directory it is joined to: not absolute, no drive
+/// or root, and no `..` climbing above where it starts
+pub fn is_contained<P: AsRef<Path>>(relative: P) -> bool {
+    let mut depth = 0usize;
+    for component in relative.as_ref().components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// Check if a symlink at `link` (relative to a directory) points outside that directory,
+/// either through an absolute target or by climbing above it
+pub fn symlink_escapes(link: &str, target: &Path) -> bool {
+    let parent = Path::new(link).parent().unwrap_or_else(|| Path::new(""));
+    target.is_absolute() || !is_contained(parent.join(target))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::env;
+
+    #[test]
+    fn test_is_contained() {
+        assert!(is_contained("src/main.rs"));
+        assert!(is_contained("./src/../README.md"));
+        assert!(!is_contained("../outside"));
+        assert!(!is_contained("src/../../outside"));
+        #[cfg(unix)]
+        assert!(!is_contained("/etc/passwd"));
+    }
+
+    #[test]
+    fn test_symlink_escapes() {
+        assert!(!symlink_escapes("bin/tool", Path::new("../scripts/tool.sh")));
+        assert!(symlink_escapes("bin/tool", Path::new("../../tool.sh")));
+        assert!(symlink_escapes("link", Path::new("..")));
+        #[cfg(unix)]
+        assert!(symlink_escapes("link", Path::new("/etc/passwd")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_xdg_dir() {
+        unsafe {
+            env::set_var("TEST_XDG_HOME", "/xdg/data");
+            env::set_var("TEST_XDG_RELATIVE", "relative/data");
+        }
+
+        assert_eq!(xdg_dir("TEST_XDG_HOME", ".local/share"), PathBuf::from("/xdg/data/template-rs"));
+        let fallback = xdg_dir("TEST_XDG_RELATIVE", ".local/share");
+        assert!(fallback.ends_with(".local/share/template-rs"));
+        assert!(fallback.is_absolute());
+
+        unsafe {
+            env::remove_var("TEST_XDG_HOME");
+            env::remove_var("TEST_XDG_RELATIVE");
+        }
+    }
     
     #[test]
+    fn test_configured_storage() {
+        let config_dir = env::temp_dir().join(format!("template-rs-configured-storage-{}", std::process::id()));
+        std::fs::create_dir_all(&config_dir).unwrap();
+        assert_eq!(configured_storage(&config_dir), None);
+
+        std::fs::write(config_dir.join("config.toml"), "storage = \"shared/templates\"\n").unwrap();
+        assert_eq!(configured_storage(&config_dir), Some(config_dir.join("shared/templates")));
+        std::fs::write(config_dir.join("config.toml"), "storage = \"\"\n").unwrap();
+        assert_eq!(configured_storage(&config_dir), None);
+
+        std::fs::remove_dir_all(&config_dir).unwrap();
+    }
+
+    #[test]
+    #[allow(clippy::cmp_owned)]
     fn test_current_directory() {
         let result = resolve_path(".", None).unwrap();
         assert!(result.is_absolute() || result == PathBuf::from("."));
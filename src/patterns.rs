@@ -0,0 +1,142 @@
+/// Check if a relative path (forward slashes) matches an rsync/gitignore-style pattern.
+///
+/// * `name` matches a file or directory with that name at any depth
+/// * `dir/` matches only directories (and so everything below them)
+/// * `/name` is anchored to the root
+/// * `a/b` (containing a slash) is matched against the whole path
+/// * `*` matches within a path component, `?` matches a single character
+pub fn matches_pattern(path: &str, pattern: &str) -> bool {
+    matches_entry(path, false, pattern)
+}
+
+/// Check if a directory (relative path, forward slashes) matches a pattern, so `dir/` patterns
+/// match the directory itself and not only what is inside it
+pub fn matches_dir(path: &str, pattern: &str) -> bool {
+    matches_entry(path, true, pattern)
+}
+
+/// Matches a path whose last component is a directory (`is_dir`) or a file
+fn matches_entry(path: &str, is_dir: bool, pattern: &str) -> bool {
+    let dir_only = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    let anchored = pattern.starts_with('/') || pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+
+    if pattern.is_empty() {
+        return false;
+    }
+
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+    let pattern_components: Vec<&str> = pattern.split('/').collect();
+
+    // Every prefix of the path is a candidate; prefixes shorter than the path are directories
+    for end in 1..=components.len() {
+        let is_dir = end < components.len() || is_dir;
+        if dir_only && !is_dir {
+            continue;
+        }
+
+        let candidate = &components[..end];
+        let matched = if anchored {
+            candidate.len() == pattern_components.len()
+                && candidate.iter().zip(&pattern_components).all(|(c, p)| wildcard_match(p, c))
+        } else {
+            wildcard_match(pattern, candidate[candidate.len() - 1])
+        };
+
+        if matched {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Check if a path matches any of the given patterns
+pub fn matches_any(path: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|p| matches_pattern(path, p))
+}
+
+/// Reads the patterns of an ignore file (.templateignore): one per line, blank lines and `#`
+/// comments skipped. `**/name` and `dir/**` are rewritten to the `name` and `dir/` forms
+/// [`matches_pattern`] understands; negations (`!pattern`) aren't supported and are skipped.
+pub fn parse_ignore_file(content: &str) -> Vec<String> {
+    content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .map(|line| {
+            let line = line.strip_prefix("**/").unwrap_or(line);
+            line.strip_suffix("/**").map_or_else(|| line.to_string(), |dir| format!("{dir}/"))
+        })
+        .collect()
+}
+
+/// Matches a single path component against a pattern with `*` and `?` wildcards
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_matches_at_any_depth() {
+        assert!(matches_pattern(".DS_Store", ".DS_Store"));
+        assert!(matches_pattern("assets/.DS_Store", ".DS_Store"));
+        assert!(!matches_pattern("assets/x.DS_Store", ".DS_Store"));
+    }
+
+    #[test]
+    fn test_directory_patterns() {
+        assert!(matches_pattern(".git/config", ".git/"));
+        assert!(matches_pattern("sub/node_modules/a/b.js", "node_modules/"));
+        assert!(!matches_pattern(".git", ".git/"));
+        assert!(matches_dir(".git", ".git/"));
+        assert!(matches_dir("sub/node_modules", "node_modules/"));
+    }
+
+    #[test]
+    fn test_anchored_patterns() {
+        assert!(matches_pattern("build/out.o", "/build/"));
+        assert!(!matches_pattern("src/build/out.o", "/build/"));
+        assert!(matches_pattern("docs/internal/notes.md", "docs/internal"));
+        assert!(!matches_pattern("other/docs/internal", "docs/internal"));
+    }
+
+    #[test]
+    fn test_parse_ignore_file() {
+        let content = "# notes for authors\n\n/docs/\n*.psd  \n**/scratch\nassets/raw/**\n!keep.psd\n";
+        assert_eq!(parse_ignore_file(content), ["/docs/", "*.psd", "scratch", "assets/raw/"]);
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(matches_pattern("src/debug.log", "*.log"));
+        assert!(matches_pattern("a.txt", "?.txt"));
+        assert!(!matches_pattern("ab.txt", "?.txt"));
+        assert!(matches_pattern("src/gen/a.rs", "src/*/a.rs"));
+    }
+}
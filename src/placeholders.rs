@@ -0,0 +1,45 @@
+/// A `{{ ... }}` placeholder found in text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    /// 1-based line number
+    pub line: usize,
+    /// The placeholder as written, including braces
+    pub text: String,
+}
+
+/// Finds every `{{ ... }}` placeholder in some text, with its line number
+pub fn find_placeholders(content: &str) -> Vec<Placeholder> {
+    let mut found = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let mut rest = line;
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start..].find("}}") else {
+                break;
+            };
+            let end = start + len + 2;
+            found.push(Placeholder { line: index + 1, text: rest[start..end].to_string() });
+            rest = &rest[end..];
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_placeholders() {
+        let found = find_placeholders("name = \"{{ project_name }}\"\nplain\n{{a}} and {{ b }}\n{{ unclosed");
+        assert_eq!(
+            found,
+            vec![
+                Placeholder { line: 1, text: "{{ project_name }}".to_string() },
+                Placeholder { line: 3, text: "{{a}}".to_string() },
+                Placeholder { line: 3, text: "{{ b }}".to_string() },
+            ]
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use crate::template::{Template, TEMPLATE_PLUGIN_DIR};
+use crate::theme::Icon;
+use crate::timing::{Phase, span};
+
+use std::fs;
+use std::path::Path;
+
+/// Runs the template's WASM post-processors (`plugins = [...]` in .template.toml, files in
+/// .template-plugins/) over files that were just copied into the target.
+///
+/// A plugin is a core WASM module with no imports, so it can't touch the filesystem, network
+/// or clock. It exports `memory`, `alloc(len: i32) -> i32` returning a buffer for the host to
+/// write into, and `post_process(path_ptr, path_len, content_ptr, content_len) -> i64` which
+/// returns `(ptr << 32) | len` of the replacement content, or -1 to leave the file unchanged.
+/// Each call runs with a fuel budget and a memory cap so a broken plugin can't hang the host.
+pub fn run_post_processors(
+    template: &Template,
+    target_path: &Path,
+    files: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let _timing = span(Phase::Render);
+    let Some(plugins) = template.config.plugins.as_deref().filter(|p| !p.is_empty()) else {
+        return Ok(());
+    };
+
+    if !cfg!(feature = "wasm-plugins") {
+        eprintln!(
+            "{} Template '{}' declares {} plugin(s), but this build has no WASM plugin support; skipping them",
+            Icon::Warning,
+            template.name,
+            plugins.len()
+        );
+        return Ok(());
+    }
+
+    for plugin in plugins {
+        let plugin_path = template.path.join(TEMPLATE_PLUGIN_DIR).join(plugin);
+        eprintln!("Running plugin {plugin}");
+        let mut runtime = wasm::PluginRuntime::load(&plugin_path)?;
+        for file in files {
+            let path = target_path.join(file);
+            let content = fs::read(&path)?;
+            if let Some(processed) = runtime.post_process(file, &content)
+                .map_err(|e| format!("Plugin {plugin} failed on '{file}': {e}"))?
+            {
+                fs::write(&path, processed)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Functions from the template's WASM plugins used while rendering. Plugins are asked in the
+/// order the template lists them.
+///
+/// Filters cover `{{ name | filter }}` placeholders the built-in filters don't: a plugin exports
+/// `filter(name_ptr, name_len, value_ptr, value_len) -> i64`, returning `(ptr << 32) | len` of the
+/// filtered value, or -1 if it has no such filter. Validators check variable values: a plugin
+/// exports `validate(name_ptr, name_len, value_ptr, value_len) -> i64` with the same encoding,
+/// returning a message explaining why the value is rejected, or -1 to accept it.
+#[derive(Default)]
+pub struct PluginFunctions {
+    runtimes: Vec<(String, wasm::PluginRuntime)>,
+}
+
+impl PluginFunctions {
+    /// Loads the template's plugins; builds without WASM support get no plugin functions
+    pub fn load(template: &Template) -> Result<Self, Box<dyn std::error::Error>> {
+        let plugins = template.config.plugins.as_deref().unwrap_or_default();
+        if plugins.is_empty() || !cfg!(feature = "wasm-plugins") {
+            return Ok(Self::default());
+        }
+        let runtimes = plugins.iter()
+            .map(|plugin| {
+                let runtime = wasm::PluginRuntime::load(&template.path.join(TEMPLATE_PLUGIN_DIR).join(plugin))?;
+                Ok((plugin.clone(), runtime))
+            })
+            .collect::<Result<_, Box<dyn std::error::Error>>>()?;
+        Ok(Self { runtimes })
+    }
+
+    /// Filters a value with the first plugin that has the filter, None when none has it
+    pub fn filter(&mut self, filter: &str, value: &str) -> Result<Option<String>, String> {
+        for (plugin, runtime) in &mut self.runtimes {
+            let output = runtime.filter(filter, value).map_err(|e| format!("plugin {plugin}: {e}"))?;
+            if let Some(output) = output {
+                return String::from_utf8(output)
+                    .map(Some)
+                    .map_err(|_| format!("plugin {plugin} returned a value that isn't UTF-8"));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Asks every plugin with a validator about a variable's value, failing with the first objection
+    pub fn validate(&mut self, name: &str, value: &str) -> Result<(), String> {
+        for (plugin, runtime) in &mut self.runtimes {
+            if let Some(message) = runtime.validate(name, value).map_err(|e| format!("plugin {plugin}: {e}"))? {
+                return Err(String::from_utf8_lossy(&message).into_owned());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stand-in for builds without the `wasm-plugins` feature; never reached past the checks above
+#[cfg(not(feature = "wasm-plugins"))]
+mod wasm {
+    use std::path::Path;
+
+    pub struct PluginRuntime;
+
+    impl PluginRuntime {
+        pub fn load(_path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+            Err("WASM plugin support is not enabled in this build".into())
+        }
+
+        pub fn post_process(&mut self, _file: &str, _content: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+            Ok(None)
+        }
+
+        pub fn filter(&mut self, _name: &str, _value: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+            Ok(None)
+        }
+
+        pub fn validate(&mut self, _name: &str, _value: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(feature = "wasm-plugins")]
+mod wasm {
+    use wasmtime::{Config, Engine, Instance, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc};
+
+    use std::path::Path;
+
+    /// Instructions a plugin may execute per file before it is stopped
+    const FUEL_PER_CALL: u64 = 1_000_000_000;
+    /// Largest linear memory a plugin may grow to
+    const MAX_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+
+    /// Signature of the exports taking two buffers and returning one (or -1): post_process, filter and validate
+    type TransformFn = TypedFunc<(i32, i32, i32, i32), i64>;
+
+    /// A loaded plugin instance with its own store, so plugins never share state
+    pub struct PluginRuntime {
+        store: Store<StoreLimits>,
+        memory: Memory,
+        alloc: TypedFunc<i32, i32>,
+        post_process: Option<TransformFn>,
+        filter: Option<TransformFn>,
+        validate: Option<TransformFn>,
+    }
+
+    impl PluginRuntime {
+        pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+            let mut config = Config::new();
+            config.consume_fuel(true);
+            let engine = Engine::new(&config)?;
+            let module = Module::from_file(&engine, path)
+                .map_err(|e| format!("Failed to load plugin {}: {e}", path.display()))?;
+
+            let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).build();
+            let mut store = Store::new(&engine, limits);
+            store.limiter(|limits| limits);
+            store.set_fuel(FUEL_PER_CALL)?;
+
+            // No imports are provided: plugins are pure functions of their input
+            let instance = Instance::new(&mut store, &module, &[])
+                .map_err(|e| format!("Failed to instantiate plugin {}: {e}", path.display()))?;
+
+            let memory = instance.get_memory(&mut store, "memory")
+                .ok_or_else(|| format!("Plugin {} does not export its memory", path.display()))?;
+            let alloc = instance.get_typed_func(&mut store, "alloc")?;
+            let post_process = instance.get_typed_func(&mut store, "post_process").ok();
+            let filter = instance.get_typed_func(&mut store, "filter").ok();
+            let validate = instance.get_typed_func(&mut store, "validate").ok();
+
+            Ok(Self { store, memory, alloc, post_process, filter, validate })
+        }
+
+        /// Passes a file through the plugin, None when the plugin leaves it unchanged
+        pub fn post_process(&mut self, file: &str, content: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+            match self.post_process.clone() {
+                Some(post_process) => self.transform(&post_process, file.as_bytes(), content),
+                None => Ok(None),
+            }
+        }
+
+        /// Applies the plugin's filter of that name to a value, None when it has no such filter
+        pub fn filter(&mut self, name: &str, value: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+            match self.filter.clone() {
+                Some(filter) => self.transform(&filter, name.as_bytes(), value.as_bytes()),
+                None => Ok(None),
+            }
+        }
+
+        /// Checks a variable's value, returning the plugin's objection, None when it accepts the value
+        pub fn validate(&mut self, name: &str, value: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+            match self.validate.clone() {
+                Some(validate) => self.transform(&validate, name.as_bytes(), value.as_bytes()),
+                None => Ok(None),
+            }
+        }
+
+        /// Calls an export with two buffers, returning the buffer it answers with, None for -1
+        fn transform(&mut self, function: &TransformFn, first: &[u8], second: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+            self.store.set_fuel(FUEL_PER_CALL)?;
+
+            let (first_ptr, first_len) = self.write(first)?;
+            let (second_ptr, second_len) = self.write(second)?;
+            let result = function.call(&mut self.store, (first_ptr, first_len, second_ptr, second_len))?;
+            if result == -1 {
+                return Ok(None);
+            }
+
+            let ptr = usize::try_from(result >> 32)?;
+            let len = usize::try_from(result & 0xFFFF_FFFF)?;
+            let data = self.memory.data(&self.store);
+            let output = data.get(ptr..ptr + len).ok_or("Plugin returned a buffer outside its memory")?;
+            Ok(Some(output.to_vec()))
+        }
+
+        /// Copies bytes into a buffer allocated by the plugin, returns its pointer and length
+        fn write(&mut self, bytes: &[u8]) -> Result<(i32, i32), Box<dyn std::error::Error>> {
+            let len = i32::try_from(bytes.len())?;
+            let ptr = self.alloc.call(&mut self.store, len)?;
+            self.memory.write(&mut self.store, usize::try_from(ptr)?, bytes)?;
+            Ok((ptr, len))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "wasm-plugins"))]
+mod tests {
+    use super::wasm::PluginRuntime;
+
+    use std::env;
+    use std::fs;
+
+    /// Replaces files whose path ends in 't' (like notes.txt) with "processed", leaves the rest
+    /// alone, filters every value into "processed" and rejects empty values
+    const PLUGIN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (data (i32.const 16) "processed")
+          (global $next (mut i32) (i32.const 1024))
+          (func (export "alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get $len)))
+            (local.get $ptr))
+          (func (export "post_process") (param $path i32) (param $path_len i32) (param i32 i32) (result i64)
+            (if (i32.ne (i32.load8_u (i32.sub (i32.add (local.get $path) (local.get $path_len)) (i32.const 1)))
+                        (i32.const 116))
+              (then (return (i64.const -1))))
+            (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 9)))
+          (func (export "filter") (param i32 i32 i32 i32) (result i64)
+            (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 9)))
+          (func (export "validate") (param i32 i32 i32) (param $value_len i32) (result i64)
+            (if (i32.ne (local.get $value_len) (i32.const 0))
+              (then (return (i64.const -1))))
+            (i64.or (i64.shl (i64.const 32) (i64.const 32)) (i64.const 5)))
+          (data (i32.const 32) "empty"))
+    "#;
+
+    #[test]
+    fn test_post_process() {
+        let path = env::temp_dir().join(format!("template-rs-plugin-{}.wasm", std::process::id()));
+        fs::write(&path, wat::parse_str(PLUGIN).unwrap()).unwrap();
+
+        let mut runtime = PluginRuntime::load(&path).unwrap();
+        assert_eq!(runtime.post_process("notes.txt", b"original").unwrap().as_deref(), Some(&b"processed"[..]));
+        assert_eq!(runtime.post_process("main.rs", b"fn main() {}").unwrap(), None);
+        assert_eq!(runtime.filter("any", "value").unwrap().as_deref(), Some(&b"processed"[..]));
+        assert_eq!(runtime.validate("name", "").unwrap().as_deref(), Some(&b"empty"[..]));
+        assert_eq!(runtime.validate("name", "x").unwrap(), None);
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,62 @@
+use crate::file::{find_in_path, format_size};
+
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+/// What a template needs from the machine it is applied on, from the [prerequisites] table
+/// of .template.toml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Prerequisites {
+    /// Executables that must be on PATH (e.g. "node", "cargo")
+    #[serde(default)]
+    pub binaries: Vec<String>,
+    /// Free space the target's filesystem needs, in MiB
+    pub min_free_disk_mib: Option<u64>,
+}
+
+impl Prerequisites {
+    /// Lists every unmet prerequisite for applying into `target_path`, empty when all are met.
+    /// Everything is checked up front so the user can fix it all at once.
+    pub fn missing(&self, target_path: &Path) -> Vec<String> {
+        let mut missing: Vec<String> = self.binaries.iter()
+            .filter(|binary| find_in_path(binary).is_none())
+            .map(|binary| format!("'{binary}' was not found on PATH"))
+            .collect();
+
+        if let Some(min_mib) = self.min_free_disk_mib {
+            let required = min_mib.saturating_mul(1024 * 1024);
+            // The target may not exist yet (dry run of `new`), measure the closest parent that does
+            let existing = target_path.ancestors().find(|p| p.exists()).unwrap_or(target_path);
+            match fs4::available_space(existing) {
+                Ok(available) if available < required => missing.push(format!(
+                    "{} of free disk space needed, {} available",
+                    format_size(required),
+                    format_size(available)
+                )),
+                Ok(_) => {}
+                Err(e) => missing.push(format!("could not check free disk space: {e}")),
+            }
+        }
+
+        missing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing() {
+        let prerequisites = Prerequisites {
+            binaries: vec!["surely-not-an-installed-binary".to_string()],
+            min_free_disk_mib: Some(u64::MAX / (1024 * 1024)),
+        };
+        let missing = prerequisites.missing(&std::env::temp_dir());
+        assert_eq!(missing.len(), 2);
+        assert!(missing[0].contains("surely-not-an-installed-binary"));
+
+        assert!(Prerequisites::default().missing(&std::env::temp_dir()).is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::commands::init::ApplyOptions;
+use crate::config::{USER_CONFIG_FILE, UserConfig};
+use crate::copy::{Timestamps, parse_mode_mask};
+use crate::error::TemplateError;
+use crate::receipt::ReceiptTarget;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+/// Prefix that makes the template argument of `init` and `new` a preset name: `new @work-svc my-svc`
+pub const PRESET_PREFIX: char = '@';
+
+/// A named combination of template, options and variables from the [presets] table of
+/// config.toml, so a long command line becomes `new @name path`:
+///
+/// ```toml
+/// [presets.work-microservice]
+/// template = "go/service"
+/// features = ["ci", "docker"]
+/// variables = { organization = "acme" }
+/// check = true
+/// ```
+///
+/// What the command line gives wins over the preset.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preset {
+    /// Name of the template to apply, or oci://REGISTRY/REPOSITORY:TAG
+    pub template: String,
+    /// What the preset is for, shown in completions
+    pub description: Option<String>,
+    /// Bool variables of the template set to true, for templates with optional parts
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Values for template variables
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    /// Run the language's sanity check on the result (--check)
+    #[serde(default)]
+    pub check: bool,
+    /// Skip the template's hooks (--no-hooks)
+    #[serde(default)]
+    pub no_hooks: bool,
+    /// Copy what the built-in exclusions would skip (--no-default-excludes)
+    #[serde(default)]
+    pub no_default_excludes: bool,
+    /// Where to record a receipt (--receipt)
+    pub receipt: Option<ReceiptTarget>,
+    /// Modification times of created files (--timestamps)
+    pub timestamps: Option<Timestamps>,
+    /// Permission bits cleared on created files, in octal (--mode-mask)
+    pub mode_mask: Option<String>,
+}
+
+/// The preset named by a template argument starting with `@`, None for a plain template name
+pub fn find_preset(template: &str, user_config: &UserConfig) -> Result<Option<(String, Preset)>, Box<dyn std::error::Error>> {
+    let Some(name) = template.strip_prefix(PRESET_PREFIX) else {
+        return Ok(None);
+    };
+    let preset = user_config.presets.get(name).ok_or_else(|| {
+        let known: Vec<String> = user_config.presets.keys().map(|name| format!("{PRESET_PREFIX}{name}")).collect();
+        let known = if known.is_empty() { "none are defined".to_string() } else { known.join(", ") };
+        TemplateError::Config(format!("No preset '{name}' in the [presets] of {USER_CONFIG_FILE} ({known})"))
+    })?;
+    Ok(Some((name.to_string(), preset.clone())))
+}
+
+/// Resolves a template argument that names a preset into the preset's template and the options
+/// merged with it; any other argument is returned as it is with the options unchanged
+pub fn expand_preset(template: &str, options: &ApplyOptions) -> Result<(String, ApplyOptions), Box<dyn std::error::Error>> {
+    match find_preset(template, &UserConfig::load()?)? {
+        Some((name, preset)) => {
+            let options = preset.merge_into(options).map_err(|e| TemplateError::Config(format!("Preset '{name}': {e}")))?;
+            Ok((preset.template, options))
+        }
+        None => Ok((template.to_string(), options.clone())),
+    }
+}
+
+impl Preset {
+    /// The options with the preset's filled in where the command line left them unset; flags
+    /// the preset turns on stay on, and variables given on the command line keep their value
+    fn merge_into(&self, options: &ApplyOptions) -> Result<ApplyOptions, String> {
+        let mut merged = options.clone();
+        merged.check |= self.check;
+        merged.no_hooks |= self.no_hooks;
+        merged.default_excludes &= !self.no_default_excludes;
+        merged.receipt = options.receipt.or(self.receipt);
+        merged.timestamps = options.timestamps.or(self.timestamps);
+        if merged.mode_mask.is_none()
+            && let Some(mask) = &self.mode_mask
+        {
+            merged.mode_mask = Some(parse_mode_mask(mask).map_err(|e| format!("mode_mask: {e}"))?);
+        }
+        let features = self.features.iter().map(|feature| (feature.clone(), "true".to_string()));
+        for (name, value) in features.chain(self.variables.clone()) {
+            merged.variables.entry(name).or_insert(value);
+        }
+        Ok(merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_merge() {
+        let config: UserConfig = toml::from_str(r#"
+            [presets.svc]
+            template = "go/service"
+            features = ["ci", "docker"]
+            variables = { organization = "acme", ci = "false" }
+            check = true
+            no_default_excludes = true
+            mode_mask = "027"
+        "#).unwrap();
+        assert!(find_preset("go/service", &config).unwrap().is_none());
+        assert!(find_preset("@missing", &config).is_err());
+        let (name, preset) = find_preset("@svc", &config).unwrap().unwrap();
+        assert_eq!((name.as_str(), preset.template.as_str()), ("svc", "go/service"));
+
+        let options = ApplyOptions {
+            default_excludes: true,
+            mode_mask: Some(0o022),
+            variables: BTreeMap::from([("docker".to_string(), "false".to_string())]),
+            ..ApplyOptions::default()
+        };
+        let merged = preset.merge_into(&options).unwrap();
+        assert!(merged.check && !merged.no_hooks && !merged.default_excludes);
+        // The command line wins over the preset, features over the preset's own variables
+        assert_eq!(merged.mode_mask, Some(0o022));
+        assert_eq!(merged.variables["docker"], "false");
+        assert_eq!(merged.variables["ci"], "true");
+        assert_eq!(merged.variables["organization"], "acme");
+
+        let invalid = Preset { mode_mask: Some("9".to_string()), ..preset };
+        assert!(invalid.merge_into(&ApplyOptions::default()).is_err());
+    }
+}
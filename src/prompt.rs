@@ -0,0 +1,200 @@
+use crate::variables::{Question, VariableKind};
+
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+
+/// Asks a yes/no question on stderr and reads the answer from stdin, defaulting to no (also on end of input)
+pub fn confirm(question: &str) -> io::Result<bool> {
+    eprint!("{question} [y/N] ");
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Asks a free-form question on stderr and reads the answer from stdin. An empty answer takes
+/// `default` (which may be empty too); None at end of input.
+pub fn ask(question: &str, default: &str) -> io::Result<Option<String>> {
+    if default.is_empty() {
+        eprint!("{question}: ");
+    } else {
+        eprint!("{question} [{default}]: ");
+    }
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer)? == 0 {
+        return Ok(None);
+    }
+    let answer = answer.trim();
+    Ok(Some(if answer.is_empty() { default } else { answer }.to_string()))
+}
+
+/// Asks for a template variable's value on stderr, showing its description, accepted values and
+/// default, and reads the answer from stdin. An empty answer takes the default; without one the
+/// question is asked again. None at end of input.
+pub fn ask_variable(question: &Question) -> io::Result<Option<String>> {
+    if let Some(problem) = question.problem {
+        eprintln!("  {problem}");
+    }
+    let spec = question.spec;
+    let accepted = match (&spec.choices, spec.kind) {
+        (Some(choices), _) => format!(" ({})", choices.join("/")),
+        (None, VariableKind::Bool) => " (y/n)".to_string(),
+        (None, VariableKind::Integer) => " (number)".to_string(),
+        (None, VariableKind::String) => String::new(),
+    };
+
+    loop {
+        match &spec.description {
+            Some(description) => eprint!("{} - {description}{accepted}", question.name),
+            None => eprint!("{}{accepted}", question.name),
+        }
+        match question.default {
+            Some(default) => eprint!(" [{default}]: "),
+            None => eprint!(": "),
+        }
+        io::stderr().flush()?;
+
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer)? == 0 {
+            return Ok(None);
+        }
+        match (answer.trim_end_matches(['\r', '\n']), question.default) {
+            ("", Some(default)) => return Ok(Some(default.to_string())),
+            ("", None) => eprintln!("  A value is required"),
+            (answer, _) => return Ok(Some(answer.to_string())),
+        }
+    }
+}
+
+/// A row of the file picker: a directory (ends in '/') or a file, with its nesting depth
+pub struct PickerRow {
+    pub path: String,
+    pub depth: usize,
+}
+
+/// Lets the user pick files from a checkbox tree on stderr, all selected to start with.
+/// Numbers toggle rows (a directory toggles everything below it), `a`/`n` select all/none,
+/// an empty line accepts. Returns None when the user aborts (`q` or end of input).
+pub fn select_files(files: &[String]) -> io::Result<Option<Vec<String>>> {
+    let rows = picker_rows(files);
+    let mut selected: BTreeSet<&str> = files.iter().map(String::as_str).collect();
+
+    loop {
+        for (index, row) in rows.iter().enumerate() {
+            let below: Vec<&String> = files.iter().filter(|f| covers(&row.path, f)).collect();
+            let count = below.iter().filter(|f| selected.contains(f.as_str())).count();
+            let mark = if count == below.len() { "x" } else if count == 0 { " " } else { "-" };
+            let name = row.path.trim_end_matches('/').rsplit('/').next().unwrap_or(&row.path);
+            let suffix = if row.path.ends_with('/') { "/" } else { "" };
+            eprintln!("{:>4} [{mark}] {}{name}{suffix}", index + 1, "  ".repeat(row.depth));
+        }
+        eprint!("{} of {} file(s) selected. Toggle numbers (e.g. 1 4-6), a = all, n = none, Enter = apply, q = abort: ",
+            selected.len(), files.len());
+        io::stderr().flush()?;
+
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer)? == 0 {
+            return Ok(None);
+        }
+
+        match answer.trim() {
+            "" => break,
+            "q" => return Ok(None),
+            "a" => selected = files.iter().map(String::as_str).collect(),
+            "n" => selected.clear(),
+            input => {
+                let Some(indices) = parse_selection(input, rows.len()) else {
+                    eprintln!("Invalid selection '{input}'");
+                    continue;
+                };
+                for index in indices {
+                    let row = &rows[index];
+                    let below: Vec<&str> = files.iter().map(String::as_str).filter(|f| covers(&row.path, f)).collect();
+                    if below.iter().all(|f| selected.contains(f)) {
+                        for file in below {
+                            selected.remove(file);
+                        }
+                    } else {
+                        selected.extend(below);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Some(files.iter().filter(|f| selected.contains(f.as_str())).cloned().collect()))
+}
+
+/// Builds the tree rows for sorted relative paths: each directory once, before its contents
+pub fn picker_rows(files: &[String]) -> Vec<PickerRow> {
+    let mut rows = Vec::new();
+    let mut seen_dirs = BTreeSet::new();
+
+    for file in files {
+        let components: Vec<&str> = file.split('/').collect();
+        for depth in 0..components.len() - 1 {
+            let dir = format!("{}/", components[..=depth].join("/"));
+            if seen_dirs.insert(dir.clone()) {
+                rows.push(PickerRow { path: dir, depth });
+            }
+        }
+        rows.push(PickerRow { path: file.clone(), depth: components.len() - 1 });
+    }
+    rows
+}
+
+/// Check if a picker row covers a file: the file itself, or a directory containing it
+fn covers(row: &str, file: &str) -> bool {
+    if row.ends_with('/') { file.starts_with(row) } else { file == row }
+}
+
+/// Parses "1 3-5,7" into zero-based row indices, None if anything is malformed or out of range
+fn parse_selection(input: &str, rows: usize) -> Option<Vec<usize>> {
+    let mut indices = Vec::new();
+    for part in input.split([' ', ',']).filter(|p| !p.is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.parse::<usize>().ok()?, end.parse::<usize>().ok()?),
+            None => {
+                let number = part.parse::<usize>().ok()?;
+                (number, number)
+            }
+        };
+        if start == 0 || end < start || end > rows {
+            return None;
+        }
+        indices.extend(start - 1..end);
+    }
+    Some(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_picker_rows() {
+        let files = ["Cargo.toml", "src/bin/tool.rs", "src/main.rs"].map(String::from);
+        let rows: Vec<(String, usize)> = picker_rows(&files).into_iter().map(|r| (r.path, r.depth)).collect();
+        assert_eq!(rows, [
+            ("Cargo.toml".to_string(), 0),
+            ("src/".to_string(), 0),
+            ("src/bin/".to_string(), 1),
+            ("src/bin/tool.rs".to_string(), 2),
+            ("src/main.rs".to_string(), 1),
+        ]);
+        assert!(covers("src/", "src/bin/tool.rs"));
+        assert!(!covers("src/", "srcs/x"));
+    }
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("1 3-4,2", 5), Some(vec![0, 2, 3, 1]));
+        assert_eq!(parse_selection("0", 5), None);
+        assert_eq!(parse_selection("4-9", 5), None);
+        assert_eq!(parse_selection("x", 5), None);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::history::{format_date, generation_secs};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Log of receipts kept in the project, one `[[receipt]]` table per application or upgrade
+pub const TEMPLATE_HISTORY_FILE: &str = ".template-history";
+
+/// Changelog receipts are added to, newest first
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+/// Where `init`, `new` and `upgrade` leave a receipt of what they did (`receipts` in config.toml)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ReceiptTarget {
+    /// No receipt
+    #[default]
+    None,
+    /// An entry at the top of the project's CHANGELOG.md
+    Changelog,
+    /// A `[[receipt]]` table appended to the project's .template-history
+    History,
+}
+
+/// What one application or upgrade of a template did to a project
+#[derive(Debug, Clone, Serialize)]
+pub struct Receipt {
+    /// `init` or `upgrade`
+    pub action: String,
+    pub template: String,
+    pub version: Option<String>,
+    /// Template version the project had before an upgrade
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<String>,
+    pub tool_version: String,
+    /// Unix timestamp (seconds)
+    pub at: u64,
+    /// Touched files by what happened to them (added, replaced, deleted, updated, merged, conflicted)
+    pub files: BTreeMap<String, Vec<String>>,
+}
+
+impl Receipt {
+    /// A receipt stamped with the generation time and tool version, with no files yet
+    pub fn new(action: &str, template: &str, version: Option<&str>) -> Self {
+        Self {
+            action: action.to_string(),
+            template: template.to_string(),
+            version: version.map(String::from),
+            previous_version: None,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            at: generation_secs(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Notes a touched file under what happened to it
+    pub fn add_file(&mut self, change: &str, path: &str) {
+        self.files.entry(change.to_string()).or_default().push(path.to_string());
+    }
+
+    /// Writes the receipt into the project, returning the file it went to (None for `ReceiptTarget::None`)
+    pub fn record(&self, target_path: &Path, target: ReceiptTarget) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+        let (path, content) = match target {
+            ReceiptTarget::None => return Ok(None),
+            ReceiptTarget::Changelog => {
+                let path = target_path.join(CHANGELOG_FILE);
+                let existing = if path.exists() { fs::read_to_string(&path)? } else { String::new() };
+                let content = insert_changelog_entry(&existing, &self.changelog_entry());
+                (path, content)
+            }
+            ReceiptTarget::History => {
+                let path = target_path.join(TEMPLATE_HISTORY_FILE);
+                let mut content = if path.exists() { fs::read_to_string(&path)? } else { String::new() };
+                if !content.is_empty() && !content.ends_with("\n\n") {
+                    content.push_str(if content.ends_with('\n') { "\n" } else { "\n\n" });
+                }
+                content.push_str(&self.history_entry()?);
+                (path, content)
+            }
+        };
+        fs::write(&path, content)?;
+        Ok(Some(path))
+    }
+
+    /// The receipt as a `[[receipt]]` TOML table
+    fn history_entry(&self) -> Result<String, Box<dyn std::error::Error>> {
+        #[derive(Serialize)]
+        struct Entry<'a> {
+            receipt: [&'a Receipt; 1],
+        }
+        Ok(toml::to_string(&Entry { receipt: [self] })?)
+    }
+
+    /// The receipt as a Markdown section for a changelog
+    fn changelog_entry(&self) -> String {
+        let what = match (&self.previous_version, &self.version) {
+            (Some(previous), Some(version)) => format!("upgraded from {previous} to {version}"),
+            (None, Some(version)) => format!("{version} applied"),
+            (_, None) if self.action == "upgrade" => "upgraded".to_string(),
+            (_, None) => "applied".to_string(),
+        };
+        let mut entry = format!("## {} - template {} {what}\n\n", format_date(self.at), self.template);
+        entry.push_str(&format!("Recorded by {} {}.\n\n", env!("CARGO_PKG_NAME"), self.tool_version));
+        if self.files.is_empty() {
+            entry.push_str("No files changed.\n");
+        }
+        for (change, paths) in &self.files {
+            for path in paths {
+                entry.push_str(&format!("- {} `{path}`\n", capitalize(change)));
+            }
+        }
+        entry
+    }
+}
+
+/// Puts an entry at the top of a changelog: below its title heading if it starts with one,
+/// creating the title for a new changelog
+pub fn insert_changelog_entry(changelog: &str, entry: &str) -> String {
+    if changelog.trim().is_empty() {
+        return format!("# Changelog\n\n{entry}");
+    }
+
+    let mut lines = changelog.split_inclusive('\n').peekable();
+    let mut head = String::new();
+    // Keep the title and the introduction under it, up to the first entry
+    if lines.peek().is_some_and(|line| line.starts_with("# ")) {
+        while let Some(line) = lines.next_if(|line| !line.starts_with("## ")) {
+            head.push_str(line);
+        }
+    }
+    let rest: String = lines.collect();
+
+    let mut result = head;
+    if !result.is_empty() && !result.ends_with("\n\n") {
+        result.push_str(if result.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+    result.push_str(entry);
+    if !rest.is_empty() {
+        result.push('\n');
+        result.push_str(&rest);
+    }
+    result
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_changelog_entry() {
+        let entry = "## new\n";
+        assert_eq!(insert_changelog_entry("", entry), "# Changelog\n\n## new\n");
+        assert_eq!(
+            insert_changelog_entry("# Changelog\n\nAll notable changes.\n\n## old\n- x\n", entry),
+            "# Changelog\n\nAll notable changes.\n\n## new\n\n## old\n- x\n"
+        );
+        assert_eq!(insert_changelog_entry("## old\n", entry), "## new\n\n## old\n");
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut receipt = Receipt::new("init", "rust/cli", Some("1.2.0"));
+        receipt.at = 0;
+        receipt.add_file("added", "src/main.rs");
+        receipt.add_file("replaced", "README.md");
+
+        let changelog = receipt.changelog_entry();
+        assert!(changelog.starts_with("## 1970-01-01 - template rust/cli 1.2.0 applied\n"));
+        assert!(changelog.ends_with("- Added `src/main.rs`\n- Replaced `README.md`\n"));
+
+        let history = receipt.history_entry().unwrap();
+        assert!(history.starts_with("[[receipt]]\naction = \"init\"\n"));
+        assert!(history.contains("[receipt.files]\nadded = [\"src/main.rs\"]\n"));
+    }
+}
@@ -0,0 +1,112 @@
+/// Marker opening a managed region, e.g. `# template-rs:begin deps`
+pub const REGION_BEGIN: &str = "template-rs:begin";
+/// Marker closing a managed region, e.g. `# template-rs:end`
+pub const REGION_END: &str = "template-rs:end";
+
+/// A managed region: its optional name and the byte range of its body (between marker lines)
+struct Region {
+    name: String,
+    body_start: usize,
+    body_end: usize,
+}
+
+/// Finds the managed regions in a file. Markers may sit in any comment syntax (`#`, `//`, `<!-- -->`).
+/// Returns None if markers are unbalanced or nested.
+fn find_regions(content: &str) -> Option<Vec<Region>> {
+    let mut regions = Vec::new();
+    let mut open: Option<(String, usize)> = None;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let line_end = offset + line.len();
+
+        if let Some(index) = line.find(REGION_BEGIN) {
+            if open.is_some() {
+                return None;
+            }
+            let name = line[index + REGION_BEGIN.len()..]
+                .trim()
+                .trim_end_matches("-->")
+                .trim_end_matches("*/")
+                .trim()
+                .to_string();
+            open = Some((name, line_end));
+        } else if line.contains(REGION_END) {
+            let (name, body_start) = open.take()?;
+            regions.push(Region { name, body_start, body_end: offset });
+        }
+
+        offset = line_end;
+    }
+
+    if open.is_some() {
+        return None;
+    }
+    Some(regions)
+}
+
+/// Rewrites the managed regions of `local` with the matching region bodies from `upstream`,
+/// preserving everything outside the markers.
+///
+/// Returns None when the two files don't have the same regions (by count and name),
+/// in which case the caller should fall back to a regular merge.
+pub fn regenerate_regions(local: &str, upstream: &str) -> Option<String> {
+    let local_regions = find_regions(local)?;
+    let upstream_regions = find_regions(upstream)?;
+
+    if upstream_regions.is_empty() || local_regions.len() != upstream_regions.len() {
+        return None;
+    }
+    if local_regions.iter().zip(&upstream_regions).any(|(l, u)| l.name != u.name) {
+        return None;
+    }
+
+    let mut result = String::with_capacity(local.len());
+    let mut cursor = 0;
+    for (l, u) in local_regions.iter().zip(&upstream_regions) {
+        result.push_str(&local[cursor..l.body_start]);
+        result.push_str(&upstream[u.body_start..u.body_end]);
+        cursor = l.body_end;
+    }
+    result.push_str(&local[cursor..]);
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regenerate_preserves_user_content() {
+        let local = "custom:\n\techo mine\n# template-rs:begin\nbuild:\n\tcargo build\n# template-rs:end\nmore: custom\n";
+        let upstream = "# template-rs:begin\nbuild:\n\tcargo build --release\n# template-rs:end\n";
+
+        let result = regenerate_regions(local, upstream).unwrap();
+        assert_eq!(
+            result,
+            "custom:\n\techo mine\n# template-rs:begin\nbuild:\n\tcargo build --release\n# template-rs:end\nmore: custom\n"
+        );
+    }
+
+    #[test]
+    fn test_named_regions_must_match() {
+        let local = "<!-- template-rs:begin badges -->\nold\n<!-- template-rs:end -->\n";
+        let upstream = "<!-- template-rs:begin links -->\nnew\n<!-- template-rs:end -->\n";
+        assert!(regenerate_regions(local, upstream).is_none());
+
+        let upstream = "<!-- template-rs:begin badges -->\nnew\n<!-- template-rs:end -->\n";
+        assert_eq!(
+            regenerate_regions(local, upstream).unwrap(),
+            "<!-- template-rs:begin badges -->\nnew\n<!-- template-rs:end -->\n"
+        );
+    }
+
+    #[test]
+    fn test_unbalanced_markers() {
+        assert!(find_regions("// template-rs:begin\nno end\n").is_none());
+        assert!(find_regions("// template-rs:end\n").is_none());
+        assert!(find_regions("plain file\n").is_some_and(|r| r.is_empty()));
+        assert!(regenerate_regions("plain file\n", "plain file\n").is_none());
+    }
+}
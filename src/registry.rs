@@ -0,0 +1,84 @@
+use crate::compat::parse_version;
+use crate::config::UserConfig;
+use crate::path::resolve_path;
+use crate::template::Template;
+
+use std::cmp::Ordering;
+
+/// A newer version of an installed template published in one of the configured registries
+#[derive(Debug, Clone)]
+pub struct AvailableUpdate {
+    /// Template name (storage path), the same in storage and in the registry
+    pub template: String,
+    /// Version in the template storage, if it has one
+    pub installed: Option<String>,
+    /// Newest version published in a registry
+    pub available: String,
+    /// The registry (as written in config.toml) that has it
+    pub registry: String,
+}
+
+/// Finds installed templates that a registry has a newer version of. Registries are directories
+/// laid out like the template storage (a shared checkout, a network mount, ...) listed under
+/// `registries` in config.toml. Nothing is read from or written to the templates themselves.
+pub fn find_updates(
+    installed: &[Template],
+    user_config: &UserConfig,
+) -> Result<Vec<AvailableUpdate>, Box<dyn std::error::Error>> {
+    let mut published = Vec::new();
+    for registry in &user_config.registries {
+        let root = resolve_path(registry, None)?;
+        if !root.is_dir() {
+            eprintln!("Warning: registry {registry} is not a directory, skipping it");
+            continue;
+        }
+        published.extend(Template::discover_in(&root)?.into_iter().map(|t| (registry, t)));
+    }
+
+    let mut updates = Vec::new();
+    for template in installed.iter().filter(|t| !t.is_builtin()) {
+        let newest = published.iter()
+            .filter(|(_, candidate)| candidate.name == template.name)
+            .filter_map(|(registry, candidate)| Some((*registry, candidate.version()?)))
+            .filter(|(_, version)| is_newer(version, template.version()))
+            .max_by(|(_, a), (_, b)| compare_versions(a, b));
+
+        if let Some((registry, available)) = newest {
+            updates.push(AvailableUpdate {
+                template: template.name.clone(),
+                installed: template.version().map(String::from),
+                available: available.to_string(),
+                registry: registry.clone(),
+            });
+        }
+    }
+    Ok(updates)
+}
+
+/// Check if `available` is newer than `installed`; an unversioned install is older than any
+/// version, and versions that don't parse are never reported as newer
+fn is_newer(available: &str, installed: Option<&str>) -> bool {
+    let Some(available) = parse_version(available) else {
+        return false;
+    };
+    installed.is_none_or(|installed| parse_version(installed).is_some_and(|installed| available > installed))
+}
+
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    parse_version(a).cmp(&parse_version(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("1.2.0", Some("1.1.9")));
+        assert!(is_newer("0.1.0", None));
+        assert!(!is_newer("1.0.0", Some("1.0.0")));
+        assert!(!is_newer("1.0.0", Some("2.0.0")));
+        assert!(!is_newer("next", Some("1.0.0")));
+        assert!(!is_newer("2.0.0", Some("custom")));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::file::find_in_path;
+
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+
+/// A directory on another machine, written like scp does: `[user@]host:/path`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteTarget {
+    /// `host` or `user@host`, anything ssh accepts including aliases from ~/.ssh/config
+    pub host: String,
+    /// Path on the host; relative paths are relative to the login directory
+    pub path: String,
+}
+
+impl RemoteTarget {
+    /// Recognizes `[user@]host:path` targets. Local paths never match: the host part can't hold
+    /// a '/' and a single letter is taken as a Windows drive (`C:\projects`).
+    pub fn parse(target: &str) -> Option<Self> {
+        let (host, path) = target.split_once(':')?;
+        let host_name = host.rsplit('@').next().unwrap_or(host);
+        if host_name.is_empty() || host.contains(['/', '\\']) || host.len() == 1 || path.is_empty() {
+            return None;
+        }
+        Some(Self { host: host.to_string(), path: path.to_string() })
+    }
+
+    /// Copies the contents of `source` into the remote directory, creating it first. rsync is used
+    /// when installed and keeps existing remote files unless `overwrite`; scp always overwrites,
+    /// so without rsync only `overwrite` is allowed.
+    pub fn upload(&self, source: &Path, overwrite: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let use_rsync = find_in_path("rsync").is_some();
+        if !use_rsync && !overwrite {
+            return Err("Keeping existing remote files needs rsync; install it, or pass --force to overwrite them with scp".into());
+        }
+
+        run(Command::new("ssh").arg(&self.host).args(["mkdir", "-p", "--"]).arg(quote(&self.path)), "ssh")?;
+
+        if use_rsync {
+            let mut command = Command::new("rsync");
+            command.args(["-a", "-e", "ssh"]);
+            if !overwrite {
+                command.arg("--ignore-existing");
+            }
+            // Trailing slashes copy the directory's contents rather than the directory
+            command.arg(format!("{}/", source.display())).arg(format!("{}:{}/", self.host, self.path));
+            run(&mut command, "rsync")
+        } else {
+            run(
+                Command::new("scp").args(["-r", "-p", "-q"]).arg(source.join(".")).arg(self.to_string()),
+                "scp",
+            )
+        }
+    }
+}
+
+impl fmt::Display for RemoteTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.host, self.path)
+    }
+}
+
+/// Runs a transfer command, leaving the terminal to it for password and host key prompts
+fn run(command: &mut Command, program: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let status = command.status().map_err(|e| format!("Cannot run {program}: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{program} failed ({status})").into())
+    }
+}
+
+/// Quotes a path for the remote shell ssh hands commands to
+fn quote(path: &str) -> String {
+    format!("'{}'", path.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_remote_targets() {
+        assert_eq!(
+            RemoteTarget::parse("deploy@web1:/srv/app"),
+            Some(RemoteTarget { host: "deploy@web1".to_string(), path: "/srv/app".to_string() })
+        );
+        assert_eq!(RemoteTarget::parse("jump:configs").map(|t| t.to_string()).as_deref(), Some("jump:configs"));
+    }
+
+    #[test]
+    fn test_local_paths_are_not_remote() {
+        assert_eq!(RemoteTarget::parse("./my-app"), None);
+        assert_eq!(RemoteTarget::parse("C:\\projects\\app"), None);
+        assert_eq!(RemoteTarget::parse("./odd:name"), None);
+        assert_eq!(RemoteTarget::parse("host:"), None);
+        assert_eq!(RemoteTarget::parse("user@:/srv"), None);
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("/srv/it's here"), r"'/srv/it'\''s here'");
+    }
+}
@@ -0,0 +1,889 @@
+use crate::encoding::decode;
+use crate::patterns::matches_any;
+use crate::placeholders::Placeholder;
+use crate::plugins::PluginFunctions;
+use crate::template::Template;
+use crate::timing::{Phase, span};
+use crate::variables::is_valid_name;
+
+use handlebars::{Handlebars, Helper, HelperDef, JsonRender, RenderContext, RenderError, RenderErrorReason, ScopedJson};
+use liquid::reflection::ParserReflection;
+use liquid_core::parser::{FilterArguments, FilterReflection, ParameterReflection, ParseFilter};
+use liquid_core::{Filter, Runtime, ValueView};
+use serde::{Deserialize, Serialize};
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Opens a block copied verbatim, e.g. around `${{ github.token }}` in a workflow file
+const RAW_BEGIN: &str = "raw";
+/// Closes a raw block
+const RAW_END: &str = "endraw";
+
+/// Callback for filters that aren't built in, given the filter name and the value. None when
+/// no such filter exists.
+pub type CustomFilter<'a> = dyn FnMut(&str, &str) -> Result<Option<String>, String> + 'a;
+
+/// A filter registered with a rendering engine, given the filter name and the value
+type EngineFilter = dyn Fn(&str, &str) -> Result<String, String> + Send + Sync;
+
+/// Placeholder syntax of a template's files and paths, `engine` in .template.toml. Templates
+/// imported from other ecosystems keep their native syntax.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderEngine {
+    /// `{{ name | filter }}` and `{% raw %}` blocks (Tera/Jinja style)
+    #[default]
+    Tera,
+    /// Handlebars: `{{name}}` (HTML-escaped), `{{{name}}}`, `{{helper name}}` and block helpers
+    Handlebars,
+    /// Liquid: `{{ name | filter }}` with Liquid's filters, and its tags (`{% if %}`, `{% for %}`, ...)
+    Liquid,
+    /// Files are copied as they are
+    None,
+}
+
+impl RenderEngine {
+    /// The renderer implementing the syntax
+    pub fn renderer(self) -> &'static dyn Renderer {
+        match self {
+            Self::Tera => &TeraSyntax,
+            Self::Handlebars => &HandlebarsSyntax,
+            Self::Liquid => &LiquidSyntax,
+            Self::None => &NoRendering,
+        }
+    }
+}
+
+/// A placeholder syntax, substituting an application's variable values into text.
+///
+/// Placeholders naming a variable that has no value are left as written and reported as
+/// leftovers, whatever the syntax.
+pub trait Renderer {
+    /// Check if the text may have placeholders; text without any is left alone
+    fn has_placeholders(&self, text: &str) -> bool;
+
+    /// How literal braces are written in this syntax, for hints about leftover placeholders
+    fn literal_braces(&self) -> &'static str {
+        "{% raw %}...{% endraw %}"
+    }
+
+    /// Renders the text, calling `custom` for filters (helpers) that aren't built in
+    fn render(&self, text: &str, values: &BTreeMap<String, String>, custom: &mut CustomFilter) -> Result<Rendered, String>;
+}
+
+/// The default syntax, see `render_text`
+pub struct TeraSyntax;
+
+impl Renderer for TeraSyntax {
+    fn has_placeholders(&self, text: &str) -> bool {
+        text.contains("{{") || text.contains("{%")
+    }
+
+    fn render(&self, text: &str, values: &BTreeMap<String, String>, custom: &mut CustomFilter) -> Result<Rendered, String> {
+        render_text(text, values, custom)
+    }
+}
+
+/// Liquid templates, rendered by the `liquid` crate with its standard tags and filters, plus the
+/// built-in filters and the template's plugin filters. Filters taking arguments other than
+/// Liquid's own aren't supported.
+pub struct LiquidSyntax;
+
+impl Renderer for LiquidSyntax {
+    fn has_placeholders(&self, text: &str) -> bool {
+        text.contains("{{") || text.contains("{%")
+    }
+
+    fn render(&self, text: &str, values: &BTreeMap<String, String>, custom: &mut CustomFilter) -> Result<Rendered, String> {
+        let (text, leftovers) = escape_liquid_leftovers(text, values);
+        let deferred = DeferredFilters::default();
+        let mut builder = liquid::ParserBuilder::with_stdlib();
+        let standard: BTreeSet<String> = builder.filters().map(|filter| filter.name().to_string()).collect();
+        for name in BUILTIN_FILTERS {
+            builder = builder.filter(LiquidFilter::new(name, |name, value| Ok(apply_builtin_filter(name, value).unwrap_or_default())));
+        }
+        // Any name following a '|' may be a plugin filter; registering one that isn't costs nothing
+        for name in filter_names(&text).into_iter().filter(|name| !standard.contains(*name) && !BUILTIN_FILTERS.contains(name)) {
+            let deferred = deferred.clone();
+            builder = builder.filter(LiquidFilter::new(name, move |name, value| deferred.call(name, value)));
+        }
+        let template = builder.build().and_then(|parser| parser.parse(&text)).map_err(|e| e.to_string().trim().to_string())?;
+
+        let globals: liquid::Object = values.iter()
+            .map(|(name, value)| {
+                let value = match value.as_str() {
+                    "true" | "false" => liquid::model::Value::scalar(value == "true"),
+                    _ => liquid::model::Value::scalar(value.clone()),
+                };
+                (name.clone().into(), value)
+            })
+            .collect();
+        let text = deferred.render("filter", || template.render(&globals).map_err(|e| e.to_string().trim().to_string()), custom)?;
+        Ok(Rendered { text, leftovers })
+    }
+}
+
+/// Handlebars templates, rendered by the `handlebars` crate: `{{name}}` HTML-escapes the value,
+/// `{{{name}}}` doesn't, built-in filters and plugin filters are helpers (`{{snake_case name}}`),
+/// and the standard block helpers (`{{#if}}`, `{{#each}}`, ...) work. Partials aren't supported.
+pub struct HandlebarsSyntax;
+
+impl Renderer for HandlebarsSyntax {
+    fn has_placeholders(&self, text: &str) -> bool {
+        text.contains("{{")
+    }
+
+    fn literal_braces(&self) -> &'static str {
+        "\\{{"
+    }
+
+    fn render(&self, text: &str, values: &BTreeMap<String, String>, custom: &mut CustomFilter) -> Result<Rendered, String> {
+        let (text, leftovers) = escape_handlebars_leftovers(text, values);
+        let deferred = DeferredFilters::default();
+        let mut registry = Handlebars::new();
+        for name in BUILTIN_FILTERS {
+            registry.register_helper(name, Box::new(FilterHelper(|name: &str, value: &str| Ok(apply_builtin_filter(name, value).unwrap_or_default()))));
+        }
+        // Helpers that aren't registered end up here, which plugin filters resolve
+        let missing = deferred.clone();
+        registry.register_helper("helperMissing", Box::new(FilterHelper(move |name: &str, value: &str| missing.call(name, value))));
+        registry.register_template_string(HANDLEBARS_TEMPLATE, &text).map_err(|e| e.to_string())?;
+
+        let context: serde_json::Map<String, serde_json::Value> = values.iter()
+            .map(|(name, value)| {
+                let value = match value.as_str() {
+                    "true" | "false" => serde_json::Value::Bool(value == "true"),
+                    _ => serde_json::Value::String(value.clone()),
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        let text = deferred.render("helper", || registry.render(HANDLEBARS_TEMPLATE, &context).map_err(|e| e.to_string()), custom)?;
+        Ok(Rendered { text, leftovers })
+    }
+}
+
+/// Name the text is registered under for one Handlebars render
+const HANDLEBARS_TEMPLATE: &str = "text";
+
+/// A Handlebars helper taking one value and giving `filter(helper name, value)`. A helper used
+/// without a value is a variable without one, which renders as nothing.
+struct FilterHelper<F>(F);
+
+impl<F: Fn(&str, &str) -> Result<String, String> + Send + Sync> HelperDef for FilterHelper<F> {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        helper: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc handlebars::Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        match helper.params().as_slice() {
+            [] => Ok(ScopedJson::Missing),
+            [value] => (self.0)(helper.name(), &value.value().render())
+                .map(|filtered| ScopedJson::Derived(serde_json::Value::String(filtered)))
+                .map_err(|e| RenderErrorReason::Other(e).into()),
+            _ => Err(RenderErrorReason::Other(format!("helper '{}' takes one value", helper.name())).into()),
+        }
+    }
+}
+
+/// A Liquid filter without arguments giving `filter(filter name, value)`
+#[derive(Clone)]
+struct LiquidFilter {
+    name: String,
+    filter: Arc<EngineFilter>,
+}
+
+impl LiquidFilter {
+    fn new(name: &str, filter: impl Fn(&str, &str) -> Result<String, String> + Send + Sync + 'static) -> Self {
+        Self { name: name.to_string(), filter: Arc::new(filter) }
+    }
+}
+
+impl fmt::Debug for LiquidFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LiquidFilter").field("name", &self.name).finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for LiquidFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl FilterReflection for LiquidFilter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        ""
+    }
+
+    fn positional_parameters(&self) -> &'static [ParameterReflection] {
+        &[]
+    }
+
+    fn keyword_parameters(&self) -> &'static [ParameterReflection] {
+        &[]
+    }
+}
+
+impl ParseFilter for LiquidFilter {
+    fn parse(&self, mut arguments: FilterArguments) -> liquid_core::Result<Box<dyn Filter>> {
+        if arguments.positional.next().is_some() || arguments.keyword.next().is_some() {
+            return Err(liquid_core::Error::with_msg(format!("filter '{}' takes no arguments", self.name)));
+        }
+        Ok(Box::new(self.clone()))
+    }
+
+    fn reflection(&self) -> &dyn FilterReflection {
+        self
+    }
+}
+
+impl Filter for LiquidFilter {
+    fn evaluate(&self, input: &dyn ValueView, _: &dyn Runtime) -> liquid_core::Result<liquid::model::Value> {
+        (self.filter)(&self.name, input.to_kstr().as_str())
+            .map(liquid::model::Value::scalar)
+            .map_err(liquid_core::Error::with_msg)
+    }
+}
+
+/// Plugin filters called while rendering with an engine. Engines need filters they can share
+/// between threads, which plugins aren't, so a call without a known result is recorded and
+/// stops the render; `render` then runs the plugin and renders again, until every call the
+/// text makes has a result.
+#[derive(Clone, Default)]
+struct DeferredFilters(Arc<Mutex<DeferredCalls>>);
+
+#[derive(Default)]
+struct DeferredCalls {
+    /// Plugin results by filter name and value
+    results: BTreeMap<(String, String), String>,
+    /// The call that stopped the last render
+    pending: Option<(String, String)>,
+}
+
+impl DeferredFilters {
+    fn calls(&self) -> MutexGuard<'_, DeferredCalls> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn call(&self, filter: &str, value: &str) -> Result<String, String> {
+        let mut calls = self.calls();
+        let call = (filter.to_string(), value.to_string());
+        if let Some(result) = calls.results.get(&call) {
+            return Ok(result.clone());
+        }
+        calls.pending = Some(call);
+        Err(format!("'{filter}' is waiting for its plugin"))
+    }
+
+    /// Renders with `render` until no call is pending, running the pending call through
+    /// `custom` in between. `kind` names filters in errors ("filter" or "helper").
+    fn render(&self, kind: &str, mut render: impl FnMut() -> Result<String, String>, custom: &mut CustomFilter) -> Result<String, String> {
+        loop {
+            let rendered = render();
+            let Some((filter, value)) = self.calls().pending.take() else {
+                return rendered;
+            };
+            let result = custom(&filter, &value)
+                .map_err(|e| format!("{kind} '{filter}' failed: {e}"))?
+                .ok_or_else(|| format!("unknown {kind} '{filter}'"))?;
+            self.calls().results.insert((filter, value), result);
+        }
+    }
+}
+
+/// Names following a '|' in some text, the filters Liquid tags may use
+fn filter_names(text: &str) -> BTreeSet<&str> {
+    text.split('|')
+        .skip(1)
+        .filter_map(|after| {
+            let after = after.trim_start();
+            let end = after.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(after.len());
+            is_valid_name(&after[..end]).then(|| &after[..end])
+        })
+        .collect()
+}
+
+/// Wraps the Liquid output tags naming a variable without a value in raw blocks, so they are
+/// left as written, and lists them. Raw and comment blocks are skipped, and so are the names
+/// tags bind (`for`, `assign`, `capture`, ...).
+fn escape_liquid_leftovers(text: &str, values: &BTreeMap<String, String>) -> (String, Vec<Placeholder>) {
+    let mut bound: BTreeSet<&str> = LIQUID_OBJECTS.into_iter().collect();
+    let mut rest = text;
+    while let Some(start) = rest.find("{%") {
+        let Some(end) = rest[start..].find("%}") else {
+            break;
+        };
+        let mut words = rest[start + 2..start + end].trim_matches('-').split_whitespace();
+        if let (Some("for" | "tablerow" | "assign" | "capture" | "increment" | "decrement"), Some(name)) = (words.next(), words.next()) {
+            bound.insert(name.split('=').next().unwrap_or_default());
+        }
+        rest = &rest[start + end + 2..];
+    }
+
+    let mut output = String::with_capacity(text.len());
+    let mut leftovers = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{").into_iter().chain(rest.find("{%")).min() {
+        output.push_str(&rest[..start]);
+        let line = text[..text.len() - rest.len() + start].matches('\n').count() + 1;
+        let tag = &rest[start..];
+        let close = if tag.starts_with("{{") { "}}" } else { "%}" };
+        let Some(end) = tag.find(close) else {
+            output.push_str(tag);
+            return (output, leftovers);
+        };
+        let placeholder = &tag[..end + 2];
+        rest = &tag[end + 2..];
+        let inner = tag[2..end].trim_matches('-').trim();
+
+        if close == "%}" {
+            output.push_str(placeholder);
+            if let Some(block @ ("raw" | "comment")) = inner.split_whitespace().next()
+                && let Some((_, after)) = split_block_end(rest, &format!("end{block}"))
+            {
+                output.push_str(&rest[..rest.len() - after.len()]);
+                rest = after;
+            }
+            continue;
+        }
+
+        let name = inner.split('|').next().unwrap_or_default().trim();
+        if is_valid_name(name) && !values.contains_key(name) && !bound.contains(name) {
+            output.push_str("{% raw %}");
+            output.push_str(placeholder);
+            output.push_str("{% endraw %}");
+            leftovers.push(Placeholder { line, text: placeholder.to_string() });
+        } else {
+            output.push_str(placeholder);
+        }
+    }
+    output.push_str(rest);
+    (output, leftovers)
+}
+
+/// Names Liquid defines itself, which never need a value
+const LIQUID_OBJECTS: [&str; 8] = ["forloop", "tablerowloop", "true", "false", "nil", "null", "empty", "blank"];
+
+/// Escapes the Handlebars expressions naming a variable without a value, so they are left as
+/// written, and lists them. Comments, raw blocks and escaped expressions are skipped, and so
+/// are block parameters (`as |item|`) and `this`.
+fn escape_handlebars_leftovers(text: &str, values: &BTreeMap<String, String>) -> (String, Vec<Placeholder>) {
+    let mut bound: BTreeSet<&str> = ["this", "else"].into_iter().collect();
+    for params in text.split("as |").skip(1) {
+        bound.extend(params.split('|').next().unwrap_or_default().split_whitespace());
+    }
+    let missing = |name: &str| is_valid_name(name) && !values.contains_key(name) && !bound.contains(name);
+
+    let mut output = String::with_capacity(text.len());
+    let mut leftovers = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let line = text[..text.len() - rest.len() + start].matches('\n').count() + 1;
+        let tag = &rest[start..];
+
+        let escaped = rest[..start].ends_with('\\');
+        let comment = tag[2..].trim_start_matches('~').strip_prefix('!');
+        if escaped || tag.starts_with("{{{{") || comment.is_some() {
+            let end = if escaped {
+                tag.find("}}").map(|end| end + 2)
+            } else if let Some(comment) = comment {
+                let close = if comment.starts_with("--") { "--}}" } else { "}}" };
+                comment.find(close).map(|end| tag.len() - comment.len() + end + close.len())
+            } else {
+                tag.find("{{{{/").and_then(|close| tag[close..].find("}}}}").map(|end| close + end + 4))
+            };
+            let end = end.unwrap_or(tag.len());
+            output.push_str(&tag[..end]);
+            rest = &tag[end..];
+            continue;
+        }
+
+        let close = if tag.starts_with("{{{") { "}}}" } else { "}}" };
+        let Some(end) = tag.find(close) else {
+            output.push_str(tag);
+            return (output, leftovers);
+        };
+        let placeholder = &tag[..end + close.len()];
+        rest = &tag[end + close.len()..];
+        let inner = tag[close.len()..end].trim_matches('~');
+        let leftover = match inner.split_whitespace().collect::<Vec<_>>()[..] {
+            [name] => missing(name) && !BUILTIN_FILTERS.contains(&name),
+            [helper, name] => is_valid_name(helper) && missing(name),
+            _ => false,
+        };
+        if leftover {
+            output.push('\\');
+            leftovers.push(Placeholder { line, text: placeholder.to_string() });
+        }
+        output.push_str(placeholder);
+    }
+    output.push_str(rest);
+    (output, leftovers)
+}
+
+/// Leaves text as it is, for templates with `engine = "none"`
+pub struct NoRendering;
+
+impl Renderer for NoRendering {
+    fn has_placeholders(&self, _text: &str) -> bool {
+        false
+    }
+
+    fn render(&self, text: &str, _values: &BTreeMap<String, String>, _custom: &mut CustomFilter) -> Result<Rendered, String> {
+        Ok(Rendered { text: text.to_string(), leftovers: Vec::new() })
+    }
+}
+
+/// Text with its placeholders substituted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rendered {
+    pub text: String,
+    /// Placeholders outside raw blocks that were left as written because their variable has no value
+    pub leftovers: Vec<Placeholder>,
+}
+
+/// Substitutes `{{ name }}` and `{{ name | filter | ... }}` placeholders with variable values.
+///
+/// Placeholders naming a variable that has no value are left as written, so text that uses
+/// braces for something else (Handlebars, Jinja, GitHub Actions) passes through unharmed; they
+/// are reported as leftovers. `{% raw %}...{% endraw %}` blocks are copied verbatim without their markers.
+pub fn render_text(
+    text: &str,
+    values: &BTreeMap<String, String>,
+    custom: &mut CustomFilter,
+) -> Result<Rendered, String> {
+    let mut output = String::with_capacity(text.len());
+    let mut leftovers = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{").into_iter().chain(rest.find("{%")).min() {
+        output.push_str(&rest[..start]);
+        let line = text[..text.len() - rest.len() + start].matches('\n').count() + 1;
+        let tag = &rest[start..];
+
+        if tag.starts_with("{%") {
+            let Some(end) = tag.find("%}") else {
+                output.push_str(tag);
+                return Ok(Rendered { text: output, leftovers });
+            };
+            if tag[2..end].trim() != RAW_BEGIN {
+                output.push_str(&tag[..end + 2]);
+                rest = &tag[end + 2..];
+                continue;
+            }
+            let body = &tag[end + 2..];
+            let (raw, after) = split_block_end(body, RAW_END).ok_or_else(|| format!("line {line}: {{% raw %}} is never closed"))?;
+            output.push_str(raw);
+            rest = after;
+            continue;
+        }
+
+        // Placeholders don't span lines; an unclosed one is plain text
+        let Some(end) = tag.find("}}").filter(|end| !tag[..*end].contains('\n')) else {
+            output.push_str("{{");
+            rest = &tag[2..];
+            continue;
+        };
+        let placeholder = &tag[..end + 2];
+        rest = &tag[end + 2..];
+
+        let mut parts = tag[2..end].split('|').map(str::trim);
+        let name = parts.next().unwrap_or_default();
+        let Some(value) = values.get(name).filter(|_| is_valid_name(name)) else {
+            output.push_str(placeholder);
+            leftovers.push(Placeholder { line, text: placeholder.to_string() });
+            continue;
+        };
+        let mut value = value.clone();
+        for filter in parts {
+            value = match apply_builtin_filter(filter, &value) {
+                Some(filtered) => filtered,
+                None => custom(filter, &value)
+                    .map_err(|e| format!("line {line}: filter '{filter}' failed: {e}"))?
+                    .ok_or_else(|| format!("line {line}: unknown filter '{filter}' in {placeholder}"))?,
+            };
+        }
+        output.push_str(&value);
+    }
+
+    output.push_str(rest);
+    Ok(Rendered { text: output, leftovers })
+}
+
+/// Names of the variables the placeholders in some text refer to
+pub fn referenced_names(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let inner = &rest[start + 2..start + end];
+        let name = inner.split('|').next().unwrap_or_default().trim();
+        if is_valid_name(name) {
+            names.push(name.to_string());
+        }
+        rest = &rest[start + end + 2..];
+    }
+    names
+}
+
+/// Splits the body of a block at its `{% end %}` tag (`{%- end -%}` too), returning the text
+/// inside and what follows the tag
+fn split_block_end<'a>(body: &'a str, end_tag: &str) -> Option<(&'a str, &'a str)> {
+    let mut offset = 0;
+    while let Some(start) = body[offset..].find("{%") {
+        let start = offset + start;
+        let end = start + body[start..].find("%}")?;
+        if body[start + 2..end].trim_matches('-').trim() == end_tag {
+            return Some((&body[..start], &body[end + 2..]));
+        }
+        offset = end + 2;
+    }
+    None
+}
+
+/// Filters every template can use, whatever its syntax
+const BUILTIN_FILTERS: [&str; 6] = ["lower", "upper", "snake_case", "kebab_case", "camel_case", "pascal_case"];
+
+/// Applies one of `BUILTIN_FILTERS`, None for any other filter
+fn apply_builtin_filter(filter: &str, value: &str) -> Option<String> {
+    let words = || split_words(value);
+    let capitalized = |word: &String| {
+        let mut chars = word.chars();
+        chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+    };
+    Some(match filter {
+        "lower" => value.to_lowercase(),
+        "upper" => value.to_uppercase(),
+        "snake_case" => words().join("_"),
+        "kebab_case" => words().join("-"),
+        "pascal_case" => words().iter().map(capitalized).collect(),
+        "camel_case" => {
+            let words = words();
+            let mut camel = words.first().cloned().unwrap_or_default();
+            camel.extend(words.iter().skip(1).map(capitalized));
+            camel
+        }
+        _ => return None,
+    })
+}
+
+/// Lowercase words of an identifier or phrase: "My app", "my-app" and "MyApp" give ["my", "app"]
+fn split_words(value: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in value.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase() || c.is_numeric();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Files rendered into a target
+#[derive(Debug, Default)]
+pub struct RenderedFiles {
+    /// Where each file ended up, relative to the target
+    pub paths: Vec<String>,
+    /// Placeholders left unrendered, with the path of the file they're in
+    pub leftovers: Vec<(String, Placeholder)>,
+}
+
+/// Renders a template's files and paths with the values of one application, in the template's syntax
+pub struct TemplateRenderer {
+    engine: &'static dyn Renderer,
+    values: BTreeMap<String, String>,
+    /// Globs of files copied without rendering (`verbatim` in .template.toml)
+    verbatim: Vec<String>,
+    /// Declared encodings of files that aren't UTF-8
+    encodings: BTreeMap<String, String>,
+    /// Filters exported by the template's WASM plugins
+    plugins: RefCell<PluginFunctions>,
+}
+
+impl TemplateRenderer {
+    pub fn new(template: &Template, values: BTreeMap<String, String>) -> Result<Self, Box<dyn std::error::Error>> {
+        let plugins = if values.is_empty() { PluginFunctions::default() } else { PluginFunctions::load(template)? };
+        Ok(Self {
+            engine: template.config.engine.unwrap_or_default().renderer(),
+            values,
+            verbatim: template.config.verbatim.clone().unwrap_or_default(),
+            encodings: template.config.files.iter()
+                .flatten()
+                .filter_map(|(file, config)| Some((file.clone(), config.encoding.clone()?)))
+                .collect(),
+            plugins: RefCell::new(plugins),
+        })
+    }
+
+    /// How literal braces are written in the template's syntax
+    pub fn literal_braces(&self) -> &'static str {
+        self.engine.literal_braces()
+    }
+
+    /// Check if there is anything to substitute; templates without variables are copied as they are
+    pub fn is_active(&self) -> bool {
+        !self.values.is_empty()
+    }
+
+    /// The path a template file is written to, with placeholders in its name substituted
+    pub fn render_path(&self, file: &str) -> Result<String, String> {
+        if self.values.is_empty() || !self.engine.has_placeholders(file) {
+            return Ok(file.to_string());
+        }
+        let rendered = self.render(file).map_err(|e| format!("{file}: {e}"))?.text;
+        if rendered.split('/').any(|part| part.is_empty() || part == "..") {
+            return Err(format!("{file}: renders to '{rendered}', which is not a valid relative path"));
+        }
+        Ok(rendered)
+    }
+
+    /// Renders a one-off text that isn't a file, such as a hook command
+    pub fn render_value(&self, text: &str) -> Result<String, String> {
+        if self.values.is_empty() || !self.engine.has_placeholders(text) {
+            return Ok(text.to_string());
+        }
+        Ok(self.render(text)?.text)
+    }
+
+    /// Renders a file's content, None when it stays unchanged (binary, verbatim or nothing to substitute)
+    pub fn apply(&self, file: &str, content: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.render_content(file, content)?.0)
+    }
+
+    /// Renders a file's content like `apply`, also returning the placeholders left in it
+    fn render_content(&self, file: &str, content: &[u8]) -> Result<(Option<Vec<u8>>, Vec<Placeholder>), String> {
+        if !self.is_active() || matches_any(file, &self.verbatim) {
+            return Ok((None, Vec::new()));
+        }
+        let declared = self.encodings.get(file).map(String::as_str);
+        let Some((text, encoding)) = decode(content, declared) else {
+            return Ok((None, Vec::new()));
+        };
+        if !self.engine.has_placeholders(&text) {
+            return Ok((None, Vec::new()));
+        }
+        let rendered = self.render(&text).map_err(|e| format!("{file}: {e}"))?;
+        if rendered.text == text {
+            return Ok((None, rendered.leftovers));
+        }
+        let bytes = encoding.encode(&rendered.text)
+            .ok_or_else(|| format!("{file}: rendered text has characters its encoding can't represent"))?;
+        Ok((Some(bytes), rendered.leftovers))
+    }
+
+    /// Renders files just copied into the target and moves the ones with placeholders in their
+    /// path to the rendered path. `overwrite` says whether an existing file at a rendered path
+    /// may be replaced; if not, the copy is dropped.
+    pub fn apply_to_files(
+        &self,
+        target_path: &Path,
+        files: &[String],
+        overwrite: impl Fn(&str) -> bool,
+    ) -> Result<RenderedFiles, Box<dyn std::error::Error>> {
+        let _timing = span(Phase::Render);
+        let mut rendered_files = RenderedFiles::default();
+        for file in files {
+            let source = target_path.join(file);
+            let (rendered, leftovers) = self.render_content(file, &fs::read(&source)?)?;
+            if let Some(rendered) = rendered {
+                fs::write(&source, rendered)?;
+            }
+
+            let rendered_path = self.render_path(file)?;
+            if rendered_path != *file {
+                let destination = target_path.join(&rendered_path);
+                let keep_existing = destination.exists() && !overwrite(file);
+                if keep_existing {
+                    fs::remove_file(&source)?;
+                } else {
+                    if let (Some(parent), Some(copied_into)) = (destination.parent(), source.parent()) {
+                        create_dirs_like(parent, copied_into)?;
+                    }
+                    fs::rename(&source, &destination)?;
+                }
+                remove_emptied_dirs(target_path, &source);
+                if keep_existing {
+                    continue;
+                }
+            }
+            rendered_files.leftovers.extend(leftovers.into_iter().map(|leftover| (rendered_path.clone(), leftover)));
+            rendered_files.paths.push(rendered_path);
+        }
+        Ok(rendered_files)
+    }
+
+    fn render(&self, text: &str) -> Result<Rendered, String> {
+        let mut plugins = self.plugins.borrow_mut();
+        self.engine.render(text, &self.values, &mut |filter, value| plugins.filter(filter, value))
+    }
+}
+
+/// Creates `dir` and any missing parents with the permissions of `like`, the directory the
+/// file was copied into, so the copy's permission mask carries over to rendered paths
+fn create_dirs_like(dir: &Path, like: &Path) -> std::io::Result<()> {
+    let missing: Vec<&Path> = dir.ancestors().take_while(|ancestor| !ancestor.exists()).collect();
+    fs::create_dir_all(dir)?;
+    let permissions = fs::metadata(like)?.permissions();
+    for created in missing {
+        fs::set_permissions(created, permissions.clone())?;
+    }
+    Ok(())
+}
+
+/// Removes the directories a moved file leaves empty, up to the target itself
+pub fn remove_emptied_dirs(target_path: &Path, moved: &Path) {
+    for dir in moved.ancestors().skip(1).take_while(|dir| *dir != target_path) {
+        if fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> BTreeMap<String, String> {
+        BTreeMap::from([("name".to_string(), "My App".to_string())])
+    }
+
+    fn render(text: &str) -> Result<String, String> {
+        let rendered = render_text(text, &values(), &mut |filter, value| Ok((filter == "reverse").then(|| value.chars().rev().collect())))?;
+        Ok(rendered.text)
+    }
+
+    #[test]
+    fn test_render_text() {
+        assert_eq!(render("name = \"{{ name }}\"").unwrap(), "name = \"My App\"");
+        assert_eq!(render("{{name|snake_case}} {{ name | kebab_case }}").unwrap(), "my_app my-app");
+        assert_eq!(render("{{ name | pascal_case }} {{ name | camel_case }}").unwrap(), "MyApp myApp");
+        assert_eq!(render("{{ name | upper | reverse }}").unwrap(), "PPA YM");
+
+        // Unknown variables, other tags and unclosed braces pass through
+        assert_eq!(render("{{ other }} {% if x %} {{ name").unwrap(), "{{ other }} {% if x %} {{ name");
+        assert_eq!(render("a {% raw %}${{ name }}{% endraw %} b").unwrap(), "a ${{ name }} b");
+
+        assert_eq!(render("\n{{ name | shout }}").unwrap_err(), "line 2: unknown filter 'shout' in {{ name | shout }}");
+        assert!(render("{% raw %} {{ name }}").is_err());
+    }
+
+    #[test]
+    fn test_leftovers() {
+        let text = "{{ name }}\n{{ nmae }} {% raw %}{{ other }}{% endraw %}\n{{ 1 }}";
+        let rendered = render_text(text, &values(), &mut |_, _| Ok(None)).unwrap();
+        assert_eq!(
+            rendered.leftovers,
+            vec![
+                Placeholder { line: 2, text: "{{ nmae }}".to_string() },
+                Placeholder { line: 3, text: "{{ 1 }}".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_handlebars() {
+        let values = BTreeMap::from([
+            ("name".to_string(), "My App".to_string()),
+            ("html".to_string(), "<a href=\"x\">".to_string()),
+            ("serde".to_string(), "false".to_string()),
+        ]);
+        let render = |text: &str| HandlebarsSyntax.render(text, &values, &mut |_, _| Ok(None)).map(|rendered| rendered.text);
+        assert_eq!(render("{{name}} {{ snake_case name }}").unwrap(), "My App my_app");
+        assert_eq!(render("{{html}} {{{html}}}").unwrap(), "&lt;a href&#x3D;&quot;x&quot;&gt; <a href=\"x\">");
+        assert_eq!(render("a {{! note }}b{{!-- }} --}} \\{{name}}").unwrap(), "a b {{name}}");
+        assert_eq!(render("x   {{~name~}}   y").unwrap(), "xMy Appy");
+        assert_eq!(render("{{#if serde}}serde{{else}}plain{{/if}} {{#unless serde}}{{upper name}}{{/unless}}").unwrap(), "plain MY APP");
+
+        // Unknown variables are left as written, also inside blocks
+        let rendered = HandlebarsSyntax.render("{{#if name}}{{other}}{{/if}}\n{{upper other}}", &values, &mut |_, _| Ok(None)).unwrap();
+        assert_eq!(rendered.text, "{{other}}\n{{upper other}}");
+        assert_eq!(
+            rendered.leftovers,
+            vec![
+                Placeholder { line: 1, text: "{{other}}".to_string() },
+                Placeholder { line: 2, text: "{{upper other}}".to_string() },
+            ]
+        );
+        assert_eq!(render("{{shout name}}").unwrap_err(), "unknown helper 'shout'");
+        assert!(render("{{#if name}}unclosed").is_err());
+    }
+
+    #[test]
+    fn test_liquid_and_none() {
+        let values = BTreeMap::from([("name".to_string(), "My App".to_string()), ("serde".to_string(), "false".to_string())]);
+        let render = |text: &str| LiquidSyntax.render(text, &values, &mut |_, _| Ok(None));
+        let liquid = render("{{ name | upcase }} {{ name | downcase | capitalize }} {{ name | snake_case }}").unwrap();
+        assert_eq!(liquid.text, "MY APP My app my_app");
+        let liquid = render("{% if serde %}serde{% else %}plain{% endif %}{% for c in (1..2) %} {{ c }}{% endfor %} {{ name | replace: \" \", \"\" }}").unwrap();
+        assert_eq!(liquid.text, "plain 1 2 MyApp");
+
+        // Unknown variables are left as written, raw blocks aren't rendered
+        let liquid = render("{{ other | upcase }} {% raw %}{{ name }}{% endraw %}\n{{- name -}}").unwrap();
+        assert_eq!(liquid.text, "{{ other | upcase }} {{ name }}My App");
+        assert_eq!(liquid.leftovers, vec![Placeholder { line: 1, text: "{{ other | upcase }}".to_string() }]);
+        assert_eq!(render("{{ name | shout }}").unwrap_err(), "unknown filter 'shout'");
+        assert!(render("{% if serde %}unclosed").is_err());
+
+        assert!(!NoRendering.has_placeholders("{{ name }}"));
+        assert_eq!(RenderEngine::None.renderer().render("{{ name }}", &values, &mut |_, _| Ok(None)).unwrap().text, "{{ name }}");
+    }
+
+    #[test]
+    fn test_engines_call_plugin_filters() {
+        let values = values();
+        let mut calls = Vec::new();
+        let mut mirror = |filter: &str, value: &str| {
+            calls.push(value.to_string());
+            Ok((filter == "mirror").then(|| value.chars().rev().collect()))
+        };
+        let liquid = LiquidSyntax.render("{{ name | mirror | upcase | mirror }}", &values, &mut mirror).unwrap();
+        assert_eq!(liquid.text, "MY APP");
+        let handlebars = HandlebarsSyntax.render("{{mirror name}} {{upper (mirror name)}}", &values, &mut mirror).unwrap();
+        assert_eq!(handlebars.text, "ppA yM PPA YM");
+        // Each distinct call reaches the plugin once
+        assert_eq!(calls, ["My App", "PPA YM", "My App"]);
+
+        let mut failing = |_: &str, _: &str| Err("boom".to_string());
+        assert_eq!(LiquidSyntax.render("{{ name | mirror }}", &values, &mut failing).unwrap_err(), "filter 'mirror' failed: boom");
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words("HTTPServer2go"), vec!["httpserver2go"]);
+        assert_eq!(split_words("myHttp-server_v2"), vec!["my", "http", "server", "v2"]);
+        assert_eq!(split_words("  My   App "), vec!["my", "app"]);
+    }
+
+    #[test]
+    fn test_referenced_names() {
+        assert_eq!(referenced_names("{{ a }}-{{ b | upper }} {{ not valid }}"), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::categories::get_category_display_name;
+use crate::languages::{edit_distance, get_display_name};
+use crate::tags::normalize_tags;
+use crate::template::Template;
+
+/// How much a match in each field counts: the name says the most about a template
+const NAME_WEIGHT: u32 = 3;
+const TAG_WEIGHT: u32 = 2;
+const LANGUAGE_WEIGHT: u32 = 2;
+const DESCRIPTION_WEIGHT: u32 = 1;
+
+/// Templates matching every word of `query`, best first (ties by name) with their scores
+pub fn search<'a>(templates: &'a [Template], query: &str) -> Vec<(&'a Template, u32)> {
+    let mut results: Vec<(&Template, u32)> = templates.iter()
+        .filter_map(|template| score(template, query).map(|score| (template, score)))
+        .collect();
+    results.sort_by(|(a, a_score), (b, b_score)| b_score.cmp(a_score).then_with(|| a.name.cmp(&b.name)));
+    results
+}
+
+/// How well a template matches a search across its name, tags, language, category and
+/// description, higher is better. None when a word of the query matches nothing.
+pub fn score(template: &Template, query: &str) -> Option<u32> {
+    let mut fields: Vec<(u32, String)> = vec![(NAME_WEIGHT, template.name.clone())];
+    fields.extend(template.config.name.iter().map(|name| (NAME_WEIGHT, name.clone())));
+    for tag in template.tags().map(normalize_tags).unwrap_or_default() {
+        fields.push((TAG_WEIGHT, tag));
+    }
+    if let Some(language) = template.language() {
+        fields.push((LANGUAGE_WEIGHT, get_display_name(language)));
+    }
+    if let Some(category) = template.category() {
+        fields.push((TAG_WEIGHT, get_category_display_name(category)));
+    }
+    fields.extend(template.description().map(|d| (DESCRIPTION_WEIGHT, d.to_string())));
+
+    let mut total = 0;
+    for term in query.split_whitespace().map(str::to_lowercase) {
+        let best = fields.iter().map(|(weight, text)| weight * field_score(&term, text)).max().unwrap_or(0);
+        if best == 0 {
+            return None;
+        }
+        total += best;
+    }
+    (total > 0).then_some(total)
+}
+
+/// How well one lowercase search term matches a text, 0 for not at all: whole text, whole word,
+/// word prefix, substring, a typo away from a word, then the letters in order (`rtcl` in `rust-cli`).
+/// Letters in order only count in single-word texts like names, they'd match most sentences.
+fn field_score(term: &str, text: &str) -> u32 {
+    let text = text.to_lowercase();
+    let words: Vec<&str> = text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+
+    if text == term {
+        return 100;
+    }
+    if words.contains(&term) {
+        return 90;
+    }
+    if words.iter().any(|word| word.starts_with(term)) {
+        return 70;
+    }
+    if text.contains(term) {
+        return 50;
+    }
+    let allowed_typos = if term.chars().count() <= 4 { 1 } else { 2 };
+    if term.chars().count() >= 3
+        && let Some(distance) = words.iter().map(|word| edit_distance(term, word)).min()
+        && distance <= allowed_typos
+    {
+        return 40 - 10 * u32::try_from(distance).unwrap_or(0);
+    }
+    if term.chars().count() >= 2 && !text.contains(char::is_whitespace) && is_subsequence(term, &text) {
+        return 20;
+    }
+    0
+}
+
+/// Check if the characters of `needle` appear in `haystack` in order
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::TemplateConfig;
+    use std::path::PathBuf;
+
+    fn template(name: &str, language: &str, description: &str, tags: &[&str]) -> Template {
+        Template {
+            name: name.to_string(),
+            path: PathBuf::from(name),
+            config: TemplateConfig {
+                language: Some(language.to_string()),
+                description: Some(description.to_string()),
+                tags: Some(tags.iter().map(|t| (*t).to_string()).collect()),
+                ..TemplateConfig::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_field_score() {
+        assert_eq!(field_score("cli", "rust-cli"), 90);
+        assert_eq!(field_score("ru", "rust-cli"), 70);
+        assert_eq!(field_score("pyhton", "Python"), 20);
+        assert_eq!(field_score("rtcl", "rust-cli"), 20);
+        assert_eq!(field_score("go", "rust-cli"), 0);
+        assert_eq!(field_score("cli", "plain HTML, CSS and JavaScript"), 0);
+    }
+
+    #[test]
+    fn test_search_ranks_names_first() {
+        let templates = [
+            template("web/api", "go", "HTTP service with a cli for migrations", &["web"]),
+            template("rust/cli", "rs", "Command-line app", &["cli"]),
+            template("python/lib", "py", "Library", &[]),
+        ];
+        let names: Vec<&str> = search(&templates, "cli").iter().map(|(t, _)| t.name.as_str()).collect();
+        assert_eq!(names, ["rust/cli", "web/api"]);
+
+        assert_eq!(search(&templates, "rust cli").len(), 1);
+        assert_eq!(search(&templates, "pyton")[0].0.name, "python/lib");
+        assert!(search(&templates, "haskell").is_empty());
+    }
+}
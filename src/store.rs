@@ -0,0 +1,137 @@
+use crate::commands::init::{ApplyOptions, ApplyReport, Unattended, apply};
+use crate::oci::oci_reference;
+use crate::file::{ensure_all_storage_dirs, ensure_template_storage_dir};
+use crate::migrate;
+use crate::template::Template;
+
+use std::path::{Path, PathBuf};
+
+/// The user's templates: the template storage directory plus the built-in starters
+#[derive(Debug, Clone)]
+pub struct TemplateStore {
+    root: PathBuf,
+}
+
+impl TemplateStore {
+    /// Opens the user's template storage, creating it (and moving storage from old locations)
+    /// the way the CLI does on startup
+    pub fn open() -> Result<Self, Box<dyn std::error::Error>> {
+        migrate::migrate_legacy_storage()?;
+        ensure_all_storage_dirs()?;
+        migrate::migrate_storage()?;
+        Ok(Self { root: ensure_template_storage_dir()? })
+    }
+
+    /// The directory stored templates live in, named by their path below it
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Every available template, stored and built-in, sorted by name
+    pub fn templates(&self) -> Result<Vec<Template>, Box<dyn std::error::Error>> {
+        Template::discover_all()
+    }
+
+    /// The stored templates only, without the built-in starters
+    pub fn stored(&self) -> Result<Vec<Template>, Box<dyn std::error::Error>> {
+        Template::discover_in(&self.root)
+    }
+
+    /// Finds a template by its path in the storage or the name in its config
+    pub fn find(&self, name: &str) -> Result<Option<Template>, Box<dyn std::error::Error>> {
+        Template::find(name)
+    }
+
+    /// Applies the template called `name` to the `target` directory, the way `init` does (or
+    /// `new`, with `create_dir` set), and reports what changed. Nothing is printed and nothing is
+    /// asked: variables come from `options.variables`, the environment, config.toml and their
+    /// defaults, a missing value fails, and hooks and deletions only happen with `yes` set.
+    /// Picking files, patches and progress events (`select`, `as_patch`, `json_events`) and
+    /// templates pulled from a registry are left to the command line.
+    pub fn apply(&self, name: &str, target: &Path, options: &ApplyOptions) -> Result<ApplyReport, Box<dyn std::error::Error>> {
+        if options.select || options.as_patch || options.json_events {
+            return Err("select, as_patch and json_events need the command line".into());
+        }
+        if oci_reference(name).is_some() {
+            return Err(format!("Template '{name}' comes from a registry, which only the command line pulls from").into());
+        }
+        apply(Some(name), &std::path::absolute(target)?, options, &mut Unattended)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TemplateError;
+    use crate::lock::TEMPLATE_LOCK_FILE;
+    use crate::path::TestStorage;
+    use crate::template::TEMPLATE_CONFIG_FILE;
+    use std::collections::BTreeMap;
+    use std::fs;
+
+    fn write_template(root: &Path, name: &str, config: &str, files: &[(&str, &str)]) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(TEMPLATE_CONFIG_FILE), config).unwrap();
+        for (file, content) in files {
+            fs::write(dir.join(file), content).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_discovers_stored_templates() {
+        let _storage = TestStorage::new();
+        let store = TemplateStore::open().unwrap();
+        write_template(store.root(), "web/app", "description = \"Web app\"\n", &[("index.html", "<html>\n")]);
+        write_template(store.root(), "cli", "", &[("main.rs", "fn main() {}\n")]);
+
+        let stored: Vec<String> = store.stored().unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(stored, ["cli", "web/app"]);
+        // The built-in starters come on top of the stored templates
+        let all = store.templates().unwrap();
+        assert!(all.len() > stored.len());
+        assert!(all.iter().any(|t| t.name == "web/app"));
+
+        assert_eq!(store.find("web/app").unwrap().unwrap().description(), Some("Web app"));
+        assert!(store.find("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_applies_without_asking() {
+        let storage = TestStorage::new();
+        let store = TemplateStore::open().unwrap();
+        write_template(
+            store.root(),
+            "service",
+            "[variables]\nname = { default = \"demo\" }\n\n[hooks]\npost_init = [\"touch hooked\"]\n",
+            &[("README.md", "# {{ name }}\n")],
+        );
+        let target = storage.path().join("project");
+
+        let options = ApplyOptions {
+            create_dir: true,
+            variables: BTreeMap::from([("name".to_string(), "svc".to_string())]),
+            ..ApplyOptions::default()
+        };
+        let report = store.apply("service", &target, &options).unwrap();
+        assert_eq!(report.template, "service");
+        assert_eq!(report.written, ["README.md"]);
+        assert_eq!(fs::read_to_string(target.join("README.md")).unwrap(), "# svc\n");
+        assert!(target.join(TEMPLATE_LOCK_FILE).exists());
+        // Nobody confirmed the hooks, so they were skipped
+        assert!(!target.join("hooked").exists());
+
+        // Applying again keeps what the target has, and deleting needs `yes`
+        fs::write(target.join("notes.txt"), "mine\n").unwrap();
+        let report = store.apply("service", &target, &ApplyOptions::default()).unwrap();
+        assert_eq!(report.kept, [("README.md".to_string(), "kept-existing")]);
+        let deleting = ApplyOptions { delete: true, ..ApplyOptions::default() };
+        let error = store.apply("service", &target, &deleting).unwrap_err();
+        assert!(matches!(error.downcast_ref::<TemplateError>(), Some(TemplateError::Aborted(_))));
+        assert!(target.join("notes.txt").exists());
+
+        let selecting = ApplyOptions { select: true, ..ApplyOptions::default() };
+        assert!(store.apply("service", &target, &selecting).is_err());
+        assert!(store.apply("missing", &target, &ApplyOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+
+/// Tag definition with display name and aliases
+pub struct Tag {
+    pub display_name: &'static str,
+    pub aliases: &'static [&'static str],
+}
+
+impl Tag {
+    const fn new(display_name: &'static str, aliases: &'static [&'static str]) -> Self {
+        Self { display_name, aliases }
+    }
+}
+
+/// User-defined tags from the [tags] table of the config, alias -> display name
+static USER_TAGS: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Map of all built-in tags with their aliases
+pub fn get_tag_map() -> HashMap<&'static str, &'static Tag> {
+    // Define all tags
+    static TAGS: &[Tag] = &[
+        Tag::new("Async", &["async", "asynchronous", "tokio"]),
+        Tag::new("CI", &["ci", "continuous-integration", "ci/cd", "cicd"]),
+        Tag::new("CLI", &["cli", "command-line", "commandline"]),
+        Tag::new("Database", &["database", "db", "sql"]),
+        Tag::new("Docker", &["docker", "container", "containers", "dockerfile"]),
+        Tag::new("Documentation", &["documentation", "docs", "doc"]),
+        Tag::new("Kubernetes", &["kubernetes", "k8s"]),
+        Tag::new("Linting", &["linting", "lint", "linter"]),
+        Tag::new("Minimal", &["minimal", "barebones", "bare", "starter"]),
+        Tag::new("Monorepo", &["monorepo", "workspace"]),
+        Tag::new("Testing", &["testing", "tests", "test", "unit-tests"]),
+        Tag::new("Web", &["web", "http", "www"]),
+    ];
+
+    // Build the map
+    let mut map = HashMap::new();
+    for tag in TAGS {
+        for &alias in tag.aliases {
+            map.insert(alias, tag);
+        }
+    }
+    map
+}
+
+/// Registers the [tags] table from the user config (display name -> aliases), once at startup.
+/// User aliases take precedence over the built-in ones.
+pub fn set_user_tags(tags: &BTreeMap<String, Vec<String>>) {
+    let mut map = HashMap::new();
+    for (display_name, aliases) in tags {
+        map.insert(normalize(display_name), display_name.clone());
+        for alias in aliases {
+            map.insert(normalize(alias), display_name.clone());
+        }
+    }
+    let _ = USER_TAGS.set(map);
+}
+
+/// Get the display name for a tag (case-insensitive, spaces, dashes and underscores interchangeable)
+/// Returns the canonical display name if recognized, otherwise returns the trimmed tag
+pub fn get_tag_display_name(tag: &str) -> String {
+    let normalized = normalize(tag);
+    if let Some(display_name) = USER_TAGS.get().and_then(|tags| tags.get(&normalized)) {
+        return display_name.clone();
+    }
+    get_tag_map()
+        .get(normalized.as_str())
+        .map_or_else(|| tag.trim().to_string(), |tag| tag.display_name.to_string())
+}
+
+/// Normalizes a template's tags to display names, dropping duplicates that resolve to the same tag
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let display_name = get_tag_display_name(tag);
+        if !display_name.is_empty() && !normalized.iter().any(|t| t.eq_ignore_ascii_case(&display_name)) {
+            normalized.push(display_name);
+        }
+    }
+    normalized
+}
+
+/// Lowercases and joins words with dashes, so "Continuous Integration" matches "continuous-integration"
+fn normalize(tag: &str) -> String {
+    tag.trim().to_lowercase().replace('_', " ").split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases() {
+        assert_eq!(get_tag_display_name("ci"), "CI");
+        assert_eq!(get_tag_display_name("Continuous Integration"), "CI");
+        assert_eq!(get_tag_display_name("continuous_integration"), "CI");
+        assert_eq!(get_tag_display_name(" k8s "), "Kubernetes");
+        assert_eq!(get_tag_display_name("my-own-tag"), "my-own-tag");
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = ["ci", "CICD", "docker", "Containers", "custom"].map(String::from);
+        assert_eq!(normalize_tags(&tags), ["CI", "Docker", "custom"]);
+    }
+}
@@ -1,20 +1,52 @@
-use crate::file::{ensure_template_storage_dir, create_dir_if_missing};
+use crate::archive::{ArchiveFormat, extract_archive, list_archive};
+use crate::builtin::{BUILTIN_PREFIX, builtin_templates};
+use crate::compat::check_compatibility;
+use crate::config::UserConfig;
+use crate::eol::{Eol, EolOverride};
+use crate::error::TemplateError;
+use crate::file::{ensure_cache_storage_dir, ensure_template_storage_dir, create_dir_if_missing, dir_size};
+use crate::hooks::Hooks;
+use crate::index::DiscoveryIndex;
+use crate::lock::{TEMPLATE_LOCK_FILE, hash_bytes};
+use crate::objects::{Manifest, has_blob};
+use crate::origin::TEMPLATE_ORIGIN_FILE;
+use crate::patterns::{matches_any, parse_ignore_file};
+use crate::path::{resolve_path, storage_dirs, symlink_escapes};
+use crate::prerequisites::Prerequisites;
+use crate::render::RenderEngine;
+use crate::timing::{Phase, span};
+use crate::variables::VariableSpec;
+use crate::workspace::WorkspaceConfig;
 
 use serde::{Deserialize, Serialize};
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 use std::fs;
+use std::io;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 pub const TEMPLATE_CONFIG_FILE: &str = ".template.toml";
+pub const TEMPLATE_TEST_FILE: &str = ".template-test.toml";
+pub const TEMPLATE_SNAPSHOT_DIR: &str = ".template-snapshot";
+pub const TEMPLATE_MANIFEST_FILE: &str = ".template-manifest.toml";
+pub const TEMPLATE_ARCHIVE_FILE: &str = ".template-archive.tar.gz";
+pub const TEMPLATE_PLUGIN_DIR: &str = ".template-plugins";
+/// Usage documentation for the template itself, shown by `info` but never copied into projects
+pub const TEMPLATE_README_FILE: &str = "TEMPLATE_README.md";
+/// Gitignore-style patterns of template files that are never applied
+pub const TEMPLATE_IGNORE_FILE: &str = ".templateignore";
 
 /// Template configuration from .template.toml
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TemplateConfig {
     /// Template name (optional, defaults to directory name)
     pub name: Option<String>,
     /// Template main programming language
     pub language: Option<String>,
+    /// Kind of project the template creates (cli, web-service, library, ...)
+    pub category: Option<String>,
     /// Template description
     pub description: Option<String>,
     /// Template author
@@ -25,11 +57,73 @@ pub struct TemplateConfig {
     pub tags: Option<Vec<String>>,
     /// Minimum required version of this tool
     pub min_tool_version: Option<String>,
+    /// Newest version of this tool the template is known to work with
+    pub max_tool_version: Option<String>,
+    /// Capabilities the template relies on (hooks, variables, ...), checked before it is applied
+    pub requires_features: Option<Vec<String>>,
+    /// Binaries and free disk space needed on the machine the template is applied on
+    pub prerequisites: Option<Prerequisites>,
+    /// Line endings of applied text files (lf, crlf, native or keep)
+    pub eol: Option<Eol>,
+    /// Per-glob line endings, later entries win: `[{ pattern = "*.bat", eol = "crlf" }]`
+    pub eol_overrides: Option<Vec<EolOverride>>,
+    /// Per-file settings keyed by relative path
+    pub files: Option<BTreeMap<String, FileConfig>>,
+    /// WASM post-processors run over applied files, relative to .template-plugins/
+    pub plugins: Option<Vec<String>>,
+    /// Variables substituted for `{{ name }}` placeholders in applied files and paths
+    pub variables: Option<BTreeMap<String, VariableSpec>>,
+    /// Placeholder syntax of the files and paths: tera (default), handlebars, liquid or none
+    pub engine: Option<RenderEngine>,
+    /// Globs of files copied as they are, without substituting placeholders
+    pub verbatim: Option<Vec<String>>,
+    /// Sibling projects created from other templates, making this a workspace template
+    pub workspace: Option<WorkspaceConfig>,
+    /// Shell commands run in the target directory before and after the template is applied
+    pub hooks: Option<Hooks>,
+    /// Run the language's sanity check (`cargo check`, ...) on every generated project
+    pub check: Option<bool>,
     /// Additional metadata
     #[serde(flatten)]
     pub metadata: Option<toml::Table>,
 }
 
+/// Per-file settings from the [files."path"] tables of .template.toml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileConfig {
+    /// Create the file if it's missing, but never overwrite it (init --force, upgrade)
+    #[serde(default)]
+    pub create_once: bool,
+    /// Encoding of a text file that isn't UTF-8 (e.g. "latin1", "windows-1252", "utf-16le"),
+    /// so text processing can transcode it instead of copying it verbatim
+    pub encoding: Option<String>,
+}
+
+/// Label of the template storage directory among the template roots
+pub const STORAGE_ROOT: &str = "storage";
+
+/// Label of the starter templates compiled into the binary
+pub const BUILTIN_ROOT: &str = "builtin";
+
+/// A directory templates are discovered in: the template storage, or one of the `roots` of
+/// config.toml (a team share, /usr/share/template-rs, ...), labelled as configured
+#[derive(Debug, Clone)]
+pub struct TemplateRoot {
+    pub label: String,
+    pub path: PathBuf,
+}
+
+/// The template roots in order of precedence: the template storage, then the configured roots
+/// in the order given. Relative roots are relative to the config directory.
+pub fn template_roots() -> Result<Vec<TemplateRoot>, Box<dyn std::error::Error>> {
+    let mut roots = vec![TemplateRoot { label: STORAGE_ROOT.to_string(), path: ensure_template_storage_dir()? }];
+    let config_dir = &storage_dirs().config;
+    for root in UserConfig::load()?.roots {
+        roots.push(TemplateRoot { path: resolve_path(&root, Some(config_dir))?, label: root });
+    }
+    Ok(roots)
+}
+
 /// Represents a discovered template
 #[derive(Debug, Clone)]
 pub struct Template {
@@ -38,20 +132,109 @@ pub struct Template {
     pub config: TemplateConfig,
 }
 
-/// Helper function to recursively search for templates
+/// A symlinked directory that discovery skipped because it leads back into one of its own parents
+#[derive(Debug, Clone)]
+pub struct SymlinkCycle {
+    /// The symlink (or directory) that was skipped
+    pub path: PathBuf,
+    /// The directory it resolves to
+    pub target: PathBuf,
+}
+
+/// A directory with a .template.toml that discovery skipped because the file can't be parsed
+#[derive(Debug, Clone)]
+pub struct BrokenTemplate {
+    /// Name the template would have, its path in the searched directory
+    pub name: String,
+    pub path: PathBuf,
+    /// Why the .template.toml was rejected
+    pub error: String,
+}
+
+/// A directory (or entry of one) that discovery skipped because it can't be read: missing
+/// permissions, a stale network mount, ...
+#[derive(Debug, Clone)]
+pub struct UnreadableEntry {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+/// Whether discovery fails on the first unreadable directory instead of skipping it (`--strict`)
+static STRICT_DISCOVERY: AtomicBool = AtomicBool::new(false);
+
+/// Switches strict discovery on or off for the rest of the run
+pub fn set_strict_discovery(strict: bool) {
+    STRICT_DISCOVERY.store(strict, Ordering::Relaxed);
+}
+
+/// State of a template search: the canonical directories currently being walked, innermost last,
+/// and the cycles, broken templates and unreadable entries skipped so far
+struct Walk {
+    ancestors: Vec<PathBuf>,
+    cycles: Vec<SymlinkCycle>,
+    broken: Vec<BrokenTemplate>,
+    unreadable: Vec<UnreadableEntry>,
+    index: DiscoveryIndex,
+}
+
+impl Walk {
+    /// Skips a path that can't be read, so one bad directory doesn't hide all the other
+    /// templates; with strict discovery the error is returned instead
+    fn skip_unreadable(&mut self, path: &Path, error: io::Error) -> Result<(), Box<dyn std::error::Error>> {
+        if STRICT_DISCOVERY.load(Ordering::Relaxed) {
+            return Err(error.into());
+        }
+        self.unreadable.push(UnreadableEntry { path: path.to_path_buf(), error: error.to_string() });
+        Ok(())
+    }
+
+    /// Warns (on stderr) about the entries skipped because they can't be read
+    fn report_unreadable(&self) {
+        if self.unreadable.is_empty() {
+            return;
+        }
+        eprintln!("Warning: Skipped {} unreadable path(s) while looking for templates (--strict fails instead):", self.unreadable.len());
+        for entry in &self.unreadable {
+            eprintln!("  {}: {}", entry.path.display(), entry.error);
+        }
+    }
+}
+
+/// Helper function to recursively search for templates. Symlinked directories are followed,
+/// so template roots can live elsewhere, but never back into a directory being walked.
 fn search_templates(
     base_dir: &Path,
     current_dir: &Path,
     templates: &mut Vec<Template>,
+    walk: &mut Walk,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    for entry in fs::read_dir(current_dir)? {
-        let entry = entry?;
+    let entries = match fs::read_dir(current_dir) {
+        Ok(entries) => entries,
+        Err(e) => return walk.skip_unreadable(current_dir, e),
+    };
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                walk.skip_unreadable(current_dir, e)?;
+                continue;
+            }
+        };
         let path = entry.path();
         
         // Skip if not a directory
         if !path.is_dir() {
             continue;
         }
+
+        // A directory resolving to one of its parents would be walked forever
+        let Ok(canonical) = fs::canonicalize(&path) else {
+            continue;
+        };
+        if walk.ancestors.contains(&canonical) {
+            walk.cycles.push(SymlinkCycle { path, target: canonical });
+            continue;
+        }
         
         // Check if this directory is a valid template
         if Template::is_valid_template(&path) {
@@ -69,12 +252,16 @@ fn search_templates(
                     |s| s.replace('\\', "/") // Normalize path separators
                 );
             
-            // Try to parse template config
+            // Try to parse template config, unless the index has it from an earlier run
             let config_path = path.join(TEMPLATE_CONFIG_FILE);
-            let config = match Template::parse_config(&config_path) {
+            let parsed = walk.index.get(&config_path).map_or_else(
+                || Template::parse_config(&config_path).inspect(|config| walk.index.insert(&config_path, config)),
+                Ok,
+            );
+            let config = match parsed {
                 Ok(config) => config,
                 Err(e) => {
-                    eprintln!("Warning: Failed to parse {}: {e}", config_path.display());
+                    walk.broken.push(BrokenTemplate { name, path, error: e.to_string() });
                     continue;
                 }
             };
@@ -86,7 +273,77 @@ fn search_templates(
             });
         } else {
             // If not a template, recursively search its subdirectories
-            search_templates(base_dir, &path, templates)?;
+            walk.ancestors.push(canonical);
+            search_templates(base_dir, &path, templates, walk)?;
+            walk.ancestors.pop();
+        }
+    }
+    Ok(())
+}
+
+/// Disk usage of a template directory
+#[derive(Debug, Clone, Default)]
+pub struct SizeReport {
+    /// Total bytes of all regular files
+    pub total: u64,
+    /// Top-level entries (directories end in '/') with their sizes, largest first
+    pub entries: Vec<(String, u64)>,
+}
+
+/// Helper function to recursively collect regular files below a directory
+pub fn collect_files(
+    base_dir: &Path,
+    current_dir: &Path,
+    files: &mut Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in fs::read_dir(current_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            collect_files(base_dir, &path, files)?;
+        } else if file_type.is_file()
+            && let Some(relative) = path.strip_prefix(base_dir).ok().and_then(|p| p.to_str())
+        {
+            files.push(relative.replace('\\', "/")); // Normalize path separators
+        }
+    }
+    Ok(())
+}
+
+/// Check if a template file is metadata (.template.toml, README, .templateignore, manifest, test
+/// expectations and snapshots, plugins, origin, stray lock files) rather than content
+fn is_metadata_file(file: &str) -> bool {
+    file == TEMPLATE_CONFIG_FILE
+        || file == TEMPLATE_README_FILE
+        || file == TEMPLATE_IGNORE_FILE
+        || file == TEMPLATE_MANIFEST_FILE
+        || file == TEMPLATE_ARCHIVE_FILE
+        || file == TEMPLATE_TEST_FILE
+        || file == TEMPLATE_LOCK_FILE
+        || file == TEMPLATE_ORIGIN_FILE
+        || file.starts_with(&format!("{TEMPLATE_SNAPSHOT_DIR}/"))
+        || file.starts_with(&format!("{TEMPLATE_PLUGIN_DIR}/"))
+}
+
+/// Helper function to recursively collect the symlinks below a directory with their targets
+fn collect_symlinks(
+    base_dir: &Path,
+    current_dir: &Path,
+    links: &mut Vec<(String, PathBuf)>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in fs::read_dir(current_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            collect_symlinks(base_dir, &path, links)?;
+        } else if file_type.is_symlink()
+            && let Some(relative) = path.strip_prefix(base_dir).ok().and_then(|p| p.to_str())
+        {
+            links.push((relative.replace('\\', "/"), fs::read_link(&path)?));
         }
     }
     Ok(())
@@ -98,6 +355,11 @@ impl Template {
         self.config.language.as_deref()
     }
 
+    /// Get the category if available
+    pub fn category(&self) -> Option<&str> {
+        self.config.category.as_deref()
+    }
+
     /// Get description if available
     pub fn description(&self) -> Option<&str> {
         self.config.description.as_deref()
@@ -118,44 +380,374 @@ impl Template {
         self.config.tags.as_deref()
     }
 
+    /// Check if the template declares variables, or shares some with its workspace members
+    pub fn has_variables(&self) -> bool {
+        self.config.variables.as_ref().is_some_and(|variables| !variables.is_empty())
+            || self.config.workspace.as_ref().is_some_and(|w| !w.variables.is_empty())
+    }
+
+    /// Check if the template runs hooks
+    pub fn has_hooks(&self) -> bool {
+        self.config.hooks.as_ref().is_some_and(|hooks| !hooks.is_empty())
+    }
+
+    /// Capabilities the template uses, lowercase: the features it requires, the ones its config
+    /// implies (eol, workspaces, ...), its prerequisite binaries and its tags
+    pub fn features(&self) -> BTreeSet<String> {
+        let config = &self.config;
+        let files = config.files.iter().flatten().map(|(_, file)| file);
+        let implied = [
+            ("categories", config.category.is_some()),
+            ("checks", config.check == Some(true)),
+            ("create-once", files.clone().any(|file| file.create_once)),
+            ("encodings", files.clone().any(|file| file.encoding.is_some())),
+            ("eol", config.eol.is_some() || config.eol_overrides.is_some()),
+            ("hooks", self.has_hooks()),
+            ("plugins", config.plugins.as_ref().is_some_and(|p| !p.is_empty())),
+            ("prerequisites", config.prerequisites.is_some()),
+            ("render-engines", config.engine.is_some_and(|engine| engine != RenderEngine::Tera)),
+            ("variable-sources", config.variables.iter().flatten().any(|(_, spec)| spec.from.is_some())),
+            ("variables", self.has_variables()),
+            ("workspaces", config.workspace.is_some()),
+        ];
+
+        let mut features: BTreeSet<String> = implied.into_iter()
+            .filter(|(_, used)| *used)
+            .map(|(feature, _)| feature.to_string())
+            .collect();
+        let declared = config.requires_features.iter().flatten()
+            .chain(config.prerequisites.iter().flat_map(|p| &p.binaries))
+            .chain(config.tags.iter().flatten());
+        features.extend(declared.map(|feature| feature.to_lowercase()));
+        features
+    }
+
+    /// Fails with an explanation if this build is too old or too new for the template
+    pub fn check_compatibility(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(check_compatibility(&self.name, &self.config)?)
+    }
+
+    /// Lists what keeps a template that parsed from being applied: a manifest that can't be read
+    /// or objects missing from the store for packed templates, and a broken workspace member list
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.is_packed() {
+            match Manifest::load(self.path.join(TEMPLATE_MANIFEST_FILE)) {
+                Ok(manifest) => {
+                    let missing = manifest.files.values().filter(|entry| !has_blob(&entry.hash).unwrap_or(false)).count();
+                    if missing > 0 {
+                        problems.push(format!("{missing} packed file(s) are missing from the object store"));
+                    }
+                }
+                Err(e) => problems.push(e.to_string()),
+            }
+        }
+        if let Some(workspace) = &self.config.workspace {
+            problems.extend(workspace.problems());
+        }
+        problems
+    }
+
+    /// Refuses templates with symlinks that would point outside the project they are applied to:
+    /// to absolute paths or above the template. File paths can't climb out, only rendering them
+    /// could, which `TemplateRenderer::render_path` refuses.
+    pub fn check_contents(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let content_dir = self.content_dir()?;
+        let mut problems = Vec::new();
+        let mut links = Vec::new();
+        collect_symlinks(&content_dir, &content_dir, &mut links)?;
+        for (link, target) in links {
+            if symlink_escapes(&link, &target) {
+                problems.push(format!("{link} links outside the template ({})", target.display()));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Template '{}' has unsafe entries:\n  {}", self.name, problems.join("\n  ")).into())
+        }
+    }
+
+    /// The encoding declared for a file in its [files."path"] table, if any
+    pub fn declared_encoding(&self, file: &str) -> Option<&str> {
+        self.config.files.as_ref()
+            .and_then(|files| files.get(file))
+            .and_then(|f| f.encoding.as_deref())
+    }
+
+    /// Check if a file is marked `create_once` and must never overwrite an existing copy
+    pub fn is_create_once(&self, file: &str) -> bool {
+        self.config.files.as_ref()
+            .and_then(|files| files.get(file))
+            .is_some_and(|f| f.create_once)
+    }
+
     /// Checks if a directory is a valid template (contains .template.toml)
     pub fn is_valid_template<P: AsRef<Path>>(dir_path: P) -> bool {
         dir_path.as_ref().join(TEMPLATE_CONFIG_FILE).exists()
     }
 
     /// Deserializes a .template.toml file for template metadata
-    fn parse_config<P: AsRef<Path>>(config_path: P) -> Result<TemplateConfig, Box<dyn std::error::Error>> {
+    pub fn parse_config<P: AsRef<Path>>(config_path: P) -> Result<TemplateConfig, TemplateError> {
+        let _timing = span(Phase::Config);
         let content = fs::read_to_string(config_path)?;
-        let config: TemplateConfig = toml::from_str(&content)?;
+        let config: TemplateConfig = toml::from_str(&content).map_err(|e| TemplateError::Config(e.to_string()))?;
         Ok(config)
     }
 
-    /// Discovers all available templates in the template storage directory (recursively)
+    /// Discovers all available templates: those of every template root and the built-in starters.
+    /// A template in an earlier root shadows one with the same name in a later root; roots that
+    /// don't exist (an unmounted share) contribute nothing.
     pub fn discover_all() -> Result<Vec<Self>, Box<dyn std::error::Error>> {
-        let template_dir = ensure_template_storage_dir()?;
+        let mut templates: Vec<Self> = Vec::new();
+        for root in template_roots()? {
+            if !root.path.is_dir() {
+                continue;
+            }
+            for template in Self::discover_in(&root.path)? {
+                if !templates.iter().any(|known| known.name == template.name) {
+                    templates.push(template);
+                }
+            }
+        }
+        templates.extend(builtin_templates()?);
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
+    /// Discovers the templates in the template storage directory (recursively), without built-ins
+    pub fn discover_stored() -> Result<Vec<Self>, Box<dyn std::error::Error>> {
+        Self::discover_in(&ensure_template_storage_dir()?)
+    }
+
+    /// Discovers the templates below a directory laid out like the template storage, named by their path in it
+    /// Warns (on stderr) about templates whose .template.toml can't be parsed and skips them.
+    pub fn discover_in(root: &Path) -> Result<Vec<Self>, Box<dyn std::error::Error>> {
+        let (templates, walk) = Self::walk(root)?;
+        for broken in &walk.broken {
+            eprintln!("Warning: Failed to parse {}: {}", broken.path.join(TEMPLATE_CONFIG_FILE).display(), broken.error);
+        }
+        walk.report_unreadable();
+        Ok(templates)
+    }
+
+    /// Lists the symlinked directories below a storage-like directory that discovery skips
+    /// because they loop back into their own parents
+    pub fn symlink_cycles(root: &Path) -> Result<Vec<SymlinkCycle>, Box<dyn std::error::Error>> {
+        Ok(Self::walk(root)?.1.cycles)
+    }
+
+    /// Discovers like [`Template::discover_in`], but returns the templates whose .template.toml
+    /// can't be parsed instead of warning about them
+    pub fn discover_with_broken(root: &Path) -> Result<(Vec<Self>, Vec<BrokenTemplate>), Box<dyn std::error::Error>> {
+        let (templates, walk) = Self::walk(root)?;
+        walk.report_unreadable();
+        Ok((templates, walk.broken))
+    }
+
+    fn walk(root: &Path) -> Result<(Vec<Self>, Walk), Box<dyn std::error::Error>> {
+        let _timing = span(Phase::Discovery);
         let mut templates = Vec::new();
+        let mut walk = Walk {
+            ancestors: vec![fs::canonicalize(root)?],
+            cycles: Vec::new(),
+            broken: Vec::new(),
+            unreadable: Vec::new(),
+            index: DiscoveryIndex::load(),
+        };
         
-        search_templates(&template_dir, &template_dir, &mut templates)?;
+        search_templates(root, root, &mut templates, &mut walk)?;
+        walk.index.save();
         
         // Sort templates by name (which is now the path)
         templates.sort_by(|a, b| a.name.cmp(&b.name));
+        walk.broken.sort_by(|a, b| a.name.cmp(&b.name));
         
-        Ok(templates)
+        Ok((templates, walk))
     }
 
     /// Finds a specific template by name (matches both path and config name)
     pub fn find(template_name: &str) -> Result<Option<Self>, Box<dyn std::error::Error>> {
-        let templates = Self::discover_all()?;
+        let mut templates = Self::discover_all()?;
         
         // Normalize the search name (convert backslashes to forward slashes)
         let normalized_search = template_name.replace('\\', "/");
         
-        Ok(templates.into_iter().find(|t| {
-            // Match against the path (template.name)
-            t.name == normalized_search || 
-            // Also match against the config name if it exists
-            t.config.name.as_deref() == Some(template_name)
-        }))
+        // Match against the path (template.name) first, so an ejected copy named like a
+        // built-in's config name wins over the built-in
+        if let Some(index) = templates.iter().position(|t| t.name == normalized_search) {
+            return Ok(Some(templates.swap_remove(index)));
+        }
+        // Also match against the config name if it exists
+        Ok(templates.into_iter().find(|t| t.config.name.as_deref() == Some(template_name)))
+    }
+
+    /// Label of the root the template was discovered in (see `template_roots`): the most
+    /// specific root holding it, `builtin` for the starters
+    pub fn root_label<'a>(&self, roots: &'a [TemplateRoot]) -> &'a str {
+        if self.is_builtin() {
+            return BUILTIN_ROOT;
+        }
+        roots.iter()
+            .filter(|root| self.path.starts_with(&root.path))
+            .max_by_key(|root| root.path.components().count())
+            .map_or(STORAGE_ROOT, |root| &root.label)
+    }
+
+    /// Check if the template is one of the starters embedded in the binary
+    pub fn is_builtin(&self) -> bool {
+        self.name.starts_with(BUILTIN_PREFIX)
+    }
+
+    /// Check if the template's contents live in the object store (see `template-rs pack`)
+    pub fn is_packed(&self) -> bool {
+        self.path.join(TEMPLATE_MANIFEST_FILE).exists()
+    }
+
+    /// Check if the template's contents are compressed into an archive (see `template-rs compact`)
+    pub fn is_compacted(&self) -> bool {
+        self.path.join(TEMPLATE_ARCHIVE_FILE).exists()
+    }
+
+    /// Directory holding the template's files: the template directory itself, or a cached copy
+    /// for packed templates (materialized from the object store) and compacted ones (extracted
+    /// from their archive). Either stays packed or compacted; see `inflate` for editing.
+    pub fn content_dir(&self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        if self.is_packed() {
+            return Manifest::load(self.path.join(TEMPLATE_MANIFEST_FILE))?.materialize_cached();
+        }
+        if self.is_compacted() {
+            return self.extract_cached();
+        }
+        Ok(self.path.clone())
+    }
+
+    /// Extracts the archive of a compacted template into the cache (once per distinct archive),
+    /// returns the directory
+    fn extract_cached(&self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let _timing = span(Phase::Io);
+        let archive_path = self.path.join(TEMPLATE_ARCHIVE_FILE);
+        let key = hash_bytes(&fs::read(&archive_path)?);
+        let cache_dir = ensure_cache_storage_dir()?.join("extracted");
+        let dest_dir = cache_dir.join(&key);
+        if dest_dir.exists() {
+            return Ok(dest_dir);
+        }
+
+        // Extract into a scratch directory and move it into place so a partial copy is never reused
+        let scratch_dir = cache_dir.join(format!("{key}.tmp-{}", std::process::id()));
+        if scratch_dir.exists() {
+            fs::remove_dir_all(&scratch_dir)?;
+        }
+        create_dir_if_missing(&scratch_dir)?;
+        extract_archive(ArchiveFormat::Gzip, &archive_path, &scratch_dir)?;
+        fs::rename(&scratch_dir, &dest_dir)?;
+        Ok(dest_dir)
+    }
+
+    /// Extracts a compacted template's archive back into its directory and removes the archive,
+    /// so its files can be edited. Does nothing for a template that isn't compacted.
+    pub fn inflate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.is_compacted() {
+            return Ok(());
+        }
+        let archive_path = self.path.join(TEMPLATE_ARCHIVE_FILE);
+        extract_archive(ArchiveFormat::Gzip, &archive_path, &self.path)?;
+        fs::remove_file(archive_path)?;
+        Ok(())
+    }
+
+    /// Lists the template's files as relative paths (forward slashes), excluding template
+    /// metadata (.template.toml, README, .templateignore, manifest, test expectations and snapshots, plugins, origin, stray lock files)
+    pub fn files(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let content_dir = self.content_dir()?;
+        let mut files = Vec::new();
+        collect_files(&content_dir, &content_dir, &mut files)?;
+        files.retain(|f| !is_metadata_file(f));
+        files.sort();
+        Ok(files)
+    }
+
+    /// Lists the same files as `files`, reading a compacted template's archive and a packed
+    /// template's manifest instead of extracting them, for commands that only show the list
+    pub fn list_files(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut files = Vec::new();
+        if self.is_packed() {
+            files.extend(Manifest::load(self.path.join(TEMPLATE_MANIFEST_FILE))?.files.into_keys());
+        } else {
+            collect_files(&self.path, &self.path, &mut files)?;
+            if self.is_compacted() {
+                files.extend(list_archive(ArchiveFormat::Gzip, &self.path.join(TEMPLATE_ARCHIVE_FILE))?);
+            }
+        }
+        files.retain(|f| !is_metadata_file(f));
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// Measures the template on disk, including files that are excluded when applying
+    pub fn size_report(&self) -> Result<SizeReport, Box<dyn std::error::Error>> {
+        let mut report = SizeReport::default();
+
+        for entry in fs::read_dir(&self.path)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            let (label, size) = if file_type.is_dir() {
+                (format!("{name}/"), dir_size(entry.path())?)
+            } else if file_type.is_file() {
+                (name, entry.metadata()?.len())
+            } else {
+                continue;
+            };
+
+            report.total += size;
+            report.entries.push((label, size));
+        }
+
+        report.entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(report)
+    }
+
+    /// Lists the files that get applied: `files()` minus anything matching an exclude pattern or
+    /// the template's .templateignore
+    pub fn apply_files(&self, excludes: &[String]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let ignored = self.ignore_patterns()?;
+        let mut files = self.files()?;
+        files.retain(|f| !matches_any(f, excludes) && !matches_any(f, &ignored));
+        Ok(files)
+    }
+
+    /// The patterns of the template's .templateignore, empty without one
+    pub fn ignore_patterns(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let path = self.path.join(TEMPLATE_IGNORE_FILE);
+        if !path.is_file() {
+            return Ok(Vec::new());
+        }
+        Ok(parse_ignore_file(&fs::read_to_string(path)?))
+    }
+
+    /// Copies the template's files into a directory, returns the copied relative paths
+    pub fn copy_files_to<P: AsRef<Path>>(
+        &self,
+        dest_dir: P,
+        excludes: &[String],
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let _timing = span(Phase::Io);
+        let dest_dir = dest_dir.as_ref();
+        let content_dir = self.content_dir()?;
+        let files = self.apply_files(excludes)?;
+        for file in &files {
+            let destination = dest_dir.join(file);
+            if let Some(parent) = destination.parent() {
+                create_dir_if_missing(parent)?;
+            }
+            fs::copy(content_dir.join(file), destination)?;
+        }
+        Ok(files)
     }
 
     /// Serializes a `TemplateConfig` to a TOML file
@@ -177,3 +769,27 @@ impl Template {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::archive::create_archive;
+
+    #[test]
+    fn test_list_files_of_compacted_template() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join(TEMPLATE_CONFIG_FILE), "").unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.path().join("README.md"), "# App\n").unwrap();
+        let content = vec!["README.md".to_string(), "src/main.rs".to_string()];
+        create_archive(ArchiveFormat::Gzip, dir.path(), &content, &dir.path().join(TEMPLATE_ARCHIVE_FILE)).unwrap();
+        fs::remove_dir_all(dir.path().join("src")).unwrap();
+        fs::remove_file(dir.path().join("README.md")).unwrap();
+
+        let template = Template { name: "app".to_string(), path: dir.path().to_path_buf(), config: TemplateConfig::default() };
+        assert_eq!(template.list_files().unwrap(), content);
+        assert!(template.is_compacted() && !dir.path().join("src").exists());
+    }
+}
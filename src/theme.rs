@@ -0,0 +1,212 @@
+use crate::output::is_plain;
+
+use clap::builder::styling::{AnsiColor, Effects, Style, Styles};
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether icons are printed as plain text instead of emoji (`--ascii`)
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Output theme from the [theme] table of config.toml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    /// Named palette: "default", "mono" or "ocean"
+    pub name: Option<String>,
+    /// Replace emoji with plain text (same as `--ascii`)
+    #[serde(default)]
+    pub ascii: bool,
+    /// Per-element colors overriding the palette, e.g. `error = "bright-red bold"`.
+    /// Elements: header, usage, literal, placeholder, error, valid, invalid
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+}
+
+/// Status icons used in command output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Icon {
+    Success,
+    Warning,
+    Failure,
+    DryRun,
+    Protected,
+    Folder,
+    Package,
+    Note,
+    Snapshot,
+    Disk,
+    Website,
+    Tool,
+}
+
+impl Icon {
+    /// Emoji form; the warning sign renders narrow in most terminals so it carries its own padding
+    pub const fn emoji(self) -> &'static str {
+        match self {
+            Self::Success => "✅",
+            Self::Warning => "⚠️ ",
+            Self::Failure => "❌",
+            Self::DryRun => "🔍",
+            Self::Protected => "🔒",
+            Self::Folder => "📁",
+            Self::Package => "📦",
+            Self::Note => "📝",
+            Self::Snapshot => "📸",
+            Self::Disk => "💾",
+            Self::Website => "🌐",
+            Self::Tool => "🔧",
+        }
+    }
+
+    /// Plain text form for `--ascii` and `--plain`
+    pub const fn ascii(self) -> &'static str {
+        match self {
+            Self::Success => "[ok]",
+            Self::Warning => "[!]",
+            Self::Failure => "[x]",
+            Self::DryRun => "[dry-run]",
+            Self::Protected => "[protected]",
+            Self::Folder | Self::Package | Self::Note | Self::Snapshot | Self::Disk => "*",
+            Self::Website | Self::Tool => "-",
+        }
+    }
+}
+
+impl fmt::Display for Icon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if ASCII.load(Ordering::Relaxed) || is_plain() {
+            f.write_str(self.ascii())
+        } else {
+            f.write_str(self.emoji())
+        }
+    }
+}
+
+/// Switches icons between emoji and plain text for the rest of the run
+pub fn set_ascii(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// Builds the help/error styles from the configured palette and per-element overrides
+pub fn styles(theme: &ThemeConfig) -> Result<Styles, Box<dyn std::error::Error>> {
+    let mut palette = match theme.name.as_deref().unwrap_or("default") {
+        "default" => [
+            ("header", "green bold"),
+            ("usage", "green bold"),
+            ("literal", "cyan bold"),
+            ("placeholder", "blue"),
+            ("error", "red bold"),
+            ("valid", "cyan bold"),
+            ("invalid", "yellow bold"),
+        ],
+        "mono" => [
+            ("header", "bold"),
+            ("usage", "bold"),
+            ("literal", "bold"),
+            ("placeholder", "underline"),
+            ("error", "bold"),
+            ("valid", "bold"),
+            ("invalid", "bold"),
+        ],
+        "ocean" => [
+            ("header", "blue bold"),
+            ("usage", "blue bold"),
+            ("literal", "bright-cyan bold"),
+            ("placeholder", "cyan"),
+            ("error", "magenta bold"),
+            ("valid", "bright-cyan bold"),
+            ("invalid", "bright-yellow bold"),
+        ],
+        other => return Err(format!("Unknown theme '{other}' (expected default, mono or ocean)").into()),
+    }
+    .map(|(element, spec)| (element.to_string(), spec.to_string()))
+    .into_iter()
+    .collect::<BTreeMap<_, _>>();
+
+    for (element, spec) in &theme.colors {
+        if !palette.contains_key(element) {
+            return Err(format!("Unknown theme element '{element}'").into());
+        }
+        palette.insert(element.clone(), spec.clone());
+    }
+
+    let style = |element: &str| parse_style(&palette[element]);
+    Ok(Styles::styled()
+        .header(style("header")?)
+        .usage(style("usage")?)
+        .literal(style("literal")?)
+        .placeholder(style("placeholder")?)
+        .error(style("error")?)
+        .valid(style("valid")?)
+        .invalid(style("invalid")?))
+}
+
+/// Parses a style like "bright-red bold": at most one color plus any of bold, dimmed, italic, underline
+fn parse_style(spec: &str) -> Result<Style, Box<dyn std::error::Error>> {
+    let mut style = Style::new();
+    for word in spec.split_whitespace() {
+        style = match word {
+            "bold" => style | Effects::BOLD,
+            "dimmed" => style | Effects::DIMMED,
+            "italic" => style | Effects::ITALIC,
+            "underline" => style | Effects::UNDERLINE,
+            color => style.fg_color(Some(parse_color(color)?.into())),
+        };
+    }
+    Ok(style)
+}
+
+fn parse_color(name: &str) -> Result<AnsiColor, Box<dyn std::error::Error>> {
+    let color = match name {
+        "black" => AnsiColor::Black,
+        "red" => AnsiColor::Red,
+        "green" => AnsiColor::Green,
+        "yellow" => AnsiColor::Yellow,
+        "blue" => AnsiColor::Blue,
+        "magenta" => AnsiColor::Magenta,
+        "cyan" => AnsiColor::Cyan,
+        "white" => AnsiColor::White,
+        "bright-black" => AnsiColor::BrightBlack,
+        "bright-red" => AnsiColor::BrightRed,
+        "bright-green" => AnsiColor::BrightGreen,
+        "bright-yellow" => AnsiColor::BrightYellow,
+        "bright-blue" => AnsiColor::BrightBlue,
+        "bright-magenta" => AnsiColor::BrightMagenta,
+        "bright-cyan" => AnsiColor::BrightCyan,
+        "bright-white" => AnsiColor::BrightWhite,
+        other => return Err(format!("Unknown color '{other}'").into()),
+    };
+    Ok(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_style() {
+        assert_eq!(
+            parse_style("bright-red bold").unwrap(),
+            Style::new().fg_color(Some(AnsiColor::BrightRed.into())) | Effects::BOLD
+        );
+        assert!(parse_style("purple").is_err());
+    }
+
+    #[test]
+    fn test_overrides_are_validated() {
+        let mut theme = ThemeConfig::default();
+        assert!(styles(&theme).is_ok());
+
+        theme.colors.insert("error".to_string(), "magenta".to_string());
+        assert!(styles(&theme).is_ok());
+
+        theme.colors.insert("footer".to_string(), "red".to_string());
+        assert!(styles(&theme).is_err());
+
+        theme.colors.clear();
+        theme.name = Some("neon".to_string());
+        assert!(styles(&theme).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Whether phases are timed and reported (`--profile-timing`)
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The parts of a run `--profile-timing` reports separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Walking the template storage and built-ins to find templates
+    Discovery,
+    /// Reading and parsing .template.toml, config.toml and lock files
+    Config,
+    /// Transforming file contents: line endings, plugins, upgrade merges
+    Render,
+    /// Copying files, archives and the object store
+    Io,
+}
+
+impl Phase {
+    const ALL: [Self; 4] = [Self::Discovery, Self::Config, Self::Render, Self::Io];
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Discovery => "discovery",
+            Self::Config => "config",
+            Self::Render => "render",
+            Self::Io => "io",
+        }
+    }
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+struct Timings {
+    started: Instant,
+    /// Time spent in each phase, excluding phases nested inside it, and how often it was entered
+    totals: [(Duration, u32); 4],
+    /// Phases currently running, innermost last, with when they last started counting
+    stack: Vec<(Phase, Instant)>,
+}
+
+thread_local! {
+    static TIMINGS: RefCell<Timings> = RefCell::new(Timings {
+        started: Instant::now(),
+        totals: [(Duration::ZERO, 0); 4],
+        stack: Vec::new(),
+    });
+}
+
+/// Switches timing on for the rest of the run
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Check if `--profile-timing` was requested
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Times a phase until the returned guard is dropped. A phase started inside another pauses
+/// the outer one, so every moment is counted once (discovery doesn't include config parsing).
+pub fn span(phase: Phase) -> Span {
+    if is_enabled() {
+        TIMINGS.with_borrow_mut(|timings| {
+            let now = Instant::now();
+            if let Some((outer, since)) = timings.stack.last() {
+                timings.totals[outer.index()].0 += now - *since;
+            }
+            timings.totals[phase.index()].1 += 1;
+            timings.stack.push((phase, now));
+        });
+    }
+    Span { active: is_enabled() }
+}
+
+/// Guard returned by `span`
+pub struct Span {
+    active: bool,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
+        TIMINGS.with_borrow_mut(|timings| {
+            let now = Instant::now();
+            if let Some((phase, since)) = timings.stack.pop() {
+                timings.totals[phase.index()].0 += now - since;
+            }
+            if let Some((_, since)) = timings.stack.last_mut() {
+                *since = now;
+            }
+        });
+    }
+}
+
+/// Starts the measurement over, so `bench` can time each apply on its own
+pub fn reset() {
+    TIMINGS.with_borrow_mut(|timings| {
+        timings.started = Instant::now();
+        timings.totals = [(Duration::ZERO, 0); 4];
+        timings.stack.clear();
+    });
+}
+
+/// Time spent in each phase since the run started or the last `reset`, by phase name
+pub fn totals() -> Vec<(&'static str, Duration)> {
+    TIMINGS.with_borrow(|timings| Phase::ALL.iter().map(|phase| (phase.label(), timings.totals[phase.index()].0)).collect())
+}
+
+/// Prints the time spent per phase to stderr, if timing is enabled
+pub fn report() {
+    if !is_enabled() {
+        return;
+    }
+    TIMINGS.with_borrow(|timings| {
+        let total = timings.started.elapsed();
+        let measured: Duration = timings.totals.iter().map(|(time, _)| *time).sum();
+
+        eprintln!("\nTiming:");
+        for phase in Phase::ALL {
+            let (time, calls) = timings.totals[phase.index()];
+            eprintln!("  {:<10} {:>10}  ({calls} call(s))", phase.label(), format_duration(time));
+        }
+        eprintln!("  {:<10} {:>10}", "other", format_duration(total.saturating_sub(measured)));
+        eprintln!("  {:<10} {:>10}", "total", format_duration(total));
+    });
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_spans_are_exclusive() {
+        set_enabled(true);
+        {
+            let _discovery = span(Phase::Discovery);
+            std::thread::sleep(Duration::from_millis(5));
+            let _config = span(Phase::Config);
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        TIMINGS.with_borrow(|timings| {
+            let (discovery, discovery_calls) = timings.totals[Phase::Discovery.index()];
+            let (config, config_calls) = timings.totals[Phase::Config.index()];
+            assert_eq!((discovery_calls, config_calls), (1, 1));
+            assert!(config >= Duration::from_millis(20));
+            assert!(discovery < config);
+            assert!(timings.stack.is_empty());
+        });
+    }
+}
@@ -0,0 +1,184 @@
+use crate::languages::get_display_name;
+use crate::markdown::render_markdown;
+use crate::prompt::picker_rows;
+use crate::search::search;
+use crate::tags::normalize_tags;
+use crate::template::{Template, TEMPLATE_README_FILE};
+
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Stderr};
+
+/// Rows PageUp and PageDown move the selection by
+const PAGE: usize = 10;
+
+/// Shows the templates in a full-screen list on stderr, narrowed down by typing (fuzzy, like
+/// `search`), with a preview of the highlighted one. Returns the picked template, or None when
+/// the user leaves with Esc or Ctrl-C.
+pub fn pick_template(templates: &[Template]) -> io::Result<Option<&Template>> {
+    enable_raw_mode()?;
+    execute!(io::stderr(), EnterAlternateScreen)?;
+    let result = Terminal::new(CrosstermBackend::new(io::stderr()))
+        .and_then(|mut terminal| Picker::new(templates).run(&mut terminal));
+    // Restore the terminal even when drawing failed
+    let _ = execute!(io::stderr(), LeaveAlternateScreen);
+    let _ = disable_raw_mode();
+    result
+}
+
+struct Picker<'a> {
+    templates: &'a [Template],
+    query: String,
+    matches: Vec<&'a Template>,
+    state: ListState,
+    /// Previews by template name, built when a template is first highlighted
+    previews: HashMap<String, String>,
+}
+
+impl<'a> Picker<'a> {
+    fn new(templates: &'a [Template]) -> Self {
+        let mut picker = Self { templates, query: String::new(), matches: Vec::new(), state: ListState::default(), previews: HashMap::new() };
+        picker.refilter();
+        picker
+    }
+
+    fn run(mut self, terminal: &mut Terminal<CrosstermBackend<Stderr>>) -> io::Result<Option<&'a Template>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else { continue };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+                KeyCode::Enter => {
+                    if let Some(template) = self.state.selected().and_then(|index| self.matches.get(index)) {
+                        return Ok(Some(template));
+                    }
+                }
+                KeyCode::Up => self.move_by(-1),
+                KeyCode::Down => self.move_by(1),
+                KeyCode::PageUp => self.move_by(-(PAGE as isize)),
+                KeyCode::PageDown => self.move_by(PAGE as isize),
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.refilter();
+                }
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.refilter();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Narrows the list to the templates matching the query, best first, and highlights the top one
+    fn refilter(&mut self) {
+        self.matches = if self.query.trim().is_empty() {
+            self.templates.iter().collect()
+        } else {
+            search(self.templates, &self.query).into_iter().map(|(template, _)| template).collect()
+        };
+        self.state.select((!self.matches.is_empty()).then_some(0));
+    }
+
+    fn move_by(&mut self, rows: isize) {
+        let Some(selected) = self.state.selected() else { return };
+        let last = self.matches.len().saturating_sub(1);
+        self.state.select(Some(selected.saturating_add_signed(rows).min(last)));
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame) {
+        let [filter_area, body_area, help_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(body_area);
+
+        let filter = Paragraph::new(format!("{}▏", self.query))
+            .block(Block::default().borders(Borders::ALL).title(format!(" Filter ({} of {}) ", self.matches.len(), self.templates.len())));
+        frame.render_widget(filter, filter_area);
+
+        let items: Vec<ListItem> = self.matches.iter().map(|template| ListItem::new(template.name.as_str())).collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" Templates "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+
+        let preview = match self.state.selected().and_then(|index| self.matches.get(index)) {
+            Some(template) => self.previews.entry(template.name.clone()).or_insert_with(|| preview(template)).clone(),
+            None => "No templates match the filter".to_string(),
+        };
+        let preview = Paragraph::new(preview.lines().map(Line::from).collect::<Vec<_>>())
+            .block(Block::default().borders(Borders::ALL).title(" Preview "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(preview, preview_area);
+
+        frame.render_widget(Paragraph::new("Type to filter · ↑/↓ PgUp/PgDn move · Enter apply · Esc cancel"), help_area);
+    }
+}
+
+/// What the preview pane shows for a template: its metadata, its TEMPLATE_README.md and its file tree
+fn preview(template: &Template) -> String {
+    let mut lines = vec![template.config.name.clone().unwrap_or_else(|| template.name.clone())];
+    if let Some(language) = template.language() {
+        lines.push(format!("Language: {}", get_display_name(language)));
+    }
+    if let Some(tags) = template.tags().map(normalize_tags).filter(|tags| !tags.is_empty()) {
+        lines.push(format!("Tags: {}", tags.join(", ")));
+    }
+    if let Some(description) = template.description() {
+        lines.push(String::new());
+        lines.push(description.to_string());
+    }
+    if let Ok(readme) = fs::read_to_string(template.path.join(TEMPLATE_README_FILE)) {
+        lines.push(String::new());
+        lines.push(render_markdown(&readme, false));
+    }
+
+    lines.push(String::new());
+    match template.list_files() {
+        Ok(files) => {
+            lines.push(format!("Files ({}):", files.len()));
+            lines.extend(file_tree(&files));
+        }
+        Err(e) => lines.push(format!("Files: unavailable ({e})")),
+    }
+    lines.join("\n")
+}
+
+/// Indented tree of sorted relative paths, directories ending in '/'
+fn file_tree(files: &[String]) -> Vec<String> {
+    picker_rows(files)
+        .into_iter()
+        .map(|row| {
+            let name = row.path.trim_end_matches('/').rsplit('/').next().unwrap_or(&row.path);
+            let suffix = if row.path.ends_with('/') { "/" } else { "" };
+            format!("  {}{name}{suffix}", "  ".repeat(row.depth))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_tree() {
+        let files = ["Cargo.toml", "src/bin/tool.rs", "src/main.rs"].map(String::from);
+        assert_eq!(file_tree(&files), ["  Cargo.toml", "  src/", "    bin/", "      tool.rs", "    main.rs"]);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::render::{referenced_names, render_text};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A variable a template expects, from the [variables] table of .template.toml:
+/// `project_name = { description = "Crate name", default = "my-app" }`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VariableSpec {
+    /// What the value is used for, shown when it is missing
+    pub description: Option<String>,
+    /// Value used when none is supplied. May refer to other variables: `"{{ name }}-cli"`
+    pub default: Option<String>,
+    /// Kind of value accepted (string, bool or integer)
+    #[serde(rename = "type", default)]
+    pub kind: VariableKind,
+    /// The only values accepted, when set
+    pub choices: Option<Vec<String>>,
+    /// Where an existing project already knows the value, as `FILE:KEY`: `"Cargo.toml:package.name"`.
+    /// FILE is relative to the target and may be TOML, JSON or YAML; KEY is a dotted path.
+    pub from: Option<String>,
+}
+
+/// Kind of value a variable takes (`type` in its [variables] entry)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableKind {
+    #[default]
+    String,
+    /// `true` or `false`; yes/no, y/n and 1/0 are accepted too
+    Bool,
+    /// A whole number
+    Integer,
+}
+
+impl VariableKind {
+    /// Name of the kind as written in `type`
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Bool => "bool",
+            Self::Integer => "integer",
+        }
+    }
+}
+
+impl VariableSpec {
+    /// Checks a value against the declared type and choices, returning it in canonical form
+    /// (`yes` for a bool becomes `true`)
+    pub fn check(&self, value: &str) -> Result<String, String> {
+        let value = match self.kind {
+            VariableKind::String => value.to_string(),
+            VariableKind::Bool => match value.trim().to_lowercase().as_str() {
+                "true" | "yes" | "y" | "1" => "true".to_string(),
+                "false" | "no" | "n" | "0" => "false".to_string(),
+                _ => return Err(format!("'{value}' is not a yes/no value (true or false)")),
+            },
+            VariableKind::Integer => value.trim().parse::<i64>()
+                .map(|number| number.to_string())
+                .map_err(|_| format!("'{value}' is not a whole number"))?,
+        };
+        match &self.choices {
+            Some(choices) if !choices.contains(&value) => {
+                Err(format!("'{value}' is not one of {}", choices.join(", ")))
+            }
+            _ => Ok(value),
+        }
+    }
+}
+
+/// Reads the value a `from = "FILE:KEY"` source points at in the project at `target`. None when
+/// the file or the key doesn't exist; an error when the source or the file can't be parsed.
+pub fn project_value(target: &Path, from: &str) -> Result<Option<String>, String> {
+    let (file, key) = from.split_once(':').filter(|(file, key)| !file.is_empty() && !key.is_empty())
+        .ok_or_else(|| format!("'{from}' is not a FILE:KEY source"))?;
+    let Ok(text) = fs::read_to_string(target.join(file)) else {
+        return Ok(None);
+    };
+    let document: serde_json::Value = match Path::new(file).extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(|e| format!("{file} is not valid TOML: {e}"))?,
+        Some("json") => serde_json::from_str(&text).map_err(|e| format!("{file} is not valid JSON: {e}"))?,
+        Some("yaml" | "yml") => serde_yaml_ng::from_str(&text).map_err(|e| format!("{file} is not valid YAML: {e}"))?,
+        _ => return Err(format!("{file} is not a TOML, JSON or YAML file")),
+    };
+
+    // Numbers pick array elements: `authors.0`
+    let value = key.split('.').try_fold(&document, |value, part| match value {
+        serde_json::Value::Array(items) => part.parse::<usize>().ok().and_then(|index| items.get(index)),
+        _ => value.get(part),
+    });
+    Ok(match value {
+        Some(serde_json::Value::String(text)) => Some(text.clone()),
+        Some(value @ (serde_json::Value::Number(_) | serde_json::Value::Bool(_))) => Some(value.to_string()),
+        _ => None,
+    })
+}
+
+/// A variable `resolve_values` needs an answer for
+pub struct Question<'a> {
+    pub name: &'a str,
+    pub spec: &'a VariableSpec,
+    /// The default, already rendered with the values known so far
+    pub default: Option<&'a str>,
+    /// Why the previous answer was rejected, when asking again
+    pub problem: Option<&'a str>,
+}
+
+/// Asks for the value of a variable that wasn't supplied; None leaves it to the default
+pub type Ask<'a> = dyn FnMut(&Question) -> Result<Option<String>, String> + 'a;
+
+/// Validation beyond the declared type (the template's WASM validators), given name and value
+pub type Validate<'a> = dyn FnMut(&str, &str) -> Result<(), String> + 'a;
+
+/// Check if a name can be used as a variable: letters, digits and underscores, not starting with a digit
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Works out the value of every variable for one application: supplied values win, then
+/// answers from `ask`, then defaults. Supplied values the template doesn't declare are kept too.
+/// Every value of a declared variable is checked against its type and by `validate`; a rejected
+/// answer is asked again with the reason. Fails listing every declared variable that has no
+/// value, so they can all be passed at once.
+pub fn resolve_values(
+    declared: &BTreeMap<String, VariableSpec>,
+    supplied: &BTreeMap<String, String>,
+    ask: &mut Ask,
+    validate: &mut Validate,
+) -> Result<BTreeMap<String, String>, String> {
+    let mut values = supplied.clone();
+    let mut missing = Vec::new();
+    let mut pending = Vec::new();
+    for (name, spec) in declared {
+        if !is_valid_name(name) {
+            return Err(format!("'{name}' is not a valid variable name (letters, digits and underscores)"));
+        }
+        match values.get(name) {
+            Some(value) => {
+                let value = check_value(name, spec, value, validate).map_err(|e| format!("invalid value for {name}: {e}"))?;
+                values.insert(name.clone(), value);
+            }
+            None => pending.push((name, spec)),
+        }
+    }
+
+    // Variables go in dependency order, so defaults that refer to each other see what is known so far
+    while !pending.is_empty() {
+        let waiting = |spec: &VariableSpec| {
+            spec.default.as_deref().is_some_and(|default| {
+                referenced_names(default).iter()
+                    .any(|r| !values.contains_key(r) && pending.iter().any(|(name, _)| *name == r))
+            })
+        };
+        let index = pending.iter().position(|(_, spec)| !waiting(spec)).unwrap_or(0);
+        let (name, spec) = pending.remove(index);
+        let default = spec.default.as_deref()
+            .map(|default| render_text(default, &values, &mut |_, _| Ok(None)).map(|rendered| rendered.text))
+            .transpose()
+            .map_err(|e| format!("default of '{name}': {e}"))?;
+
+        let mut problem = None;
+        loop {
+            let answer = ask(&Question { name, spec, default: default.as_deref(), problem: problem.as_deref() })?;
+            let answered = answer.is_some();
+            let Some(value) = answer.or_else(|| default.clone()) else {
+                missing.push(match &spec.description {
+                    Some(description) => format!("{name} ({description})"),
+                    None => name.clone(),
+                });
+                break;
+            };
+            match check_value(name, spec, &value, validate) {
+                Ok(value) => {
+                    values.insert(name.clone(), value);
+                    break;
+                }
+                Err(e) if answered => problem = Some(e),
+                Err(e) => return Err(format!("invalid default for {name}: {e}")),
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(values)
+    } else {
+        Err(format!("no value for {}", missing.join(", ")))
+    }
+}
+
+/// Checks a value against its declaration and the extra validation, returning it canonical
+fn check_value(name: &str, spec: &VariableSpec, value: &str, validate: &mut Validate) -> Result<String, String> {
+    let value = spec.check(value)?;
+    validate(name, &value)?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(default: Option<&str>) -> VariableSpec {
+        VariableSpec { description: Some("used somewhere".to_string()), default: default.map(str::to_string), ..VariableSpec::default() }
+    }
+
+    fn resolve(
+        declared: &BTreeMap<String, VariableSpec>,
+        supplied: &BTreeMap<String, String>,
+    ) -> Result<BTreeMap<String, String>, String> {
+        resolve_values(declared, supplied, &mut |_| Ok(None), &mut |_, _| Ok(()))
+    }
+
+    #[test]
+    fn test_resolve_values() {
+        let declared = BTreeMap::from([
+            ("name".to_string(), spec(None)),
+            ("package".to_string(), spec(Some("{{ name | snake_case }}_pkg"))),
+            ("crate_dir".to_string(), spec(Some("crates/{{ package }}"))),
+        ]);
+        let supplied = BTreeMap::from([("name".to_string(), "My App".to_string()), ("extra".to_string(), "x".to_string())]);
+        let values = resolve(&declared, &supplied).unwrap();
+        assert_eq!(values["package"], "my_app_pkg");
+        assert_eq!(values["crate_dir"], "crates/my_app_pkg");
+        assert_eq!(values["extra"], "x");
+
+        let error = resolve(&declared, &BTreeMap::new()).unwrap_err();
+        assert_eq!(error, "no value for name (used somewhere)");
+    }
+
+    #[test]
+    fn test_check() {
+        let flag = VariableSpec { kind: VariableKind::Bool, ..VariableSpec::default() };
+        assert_eq!(flag.check("Yes").as_deref(), Ok("true"));
+        assert!(flag.check("maybe").is_err());
+
+        let count = VariableSpec { kind: VariableKind::Integer, ..VariableSpec::default() };
+        assert_eq!(count.check(" 42 ").as_deref(), Ok("42"));
+        assert!(count.check("4.2").is_err());
+
+        let license = VariableSpec { choices: Some(vec!["MIT".to_string(), "Apache-2.0".to_string()]), ..VariableSpec::default() };
+        assert_eq!(license.check("MIT").as_deref(), Ok("MIT"));
+        assert!(license.check("GPL").is_err());
+    }
+
+    #[test]
+    fn test_resolve_values_asks() {
+        let declared = BTreeMap::from([
+            ("name".to_string(), spec(None)),
+            ("port".to_string(), VariableSpec { kind: VariableKind::Integer, default: Some("8080".to_string()), ..VariableSpec::default() }),
+        ]);
+
+        // A rejected answer is asked again with the reason; no answer takes the default
+        let mut asked = Vec::new();
+        let values = resolve_values(&declared, &BTreeMap::new(), &mut |question| {
+            asked.push((question.name.to_string(), question.problem.map(str::to_string)));
+            Ok(match (question.name, question.problem) {
+                ("name", None) => Some("bad".to_string()),
+                ("name", Some(_)) => Some("good".to_string()),
+                _ => None,
+            })
+        }, &mut |name, value| if name == "name" && value == "bad" { Err("too bad".to_string()) } else { Ok(()) }).unwrap();
+        assert_eq!(values["name"], "good");
+        assert_eq!(values["port"], "8080");
+        assert_eq!(asked[1], ("name".to_string(), Some("too bad".to_string())));
+
+        let supplied = BTreeMap::from([("name".to_string(), "x".to_string()), ("port".to_string(), "http".to_string())]);
+        assert!(resolve(&declared, &supplied).unwrap_err().contains("invalid value for port"));
+    }
+
+    #[test]
+    fn test_project_value() {
+        let root = std::env::temp_dir().join(format!("template-rs-project-value-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"demo\"\nauthors = [\"Ann\"]\n").unwrap();
+        fs::write(root.join("package.json"), r#"{"name": "web", "private": true}"#).unwrap();
+
+        assert_eq!(project_value(&root, "Cargo.toml:package.name").unwrap().as_deref(), Some("demo"));
+        assert_eq!(project_value(&root, "Cargo.toml:package.authors.0").unwrap().as_deref(), Some("Ann"));
+        assert_eq!(project_value(&root, "package.json:private").unwrap().as_deref(), Some("true"));
+        assert_eq!(project_value(&root, "Cargo.toml:package").unwrap(), None);
+        assert_eq!(project_value(&root, "pyproject.toml:project.name").unwrap(), None);
+        assert!(project_value(&root, "Cargo.toml").is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_is_valid_name() {
+        assert!(is_valid_name("project_name"));
+        assert!(is_valid_name("_x1"));
+        assert!(!is_valid_name("1x"));
+        assert!(!is_valid_name("kebab-case"));
+        assert!(!is_valid_name(""));
+    }
+}
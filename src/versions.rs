@@ -0,0 +1,126 @@
+use crate::error::TemplateError;
+use crate::file::ensure_version_storage_dir;
+use crate::lock::{TemplateLock, hash_bytes};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A version of a template that was applied somewhere, stored in the versions directory of storage.
+///
+/// The file contents themselves live in the object store; a record keeps them
+/// reachable so `upgrade` can use them as the merge base until `prune` drops it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionRecord {
+    /// Template name (storage path)
+    pub template: String,
+    /// Version from the template config at the time, if it had one
+    pub version: Option<String>,
+    /// Unix timestamp (seconds) of the most recent application of this version
+    pub recorded_at: u64,
+    /// Relative file paths mapped to the SHA-256 of their content in the object store
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl VersionRecord {
+    /// Builds the record for the template contents a lock file was written for
+    pub fn from_lock(lock: &TemplateLock) -> Self {
+        Self {
+            template: lock.template.clone(),
+            version: lock.version.clone(),
+            recorded_at: lock.applied_at,
+            files: lock.files.clone(),
+        }
+    }
+
+    /// Identifies the contents of a version; the same files always produce the same id
+    pub fn id(&self) -> String {
+        let listing: String = self.files.iter().map(|(file, hash)| format!("{hash}  {file}\n")).collect();
+        hash_bytes(listing.as_bytes())[..16].to_string()
+    }
+
+    /// Label for output: the config version plus the content id
+    pub fn label(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{version} ({})", self.id()),
+            None => format!("unversioned ({})", self.id()),
+        }
+    }
+
+    /// Check if the record matches a `template@version` pin (a bare template name pins every version)
+    pub fn is_pinned_by(&self, pin: &str) -> bool {
+        match pin.split_once('@') {
+            Some((template, version)) => {
+                self.template == template && (self.version.as_deref() == Some(version) || self.id() == version)
+            }
+            None => self.template == pin,
+        }
+    }
+
+    /// Writes the record, replacing an earlier record of the same contents
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let record_dir = ensure_version_storage_dir()?.join(&self.template);
+        fs::create_dir_all(&record_dir)?;
+        fs::write(record_dir.join(format!("{}.toml", self.id())), toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Loads every stored record with the path of its file
+    pub fn load_all() -> Result<Vec<(PathBuf, Self)>, Box<dyn std::error::Error>> {
+        let version_dir = ensure_version_storage_dir()?;
+        let mut records = Vec::new();
+        collect_records(&version_dir, &mut records)?;
+        Ok(records)
+    }
+}
+
+/// Helper function to recursively load the record files below a directory
+fn collect_records(
+    current_dir: &Path,
+    records: &mut Vec<(PathBuf, VersionRecord)>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in fs::read_dir(current_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            collect_records(&path, records)?;
+        } else if path.extension().is_some_and(|e| e == "toml") {
+            let content = fs::read_to_string(&path)?;
+            let record = toml::from_str(&content)
+                .map_err(|e| TemplateError::Config(format!("Failed to parse {}: {e}", path.display())))?;
+            records.push((path, record));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(version: Option<&str>, content: &[u8]) -> VersionRecord {
+        let mut lock = TemplateLock::new("rust/cli", version);
+        lock.files.insert("src/main.rs".to_string(), hash_bytes(content));
+        VersionRecord::from_lock(&lock)
+    }
+
+    #[test]
+    fn test_id_depends_on_contents_only() {
+        assert_eq!(record(Some("1.0.0"), b"a").id(), record(Some("1.1.0"), b"a").id());
+        assert_ne!(record(Some("1.0.0"), b"a").id(), record(Some("1.0.0"), b"b").id());
+    }
+
+    #[test]
+    fn test_pins() {
+        let r = record(Some("1.2.0"), b"a");
+        assert!(r.is_pinned_by("rust/cli"));
+        assert!(r.is_pinned_by("rust/cli@1.2.0"));
+        assert!(r.is_pinned_by(&format!("rust/cli@{}", r.id())));
+        assert!(!r.is_pinned_by("rust/cli@1.3.0"));
+        assert!(!r.is_pinned_by("rust/lib"));
+    }
+}
@@ -0,0 +1,142 @@
+use crate::path::is_contained;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Sibling projects a workspace template creates, from the [workspace] table of .template.toml.
+/// The template's own files go into the parent directory, each member into its subdirectory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    /// Projects to create, in order: `[{ template = "rust/api", path = "api" }, ...]`
+    #[serde(default)]
+    pub members: Vec<WorkspaceMember>,
+    /// Variables shared by every member, recorded in each member's .template.lock
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    /// Root file listing the members (cargo or pnpm), written into the parent directory
+    pub root: Option<WorkspaceRoot>,
+}
+
+/// One project of a workspace template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceMember {
+    /// Name of the template the member is created from
+    pub template: String,
+    /// Subdirectory of the workspace the member is created in
+    pub path: String,
+    /// Variables for this member only, over the shared ones
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+}
+
+/// Kind of root file that wires the members into one workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkspaceRoot {
+    /// Cargo.toml with a [workspace] table
+    Cargo,
+    /// pnpm-workspace.yaml
+    Pnpm,
+}
+
+impl WorkspaceRoot {
+    /// Name of the root file in the workspace directory
+    pub const fn file_name(self) -> &'static str {
+        match self {
+            Self::Cargo => "Cargo.toml",
+            Self::Pnpm => "pnpm-workspace.yaml",
+        }
+    }
+
+    /// Contents of the root file listing `members` (paths relative to the workspace)
+    pub fn render(self, members: &[&str]) -> String {
+        match self {
+            Self::Cargo => {
+                let list: Vec<String> = members.iter().map(|m| format!("    \"{m}\",\n")).collect();
+                format!("[workspace]\nresolver = \"2\"\nmembers = [\n{}]\n", list.concat())
+            }
+            Self::Pnpm => {
+                let list: Vec<String> = members.iter().map(|m| format!("  - '{m}'\n")).collect();
+                format!("packages:\n{}", list.concat())
+            }
+        }
+    }
+}
+
+impl WorkspaceConfig {
+    /// Lists everything wrong with the member list, empty when it can be applied
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.members.is_empty() {
+            problems.push("workspace has no members".to_string());
+        }
+        let mut seen = BTreeSet::new();
+        for member in &self.members {
+            let path = member.path.trim_end_matches('/');
+            if path.is_empty() || path == "." || !is_contained(path) {
+                problems.push(format!("member path '{}' must be a subdirectory of the workspace", member.path));
+            } else if !seen.insert(path) {
+                problems.push(format!("member path '{}' is used more than once", member.path));
+            }
+        }
+        problems
+    }
+
+    /// Variables recorded for `member`: the shared ones, then the member's own, then `overrides`
+    pub fn member_variables(
+        &self,
+        member: &WorkspaceMember,
+        overrides: &BTreeMap<String, String>,
+    ) -> BTreeMap<String, String> {
+        let mut variables = self.variables.clone();
+        variables.extend(member.variables.clone());
+        variables.extend(overrides.clone());
+        variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(template: &str, path: &str) -> WorkspaceMember {
+        WorkspaceMember { template: template.to_string(), path: path.to_string(), variables: BTreeMap::new() }
+    }
+
+    #[test]
+    fn test_render_root() {
+        assert_eq!(
+            WorkspaceRoot::Cargo.render(&["api", "infra"]),
+            "[workspace]\nresolver = \"2\"\nmembers = [\n    \"api\",\n    \"infra\",\n]\n"
+        );
+        assert_eq!(WorkspaceRoot::Pnpm.render(&["web"]), "packages:\n  - 'web'\n");
+    }
+
+    #[test]
+    fn test_problems() {
+        let workspace = WorkspaceConfig {
+            members: vec![member("a", "api"), member("b", "api/"), member("c", "../up"), member("d", ".")],
+            ..WorkspaceConfig::default()
+        };
+        assert_eq!(workspace.problems().len(), 3);
+        assert_eq!(WorkspaceConfig::default().problems().len(), 1);
+
+        let workspace = WorkspaceConfig { members: vec![member("a", "api"), member("b", "web")], ..WorkspaceConfig::default() };
+        assert!(workspace.problems().is_empty());
+    }
+
+    #[test]
+    fn test_member_variables() {
+        let mut api = member("a", "api");
+        api.variables.insert("port".to_string(), "8080".to_string());
+        let workspace = WorkspaceConfig {
+            variables: BTreeMap::from([("org".to_string(), "acme".to_string()), ("port".to_string(), "80".to_string())]),
+            ..WorkspaceConfig::default()
+        };
+        let overrides = BTreeMap::from([("org".to_string(), "initech".to_string())]);
+        let variables = workspace.member_variables(&api, &overrides);
+        assert_eq!(variables["org"], "initech");
+        assert_eq!(variables["port"], "8080");
+    }
+}